@@ -0,0 +1,20 @@
+use std::process::Command;
+
+fn main() {
+    let git_hash = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|hash| hash.trim().to_owned())
+        .filter(|hash| !hash.is_empty())
+        .unwrap_or_else(|| "unknown".to_owned());
+
+    let build_timestamp = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ");
+
+    println!("cargo:rustc-env=TF_GIT_HASH={git_hash}");
+    println!("cargo:rustc-env=TF_BUILD_TIMESTAMP={build_timestamp}");
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=../.git/HEAD");
+}
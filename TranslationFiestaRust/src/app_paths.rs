@@ -1,7 +1,12 @@
 use std::env;
+use std::fs;
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
+use walkdir::WalkDir;
+
+/// Directory name used under the platform data directory.
+const APP_DIR_NAME: &str = "TranslationFiesta";
 
 #[derive(Debug, Clone)]
 pub struct AppPaths {
@@ -11,16 +16,26 @@ pub struct AppPaths {
     pub exports_dir: PathBuf,
     pub settings_file: PathBuf,
     pub memory_db_file: PathBuf,
+    pub history_file: PathBuf,
 }
 
 impl AppPaths {
-    pub fn discover() -> Result<Self> {
+    /// Resolves the data root (see `resolve_data_root_from`) and creates it.
+    /// Outside portable mode, an existing `data/` folder beside the executable
+    /// is moved to the platform directory the first time it is used.
+    pub fn discover(portable: bool) -> Result<Self> {
         let app_root = resolve_app_root()?;
-        let data_root = resolve_data_root(&app_root)?;
+        let data_root = resolve_data_root_from(&app_root, portable, |name| env::var(name).ok());
+        let legacy_root = app_root.join("data");
+        if data_root != legacy_root {
+            migrate_data_root(&legacy_root, &data_root)?;
+        }
+        let data_root = ensure_dir(data_root)?;
         let logs_dir = ensure_dir(data_root.join("logs"))?;
         let exports_dir = ensure_dir(data_root.join("exports"))?;
         let settings_file = data_root.join("settings.json");
         let memory_db_file = data_root.join("translation_memory.db");
+        let history_file = data_root.join("history.json");
 
         Ok(Self {
             app_root,
@@ -29,6 +44,7 @@ impl AppPaths {
             exports_dir,
             settings_file,
             memory_db_file,
+            history_file,
         })
     }
 }
@@ -43,19 +59,134 @@ fn resolve_app_root() -> Result<PathBuf> {
     env::current_dir().context("failed to resolve current directory")
 }
 
-fn resolve_data_root(app_root: &Path) -> Result<PathBuf> {
-    if let Ok(override_path) = env::var("TF_APP_HOME") {
-        let trimmed = override_path.trim();
-        if !trimmed.is_empty() {
-            return ensure_dir(PathBuf::from(trimmed));
-        }
+/// `TF_APP_HOME` wins, then `--portable` (`<exe_dir>/data`), then the platform
+/// data directory: `%APPDATA%` on Windows, `~/Library/Application Support` on
+/// macOS, and `$XDG_DATA_HOME` or `~/.local/share` elsewhere. Falls back to the
+/// portable location when the platform directory cannot be determined.
+pub fn resolve_data_root_from(
+    app_root: &Path,
+    portable: bool,
+    var: impl Fn(&str) -> Option<String>,
+) -> PathBuf {
+    let var = |name: &str| var(name).filter(|value| !value.trim().is_empty());
+
+    if let Some(override_path) = var("TF_APP_HOME") {
+        return PathBuf::from(override_path.trim());
+    }
+    if portable {
+        return app_root.join("data");
     }
 
-    ensure_dir(app_root.join("data"))
+    let platform_dir = if cfg!(windows) {
+        var("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        var("HOME").map(|home| Path::new(&home).join("Library/Application Support"))
+    } else {
+        var("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+            .or_else(|| var("HOME").map(|home| Path::new(&home).join(".local/share")))
+    };
+
+    platform_dir
+        .map(|dir| dir.join(APP_DIR_NAME))
+        .unwrap_or_else(|| app_root.join("data"))
+}
+
+/// Moves `legacy` to `target` when `target` does not exist yet. Copies instead
+/// when a rename is impossible (e.g. across file systems), leaving `legacy`
+/// in place.
+pub fn migrate_data_root(legacy: &Path, target: &Path) -> Result<bool> {
+    if !legacy.is_dir() || target.exists() {
+        return Ok(false);
+    }
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create directory {}", parent.display()))?;
+    }
+    if fs::rename(legacy, target).is_ok() {
+        return Ok(true);
+    }
+
+    for entry in WalkDir::new(legacy) {
+        let entry = entry.with_context(|| format!("failed to read {}", legacy.display()))?;
+        let relative = entry.path().strip_prefix(legacy).unwrap_or(entry.path());
+        let destination = target.join(relative);
+        if entry.file_type().is_dir() {
+            ensure_dir(destination)?;
+        } else {
+            fs::copy(entry.path(), &destination).with_context(|| {
+                format!(
+                    "failed to copy {} to {}",
+                    entry.path().display(),
+                    destination.display()
+                )
+            })?;
+        }
+    }
+    Ok(true)
 }
 
 fn ensure_dir(path: PathBuf) -> Result<PathBuf> {
-    std::fs::create_dir_all(&path)
+    fs::create_dir_all(&path)
         .with_context(|| format!("failed to create directory {}", path.display()))?;
     Ok(path)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn override_beats_portable_beats_platform_directory() {
+        let app_root = Path::new("/opt/tf");
+        let vars = |override_path: &'static str| {
+            move |name: &str| {
+                match name {
+                    "TF_APP_HOME" => Some(override_path),
+                    "APPDATA" => Some("/users/me/appdata"),
+                    "HOME" => Some("/home/me"),
+                    _ => None,
+                }
+                .map(str::to_owned)
+            }
+        };
+
+        assert_eq!(
+            resolve_data_root_from(app_root, true, vars("/srv/tf")),
+            PathBuf::from("/srv/tf")
+        );
+        assert_eq!(
+            resolve_data_root_from(app_root, true, vars("  ")),
+            app_root.join("data")
+        );
+        let platform = resolve_data_root_from(app_root, false, vars(""));
+        assert!(platform.ends_with(APP_DIR_NAME), "{platform:?}");
+        assert_ne!(platform, app_root.join("data"));
+        assert_eq!(
+            resolve_data_root_from(app_root, false, |_| None),
+            app_root.join("data")
+        );
+    }
+
+    #[test]
+    fn migrates_legacy_data_only_into_a_missing_target() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let legacy = temp.path().join("exe/data");
+        fs::create_dir_all(legacy.join("logs")).unwrap();
+        fs::write(legacy.join("settings.json"), "{}").unwrap();
+        let target = temp.path().join("share/TranslationFiesta");
+
+        assert!(migrate_data_root(&legacy, &target).unwrap());
+        assert!(target.join("settings.json").exists());
+        assert!(target.join("logs").is_dir());
+
+        fs::create_dir_all(&legacy).unwrap();
+        fs::write(legacy.join("settings.json"), "{\"theme\":\"light\"}").unwrap();
+        assert!(!migrate_data_root(&legacy, &target).unwrap());
+        assert_eq!(
+            fs::read_to_string(target.join("settings.json")).unwrap(),
+            "{}"
+        );
+    }
+}
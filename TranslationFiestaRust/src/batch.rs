@@ -1,19 +1,56 @@
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::time::Instant;
+use std::process::{Command, ExitStatus};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
 
-use anyhow::Result;
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
 use tracing::{error, info, warn};
 
-use crate::file_service::{list_supported_files_in_directory, load_text};
-use crate::models::{BatchItemResult, ProviderId};
+use crate::epub;
+use crate::export::ExportService;
+use crate::file_service::{
+    ScanOptions, TextLoadOptions, list_supported_files_in_directory,
+    list_supported_files_with_options, load_text_with_options,
+};
+use crate::models::{
+    BackTranslationResult, BatchErrorKind, BatchItemResult, EpubChapter, ExportFormat, ProviderId,
+};
 use crate::translation::{TranslationError, TranslationService};
 
+const DEADLINE_POLL_INTERVAL: Duration = Duration::from_millis(40);
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// Checkpoint file name under the data root used by the CLI's `--resume`.
+pub const BATCH_CHECKPOINT_FILE: &str = "batch_checkpoint.json";
+
 #[derive(Debug, Clone)]
 pub struct BatchOptions {
     pub source_language: Option<String>,
     pub intermediate_language: String,
     pub provider_id: ProviderId,
+    /// Per-file deadline; when exceeded the file is cancelled and marked as timed out.
+    pub file_timeout: Option<Duration>,
+    pub load_options: TextLoadOptions,
+    /// Number of files translated in parallel; 1 keeps the sequential behavior.
+    pub concurrency: usize,
+    /// When set, every successful file is also exported here as `output_format`.
+    pub output_dir: Option<PathBuf>,
+    pub output_format: ExportFormat,
+    /// File name for each per-file output, e.g. `{stem}.{target}.{ext}`; see
+    /// `render_output_template`. `None` keeps the input's stem.
+    pub output_template: Option<String>,
+    /// Directory the files were collected from; per-file outputs mirror their
+    /// path relative to it instead of landing flat in `output_dir`.
+    pub source_root: Option<PathBuf>,
+    /// Successful files are recorded here as they finish.
+    pub checkpoint_path: Option<PathBuf>,
+    /// Skip files the checkpoint already lists as done instead of starting over.
+    pub resume: bool,
+    /// Only load each file and validate the request; nothing is sent to the
+    /// provider, written to `output_dir`, or recorded in the checkpoint.
+    /// Passing files come back successful with empty translations.
+    pub dry_run: bool,
 }
 
 impl Default for BatchOptions {
@@ -22,8 +59,105 @@ impl Default for BatchOptions {
             source_language: Some("en".to_owned()),
             intermediate_language: "ja".to_owned(),
             provider_id: ProviderId::GoogleUnofficial,
+            file_timeout: None,
+            load_options: TextLoadOptions::default(),
+            concurrency: 1,
+            output_dir: None,
+            output_format: ExportFormat::Txt,
+            output_template: None,
+            source_root: None,
+            checkpoint_path: None,
+            resume: false,
+            dry_run: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckpointEntry {
+    pub file_path: String,
+    /// FNV-1a hash of the back-translated text.
+    pub result_hash: String,
+}
+
+/// Files a batch has already finished successfully, persisted after every
+/// completion so an interrupted run can resume where it stopped.
+#[derive(Debug, Clone)]
+pub struct BatchCheckpoint {
+    path: PathBuf,
+    entries: Vec<CheckpointEntry>,
+}
+
+impl BatchCheckpoint {
+    /// Reads an existing checkpoint; a missing file is an empty checkpoint.
+    pub fn load(path: &Path) -> Result<Self> {
+        let entries = match std::fs::read_to_string(path) {
+            Ok(raw) => serde_json::from_str(&raw)
+                .with_context(|| format!("failed to parse checkpoint {}", path.display()))?,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(error) => {
+                return Err(error)
+                    .with_context(|| format!("failed to read checkpoint {}", path.display()));
+            }
+        };
+        Ok(Self {
+            path: path.to_path_buf(),
+            entries,
+        })
+    }
+
+    /// Starts an empty checkpoint, replacing any previous one on disk.
+    pub fn fresh(path: &Path) -> Result<Self> {
+        let checkpoint = Self {
+            path: path.to_path_buf(),
+            entries: Vec::new(),
+        };
+        checkpoint.save()?;
+        Ok(checkpoint)
+    }
+
+    pub fn entries(&self) -> &[CheckpointEntry] {
+        &self.entries
+    }
+
+    pub fn is_complete(&self, file_path: &str) -> bool {
+        self.entries
+            .iter()
+            .any(|entry| entry.file_path == file_path)
+    }
+
+    /// Records `item` if it succeeded; failed files stay pending for a retry.
+    pub fn record(&mut self, item: &BatchItemResult) -> Result<()> {
+        if !item.success || self.is_complete(&item.file_path) {
+            return Ok(());
         }
+        self.entries.push(CheckpointEntry {
+            file_path: item.file_path.clone(),
+            result_hash: format!("{:016x}", fnv1a(item.back_translated_text.as_bytes())),
+        });
+        self.save()
     }
+
+    /// Writes through a temporary file so a crash mid-write keeps the old copy.
+    fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).with_context(|| {
+                format!("failed to create checkpoint directory {}", parent.display())
+            })?;
+        }
+        let temp_path = self.path.with_extension("json.tmp");
+        let json = serde_json::to_string_pretty(&self.entries)?;
+        std::fs::write(&temp_path, json)
+            .with_context(|| format!("failed to write checkpoint {}", temp_path.display()))?;
+        std::fs::rename(&temp_path, &self.path)
+            .with_context(|| format!("failed to replace checkpoint {}", self.path.display()))
+    }
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
 }
 
 #[derive(Debug, Clone)]
@@ -31,6 +165,52 @@ pub struct BatchProgress {
     pub done: usize,
     pub total: usize,
     pub current_file: String,
+    /// The result that completed this step; `None` when a file has just started.
+    pub finished: Option<BatchItemResult>,
+    pub elapsed_ms: u64,
+    /// Wall-clock time left at the pace of the files finished so far; `None`
+    /// until the first one finishes.
+    pub estimated_remaining_ms: Option<u64>,
+}
+
+impl BatchProgress {
+    /// `12s elapsed, about 1m 30s left`, or just the elapsed time while there
+    /// is no estimate.
+    pub fn timing_label(&self) -> String {
+        match self.estimated_remaining_ms {
+            Some(remaining) => format!(
+                "{} elapsed, about {} left",
+                format_duration_ms(self.elapsed_ms),
+                format_duration_ms(remaining)
+            ),
+            None => format!("{} elapsed", format_duration_ms(self.elapsed_ms)),
+        }
+    }
+}
+
+/// Time left for `total - done` files if they take as long on average as
+/// the `done` files did in `elapsed`. Averaging wall-clock time rather than
+/// per-file durations accounts for files processed in parallel.
+fn estimate_remaining(elapsed: Duration, done: usize, total: usize) -> Option<Duration> {
+    if done == 0 {
+        return None;
+    }
+    let remaining = total.saturating_sub(done) as u32;
+    Some(elapsed / done as u32 * remaining)
+}
+
+fn format_duration_ms(ms: u64) -> String {
+    let seconds = ms.div_ceil(1000);
+    match (seconds / 3600, seconds / 60 % 60, seconds % 60) {
+        (0, 0, secs) => format!("{secs}s"),
+        (0, mins, secs) => format!("{mins}m {secs}s"),
+        (hours, mins, _) => format!("{hours}h {mins}m"),
+    }
+}
+
+enum WorkerEvent {
+    Started(String),
+    Finished(usize, BatchItemResult),
 }
 
 #[derive(Debug, Clone)]
@@ -47,111 +227,693 @@ impl BatchProcessor {
         list_supported_files_in_directory(directory)
     }
 
+    pub fn collect_files_with_options(
+        &self,
+        directory: &Path,
+        options: &ScanOptions,
+    ) -> Result<Vec<PathBuf>> {
+        list_supported_files_with_options(directory, options)
+    }
+
     pub fn process_files<F>(
         &self,
         files: &[PathBuf],
         options: &BatchOptions,
         cancel_flag: &AtomicBool,
-        mut on_progress: F,
+        on_progress: F,
     ) -> Vec<BatchItemResult>
     where
         F: FnMut(BatchProgress),
     {
-        let total = files.len();
-        if total == 0 {
-            return Vec::new();
-        }
-
-        info!("starting batch processing of {total} files");
+        self.process_files_with_pause(
+            files,
+            options,
+            cancel_flag,
+            &AtomicBool::new(false),
+            on_progress,
+        )
+    }
 
-        let mut results = Vec::with_capacity(total);
+    /// `process_files` that starts no new file while `pause_flag` is set.
+    /// Files already in flight finish, and cancelling still works while paused.
+    pub fn process_files_with_pause<F>(
+        &self,
+        files: &[PathBuf],
+        options: &BatchOptions,
+        cancel_flag: &AtomicBool,
+        pause_flag: &AtomicBool,
+        mut on_progress: F,
+    ) -> Vec<BatchItemResult>
+    where
+        F: FnMut(BatchProgress),
+    {
+        let mut checkpoint = options.checkpoint_path.as_deref().and_then(|path| {
+            let loaded = if options.resume {
+                BatchCheckpoint::load(path)
+            } else if options.dry_run {
+                return None;
+            } else {
+                BatchCheckpoint::fresh(path)
+            };
+            loaded
+                .inspect_err(|error| warn!("batch checkpoint disabled: {error:#}"))
+                .ok()
+        });
 
-        for (index, file_path) in files.iter().enumerate() {
-            if cancel_flag.load(Ordering::Relaxed) {
-                warn!("batch processing cancelled by user");
-                break;
+        let pending: Vec<PathBuf>;
+        let files = match &checkpoint {
+            Some(checkpoint) if options.resume => {
+                pending = files
+                    .iter()
+                    .filter(|path| !checkpoint.is_complete(&path.to_string_lossy()))
+                    .cloned()
+                    .collect();
+                info!(
+                    "resuming batch: skipping {} completed file(s)",
+                    files.len() - pending.len()
+                );
+                pending.as_slice()
             }
+            _ => files,
+        };
 
-            let started = Instant::now();
-            let file_label = file_path.to_string_lossy().to_string();
-            on_progress(BatchProgress {
-                done: index,
-                total,
-                current_file: file_label.clone(),
-            });
+        run_pool(
+            files,
+            options,
+            cancel_flag,
+            pause_flag,
+            |file_path| file_path.to_string_lossy().to_string(),
+            |index, file_path| self.process_file(index, file_path, options, cancel_flag),
+            |progress| {
+                if let Some(result) = &progress.finished
+                    && let Some(checkpoint) = checkpoint.as_mut().filter(|_| !options.dry_run)
+                    && let Err(error) = checkpoint.record(result)
+                {
+                    warn!("failed to update batch checkpoint: {error:#}");
+                }
+                on_progress(progress);
+            },
+        )
+    }
 
-            let item_result = match load_text(file_path) {
-                Ok(content) => {
-                    self.translate_single_file(&content, options, cancel_flag, &file_label, started)
+    /// Translates the chapters of one EPUB the way `process_files_with_pause`
+    /// translates files, with one result per chapter. A book that cannot be
+    /// read gives a single failed result for `path`.
+    pub fn process_epub_with_pause<F>(
+        &self,
+        path: &Path,
+        options: &BatchOptions,
+        cancel_flag: &AtomicBool,
+        pause_flag: &AtomicBool,
+        on_progress: F,
+    ) -> Vec<BatchItemResult>
+    where
+        F: FnMut(BatchProgress),
+    {
+        match epub::load_epub(path, options.load_options.normalize_whitespace) {
+            Ok(book) => self.process_chapters_with_pause(
+                &book.chapters,
+                options,
+                cancel_flag,
+                pause_flag,
+                on_progress,
+            ),
+            Err(error) => vec![BatchItemResult {
+                file_path: path.to_string_lossy().to_string(),
+                success: false,
+                original_text: String::new(),
+                intermediate_text: String::new(),
+                back_translated_text: String::new(),
+                error: Some(format!("{error:#}")),
+                error_kind: Some(BatchErrorKind::Io),
+                detected_source: None,
+                suspicious_reason: None,
+                duration_ms: 0,
+            }],
+        }
+    }
+
+    /// Chapters are translated in parallel up to `options.concurrency`; each
+    /// result is labelled `path (title)`. Per-file outputs and checkpoints
+    /// do not apply.
+    pub fn process_chapters_with_pause<F>(
+        &self,
+        chapters: &[EpubChapter],
+        options: &BatchOptions,
+        cancel_flag: &AtomicBool,
+        pause_flag: &AtomicBool,
+        on_progress: F,
+    ) -> Vec<BatchItemResult>
+    where
+        F: FnMut(BatchProgress),
+    {
+        run_pool(
+            chapters,
+            options,
+            cancel_flag,
+            pause_flag,
+            chapter_label,
+            |_, chapter| {
+                let started = Instant::now();
+                if options.dry_run {
+                    self.validate_single_file(
+                        &chapter.content,
+                        chapter_label(chapter),
+                        options,
+                        started,
+                    )
+                } else {
+                    self.translate_single_file(
+                        &chapter.content,
+                        chapter_label(chapter),
+                        None,
+                        options,
+                        cancel_flag,
+                        started,
+                    )
                 }
-                Err(error) => BatchItemResult {
-                    file_path: file_label,
-                    success: false,
-                    intermediate_text: String::new(),
-                    back_translated_text: String::new(),
-                    error: Some(error.to_string()),
-                    duration_ms: started.elapsed().as_millis(),
-                },
-            };
+            },
+            on_progress,
+        )
+    }
 
-            results.push(item_result);
-            on_progress(BatchProgress {
-                done: index + 1,
-                total,
-                current_file: file_path.to_string_lossy().to_string(),
-            });
+    fn process_file(
+        &self,
+        index: usize,
+        file_path: &Path,
+        options: &BatchOptions,
+        cancel_flag: &AtomicBool,
+    ) -> BatchItemResult {
+        let started = Instant::now();
+        let file_label = file_path.to_string_lossy().to_string();
+
+        match load_text_with_options(file_path, options.load_options) {
+            Ok(content) if options.dry_run => {
+                self.validate_single_file(&content, file_label, options, started)
+            }
+            Ok(content) => self.translate_single_file(
+                &content,
+                file_label,
+                Some((index, file_path)),
+                options,
+                cancel_flag,
+                started,
+            ),
+            Err(error) => BatchItemResult {
+                file_path: file_label,
+                success: false,
+                original_text: String::new(),
+                intermediate_text: String::new(),
+                back_translated_text: String::new(),
+                error: Some(error.to_string()),
+                error_kind: Some(BatchErrorKind::Io),
+                detected_source: None,
+                suspicious_reason: None,
+                duration_ms: started.elapsed().as_millis(),
+            },
         }
+    }
 
-        info!("batch processing completed with {} results", results.len());
-        results
+    fn validate_single_file(
+        &self,
+        content: &str,
+        file_label: String,
+        options: &BatchOptions,
+        started: Instant,
+    ) -> BatchItemResult {
+        let detected_source = self.detect_source(content, options);
+        let error = self
+            .translator
+            .validate_back_translation(
+                content,
+                options
+                    .source_language
+                    .as_deref()
+                    .or(detected_source.as_deref()),
+                &options.intermediate_language,
+                options.provider_id,
+            )
+            .err();
+        BatchItemResult {
+            file_path: file_label,
+            success: error.is_none(),
+            original_text: content.trim().to_owned(),
+            intermediate_text: String::new(),
+            back_translated_text: String::new(),
+            error_kind: error.as_ref().map(TranslationError::batch_error_kind),
+            error: error.map(|error| error.to_string()),
+            detected_source,
+            suspicious_reason: None,
+            duration_ms: started.elapsed().as_millis(),
+        }
     }
 
+    /// `output` is the batch index and path of an input file, whose
+    /// translation is also written to `options.output_dir`.
     fn translate_single_file(
         &self,
         content: &str,
+        file_label: String,
+        output: Option<(usize, &Path)>,
         options: &BatchOptions,
         cancel_flag: &AtomicBool,
-        file_label: &str,
         started: Instant,
     ) -> BatchItemResult {
-        match self.translator.back_translate(
-            content,
-            options.source_language.as_deref(),
-            &options.intermediate_language,
-            options.provider_id,
-            Some(cancel_flag),
-        ) {
-            Ok(result) => BatchItemResult {
-                file_path: file_label.to_owned(),
-                success: true,
-                intermediate_text: result.intermediate_text,
-                back_translated_text: result.back_translated_text,
-                error: None,
+        let detected_source = self.detect_source(content, options);
+        let source_language = options
+            .source_language
+            .as_deref()
+            .or(detected_source.as_deref());
+        let outcome = match options.file_timeout {
+            Some(timeout) => self.back_translate_with_deadline(
+                content,
+                source_language,
+                options,
+                cancel_flag,
+                timeout,
+            ),
+            None => self.back_translate(content, source_language, options, cancel_flag),
+        };
+
+        let failed =
+            |kind: BatchErrorKind, message: String, intermediate_text: String| BatchItemResult {
+                file_path: file_label.clone(),
+                success: false,
+                original_text: content.trim().to_owned(),
+                intermediate_text,
+                back_translated_text: String::new(),
+                error: Some(message),
+                error_kind: Some(kind),
+                detected_source: detected_source.clone(),
+                suspicious_reason: None,
                 duration_ms: started.elapsed().as_millis(),
-            },
+            };
+
+        let result = match outcome {
+            Ok(result) => result,
             Err(error) => {
                 if matches!(error, TranslationError::Cancelled) {
-                    warn!("translation cancelled while processing file: {file_label}");
+                    warn!(
+                        file = file_label,
+                        "translation cancelled while processing file"
+                    );
+                } else if matches!(error, TranslationError::TimedOut(_)) {
+                    warn!(file = file_label, %error, "translation timed out while processing file");
                 } else {
-                    error!("failed to process file {file_label}: {error}");
+                    error!(file = file_label, %error, "failed to process file");
                 }
+                return failed(
+                    error.batch_error_kind(),
+                    error.to_string(),
+                    error.partial_intermediate().unwrap_or_default().to_owned(),
+                );
+            }
+        };
 
-                BatchItemResult {
-                    file_path: file_label.to_owned(),
-                    success: false,
-                    intermediate_text: String::new(),
-                    back_translated_text: String::new(),
-                    error: Some(error.to_string()),
-                    duration_ms: started.elapsed().as_millis(),
+        if let Some((index, file_path)) = output
+            && let Err(error) = write_per_file_output(index, file_path, &result, options)
+        {
+            let message = format!("{error:#}");
+            error!(file = file_label, error = %message, "failed to write per-file output");
+            return failed(BatchErrorKind::Io, message, String::new());
+        }
+
+        BatchItemResult {
+            file_path: file_label.clone(),
+            success: true,
+            original_text: result.original_text,
+            intermediate_text: result.intermediate_text,
+            back_translated_text: result.back_translated_text,
+            error: None,
+            error_kind: None,
+            detected_source: detected_source.clone(),
+            suspicious_reason: result.suspicious_reason,
+            duration_ms: started.elapsed().as_millis(),
+        }
+    }
+
+    /// The language of `content` when the batch has no fixed source.
+    fn detect_source(&self, content: &str, options: &BatchOptions) -> Option<String> {
+        options
+            .source_language
+            .is_none()
+            .then(|| self.translator.detect_language(content))
+    }
+
+    fn back_translate(
+        &self,
+        content: &str,
+        source_language: Option<&str>,
+        options: &BatchOptions,
+        cancel_flag: &AtomicBool,
+    ) -> std::result::Result<BackTranslationResult, TranslationError> {
+        self.translator.back_translate(
+            content,
+            source_language,
+            &options.intermediate_language,
+            options.provider_id,
+            Some(cancel_flag),
+        )
+    }
+
+    /// Runs the translation against a file-scoped cancel flag that a watchdog
+    /// raises when either the batch is cancelled or the deadline passes.
+    fn back_translate_with_deadline(
+        &self,
+        content: &str,
+        source_language: Option<&str>,
+        options: &BatchOptions,
+        cancel_flag: &AtomicBool,
+        timeout: Duration,
+    ) -> std::result::Result<BackTranslationResult, TranslationError> {
+        let file_cancel = AtomicBool::new(false);
+        let finished = AtomicBool::new(false);
+        let timed_out = AtomicBool::new(false);
+
+        let outcome = thread::scope(|scope| {
+            scope.spawn(|| {
+                let started = Instant::now();
+                while !finished.load(Ordering::Relaxed) {
+                    if cancel_flag.load(Ordering::Relaxed) {
+                        file_cancel.store(true, Ordering::Relaxed);
+                        break;
+                    }
+                    if started.elapsed() >= timeout {
+                        timed_out.store(true, Ordering::Relaxed);
+                        file_cancel.store(true, Ordering::Relaxed);
+                        break;
+                    }
+                    thread::sleep(DEADLINE_POLL_INTERVAL);
+                }
+            });
+
+            let outcome = self.back_translate(content, source_language, options, &file_cancel);
+            finished.store(true, Ordering::Relaxed);
+            outcome
+        });
+
+        match outcome {
+            Err(TranslationError::Cancelled) if timed_out.load(Ordering::Relaxed) => Err(
+                TranslationError::TimedOut(timeout.as_millis().min(u64::MAX as u128) as u64),
+            ),
+            other => other,
+        }
+    }
+}
+
+/// Runs `work` over `jobs` on up to `options.concurrency` threads. Progress
+/// is reported as each job starts and finishes, and results keep job order.
+fn run_pool<J: Sync>(
+    jobs: &[J],
+    options: &BatchOptions,
+    cancel_flag: &AtomicBool,
+    pause_flag: &AtomicBool,
+    label: impl Fn(&J) -> String + Sync,
+    work: impl Fn(usize, &J) -> BatchItemResult + Sync,
+    mut on_progress: impl FnMut(BatchProgress),
+) -> Vec<BatchItemResult> {
+    let total = jobs.len();
+    if total == 0 {
+        return Vec::new();
+    }
+
+    let concurrency = options.concurrency.clamp(1, total);
+    let started = Instant::now();
+    info!(
+        provider = options.provider_id.as_str(),
+        target = %options.intermediate_language,
+        total,
+        concurrency,
+        "starting batch processing"
+    );
+
+    let next_index = AtomicUsize::new(0);
+    let cancel_logged = AtomicBool::new(false);
+    let mut slots: Vec<Option<BatchItemResult>> = vec![None; total];
+    let (tx, rx) = crossbeam_channel::unbounded();
+
+    thread::scope(|scope| {
+        for _ in 0..concurrency {
+            let tx = tx.clone();
+            let next_index = &next_index;
+            let cancel_logged = &cancel_logged;
+            let (label, work) = (&label, &work);
+            scope.spawn(move || {
+                loop {
+                    while pause_flag.load(Ordering::Relaxed) && !cancel_flag.load(Ordering::Relaxed)
+                    {
+                        thread::sleep(PAUSE_POLL_INTERVAL);
+                    }
+                    if cancel_flag.load(Ordering::Relaxed) {
+                        if !cancel_logged.swap(true, Ordering::Relaxed) {
+                            warn!("batch processing cancelled by user");
+                        }
+                        break;
+                    }
+
+                    let index = next_index.fetch_add(1, Ordering::Relaxed);
+                    let Some(job) = jobs.get(index) else {
+                        break;
+                    };
+
+                    let _ = tx.send(WorkerEvent::Started(label(job)));
+                    let result = work(index, job);
+                    let _ = tx.send(WorkerEvent::Finished(index, result));
+                }
+            });
+        }
+        drop(tx);
+
+        // Progress is reported from the calling thread so `on_progress`
+        // needs no synchronization and `done` only ever increases.
+        let mut done = 0;
+        for event in rx {
+            let elapsed = started.elapsed();
+            match event {
+                WorkerEvent::Started(current_file) => on_progress(BatchProgress {
+                    done,
+                    total,
+                    current_file,
+                    finished: None,
+                    elapsed_ms: elapsed.as_millis() as u64,
+                    estimated_remaining_ms: estimate_remaining(elapsed, done, total)
+                        .map(|remaining| remaining.as_millis() as u64),
+                }),
+                WorkerEvent::Finished(index, result) => {
+                    done += 1;
+                    on_progress(BatchProgress {
+                        done,
+                        total,
+                        current_file: result.file_path.clone(),
+                        finished: Some(result.clone()),
+                        elapsed_ms: elapsed.as_millis() as u64,
+                        estimated_remaining_ms: estimate_remaining(elapsed, done, total)
+                            .map(|remaining| remaining.as_millis() as u64),
+                    });
+                    slots[index] = Some(result);
                 }
             }
         }
+    });
+
+    let results: Vec<BatchItemResult> = slots.into_iter().flatten().collect();
+    info!(
+        provider = options.provider_id.as_str(),
+        target = %options.intermediate_language,
+        results = results.len(),
+        failed = results.iter().filter(|result| !result.success).count(),
+        duration_ms = started.elapsed().as_millis() as u64,
+        "batch processing completed"
+    );
+    results
+}
+
+fn chapter_label(chapter: &EpubChapter) -> String {
+    format!("{} ({})", chapter.path, chapter.title)
+}
+
+/// Keeps only the files that failed with `kind` in a previous run's results.
+pub fn retain_failed_files(
+    files: &mut Vec<PathBuf>,
+    previous: &[BatchItemResult],
+    kind: BatchErrorKind,
+) {
+    files.retain(|path| {
+        let label = path.to_string_lossy();
+        previous
+            .iter()
+            .any(|item| item.error_kind == Some(kind) && item.file_path == label)
+    });
+}
+
+/// Where `file_path`'s own export goes, or `None` without an `output_dir`.
+/// `index` is the file's zero-based position in the batch.
+pub fn per_file_output_path(
+    file_path: &Path,
+    index: usize,
+    options: &BatchOptions,
+) -> Result<Option<PathBuf>> {
+    let Some(output_dir) = options.output_dir.as_ref() else {
+        return Ok(None);
+    };
+    let Some(relative) = options
+        .source_root
+        .as_ref()
+        .and_then(|root| file_path.strip_prefix(root).ok())
+        .map(Path::to_path_buf)
+        .or_else(|| file_path.file_name().map(PathBuf::from))
+    else {
+        return Ok(None);
+    };
+
+    let path = match &options.output_template {
+        Some(template) => {
+            let name = render_output_template(template, file_path, index, options)?;
+            output_dir.join(relative.with_file_name(name))
+        }
+        None => output_dir
+            .join(relative)
+            .with_extension(options.output_format.extension()),
+    };
+    Ok(Some(path))
+}
+
+const OUTPUT_TEMPLATE_PLACEHOLDERS: [&str; 7] = [
+    "stem", "ext", "source", "target", "lang", "provider", "index",
+];
+
+/// Fills a per-file output name: `{stem}` is the input file name without its
+/// extension, `{ext}` the output format's extension, `{source}` and
+/// `{target}` (alias `{lang}`) the languages, `{provider}` the provider id,
+/// and `{index}` the file's 1-based position in the batch.
+pub fn render_output_template(
+    template: &str,
+    file_path: &Path,
+    index: usize,
+    options: &BatchOptions,
+) -> Result<String> {
+    expand_output_template(template, |name| {
+        Some(match name {
+            "stem" => file_path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            "ext" => options.output_format.extension().to_owned(),
+            "source" => options
+                .source_language
+                .clone()
+                .unwrap_or_else(|| "auto".to_owned()),
+            "target" | "lang" => options.intermediate_language.clone(),
+            "provider" => options.provider_id.as_str().to_owned(),
+            "index" => (index + 1).to_string(),
+            _ => return None,
+        })
+    })
+}
+
+/// Fails on unknown placeholders, unbalanced braces, or a template that
+/// names no file, so a bad `--output-template` stops the batch before it starts.
+pub fn validate_output_template(template: &str) -> Result<()> {
+    let sample = expand_output_template(template, |name| {
+        OUTPUT_TEMPLATE_PLACEHOLDERS
+            .contains(&name)
+            .then(|| "x".to_owned())
+    })?;
+    if sample.trim().is_empty() || sample.contains(['/', '\\']) {
+        bail!("output template must produce a file name: {template}");
+    }
+    Ok(())
+}
+
+fn expand_output_template(
+    template: &str,
+    value: impl Fn(&str) -> Option<String>,
+) -> Result<String> {
+    let mut output = String::new();
+    let mut rest = template;
+    while let Some(open) = rest.find(['{', '}']) {
+        output.push_str(&rest[..open]);
+        if rest[open..].starts_with('}') {
+            bail!("unmatched `}}` in output template: {template}");
+        }
+        let Some(close) = rest[open..].find('}') else {
+            bail!("unclosed `{{` in output template: {template}");
+        };
+        let name = &rest[open + 1..open + close];
+        let Some(expanded) = value(name) else {
+            bail!(
+                "unknown placeholder {{{name}}} in output template; expected one of {}",
+                OUTPUT_TEMPLATE_PLACEHOLDERS
+                    .map(|name| format!("{{{name}}}"))
+                    .join(", ")
+            );
+        };
+        output.push_str(&expanded);
+        rest = &rest[open + close + 1..];
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+fn write_per_file_output(
+    index: usize,
+    file_path: &Path,
+    result: &BackTranslationResult,
+    options: &BatchOptions,
+) -> Result<()> {
+    let Some(output_path) = per_file_output_path(file_path, index, options)? else {
+        return Ok(());
+    };
+    if let Some(parent) = output_path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create output directory {}", parent.display()))?;
     }
+    ExportService::default().export_single(result, &output_path, options.output_format, true)
+}
+
+/// Runs `command` through the platform shell once a batch finishes, passing the
+/// run's counts as `TF_TOTAL`, `TF_SUCCESS`, `TF_FAILED`, and `TF_OUTPUT`.
+pub fn run_completion_hook(
+    command: &str,
+    results: &[BatchItemResult],
+    output_path: Option<&Path>,
+) -> Result<ExitStatus> {
+    let successful = results.iter().filter(|item| item.success).count();
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+
+    info!("running batch completion hook: {command}");
+    shell
+        .arg(command)
+        .env("TF_TOTAL", results.len().to_string())
+        .env("TF_SUCCESS", successful.to_string())
+        .env("TF_FAILED", (results.len() - successful).to_string())
+        .env(
+            "TF_OUTPUT",
+            output_path
+                .map(|path| path.display().to_string())
+                .unwrap_or_default(),
+        )
+        .status()
+        .with_context(|| format!("failed to run completion hook `{command}`"))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::{Arc, Mutex};
+
+    use tempfile::TempDir;
+
+    use crate::memory::TranslationMemory;
+    use crate::providers::TranslationProvider;
 
     #[test]
     fn default_batch_options_use_google_unofficial() {
@@ -159,4 +921,512 @@ mod tests {
         assert_eq!(options.provider_id, ProviderId::GoogleUnofficial);
         assert_eq!(options.intermediate_language, "ja");
     }
+
+    #[test]
+    fn remaining_estimate_follows_the_pace_so_far() {
+        assert_eq!(estimate_remaining(Duration::from_secs(3), 0, 10), None);
+
+        // Files finishing every ~2s with some jitter: the estimate shrinks as
+        // files complete and lands on zero at the end.
+        let finished_at = [2.1, 3.9, 6.2, 8.0, 9.8];
+        let estimates: Vec<Duration> = finished_at
+            .iter()
+            .enumerate()
+            .map(|(index, &secs)| {
+                estimate_remaining(Duration::from_secs_f64(secs), index + 1, finished_at.len())
+                    .unwrap()
+            })
+            .collect();
+        assert!(estimates.windows(2).all(|pair| pair[1] < pair[0]));
+        assert_eq!(estimates[0], Duration::from_millis(8400));
+        assert_eq!(estimates[4], Duration::ZERO);
+
+        let progress = BatchProgress {
+            done: 1,
+            total: 5,
+            current_file: "a.txt".to_owned(),
+            finished: None,
+            elapsed_ms: 2100,
+            estimated_remaining_ms: Some(84_000),
+        };
+        assert_eq!(progress.timing_label(), "3s elapsed, about 1m 24s left");
+    }
+
+    #[derive(Debug)]
+    struct UppercaseProvider;
+
+    impl TranslationProvider for UppercaseProvider {
+        fn translate(
+            &self,
+            text: &str,
+            _source_language: &str,
+            _target_language: &str,
+            _cancel_flag: Option<&AtomicBool>,
+        ) -> std::result::Result<String, TranslationError> {
+            if text.contains("fail") {
+                return Err(TranslationError::InvalidResponse("refused".to_owned()));
+            }
+            Ok(text.to_uppercase())
+        }
+    }
+
+    /// A batch processor over a fresh memory in `temp` whose Google provider
+    /// is `provider`.
+    fn processor_with(
+        temp: &TempDir,
+        provider: impl TranslationProvider + 'static,
+    ) -> BatchProcessor {
+        let memory = Arc::new(TranslationMemory::new(&temp.path().join("memory.db"), 100).unwrap());
+        let translator = TranslationService::new(memory)
+            .unwrap()
+            .with_provider(ProviderId::GoogleUnofficial, provider);
+        BatchProcessor::new(translator)
+    }
+
+    #[test]
+    fn per_file_outputs_mirror_nested_input_dirs() {
+        let temp = TempDir::new().unwrap();
+        let processor = processor_with(&temp, UppercaseProvider);
+
+        let input = temp.path().join("input");
+        std::fs::create_dir_all(input.join("guides/advanced")).unwrap();
+        std::fs::write(input.join("intro.txt"), "hello").unwrap();
+        std::fs::write(input.join("guides/advanced/deep.md"), "deep dive").unwrap();
+        std::fs::write(input.join("guides/broken.txt"), "please fail").unwrap();
+        let files = processor.collect_files(&input).unwrap();
+
+        let output = temp.path().join("output");
+        let options = BatchOptions {
+            output_dir: Some(output.clone()),
+            output_format: ExportFormat::Txt,
+            source_root: Some(input.clone()),
+            ..BatchOptions::default()
+        };
+        let results = processor.process_files(&files, &options, &AtomicBool::new(false), |_| {});
+
+        assert_eq!(results.iter().filter(|item| item.success).count(), 2);
+        assert!(output.join("intro.txt").exists());
+        let nested = std::fs::read_to_string(output.join("guides/advanced/deep.txt")).unwrap();
+        assert!(nested.contains("DEEP DIVE"));
+        assert!(!output.join("guides/broken.txt").exists());
+    }
+
+    #[test]
+    fn unwritable_output_dir_fails_the_file_as_io() {
+        let temp = TempDir::new().unwrap();
+        let processor = processor_with(&temp, UppercaseProvider);
+
+        let input = temp.path().join("intro.txt");
+        std::fs::write(&input, "hello").unwrap();
+        let blocker = temp.path().join("not-a-dir");
+        std::fs::write(&blocker, "").unwrap();
+        let options = BatchOptions {
+            output_dir: Some(blocker),
+            output_format: ExportFormat::Txt,
+            ..BatchOptions::default()
+        };
+        let results = processor.process_files(&[input], &options, &AtomicBool::new(false), |_| {});
+
+        assert!(!results[0].success);
+        assert_eq!(results[0].error_kind, Some(BatchErrorKind::Io));
+        let error = results[0].error.as_deref().unwrap();
+        assert!(error.starts_with("failed to create output directory"));
+    }
+
+    #[test]
+    fn auto_source_detects_each_files_language() {
+        let temp = TempDir::new().unwrap();
+        let processor = processor_with(&temp, UppercaseProvider);
+
+        let english = temp.path().join("english.txt");
+        let french = temp.path().join("french.txt");
+        std::fs::write(
+            &english,
+            "The weather is lovely today and we are going for a walk.",
+        )
+        .unwrap();
+        std::fs::write(
+            &french,
+            "Il fait très beau aujourd'hui et nous allons nous promener.",
+        )
+        .unwrap();
+
+        let options = BatchOptions {
+            source_language: None,
+            ..BatchOptions::default()
+        };
+        let results = processor.process_files(
+            &[english, french],
+            &options,
+            &AtomicBool::new(false),
+            |_| {},
+        );
+
+        assert!(results.iter().all(|item| item.success));
+        let detected: Vec<_> = results
+            .iter()
+            .map(|item| item.detected_source.as_deref())
+            .collect();
+        assert_eq!(detected, [Some("en"), Some("fr")]);
+    }
+
+    #[test]
+    fn output_template_names_per_file_outputs() {
+        let options = BatchOptions {
+            output_dir: Some(PathBuf::from("out")),
+            output_format: ExportFormat::Markdown,
+            output_template: Some("{index}-{stem}.{source}-{target}.{provider}.{ext}".to_owned()),
+            source_root: Some(PathBuf::from("docs")),
+            ..BatchOptions::default()
+        };
+
+        let path = per_file_output_path(Path::new("docs/guides/intro.txt"), 2, &options).unwrap();
+        assert_eq!(
+            path,
+            Some(PathBuf::from(
+                "out/guides/3-intro.en-ja.google_unofficial.md"
+            ))
+        );
+
+        assert!(validate_output_template("{stem}.{lang}.{ext}").is_ok());
+        assert!(validate_output_template("{stem}.{language}").is_err());
+        assert!(validate_output_template("{stem").is_err());
+        assert!(validate_output_template("{stem}/{ext}").is_err());
+    }
+
+    #[test]
+    fn resume_skips_files_recorded_in_the_checkpoint() {
+        let temp = TempDir::new().unwrap();
+        let processor = processor_with(&temp, UppercaseProvider);
+
+        let files: Vec<PathBuf> = ["one", "two", "three", "please fail"]
+            .iter()
+            .enumerate()
+            .map(|(index, content)| {
+                let path = temp.path().join(format!("file_{index}.txt"));
+                std::fs::write(&path, content).unwrap();
+                path
+            })
+            .collect();
+        let checkpoint_path = temp.path().join("checkpoint.json");
+        let options = BatchOptions {
+            checkpoint_path: Some(checkpoint_path.clone()),
+            ..BatchOptions::default()
+        };
+        let cancel = AtomicBool::new(false);
+
+        processor.process_files(&files, &options, &cancel, |_| {});
+        let checkpoint = BatchCheckpoint::load(&checkpoint_path).unwrap();
+        assert_eq!(checkpoint.entries().len(), 3);
+
+        // Simulate a crash after the first file was recorded.
+        let truncated = serde_json::to_string(&checkpoint.entries()[..1]).unwrap();
+        std::fs::write(&checkpoint_path, truncated).unwrap();
+
+        let resumed = processor.process_files(
+            &files,
+            &BatchOptions {
+                resume: true,
+                ..options
+            },
+            &cancel,
+            |_| {},
+        );
+        let reprocessed: Vec<&str> = resumed.iter().map(|item| item.file_path.as_str()).collect();
+        let expected: Vec<String> = files[1..]
+            .iter()
+            .map(|path| path.to_string_lossy().to_string())
+            .collect();
+        assert_eq!(reprocessed, expected);
+        assert_eq!(
+            BatchCheckpoint::load(&checkpoint_path)
+                .unwrap()
+                .entries()
+                .len(),
+            3
+        );
+    }
+
+    #[test]
+    fn dry_run_validates_every_file_without_contacting_the_provider() {
+        let temp = TempDir::new().unwrap();
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.set_nonblocking(true).unwrap();
+        let memory = Arc::new(TranslationMemory::new(&temp.path().join("memory.db"), 100).unwrap());
+        let translator = TranslationService::new(memory)
+            .unwrap()
+            .with_endpoint(format!(
+                "http://{}/translate_a/single",
+                listener.local_addr().unwrap()
+            ));
+        let processor = BatchProcessor::new(translator);
+
+        let input = temp.path().join("input");
+        std::fs::create_dir_all(input.join("nested")).unwrap();
+        std::fs::write(input.join("one.txt"), "hello").unwrap();
+        std::fs::write(input.join("nested/two.md"), "# Title").unwrap();
+        std::fs::write(input.join("empty.txt"), "  ").unwrap();
+        let files = processor.collect_files(&input).unwrap();
+
+        let checkpoint_path = temp.path().join("checkpoint.json");
+        let options = BatchOptions {
+            dry_run: true,
+            output_dir: Some(temp.path().join("output")),
+            checkpoint_path: Some(checkpoint_path.clone()),
+            ..BatchOptions::default()
+        };
+        let results = processor.process_files(&files, &options, &AtomicBool::new(false), |_| {});
+
+        assert_eq!(results.len(), 3);
+        let passed: Vec<_> = results.iter().filter(|item| item.success).collect();
+        assert_eq!(passed.len(), 2);
+        assert!(
+            passed
+                .iter()
+                .all(|item| item.back_translated_text.is_empty())
+        );
+        assert!(listener.accept().is_err(), "dry run opened a connection");
+        assert!(!checkpoint_path.exists());
+        assert!(!temp.path().join("output").exists());
+
+        let invalid = BatchOptions {
+            intermediate_language: "not a language".to_owned(),
+            ..options
+        };
+        let results = processor.process_files(&files, &invalid, &AtomicBool::new(false), |_| {});
+        assert!(results.iter().all(|item| !item.success));
+    }
+
+    /// Never answers; returns only once the request is cancelled.
+    #[derive(Debug)]
+    struct StallingProvider;
+
+    impl TranslationProvider for StallingProvider {
+        fn translate(
+            &self,
+            _text: &str,
+            _source_language: &str,
+            _target_language: &str,
+            cancel_flag: Option<&AtomicBool>,
+        ) -> std::result::Result<String, TranslationError> {
+            while !cancel_flag.is_some_and(|flag| flag.load(Ordering::Relaxed)) {
+                thread::sleep(Duration::from_millis(10));
+            }
+            Err(TranslationError::Cancelled)
+        }
+    }
+
+    #[test]
+    fn file_timeout_marks_slow_files_failed_and_moves_on() {
+        let temp = TempDir::new().unwrap();
+        let processor = processor_with(&temp, StallingProvider);
+
+        let files = ["slow_one.txt", "slow_two.txt"].map(|name| {
+            let path = temp.path().join(name);
+            std::fs::write(&path, "A file that never finishes translating").unwrap();
+            path
+        });
+
+        let options = BatchOptions {
+            file_timeout: Some(Duration::from_millis(150)),
+            ..BatchOptions::default()
+        };
+        let cancel = AtomicBool::new(false);
+        let started = Instant::now();
+        let results = processor.process_files(&files, &options, &cancel, |_| {});
+
+        assert_eq!(results.len(), 2);
+        for result in &results {
+            assert!(!result.success);
+            assert!(result.error.as_deref().unwrap().contains("timed out"));
+        }
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn concurrent_batch_keeps_results_in_file_order() {
+        let temp = TempDir::new().unwrap();
+        let processor = processor_with(&temp, UppercaseProvider);
+
+        let files: Vec<PathBuf> = (0..6)
+            .map(|index| {
+                let path = temp.path().join(format!("file_{index}.txt"));
+                std::fs::write(&path, format!("content {index}")).unwrap();
+                path
+            })
+            .collect();
+
+        let options = BatchOptions {
+            concurrency: 3,
+            ..BatchOptions::default()
+        };
+        let cancel = AtomicBool::new(false);
+        let mut last_done = 0;
+        let results = processor.process_files(&files, &options, &cancel, |progress| {
+            assert!(progress.done >= last_done);
+            last_done = progress.done;
+        });
+
+        assert_eq!(last_done, files.len());
+        let paths: Vec<String> = results.iter().map(|item| item.file_path.clone()).collect();
+        let expected: Vec<String> = files
+            .iter()
+            .map(|path| path.to_string_lossy().to_string())
+            .collect();
+        assert_eq!(paths, expected);
+    }
+
+    #[test]
+    fn epub_chapters_translate_concurrently_into_per_chapter_results() {
+        let temp = TempDir::new().unwrap();
+        let processor = processor_with(&temp, UppercaseProvider);
+
+        let chapters: Vec<EpubChapter> = ["opening", "middle", "please fail"]
+            .into_iter()
+            .enumerate()
+            .map(|(order, content)| EpubChapter {
+                title: format!("Chapter {}", order + 1),
+                path: format!("OEBPS/ch{order}.xhtml"),
+                content: content.to_owned(),
+                order,
+            })
+            .collect();
+        let options = BatchOptions {
+            concurrency: 3,
+            ..BatchOptions::default()
+        };
+        let results = processor.process_chapters_with_pause(
+            &chapters,
+            &options,
+            &AtomicBool::new(false),
+            &AtomicBool::new(false),
+            |_| {},
+        );
+
+        let labels: Vec<&str> = results.iter().map(|item| item.file_path.as_str()).collect();
+        assert_eq!(
+            labels,
+            [
+                "OEBPS/ch0.xhtml (Chapter 1)",
+                "OEBPS/ch1.xhtml (Chapter 2)",
+                "OEBPS/ch2.xhtml (Chapter 3)"
+            ]
+        );
+        assert_eq!(results[1].back_translated_text, "MIDDLE");
+        assert!(!results[2].success);
+    }
+
+    #[derive(Debug)]
+    struct SlowProvider;
+
+    impl TranslationProvider for SlowProvider {
+        fn translate(
+            &self,
+            text: &str,
+            _source_language: &str,
+            _target_language: &str,
+            _cancel_flag: Option<&AtomicBool>,
+        ) -> std::result::Result<String, TranslationError> {
+            thread::sleep(Duration::from_millis(50));
+            Ok(text.to_owned())
+        }
+    }
+
+    #[test]
+    fn paused_batch_starts_no_new_files_until_resumed() {
+        let temp = TempDir::new().unwrap();
+        let processor = processor_with(&temp, SlowProvider);
+        let files: Vec<PathBuf> = (0..3)
+            .map(|index| {
+                let path = temp.path().join(format!("file_{index}.txt"));
+                std::fs::write(&path, format!("content {index}")).unwrap();
+                path
+            })
+            .collect();
+
+        let cancel = AtomicBool::new(false);
+        let pause = AtomicBool::new(false);
+        let paused_at = Mutex::new(None);
+        let mut started_at = Vec::new();
+        let results = thread::scope(|scope| {
+            scope.spawn(|| {
+                while paused_at.lock().unwrap().is_none() {
+                    thread::sleep(Duration::from_millis(5));
+                }
+                thread::sleep(Duration::from_millis(300));
+                pause.store(false, Ordering::Relaxed);
+            });
+            processor.process_files_with_pause(
+                &files,
+                &BatchOptions::default(),
+                &cancel,
+                &pause,
+                |progress| {
+                    if progress.finished.is_some() {
+                        return;
+                    }
+                    started_at.push(Instant::now());
+                    // Pause while the first file is still translating.
+                    if progress.done == 0 {
+                        pause.store(true, Ordering::Relaxed);
+                        *paused_at.lock().unwrap() = Some(Instant::now());
+                    }
+                },
+            )
+        });
+
+        assert_eq!(results.len(), 3);
+        assert!(results.iter().all(|item| item.success));
+        let paused_at = paused_at.into_inner().unwrap().unwrap();
+        assert!(started_at[1] - paused_at >= Duration::from_millis(250));
+    }
+
+    #[test]
+    fn cancelled_batch_starts_no_new_files() {
+        let temp = TempDir::new().unwrap();
+        let processor = processor_with(&temp, UppercaseProvider);
+        let files: Vec<PathBuf> = (0..8)
+            .map(|index| temp.path().join(format!("file_{index}.txt")))
+            .collect();
+
+        let options = BatchOptions {
+            concurrency: 4,
+            ..BatchOptions::default()
+        };
+        let cancel = AtomicBool::new(true);
+        let mut progress_events = 0;
+        let results = processor.process_files(&files, &options, &cancel, |_| progress_events += 1);
+
+        assert!(results.is_empty());
+        assert_eq!(progress_events, 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn completion_hook_receives_counts_in_env() {
+        let temp = TempDir::new().unwrap();
+        let output = temp.path().join("hook.txt");
+        let item = |success: bool| BatchItemResult {
+            file_path: "a.txt".to_owned(),
+            success,
+            original_text: String::new(),
+            intermediate_text: String::new(),
+            back_translated_text: String::new(),
+            error: None,
+            error_kind: None,
+            detected_source: None,
+            suspicious_reason: None,
+            duration_ms: 1,
+        };
+
+        let status = run_completion_hook(
+            "printf '%s %s %s' \"$TF_TOTAL\" \"$TF_SUCCESS\" \"$TF_FAILED\" > \"$TF_OUTPUT\"",
+            &[item(true), item(true), item(false)],
+            Some(&output),
+        )
+        .unwrap();
+
+        assert!(status.success());
+        assert_eq!(std::fs::read_to_string(&output).unwrap(), "3 2 1");
+    }
 }
@@ -0,0 +1,218 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::diff::{DiffSummary, diff_words};
+use crate::models::BatchItemResult;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatusChange {
+    Unchanged,
+    Fixed,
+    Regressed,
+    Added,
+    Removed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileChange {
+    pub file_path: String,
+    pub status: StatusChange,
+    pub success_before: Option<bool>,
+    pub success_after: Option<bool>,
+    pub intermediate: DiffSummary,
+    pub back_translation: DiffSummary,
+    pub duration_delta_ms: i128,
+}
+
+impl FileChange {
+    pub fn text_changed(&self) -> bool {
+        self.intermediate.has_changes() || self.back_translation.has_changes()
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BatchRunDiff {
+    pub matched: usize,
+    pub only_in_before: usize,
+    pub only_in_after: usize,
+    pub fixed: usize,
+    pub regressed: usize,
+    pub text_changed: usize,
+    pub changes: Vec<FileChange>,
+}
+
+/// Loads the `results` array from a batch JSON export (or a bare array of items).
+pub fn load_batch_report(path: &Path) -> Result<Vec<BatchItemResult>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("failed to read batch report {}", path.display()))?;
+    let parsed: Value = serde_json::from_str(&content)
+        .with_context(|| format!("failed to parse batch report {}", path.display()))?;
+
+    let results = match parsed {
+        Value::Object(mut object) => match object.remove("results") {
+            Some(results) => results,
+            None => bail!("batch report {} has no results array", path.display()),
+        },
+        Value::Array(_) => parsed,
+        _ => bail!("batch report {} is not a JSON object", path.display()),
+    };
+
+    serde_json::from_value(results)
+        .with_context(|| format!("failed to read batch items from {}", path.display()))
+}
+
+/// Matches items by file path and reports how each file changed between two runs.
+pub fn compare_runs(before: &[BatchItemResult], after: &[BatchItemResult]) -> BatchRunDiff {
+    let mut paired: BTreeMap<&str, (Option<&BatchItemResult>, Option<&BatchItemResult>)> =
+        BTreeMap::new();
+    for item in before {
+        paired.entry(item.file_path.as_str()).or_default().0 = Some(item);
+    }
+    for item in after {
+        paired.entry(item.file_path.as_str()).or_default().1 = Some(item);
+    }
+
+    let mut diff = BatchRunDiff::default();
+    for (file_path, pair) in paired {
+        let change = match pair {
+            (Some(left), Some(right)) => {
+                diff.matched += 1;
+                let status = match (left.success, right.success) {
+                    (false, true) => StatusChange::Fixed,
+                    (true, false) => StatusChange::Regressed,
+                    _ => StatusChange::Unchanged,
+                };
+                FileChange {
+                    file_path: file_path.to_owned(),
+                    status,
+                    success_before: Some(left.success),
+                    success_after: Some(right.success),
+                    intermediate: DiffSummary::from_ops(&diff_words(
+                        &left.intermediate_text,
+                        &right.intermediate_text,
+                    )),
+                    back_translation: DiffSummary::from_ops(&diff_words(
+                        &left.back_translated_text,
+                        &right.back_translated_text,
+                    )),
+                    duration_delta_ms: right.duration_ms as i128 - left.duration_ms as i128,
+                }
+            }
+            (Some(left), None) => {
+                diff.only_in_before += 1;
+                FileChange {
+                    file_path: file_path.to_owned(),
+                    status: StatusChange::Removed,
+                    success_before: Some(left.success),
+                    success_after: None,
+                    intermediate: DiffSummary::default(),
+                    back_translation: DiffSummary::default(),
+                    duration_delta_ms: 0,
+                }
+            }
+            (None, Some(right)) => {
+                diff.only_in_after += 1;
+                FileChange {
+                    file_path: file_path.to_owned(),
+                    status: StatusChange::Added,
+                    success_before: None,
+                    success_after: Some(right.success),
+                    intermediate: DiffSummary::default(),
+                    back_translation: DiffSummary::default(),
+                    duration_delta_ms: 0,
+                }
+            }
+            (None, None) => continue,
+        };
+
+        match change.status {
+            StatusChange::Fixed => diff.fixed += 1,
+            StatusChange::Regressed => diff.regressed += 1,
+            _ => {}
+        }
+        if change.text_changed() {
+            diff.text_changed += 1;
+        }
+        diff.changes.push(change);
+    }
+
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn item(path: &str, success: bool, back: &str) -> BatchItemResult {
+        BatchItemResult {
+            file_path: path.to_owned(),
+            success,
+            original_text: String::new(),
+            intermediate_text: String::new(),
+            back_translated_text: back.to_owned(),
+            error: None,
+            error_kind: None,
+            detected_source: None,
+            suspicious_reason: None,
+            duration_ms: 100,
+        }
+    }
+
+    #[test]
+    fn reports_status_and_text_changes_by_path() {
+        let before = vec![
+            item("a.txt", true, "hello world"),
+            item("b.txt", false, ""),
+            item("c.txt", true, "same text"),
+        ];
+        let after = vec![
+            item("a.txt", true, "hello there world"),
+            item("b.txt", true, "fixed now"),
+            item("d.txt", true, "new file"),
+        ];
+
+        let diff = compare_runs(&before, &after);
+
+        assert_eq!(diff.matched, 2);
+        assert_eq!(diff.only_in_before, 1);
+        assert_eq!(diff.only_in_after, 1);
+        assert_eq!(diff.fixed, 1);
+        assert_eq!(diff.regressed, 0);
+
+        let a = diff
+            .changes
+            .iter()
+            .find(|c| c.file_path == "a.txt")
+            .unwrap();
+        assert_eq!(a.status, StatusChange::Unchanged);
+        assert_eq!(a.back_translation.inserted, 1);
+        let c = diff
+            .changes
+            .iter()
+            .find(|c| c.file_path == "c.txt")
+            .unwrap();
+        assert_eq!(c.status, StatusChange::Removed);
+    }
+
+    #[test]
+    fn loads_results_from_exported_report() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("run.json");
+        let payload = serde_json::json!({
+            "metadata": null,
+            "results": [item("a.txt", true, "hello")],
+        });
+        fs::write(&path, payload.to_string()).unwrap();
+
+        let results = load_batch_report(&path).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].back_translated_text, "hello");
+    }
+}
@@ -0,0 +1,16 @@
+pub const TOOL_VERSION: &str = env!("CARGO_PKG_VERSION");
+pub const GIT_HASH: &str = env!("TF_GIT_HASH");
+pub const BUILD_TIMESTAMP: &str = env!("TF_BUILD_TIMESTAMP");
+
+/// Git hash and build time, e.g. `a1b2c3d, built 2025-01-01T00:00:00Z`.
+pub fn build_details() -> String {
+    format!("{GIT_HASH}, built {BUILD_TIMESTAMP}")
+}
+
+/// Full identifier used in logs and export footers.
+pub fn build_label() -> String {
+    format!(
+        "TranslationFiesta Rust {TOOL_VERSION} ({})",
+        build_details()
+    )
+}
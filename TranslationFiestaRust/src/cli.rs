@@ -1,28 +1,153 @@
+use std::fs;
+use std::io::{self, BufWriter, IsTerminal, Read, Write};
+use std::ops::Range;
 use std::path::{Path, PathBuf};
-use std::sync::atomic::AtomicBool;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 
-use anyhow::{Result, bail};
-use clap::{Parser, Subcommand};
+use anyhow::{Context, Result, bail};
+use clap::{ArgAction, Parser, Subcommand};
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use serde::Serialize;
+use serde_json::{Value, json};
 
 use crate::app_paths::AppPaths;
-use crate::batch::{BatchOptions, BatchProcessor};
+use crate::batch::{
+    BATCH_CHECKPOINT_FILE, BatchOptions, BatchProcessor, retain_failed_files, run_completion_hook,
+    validate_output_template,
+};
+use crate::batch_diff::{BatchRunDiff, StatusChange, compare_runs, load_batch_report};
+use crate::config::EffectiveConfig;
+use crate::epub::{
+    EpubRepackOptions, clamp_chapter_range, extract_text_range, load_epub, repack_metadata,
+    translate_chapters,
+};
 use crate::export::{BatchExportContext, ExportService};
-use crate::file_service::load_text;
+use crate::file_service::{
+    ScanOptions, SupportedFileType, TextLoadOptions, load_structured, load_text_with_options,
+    read_text_from,
+};
+use crate::history::{history_as_batch_items, history_export_context, load_history, save_history};
+use crate::language::guess_language;
 use crate::memory::TranslationMemory;
-use crate::models::{ExportFormat, ProviderId};
-use crate::translation::TranslationService;
+use crate::models::{
+    BackTranslationResult, BatchErrorKind, BatchItemResult, ExportFormat, ProviderId,
+};
+use crate::settings::{AppSettings, load_settings, save_settings};
+use crate::structured::StructuredDocument;
+use crate::subtitle::{export_srt, is_srt_path};
+use crate::text_stats::text_stats;
+use crate::translation::{TranslationError, TranslationService};
 
 #[derive(Debug, Parser)]
 #[command(name = "translation-fiesta-rust")]
 #[command(about = "TranslationFiesta Rust desktop + CLI port", long_about = None)]
 pub struct CliArgs {
+    /// Seed for retry jitter, making retry timing reproducible.
+    #[arg(long, global = true)]
+    pub seed: Option<u64>,
+    /// Emit machine-readable JSON on stdout; human-readable progress goes to stderr.
+    #[arg(long, global = true)]
+    pub json: bool,
+    /// Keep runtime data in `data/` beside the executable instead of the
+    /// platform data directory.
+    #[arg(long, global = true)]
+    pub portable: bool,
+    /// Ignore cached translations and ask the provider again; the new
+    /// results replace the cached ones.
+    #[arg(long, global = true)]
+    pub no_cache: bool,
+    /// When failed batch items make the process exit non-zero: `any` (exit 2
+    /// for some failures, 1 when every item failed), `all` (exit 1 only when
+    /// every item failed), or `none`.
+    #[arg(long, global = true, value_name = "POLICY", default_value = "any")]
+    pub fail_on: FailOn,
+    /// Also print logs to stderr: `-v` for info, `-vv` for debug, `-vvv` for trace.
+    #[arg(short, long, action = ArgAction::Count, global = true)]
+    pub verbose: u8,
     #[command(subcommand)]
     pub command: Option<CliCommand>,
 }
 
+impl CliArgs {
+    /// `tracing` filter for the stderr log `--verbose` asks for, if any.
+    pub fn console_log_level(&self) -> Option<&'static str> {
+        match self.verbose {
+            0 => None,
+            1 => Some("info"),
+            2 => Some("debug"),
+            _ => Some("trace"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FailOn {
+    #[default]
+    Any,
+    All,
+    None,
+}
+
+impl FailOn {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Any => "any",
+            Self::All => "all",
+            Self::None => "none",
+        }
+    }
+}
+
+impl FromStr for FailOn {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [Self::Any, Self::All, Self::None]
+            .into_iter()
+            .find(|policy| policy.as_str() == s.trim().to_ascii_lowercase())
+            .ok_or_else(|| format!("unknown failure policy: {s} (expected any, all, or none)"))
+    }
+}
+
+/// What `execute` did, so `main` can start the GUI or pick an exit code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CliOutcome {
+    /// No command ran; the GUI should start.
+    Gui,
+    /// The command finished, with `failed` of its `total` items failing.
+    Finished { total: usize, failed: usize },
+}
+
+impl CliOutcome {
+    /// A finished command without items that fail one by one.
+    pub const DONE: Self = Self::Finished {
+        total: 0,
+        failed: 0,
+    };
+
+    /// 0 on success, 2 when `policy` is `any` and some items failed, and 1
+    /// when every item failed under `any` or `all`.
+    pub fn exit_code(self, policy: FailOn) -> u8 {
+        let Self::Finished { total, failed } = self else {
+            return 0;
+        };
+        match policy {
+            _ if failed == 0 => 0,
+            FailOn::Any | FailOn::All if failed >= total => 1,
+            FailOn::Any => 2,
+            FailOn::All | FailOn::None => 0,
+        }
+    }
+}
+
+// Parsed once per process, so the size of the Batch variant does not matter.
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Subcommand)]
 pub enum CliCommand {
     Gui,
+    /// Back-translate TEXT, or standard input when TEXT is `-`.
     Translate {
         text: String,
         #[arg(long, default_value = "en")]
@@ -31,11 +156,21 @@ pub enum CliCommand {
         intermediate: String,
         #[arg(long, default_value = "google_unofficial")]
         provider: String,
+        /// Output file; an `.srt` input saved as `.srt` keeps its cue timings.
         #[arg(long)]
         output: Option<PathBuf>,
-        #[arg(long, default_value = "txt")]
-        format: String,
+        /// Export format; without --output the export is written to stdout.
+        #[arg(long)]
+        format: Option<String>,
+        /// Print only the back-translated text; diagnostics go to stderr.
+        #[arg(long, short)]
+        quiet: bool,
+        /// Validate the input and languages and report the planned requests
+        /// without calling the provider.
+        #[arg(long)]
+        dry_run: bool,
     },
+    /// Back-translate a file, or standard input when PATH is `-`.
     File {
         path: PathBuf,
         #[arg(long, default_value = "en")]
@@ -46,11 +181,35 @@ pub enum CliCommand {
         provider: String,
         #[arg(long)]
         output: Option<PathBuf>,
-        #[arg(long, default_value = "txt")]
-        format: String,
+        /// Export format; without --output the export is written to stdout.
+        #[arg(long)]
+        format: Option<String>,
+        /// Print only the back-translated text; diagnostics go to stderr.
+        #[arg(long, short)]
+        quiet: bool,
+        /// Keep the original spacing instead of collapsing whitespace.
+        #[arg(long)]
+        preserve_whitespace: bool,
+        /// Translate only the text of a Markdown file, keeping headings,
+        /// emphasis, links, and code intact.
+        #[arg(long)]
+        preserve_markup: bool,
+        /// Only these chapters of an EPUB, counted from 1, e.g. `3..5` or `7`.
+        #[arg(
+            long,
+            value_name = "RANGE",
+            value_parser = parse_chapter_range,
+            conflicts_with = "preserve_markup"
+        )]
+        chapters: Option<Range<usize>>,
+        /// Validate the input and languages and report the planned requests
+        /// without calling the provider.
+        #[arg(long)]
+        dry_run: bool,
     },
     Batch {
         directory: PathBuf,
+        /// Source language of every file, or `auto` to detect it per file.
         #[arg(long, default_value = "en")]
         source: String,
         #[arg(long, default_value = "ja")]
@@ -61,40 +220,250 @@ pub enum CliCommand {
         output: Option<PathBuf>,
         #[arg(long, default_value = "txt")]
         format: String,
+        /// Write these formats (e.g. `json,csv,html`) into a zip at --output
+        /// instead of a single report.
+        #[arg(
+            long,
+            value_name = "FORMATS",
+            value_delimiter = ',',
+            requires = "output"
+        )]
+        bundle: Vec<String>,
+        /// Only translate files whose path under DIRECTORY matches this glob (repeatable).
+        /// Patterns without a `/` match the file name.
+        #[arg(long, value_name = "GLOB")]
+        include: Vec<String>,
+        /// Skip files whose path under DIRECTORY matches this glob (repeatable).
+        #[arg(long, value_name = "GLOB")]
+        exclude: Vec<String>,
+        /// Directory levels to scan; 1 means only files directly in DIRECTORY.
+        #[arg(long, value_parser = clap::value_parser!(u16).range(1..))]
+        depth: Option<u16>,
+        /// Give up on a single file after this many seconds and move on.
+        #[arg(long, value_name = "SECONDS")]
+        file_timeout: Option<u64>,
+        /// Keep the original spacing instead of collapsing whitespace.
+        #[arg(long)]
+        preserve_whitespace: bool,
+        /// Files translated in parallel. Values above 1 raise the chance of the
+        /// unofficial endpoint rate limiting or blocking you; pair with --rate-limit.
+        #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u16).range(1..=32))]
+        concurrency: u16,
+        /// Maximum provider requests per second, shared across all workers.
+        #[arg(long, value_name = "REQUESTS_PER_SECOND")]
+        rate_limit: Option<f64>,
+        /// Shell command to run after the batch finishes; overrides the saved setting.
+        #[arg(long, value_name = "COMMAND")]
+        on_complete: Option<String>,
+        /// Also export each translated file into DIR, mirroring the input tree.
+        #[arg(long, value_name = "DIR")]
+        per_file_out: Option<PathBuf>,
+        /// Format of the files written by --per-file-out.
+        #[arg(long, default_value = "txt")]
+        per_file_format: String,
+        /// File name for each --per-file-out export, built from {stem}, {ext},
+        /// {source}, {target} (or {lang}), {provider}, and {index}.
+        #[arg(long, value_name = "TEMPLATE", requires = "per_file_out")]
+        output_template: Option<String>,
+        /// Skip files a previous, interrupted run already finished.
+        #[arg(long)]
+        resume: bool,
+        /// Stream one JSON result per line as each file finishes.
+        #[arg(long)]
+        ndjson: bool,
+        /// Also write a short rollup (counts, timings, failed files) to PATH;
+        /// the format follows its extension: txt, md, html, or json.
+        #[arg(long, value_name = "PATH")]
+        summary: Option<PathBuf>,
+        /// Only rerun the files that failed with this kind of error in
+        /// --previous-report: cancelled, rate_limited, network, invalid_input,
+        /// blocked, or io.
+        #[arg(long, value_name = "KIND", requires = "previous_report")]
+        retry_failed: Option<BatchErrorKind>,
+        /// JSON batch report of the run whose failures --retry-failed reruns.
+        #[arg(long, value_name = "REPORT")]
+        previous_report: Option<PathBuf>,
+        /// Load every file and validate the languages without calling the
+        /// provider or writing any output.
+        #[arg(long)]
+        dry_run: bool,
+        /// Print a line per file instead of the progress bar shown on a terminal.
+        #[arg(long)]
+        no_progress: bool,
     },
     Memory {
         #[command(subcommand)]
         command: MemoryCommand,
     },
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+    /// Manage the translation history shown in the desktop History tab.
+    History {
+        #[command(subcommand)]
+        command: HistoryCommand,
+    },
+    /// Translate every chapter of an EPUB and write a rebuilt EPUB.
+    EpubTranslate {
+        input: PathBuf,
+        #[arg(long)]
+        out: PathBuf,
+        #[arg(long, default_value = "en")]
+        source: String,
+        #[arg(long, default_value = "ja")]
+        target: String,
+        #[arg(long, default_value = "google_unofficial")]
+        provider: String,
+        /// Translate the book title and author as well.
+        #[arg(long)]
+        translate_metadata: bool,
+        /// Only translate these chapters, counted from 1, e.g. `3..5`; the
+        /// others are copied unchanged.
+        #[arg(long, value_name = "RANGE", value_parser = parse_chapter_range)]
+        chapters: Option<Range<usize>>,
+    },
+    /// Print the detected language of TEXT (`-` for stdin) or of --file.
+    Detect {
+        #[arg(required_unless_present = "file", conflicts_with = "file")]
+        text: Option<String>,
+        #[arg(long)]
+        file: Option<PathBuf>,
+    },
+    /// Compare two exported batch JSON reports file by file.
+    DiffRuns {
+        before: PathBuf,
+        after: PathBuf,
+    },
 }
 
 #[derive(Debug, Subcommand)]
 pub enum MemoryCommand {
-    Stats,
+    Stats {
+        /// Break entry and access counts down by provider or language pair.
+        #[arg(long, value_parser = ["provider", "pair"])]
+        by: Option<String>,
+    },
     Clear,
+    /// Rebuild the translation memory indexes.
+    Reindex,
+    /// Reclaim unused space in the translation memory database.
+    Compact,
     Search {
         query: String,
         #[arg(long, default_value_t = 20)]
         limit: usize,
     },
+    /// Delete entries older than the configured `entry_ttl_days`.
+    Purge,
+    /// Delete one entry by the cache key `memory search` prints.
+    Delete {
+        key: String,
+    },
+    /// Load entries from a JSON or CSV file written by `memory export`.
+    Import {
+        path: PathBuf,
+        /// Clear the memory first instead of merging into it.
+        #[arg(long)]
+        replace: bool,
+    },
+    /// Dump every translation memory entry to a JSON or CSV file.
+    Export {
+        #[arg(long, default_value = "json")]
+        format: String,
+        #[arg(long)]
+        out: PathBuf,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum HistoryCommand {
+    /// Delete every recorded translation.
+    Clear,
+    /// Write the history, newest first, as a report in any export format.
+    Export {
+        #[arg(long, default_value = "json")]
+        format: String,
+        #[arg(long)]
+        out: PathBuf,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommand {
+    /// Print the resolved settings, paths, and TF_* overrides (secrets redacted).
+    Show,
+    /// Print one saved setting.
+    Get { key: String },
+    /// Save one setting; out-of-range values are clamped like in the GUI.
+    Set { key: String, value: String },
+    /// Print the location of settings.json.
+    Path,
 }
 
 #[derive(Clone)]
 pub struct CliRuntime {
     pub paths: AppPaths,
+    pub settings: AppSettings,
     pub translator: TranslationService,
     pub batch: BatchProcessor,
     pub export: ExportService,
     pub memory: std::sync::Arc<TranslationMemory>,
 }
 
-pub fn execute(args: &CliArgs, runtime: &CliRuntime) -> Result<bool> {
+/// The bar needs `out` to be a terminal; piped and machine-readable output
+/// keeps a line per file.
+fn shows_progress_bar(no_progress: bool, machine_readable: bool, out_is_terminal: bool) -> bool {
+    !no_progress && !machine_readable && out_is_terminal
+}
+
+/// A bar on stdout that replaces the per-file progress lines.
+fn batch_progress_bar(total: usize) -> ProgressBar {
+    let bar = ProgressBar::with_draw_target(Some(total as u64), ProgressDrawTarget::stdout());
+    bar.set_style(
+        ProgressStyle::with_template("{bar:30} {pos}/{len} {wide_msg}")
+            .expect("batch progress template is valid"),
+    );
+    bar
+}
+
+/// Path argument meaning standard input.
+const STDIN_PATH: &str = "-";
+
+pub fn execute(args: &CliArgs, runtime: &CliRuntime) -> Result<CliOutcome> {
+    let terminal = io::stdout().is_terminal();
+    execute_with_streams(args, runtime, &mut io::stdin(), &mut io::stdout(), terminal)
+}
+
+/// `execute` with injectable streams: `translate -` and `file -` read from
+/// `input`, and their results go to `output`. `output` is never treated as a
+/// terminal, so batch progress is always written to it line by line.
+pub fn execute_with_io(
+    args: &CliArgs,
+    runtime: &CliRuntime,
+    input: &mut dyn Read,
+    output: &mut dyn Write,
+) -> Result<CliOutcome> {
+    execute_with_streams(args, runtime, input, output, false)
+}
+
+/// `output_is_terminal` is only set when `output` is the process stdout,
+/// which is where the batch progress bar draws.
+fn execute_with_streams(
+    args: &CliArgs,
+    runtime: &CliRuntime,
+    input: &mut dyn Read,
+    output: &mut dyn Write,
+    output_is_terminal: bool,
+) -> Result<CliOutcome> {
+    let out = output;
+    let json = args.json;
     let Some(command) = &args.command else {
-        return Ok(false);
+        return Ok(CliOutcome::Gui);
     };
 
     match command {
-        CliCommand::Gui => Ok(false),
+        CliCommand::Gui => Ok(CliOutcome::Gui),
         CliCommand::Translate {
             text,
             source,
@@ -102,26 +471,40 @@ pub fn execute(args: &CliArgs, runtime: &CliRuntime) -> Result<bool> {
             provider,
             output,
             format,
+            quiet,
+            dry_run,
         } => {
+            let text = if text == STDIN_PATH {
+                read_text_from(input, load_options(false, &runtime.settings))?
+            } else {
+                text.clone()
+            };
             let provider = ProviderId::normalize(provider);
-            let cancel = AtomicBool::new(false);
-            let result = runtime.translator.back_translate(
-                text,
-                Some(source.as_str()),
-                intermediate,
-                provider,
-                Some(&cancel),
-            )?;
-
-            print_single_result(&result);
-
-            if let Some(path) = output {
-                let format = parse_format(format, path)?;
-                runtime.export.export_single(&result, path, format, true)?;
-                println!("\nSaved to {}", path.display());
+            if *dry_run {
+                write_dry_run(runtime, &text, source, intermediate, provider, json, out)?;
+                return Ok(CliOutcome::DONE);
             }
+            let cancel = AtomicBool::new(false);
+            let result = runtime
+                .translator
+                .back_translate(
+                    &text,
+                    Some(source.as_str()),
+                    intermediate,
+                    provider,
+                    Some(&cancel),
+                )
+                .inspect_err(print_partial_intermediate)?;
 
-            Ok(true)
+            write_single_output(
+                runtime,
+                &result,
+                output.as_deref(),
+                format,
+                OutputMode::new(json, *quiet),
+                out,
+            )?;
+            Ok(CliOutcome::DONE)
         }
         CliCommand::File {
             path,
@@ -130,28 +513,87 @@ pub fn execute(args: &CliArgs, runtime: &CliRuntime) -> Result<bool> {
             provider,
             output,
             format,
+            quiet,
+            preserve_whitespace,
+            preserve_markup,
+            chapters,
+            dry_run,
         } => {
-            let content = load_text(path)?;
+            let options = load_options(*preserve_whitespace, &runtime.settings);
+            let from_stdin = path.as_os_str() == STDIN_PATH;
+            if chapters.is_some()
+                && (from_stdin || SupportedFileType::detect(path) != Some(SupportedFileType::Epub))
+            {
+                bail!("--chapters needs an .epub input file");
+            }
+            let document = match (*preserve_markup, from_stdin) {
+                (false, _) => None,
+                (true, true) => Some(StructuredDocument::parse_markdown(&read_text_from(
+                    &mut *input,
+                    load_options(true, &runtime.settings),
+                )?)),
+                (true, false) => Some(load_structured(path, options)?),
+            };
+            let content = match &document {
+                Some(document) => document.plain_text(),
+                None if from_stdin => read_text_from(input, options)?,
+                None => match chapters {
+                    Some(range) => {
+                        extract_text_range(path, range.clone(), options.normalize_whitespace)?
+                    }
+                    None => load_text_with_options(path, options)?,
+                },
+            };
             let provider = ProviderId::normalize(provider);
+            if *dry_run {
+                write_dry_run(runtime, &content, source, intermediate, provider, json, out)?;
+                return Ok(CliOutcome::DONE);
+            }
             let cancel = AtomicBool::new(false);
-            let result = runtime.translator.back_translate(
-                &content,
-                Some(source.as_str()),
-                intermediate,
-                provider,
-                Some(&cancel),
-            )?;
-
-            println!("File: {}", path.display());
-            print_single_result(&result);
-
-            if let Some(path) = output {
-                let format = parse_format(format, path)?;
-                runtime.export.export_single(&result, path, format, true)?;
-                println!("\nSaved to {}", path.display());
+            let result = match &document {
+                Some(document) => runtime.translator.back_translate_structured(
+                    document,
+                    Some(source.as_str()),
+                    intermediate,
+                    provider,
+                    Some(&cancel),
+                ),
+                None => runtime.translator.back_translate(
+                    &content,
+                    Some(source.as_str()),
+                    intermediate,
+                    provider,
+                    Some(&cancel),
+                ),
             }
+            .inspect_err(print_partial_intermediate)?;
 
-            Ok(true)
+            if !from_stdin && !*quiet && !json && format.is_none() {
+                writeln!(out, "File: {}", path.display())?;
+            }
+            if let Some(srt_path) = output.as_deref().filter(|target| is_srt_path(target)) {
+                if from_stdin || !is_srt_path(path) {
+                    bail!("saving subtitles as .srt requires an .srt input file");
+                }
+                let mode = OutputMode::new(json, *quiet);
+                mode.write_result(&result, out)?;
+                export_srt(path, &result.back_translated_text, srt_path)?;
+                if mode == OutputMode::Summary {
+                    writeln!(out, "\nSaved to {}", srt_path.display())?;
+                } else {
+                    eprintln!("Saved to {}", srt_path.display());
+                }
+                return Ok(CliOutcome::DONE);
+            }
+            write_single_output(
+                runtime,
+                &result,
+                output.as_deref(),
+                format,
+                OutputMode::new(json, *quiet),
+                out,
+            )?;
+            Ok(CliOutcome::DONE)
         }
         CliCommand::Batch {
             directory,
@@ -160,92 +602,653 @@ pub fn execute(args: &CliArgs, runtime: &CliRuntime) -> Result<bool> {
             provider,
             output,
             format,
+            bundle,
+            include,
+            exclude,
+            depth,
+            file_timeout,
+            preserve_whitespace,
+            concurrency,
+            rate_limit,
+            on_complete,
+            per_file_out,
+            per_file_format,
+            output_template,
+            resume,
+            ndjson,
+            summary: summary_path,
+            retry_failed,
+            previous_report,
+            dry_run,
+            no_progress,
         } => {
-            let files = runtime.batch.collect_files(directory)?;
+            let machine_readable = json || *ndjson;
+            if let Some(template) = output_template {
+                validate_output_template(template)?;
+            }
+            let scan = ScanOptions::with_patterns(include, exclude)?
+                .with_max_depth(depth.map(usize::from));
+            let mut files = runtime.batch.collect_files_with_options(directory, &scan)?;
+            if let (Some(kind), Some(report)) = (retry_failed, previous_report) {
+                let previous = load_batch_report(report)?;
+                retain_failed_files(&mut files, &previous, *kind);
+                if files.is_empty() {
+                    if json {
+                        write_json(out, &Vec::<BatchItemResult>::new())?;
+                    } else if !*ndjson {
+                        writeln!(out, "No files failed with {kind} in {}", report.display())?;
+                    }
+                    return Ok(CliOutcome::DONE);
+                }
+            }
             if files.is_empty() {
-                println!("No supported files found in {}", directory.display());
-                return Ok(true);
+                if json {
+                    write_json(out, &Vec::<BatchItemResult>::new())?;
+                } else if !*ndjson {
+                    writeln!(out, "No supported files found in {}", directory.display())?;
+                }
+                return Ok(CliOutcome::DONE);
             }
 
-            println!("Processing {} files...", files.len());
+            if machine_readable {
+                eprintln!("Processing {} files...", files.len());
+            } else {
+                writeln!(out, "Processing {} files...", files.len())?;
+            }
 
             let cancel = AtomicBool::new(false);
             let options = BatchOptions {
-                source_language: Some(source.clone()),
+                source_language: (!source.trim().eq_ignore_ascii_case("auto"))
+                    .then(|| source.clone()),
                 intermediate_language: intermediate.clone(),
                 provider_id: ProviderId::normalize(provider),
+                file_timeout: file_timeout.map(Duration::from_secs),
+                load_options: load_options(*preserve_whitespace, &runtime.settings),
+                concurrency: usize::from(*concurrency),
+                output_dir: per_file_out.clone(),
+                output_format: per_file_format
+                    .parse()
+                    .map_err(|error: String| anyhow::anyhow!(error))?,
+                output_template: output_template.clone(),
+                source_root: Some(directory.clone()),
+                checkpoint_path: Some(runtime.paths.data_root.join(BATCH_CHECKPOINT_FILE)),
+                resume: *resume,
+                dry_run: *dry_run,
             };
 
-            let results = runtime
-                .batch
-                .process_files(&files, &options, &cancel, |progress| {
-                    println!(
-                        "{}/{} - {}",
-                        progress.done, progress.total, progress.current_file
-                    );
-                });
+            let processor = match rate_limit {
+                Some(rate) => BatchProcessor::new(
+                    runtime
+                        .translator
+                        .clone()
+                        .with_min_request_interval(rate_limit_interval(*rate)?),
+                ),
+                None => runtime.batch.clone(),
+            };
+
+            let bar = shows_progress_bar(*no_progress, machine_readable, output_is_terminal)
+                .then(|| batch_progress_bar(files.len()));
+            let mut write_error = None;
+            let results = processor.process_files(&files, &options, &cancel, |progress| {
+                if let Some(bar) = &bar {
+                    bar.set_position(progress.done as u64);
+                    bar.set_message(format!(
+                        "{} ({})",
+                        progress.current_file,
+                        progress.timing_label()
+                    ));
+                    return;
+                }
+                let detected = progress
+                    .finished
+                    .as_ref()
+                    .and_then(|item| item.detected_source.as_deref())
+                    .map(|source| format!(" [{source}]"))
+                    .unwrap_or_default();
+                let line = format!(
+                    "{}/{} - {}{detected} ({})",
+                    progress.done,
+                    progress.total,
+                    progress.current_file,
+                    progress.timing_label()
+                );
+                let written = match &progress.finished {
+                    Some(item) if *ndjson => serde_json::to_string(item)
+                        .map_err(io::Error::from)
+                        .and_then(|item| writeln!(out, "{item}")),
+                    _ if machine_readable => {
+                        eprintln!("{line}");
+                        Ok(())
+                    }
+                    _ => writeln!(out, "{line}"),
+                };
+                if let Err(error) = written {
+                    write_error.get_or_insert(error);
+                }
+            });
+            if let Some(bar) = bar {
+                bar.finish_and_clear();
+            }
+            if let Some(error) = write_error {
+                return Err(error).context("failed to write batch progress");
+            }
 
             let successful = results.iter().filter(|item| item.success).count();
             let failed = results.len().saturating_sub(successful);
 
-            println!("\nBatch complete");
-            println!("Total: {}", results.len());
-            println!("Successful: {}", successful);
-            println!("Failed: {}", failed);
+            let summary = format!(
+                "\n{}\nTotal: {}\nSuccessful: {successful}\nFailed: {failed}",
+                if *dry_run {
+                    "Dry run complete"
+                } else {
+                    "Batch complete"
+                },
+                results.len()
+            );
+            if json && !*ndjson {
+                write_json(out, &results)?;
+            }
+            if machine_readable {
+                eprintln!("{summary}");
+            } else {
+                writeln!(out, "{summary}")?;
+            }
+            for item in results
+                .iter()
+                .filter(|item| item.suspicious_reason.is_some())
+            {
+                eprintln!(
+                    "Warning: {}: {}",
+                    item.file_path,
+                    item.suspicious_reason.as_deref().unwrap_or_default()
+                );
+            }
+
+            let outcome = CliOutcome::Finished {
+                total: results.len(),
+                failed,
+            };
+            if *dry_run {
+                return Ok(outcome);
+            }
 
             if let Some(path) = output {
-                let format = parse_format(format, path)?;
-                runtime.export.export_batch(
-                    &results,
-                    path,
-                    format,
-                    BatchExportContext {
-                        include_metadata: true,
-                        source_language: source,
-                        target_language: intermediate,
-                        provider: ProviderId::normalize(provider).as_str(),
-                    },
-                )?;
-                println!("Saved batch report to {}", path.display());
+                let context = BatchExportContext {
+                    include_metadata: true,
+                    source_language: source,
+                    target_language: intermediate,
+                    provider: ProviderId::normalize(provider).as_str(),
+                };
+                if bundle.is_empty() {
+                    let format = parse_format(format, path)?;
+                    runtime
+                        .export
+                        .export_batch(&results, path, format, context)?;
+                } else {
+                    let formats = bundle
+                        .iter()
+                        .map(|format| format.parse::<ExportFormat>())
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(|error| anyhow::anyhow!(error))?;
+                    runtime
+                        .export
+                        .export_bundle(&results, path, &formats, context)?;
+                }
+                if machine_readable {
+                    eprintln!("Saved batch report to {}", path.display());
+                } else {
+                    writeln!(out, "Saved batch report to {}", path.display())?;
+                }
+            }
+
+            if let Some(path) = summary_path {
+                let format = ExportFormat::from_path(path).unwrap_or(ExportFormat::Txt);
+                runtime
+                    .export
+                    .export_batch_summary(&results, path, format)?;
+                if machine_readable {
+                    eprintln!("Saved batch summary to {}", path.display());
+                } else {
+                    writeln!(out, "Saved batch summary to {}", path.display())?;
+                }
+            }
+
+            let hook = on_complete
+                .as_deref()
+                .unwrap_or(&runtime.settings.on_complete_command);
+            if !hook.trim().is_empty() && !cancel.load(Ordering::Relaxed) {
+                let status = run_completion_hook(hook, &results, output.as_deref())?;
+                if !status.success() {
+                    eprintln!("Completion hook exited with {status}");
+                }
             }
 
-            Ok(true)
+            Ok(outcome)
         }
         CliCommand::Memory { command } => {
             match command {
-                MemoryCommand::Stats => {
+                MemoryCommand::Stats { by: Some(by) } => {
+                    let groups = if by == "provider" {
+                        runtime.memory.stats_by_provider()?
+                    } else {
+                        runtime.memory.stats_by_language_pair()?
+                    };
+                    if json {
+                        write_json(out, &groups)?;
+                    } else if groups.is_empty() {
+                        writeln!(out, "No memory entries")?;
+                    } else {
+                        for (key, group) in &groups {
+                            writeln!(
+                                out,
+                                "{key}: {} entries, {} accesses, {} reused",
+                                group.entries,
+                                group.total_accesses,
+                                group.reuses()
+                            )?;
+                        }
+                    }
+                }
+                MemoryCommand::Stats { by: None } => {
                     let stats = runtime.memory.stats()?;
-                    println!("Entries: {} / {}", stats.total_entries, stats.max_entries);
-                    println!("Hits: {}", stats.total_hits);
-                    println!("Misses: {}", stats.total_misses);
-                    println!("Lookups: {}", stats.total_lookups);
-                    println!("Hit Rate: {:.2}%", stats.hit_rate * 100.0);
-                    println!("Avg Lookup: {:.2} ms", stats.avg_lookup_ms);
+                    if json {
+                        write_json(out, &stats)?;
+                    } else {
+                        writeln!(
+                            out,
+                            "Entries: {} / {}",
+                            stats.total_entries, stats.max_entries
+                        )?;
+                        writeln!(out, "Hits: {}", stats.total_hits)?;
+                        writeln!(out, "Misses: {}", stats.total_misses)?;
+                        writeln!(out, "Lookups: {}", stats.total_lookups)?;
+                        writeln!(out, "Hit Rate: {:.2}%", stats.hit_rate * 100.0)?;
+                        writeln!(out, "Avg Lookup: {:.2} ms", stats.avg_lookup_ms)?;
+                    }
                 }
                 MemoryCommand::Clear => {
                     runtime.memory.clear()?;
-                    println!("Translation memory cleared");
+                    write_status(
+                        out,
+                        json,
+                        json!({ "cleared": true }),
+                        "Translation memory cleared",
+                    )?;
+                }
+                MemoryCommand::Reindex => {
+                    runtime.memory.reindex()?;
+                    write_status(
+                        out,
+                        json,
+                        json!({ "reindexed": true }),
+                        "Translation memory reindexed",
+                    )?;
+                }
+                MemoryCommand::Compact => {
+                    runtime.memory.compact()?;
+                    write_status(
+                        out,
+                        json,
+                        json!({ "compacted": true }),
+                        "Translation memory compacted",
+                    )?;
+                }
+                MemoryCommand::Purge => {
+                    let ttl_days = runtime.settings.entry_ttl_days;
+                    let removed = runtime.memory.purge_expired()?;
+                    let message = if ttl_days == 0 {
+                        "No entry_ttl_days configured; nothing to purge".to_owned()
+                    } else {
+                        format!("Purged {removed} entries older than {ttl_days} days")
+                    };
+                    write_status(
+                        out,
+                        json,
+                        json!({ "removed": removed, "entry_ttl_days": ttl_days }),
+                        &message,
+                    )?;
+                }
+                MemoryCommand::Delete { key } => {
+                    if !runtime.memory.delete(key)? {
+                        bail!("no memory entry has the key {key:?}");
+                    }
+                    write_status(
+                        out,
+                        json,
+                        json!({ "deleted": key }),
+                        "Deleted 1 memory entry",
+                    )?;
+                }
+                MemoryCommand::Import { path, replace } => {
+                    let count = runtime.memory.import(path, !replace)?;
+                    write_status(
+                        out,
+                        json,
+                        json!({ "imported": count, "path": path }),
+                        &format!("Imported {count} memory entries from {}", path.display()),
+                    )?;
+                }
+                MemoryCommand::Export {
+                    format,
+                    out: out_path,
+                } => {
+                    let format = parse_format(format, out_path)?;
+                    if let Some(parent) = out_path.parent() {
+                        fs::create_dir_all(parent).with_context(|| {
+                            format!("failed to create output directory {}", parent.display())
+                        })?;
+                    }
+                    let file = fs::File::create(out_path)
+                        .with_context(|| format!("failed to create {}", out_path.display()))?;
+                    let count = runtime.memory.export_to(format, BufWriter::new(file))?;
+                    write_status(
+                        out,
+                        json,
+                        json!({ "exported": count, "path": out_path }),
+                        &format!("Exported {count} memory entries to {}", out_path.display()),
+                    )?;
                 }
                 MemoryCommand::Search { query, limit } => {
                     let items = runtime.memory.search(query, *limit)?;
-                    if items.is_empty() {
-                        println!("No memory entries matched '{query}'");
+                    if json {
+                        write_json(out, &items)?;
+                    } else if items.is_empty() {
+                        writeln!(out, "No memory entries matched '{query}'")?;
                     } else {
                         for (index, item) in items.iter().enumerate() {
-                            println!(
+                            writeln!(
+                                out,
                                 "{}. {} -> {} ({} | {} uses)",
                                 index + 1,
                                 truncate(&item.source_text, 48),
                                 truncate(&item.translated_text, 48),
                                 item.provider_id,
                                 item.access_count,
-                            );
+                            )?;
+                            writeln!(out, "   key: {}", item.cache_key)?;
                         }
                     }
                 }
             }
-            Ok(true)
+            Ok(CliOutcome::DONE)
+        }
+        CliCommand::History { command } => {
+            match command {
+                HistoryCommand::Clear => {
+                    let removed = load_history(&runtime.paths.history_file).len();
+                    save_history(&runtime.paths.history_file, &[])?;
+                    write_status(
+                        out,
+                        json,
+                        json!({ "cleared": removed }),
+                        &format!("Cleared {removed} history entries"),
+                    )?;
+                }
+                HistoryCommand::Export {
+                    format,
+                    out: out_path,
+                } => {
+                    let format = parse_format(format, out_path)?;
+                    let entries = load_history(&runtime.paths.history_file);
+                    if let Some(parent) = out_path.parent() {
+                        fs::create_dir_all(parent).with_context(|| {
+                            format!("failed to create output directory {}", parent.display())
+                        })?;
+                    }
+                    runtime.export.export_batch(
+                        &history_as_batch_items(&entries),
+                        out_path,
+                        format,
+                        history_export_context(&entries, true),
+                    )?;
+                    write_status(
+                        out,
+                        json,
+                        json!({ "exported": entries.len(), "path": out_path }),
+                        &format!(
+                            "Exported {} history entries to {}",
+                            entries.len(),
+                            out_path.display()
+                        ),
+                    )?;
+                }
+            }
+            Ok(CliOutcome::DONE)
+        }
+        CliCommand::Config {
+            command: ConfigCommand::Show,
+        } => {
+            let config = EffectiveConfig::resolve(&runtime.settings, &runtime.paths);
+            if json {
+                write_json(out, &config)?;
+            } else {
+                write!(out, "{}", config.render())?;
+            }
+            Ok(CliOutcome::DONE)
+        }
+        CliCommand::Config {
+            command: ConfigCommand::Get { key },
+        } => {
+            let value = load_settings(&runtime.paths.settings_file).get(key)?;
+            write_status(
+                out,
+                json,
+                json!({ "key": key, "value": value }),
+                &setting_text(&value),
+            )?;
+            Ok(CliOutcome::DONE)
+        }
+        CliCommand::Config {
+            command: ConfigCommand::Set { key, value },
+        } => {
+            let path = &runtime.paths.settings_file;
+            let mut settings = load_settings(path);
+            settings.set(key, value)?;
+            save_settings(path, &settings)?;
+            let saved = settings.get(key)?;
+            write_status(
+                out,
+                json,
+                json!({ "key": key, "value": saved }),
+                &format!("{key} = {}", setting_text(&saved)),
+            )?;
+            Ok(CliOutcome::DONE)
+        }
+        CliCommand::Config {
+            command: ConfigCommand::Path,
+        } => {
+            let path = runtime.paths.settings_file.display().to_string();
+            write_status(out, json, json!({ "path": path }), &path)?;
+            Ok(CliOutcome::DONE)
+        }
+        CliCommand::EpubTranslate {
+            input,
+            out: out_path,
+            source,
+            target,
+            provider,
+            translate_metadata,
+            chapters,
+        } => {
+            let book = load_epub(input, true)?;
+            let selected = clamp_chapter_range(
+                chapters.clone().unwrap_or(0..book.chapters.len()),
+                book.chapters.len(),
+            );
+            let provider = ProviderId::normalize(provider);
+            let cancel = AtomicBool::new(false);
+            let translate = |text: &str| {
+                runtime
+                    .translator
+                    .translate_text(text, source, target, provider, Some(&cancel))
+            };
+
+            let mut progress = |line: String| {
+                if json {
+                    eprintln!("{line}");
+                    Ok(())
+                } else {
+                    writeln!(out, "{line}")
+                }
+            };
+            progress(format!("Translating {} chapters...", selected.len()))?;
+            let mut current = None;
+            let mut write_error = None;
+            let chapters = translate_chapters(&book, |chapter, line| {
+                if !selected.contains(&chapter.order) {
+                    return Ok(line.to_owned());
+                }
+                if current != Some(chapter.order) {
+                    current = Some(chapter.order);
+                    let written = progress(format!(
+                        "[{}/{}] {}",
+                        chapter.order + 1,
+                        book.chapters.len(),
+                        chapter.title
+                    ));
+                    if let Err(error) = written {
+                        write_error.get_or_insert(error);
+                    }
+                }
+                translate(line)
+            })?;
+            if let Some(error) = write_error {
+                return Err(error).context("failed to write EPUB progress");
+            }
+            let options = EpubRepackOptions {
+                translate_metadata: *translate_metadata,
+            };
+            let metadata = repack_metadata(&book, &options, translate)?;
+
+            runtime
+                .export
+                .export_epub_with_metadata(&book, &chapters, &metadata, out_path)?;
+            progress(format!("Saved to {}", out_path.display()))?;
+            Ok(CliOutcome::DONE)
+        }
+        CliCommand::Detect { text, file } => {
+            let sample = match (text.as_deref(), file) {
+                (_, Some(path)) => {
+                    load_text_with_options(path, load_options(false, &runtime.settings))?
+                }
+                (Some(STDIN_PATH), None) => {
+                    read_text_from(input, load_options(false, &runtime.settings))?
+                }
+                (Some(text), None) => text.to_owned(),
+                (None, None) => bail!("pass TEXT, `-`, or --file"),
+            };
+            let guess = guess_language(&sample);
+            if json {
+                write_json(out, &guess)?;
+            } else {
+                let Some(guess) = guess else {
+                    bail!("could not detect a language");
+                };
+                let note = if guess.reliable { "" } else { ", unreliable" };
+                writeln!(
+                    out,
+                    "{} (confidence {:.0}%{note})",
+                    guess.code,
+                    guess.confidence * 100.0
+                )?;
+                writeln!(out, "{}", text_stats(&sample))?;
+            }
+            Ok(CliOutcome::DONE)
         }
+        CliCommand::DiffRuns { before, after } => {
+            let diff = compare_runs(&load_batch_report(before)?, &load_batch_report(after)?);
+            if json {
+                write_json(out, &diff)?;
+            } else {
+                print_run_diff(&diff, out)?;
+            }
+            Ok(CliOutcome::DONE)
+        }
+    }
+}
+
+fn print_run_diff(diff: &BatchRunDiff, out: &mut dyn Write) -> io::Result<()> {
+    writeln!(
+        out,
+        "Matched: {} | Only in before: {} | Only in after: {}",
+        diff.matched, diff.only_in_before, diff.only_in_after
+    )?;
+    writeln!(
+        out,
+        "Fixed: {} | Regressed: {} | Text changed: {}",
+        diff.fixed, diff.regressed, diff.text_changed
+    )?;
+
+    for change in &diff.changes {
+        let status = match change.status {
+            StatusChange::Unchanged if !change.text_changed() => continue,
+            StatusChange::Unchanged => "changed",
+            StatusChange::Fixed => "fixed",
+            StatusChange::Regressed => "regressed",
+            StatusChange::Added => "added",
+            StatusChange::Removed => "removed",
+        };
+        writeln!(
+            out,
+            "\n[{status}] {}\n  back translation: {:.0}% similar (+{} / -{} words)\n  intermediate: {:.0}% similar (+{} / -{} words)\n  duration: {:+} ms",
+            change.file_path,
+            change.back_translation.similarity() * 100.0,
+            change.back_translation.inserted,
+            change.back_translation.deleted,
+            change.intermediate.similarity() * 100.0,
+            change.intermediate.inserted,
+            change.intermediate.deleted,
+            change.duration_delta_ms,
+        )?;
+    }
+    Ok(())
+}
+
+fn rate_limit_interval(requests_per_second: f64) -> Result<Duration> {
+    if !requests_per_second.is_finite() || requests_per_second <= 0.0 {
+        bail!("--rate-limit must be a positive number of requests per second");
+    }
+    Ok(Duration::from_secs_f64(1.0 / requests_per_second))
+}
+
+/// Parses a 1-based inclusive chapter range (`3..5`, `7`, `3..`, `..5`) into
+/// zero-based indexes.
+fn parse_chapter_range(value: &str) -> std::result::Result<Range<usize>, String> {
+    let number = |part: &str| {
+        part.trim()
+            .parse::<usize>()
+            .ok()
+            .filter(|&number| number > 0)
+            .ok_or_else(|| format!("chapter numbers start at 1, got {part:?}"))
+    };
+    let (first, last) = match value.split_once("..") {
+        Some((first, last)) => (
+            if first.trim().is_empty() {
+                1
+            } else {
+                number(first)?
+            },
+            if last.trim().is_empty() {
+                usize::MAX
+            } else {
+                number(last)?
+            },
+        ),
+        None => {
+            let chapter = number(value)?;
+            (chapter, chapter)
+        }
+    };
+    if first > last {
+        return Err(format!("chapter range {value:?} ends before it starts"));
+    }
+    Ok(first - 1..last)
+}
+
+fn load_options(preserve_whitespace: bool, settings: &AppSettings) -> TextLoadOptions {
+    TextLoadOptions {
+        normalize_whitespace: !preserve_whitespace,
+        ..settings.text_load_options()
     }
 }
 
@@ -261,25 +1264,171 @@ fn parse_format(format: &str, output_path: &Path) -> Result<ExportFormat> {
     bail!("unsupported export format: {format}")
 }
 
-fn print_single_result(result: &crate::models::BackTranslationResult) {
-    println!();
-    println!("ORIGINAL");
-    println!("{}", result.original_text);
-    println!(
+fn print_partial_intermediate(error: &TranslationError) {
+    if let Some(intermediate) = error.partial_intermediate() {
+        eprintln!("INTERMEDIATE (back translation failed)");
+        eprintln!("{intermediate}");
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputMode {
+    Summary,
+    Quiet,
+    Json,
+}
+
+impl OutputMode {
+    fn new(json: bool, quiet: bool) -> Self {
+        if json {
+            Self::Json
+        } else if quiet {
+            Self::Quiet
+        } else {
+            Self::Summary
+        }
+    }
+
+    fn write_result(self, result: &BackTranslationResult, out: &mut dyn Write) -> Result<()> {
+        match self {
+            Self::Summary => print_single_result(result, out)?,
+            Self::Quiet => writeln!(out, "{}", result.back_translated_text)?,
+            Self::Json => write_json(out, result)?,
+        }
+        Ok(())
+    }
+}
+
+/// Saves to `output_path` when given; otherwise writes the export (with
+/// `--format`) or the result in `mode` to `out`.
+fn write_single_output(
+    runtime: &CliRuntime,
+    result: &BackTranslationResult,
+    output_path: Option<&Path>,
+    format: &Option<String>,
+    mode: OutputMode,
+    out: &mut dyn Write,
+) -> Result<()> {
+    if let Some(reason) = &result.suspicious_reason {
+        eprintln!("Warning: {reason}");
+    }
+
+    if let Some(path) = output_path {
+        mode.write_result(result, out)?;
+        let format = parse_format(format.as_deref().unwrap_or("txt"), path)?;
+        runtime.export.export_single(result, path, format, true)?;
+        if mode == OutputMode::Summary {
+            writeln!(out, "\nSaved to {}", path.display())?;
+        } else {
+            eprintln!("Saved to {}", path.display());
+        }
+        return Ok(());
+    }
+
+    match format {
+        Some(format) if mode != OutputMode::Json => {
+            let format = format
+                .parse::<ExportFormat>()
+                .map_err(|error| anyhow::anyhow!(error))?;
+            out.write_all(&runtime.export.render_single(result, format, true)?)?;
+        }
+        _ => mode.write_result(result, out)?,
+    }
+    Ok(())
+}
+
+fn write_json(out: &mut dyn Write, value: &impl Serialize) -> Result<()> {
+    serde_json::to_writer_pretty(&mut *out, value)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+/// Strings print as written, everything else as JSON.
+fn setting_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Validates a single back-translation and reports what it would send.
+fn write_dry_run(
+    runtime: &CliRuntime,
+    text: &str,
+    source: &str,
+    intermediate: &str,
+    provider: ProviderId,
+    json: bool,
+    out: &mut dyn Write,
+) -> Result<()> {
+    let source =
+        runtime
+            .translator
+            .validate_back_translation(text, Some(source), intermediate, provider)?;
+    let stats = text_stats(text.trim());
+    let requests = runtime.translator.estimated_round_trip_requests(text);
+    write_status(
+        out,
+        json,
+        json!({
+            "dry_run": true,
+            "source_language": source,
+            "intermediate_language": intermediate,
+            "provider": provider.as_str(),
+            "words": stats.words,
+            "characters": stats.chars,
+            "requests": requests,
+        }),
+        &format!(
+            "Dry run: {stats} would go {source} -> {intermediate} -> {source} via {} in {requests} request(s)",
+            provider.as_str()
+        ),
+    )
+}
+
+/// A one-line confirmation, or `value` as JSON.
+fn write_status(out: &mut dyn Write, json: bool, value: Value, message: &str) -> Result<()> {
+    if json {
+        write_json(out, &value)
+    } else {
+        writeln!(out, "{message}")?;
+        Ok(())
+    }
+}
+
+fn print_single_result(result: &BackTranslationResult, out: &mut dyn Write) -> io::Result<()> {
+    writeln!(out)?;
+    writeln!(out, "ORIGINAL")?;
+    writeln!(out, "{}", result.original_text)?;
+    writeln!(
+        out,
         "\nINTERMEDIATE ({})",
         result.intermediate_language.to_uppercase()
-    );
-    println!("{}", result.intermediate_text);
-    println!(
+    )?;
+    writeln!(out, "{}", result.intermediate_text)?;
+    writeln!(
+        out,
         "\nBACK TRANSLATED ({})",
         result.source_language.to_uppercase()
-    );
-    println!("{}", result.back_translated_text);
-    println!(
-        "\nProvider: {} | Duration: {:.2}s",
+    )?;
+    writeln!(out, "{}", result.back_translated_text)?;
+    writeln!(
+        out,
+        "\nProvider: {} | Duration: {:.2}s{} | Fidelity: {:.0}%",
         result.provider_id,
-        result.duration_ms as f64 / 1000.0
-    );
+        result.duration_ms as f64 / 1000.0,
+        result
+            .hop_breakdown()
+            .map(|hops| format!(" ({hops})"))
+            .unwrap_or_default(),
+        result.fidelity_score * 100.0
+    )?;
+    writeln!(
+        out,
+        "Original: {} | Back translated: {}",
+        text_stats(&result.original_text),
+        text_stats(&result.back_translated_text)
+    )
 }
 
 fn truncate(value: &str, max_len: usize) -> String {
@@ -291,3 +1440,389 @@ fn truncate(value: &str, max_len: usize) -> String {
         truncated
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    use tempfile::TempDir;
+
+    use crate::providers::TranslationProvider;
+
+    #[derive(Debug)]
+    struct TaggingProvider;
+
+    impl TranslationProvider for TaggingProvider {
+        fn translate(
+            &self,
+            text: &str,
+            _source_language: &str,
+            target_language: &str,
+            _cancel_flag: Option<&AtomicBool>,
+        ) -> std::result::Result<String, TranslationError> {
+            Ok(format!("{text} <{target_language}>"))
+        }
+    }
+
+    fn runtime(temp: &TempDir) -> CliRuntime {
+        let root = temp.path().to_path_buf();
+        let paths = AppPaths {
+            app_root: root.clone(),
+            data_root: root.join("data"),
+            logs_dir: root.join("data/logs"),
+            exports_dir: root.join("data/exports"),
+            settings_file: root.join("data/settings.json"),
+            memory_db_file: root.join("data/translation_memory.db"),
+            history_file: root.join("data/history.json"),
+        };
+        let memory = Arc::new(TranslationMemory::new(&paths.memory_db_file, 100).unwrap());
+        let translator = TranslationService::new(Arc::clone(&memory))
+            .unwrap()
+            .with_provider(ProviderId::GoogleUnofficial, TaggingProvider);
+        CliRuntime {
+            paths,
+            settings: AppSettings::default(),
+            batch: BatchProcessor::new(translator.clone()),
+            translator,
+            export: ExportService::default(),
+            memory,
+        }
+    }
+
+    fn run(args: &[&str], stdin: &str, runtime: &CliRuntime) -> String {
+        let args = CliArgs::try_parse_from(
+            std::iter::once("translation-fiesta-rust").chain(args.iter().copied()),
+        )
+        .unwrap();
+        let mut output = Vec::new();
+        let outcome = execute_with_io(&args, runtime, &mut stdin.as_bytes(), &mut output).unwrap();
+        assert_ne!(outcome, CliOutcome::Gui);
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn quiet_translate_reads_stdin_and_prints_only_the_back_translation() {
+        let temp = TempDir::new().unwrap();
+        let runtime = runtime(&temp);
+
+        let output = run(&["translate", "-", "--quiet"], "hello\n", &runtime);
+        assert_eq!(output, "hello <ja> <en>\n");
+
+        let summary = run(&["translate", "hello"], "", &runtime);
+        assert!(summary.contains("BACK TRANSLATED (EN)"));
+    }
+
+    #[test]
+    fn config_set_persists_values_that_get_reads_back() {
+        let temp = TempDir::new().unwrap();
+        let runtime = runtime(&temp);
+
+        let set = run(
+            &["config", "set", "intermediate_language", "de"],
+            "",
+            &runtime,
+        );
+        assert_eq!(set, "intermediate_language = de\n");
+        let set = run(&["config", "set", "retry_max_attempts", "50"], "", &runtime);
+        assert_eq!(set, "retry_max_attempts = 10\n");
+
+        assert_eq!(
+            run(&["config", "get", "intermediate_language"], "", &runtime),
+            "de\n"
+        );
+        let value = run(
+            &["--json", "config", "get", "retry_max_attempts"],
+            "",
+            &runtime,
+        );
+        assert_eq!(serde_json::from_str::<Value>(&value).unwrap()["value"], 10);
+        assert_eq!(
+            run(&["config", "path"], "", &runtime).trim_end(),
+            runtime.paths.settings_file.display().to_string()
+        );
+
+        let args =
+            CliArgs::try_parse_from(["translation-fiesta-rust", "config", "get", "nope"]).unwrap();
+        let error = execute_with_io(&args, &runtime, &mut "".as_bytes(), &mut Vec::new())
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("valid keys:"), "{error}");
+    }
+
+    #[test]
+    fn dry_run_reports_requests_without_translating() {
+        let temp = TempDir::new().unwrap();
+        let runtime = runtime(&temp);
+
+        let summary = run(&["translate", "hello world", "--dry-run"], "", &runtime);
+        assert_eq!(
+            summary,
+            "Dry run: 2 words, 11 characters would go en -> ja -> en via google_unofficial in 2 request(s)\n"
+        );
+        let report = run(&["--json", "translate", "-", "--dry-run"], "hi", &runtime);
+        let report: Value = serde_json::from_str(&report).unwrap();
+        assert_eq!(report["requests"], 2);
+        assert_eq!(runtime.memory.stats().unwrap().total_entries, 0);
+
+        let args = CliArgs::try_parse_from([
+            "translation-fiesta-rust",
+            "translate",
+            "hello",
+            "--intermediate",
+            "not a code",
+            "--dry-run",
+        ])
+        .unwrap();
+        assert!(execute_with_io(&args, &runtime, &mut "".as_bytes(), &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn diff_runs_writes_its_report_to_the_output_stream() {
+        let temp = TempDir::new().unwrap();
+        let runtime = runtime(&temp);
+        let input = temp.path().join("input");
+        std::fs::create_dir_all(&input).unwrap();
+        std::fs::write(input.join("a.txt"), "hello").unwrap();
+        let report = temp.path().join("report.json");
+        let results = run(
+            &["--json", "batch", input.to_str().unwrap(), "--dry-run"],
+            "",
+            &runtime,
+        );
+        std::fs::write(&report, results).unwrap();
+
+        let report = report.to_str().unwrap();
+        let output = run(&["diff-runs", report, report], "", &runtime);
+        assert!(output.starts_with("Matched: 1 |"), "{output}");
+    }
+
+    #[test]
+    fn no_progress_batch_prints_a_line_per_file() {
+        assert!(shows_progress_bar(false, false, true));
+        assert!(!shows_progress_bar(true, false, true));
+        assert!(!shows_progress_bar(false, true, true));
+        assert!(!shows_progress_bar(false, false, false));
+
+        let temp = TempDir::new().unwrap();
+        let input = temp.path().join("input");
+        std::fs::create_dir_all(&input).unwrap();
+        std::fs::write(input.join("a.txt"), "hello").unwrap();
+        std::fs::write(input.join("b.txt"), "world").unwrap();
+        let output = run(
+            &[
+                "batch",
+                input.to_str().unwrap(),
+                "--no-progress",
+                "--dry-run",
+            ],
+            "",
+            &runtime(&temp),
+        );
+        assert!(output.contains("2/2 - "), "{output}");
+        assert!(output.contains("Successful: 2"), "{output}");
+        // An injected writer is never a terminal, so it keeps the lines too.
+        let buffered = run(
+            &["batch", input.to_str().unwrap(), "--dry-run"],
+            "",
+            &runtime(&temp),
+        );
+        assert!(buffered.contains("2/2 - "), "{buffered}");
+    }
+
+    #[test]
+    fn failure_policy_picks_the_exit_code() {
+        let partial = CliOutcome::Finished {
+            total: 3,
+            failed: 1,
+        };
+        let total = CliOutcome::Finished {
+            total: 3,
+            failed: 3,
+        };
+        assert_eq!(partial.exit_code(FailOn::Any), 2);
+        assert_eq!(total.exit_code(FailOn::Any), 1);
+        assert_eq!(partial.exit_code(FailOn::All), 0);
+        assert_eq!(total.exit_code(FailOn::All), 1);
+        assert_eq!(total.exit_code(FailOn::None), 0);
+        assert_eq!(CliOutcome::DONE.exit_code(FailOn::Any), 0);
+
+        let temp = TempDir::new().unwrap();
+        let runtime = runtime(&temp);
+        let input = temp.path().join("input");
+        std::fs::create_dir_all(&input).unwrap();
+        std::fs::write(input.join("a.txt"), "hello").unwrap();
+        let args = CliArgs::try_parse_from([
+            "translation-fiesta-rust",
+            "--fail-on",
+            "all",
+            "batch",
+            input.to_str().unwrap(),
+            "--intermediate",
+            "not a code",
+            "--dry-run",
+        ])
+        .unwrap();
+        let outcome =
+            execute_with_io(&args, &runtime, &mut "".as_bytes(), &mut Vec::new()).unwrap();
+        assert_eq!(
+            outcome,
+            CliOutcome::Finished {
+                total: 1,
+                failed: 1
+            }
+        );
+        assert_eq!(outcome.exit_code(args.fail_on), 1);
+    }
+
+    #[test]
+    fn repeated_verbose_flags_raise_the_console_log_level() {
+        let level = |flags: &[&str]| {
+            let args = ["translation-fiesta-rust"]
+                .iter()
+                .chain(flags)
+                .chain(&["config", "path"]);
+            CliArgs::try_parse_from(args).unwrap().console_log_level()
+        };
+        assert_eq!(level(&[]), None);
+        assert_eq!(level(&["-v"]), Some("info"));
+        assert_eq!(level(&["-vv"]), Some("debug"));
+        assert_eq!(level(&["--verbose", "-vv"]), Some("trace"));
+    }
+
+    #[test]
+    fn chapter_ranges_are_one_based_and_inclusive() {
+        assert_eq!(parse_chapter_range("3..5"), Ok(2..5));
+        assert_eq!(parse_chapter_range("7"), Ok(6..7));
+        assert_eq!(parse_chapter_range("..2"), Ok(0..2));
+        assert_eq!(parse_chapter_range("4.."), Ok(3..usize::MAX));
+        assert!(parse_chapter_range("0..2").is_err());
+        assert!(parse_chapter_range("5..3").is_err());
+
+        let temp = TempDir::new().unwrap();
+        let text = temp.path().join("notes.txt");
+        std::fs::write(&text, "hello").unwrap();
+        let args = CliArgs::try_parse_from([
+            "translation-fiesta-rust",
+            "file",
+            text.to_str().unwrap(),
+            "--chapters",
+            "1..2",
+        ])
+        .unwrap();
+        let error = execute_with_io(&args, &runtime(&temp), &mut "".as_bytes(), &mut Vec::new())
+            .unwrap_err();
+        assert!(error.to_string().contains(".epub"), "{error}");
+    }
+
+    #[test]
+    fn json_mode_emits_parseable_models() {
+        let temp = TempDir::new().unwrap();
+        let runtime = runtime(&temp);
+
+        let single = run(&["translate", "hello", "--json"], "", &runtime);
+        let result: BackTranslationResult = serde_json::from_str(&single).unwrap();
+        assert_eq!(result.back_translated_text, "hello <ja> <en>");
+
+        let stats = run(&["--json", "memory", "stats"], "", &runtime);
+        let stats: crate::models::MemoryStats = serde_json::from_str(&stats).unwrap();
+        assert_eq!(stats.total_entries, 2);
+
+        let docs = temp.path().join("docs");
+        std::fs::create_dir_all(&docs).unwrap();
+        std::fs::write(docs.join("a.txt"), "first").unwrap();
+        std::fs::write(docs.join("b.txt"), "second").unwrap();
+        let docs = docs.to_string_lossy().to_string();
+
+        let batch = run(&["batch", &docs, "--json"], "", &runtime);
+        let items: Vec<BatchItemResult> = serde_json::from_str(&batch).unwrap();
+        assert_eq!(items.len(), 2);
+        assert!(items.iter().all(|item| item.success));
+
+        let streamed = run(&["batch", &docs, "--ndjson"], "", &runtime);
+        let lines: Vec<BatchItemResult> = streamed
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+    }
+
+    #[test]
+    fn detect_reports_japanese_from_stdin() {
+        let temp = TempDir::new().unwrap();
+        let runtime = runtime(&temp);
+
+        let output = run(
+            &["detect", "-"],
+            "これは日本語で書かれた文章です。今日は天気がいいですね。",
+            &runtime,
+        );
+        assert!(output.starts_with("ja "), "{output}");
+        assert!(output.ends_with("characters\n"), "{output}");
+    }
+
+    #[test]
+    fn history_export_writes_entries_and_clear_empties_the_file() {
+        let temp = TempDir::new().unwrap();
+        let runtime = runtime(&temp);
+        let entry = BackTranslationResult::new(
+            "hello".to_owned(),
+            "こんにちは".to_owned(),
+            "hi".to_owned(),
+            "en".to_owned(),
+            "ja".to_owned(),
+            ProviderId::GoogleUnofficial,
+            Duration::from_millis(5),
+        );
+        save_history(&runtime.paths.history_file, &[entry]).unwrap();
+
+        let report = temp.path().join("history.json");
+        let output = run(
+            &["history", "export", "--out", report.to_str().unwrap()],
+            "",
+            &runtime,
+        );
+        assert!(output.starts_with("Exported 1 history entries"));
+        assert!(fs::read_to_string(&report).unwrap().contains("こんにちは"));
+
+        assert_eq!(
+            run(&["history", "clear"], "", &runtime),
+            "Cleared 1 history entries\n"
+        );
+        assert!(load_history(&runtime.paths.history_file).is_empty());
+    }
+
+    #[test]
+    fn memory_stats_can_be_grouped_by_provider() {
+        let temp = TempDir::new().unwrap();
+        let runtime = runtime(&temp);
+        runtime
+            .memory
+            .store("hello", "こんにちは", "en", "ja", "google_unofficial")
+            .unwrap();
+        runtime
+            .memory
+            .store("hello", "bonjour", "en", "fr", "deepl")
+            .unwrap();
+
+        assert_eq!(
+            run(&["memory", "stats", "--by", "provider"], "", &runtime),
+            "deepl: 1 entries, 1 accesses, 0 reused\ngoogle_unofficial: 1 entries, 1 accesses, 0 reused\n"
+        );
+        assert!(
+            run(&["memory", "stats", "--by", "pair"], "", &runtime)
+                .starts_with("en->fr: 1 entries")
+        );
+    }
+
+    #[test]
+    fn file_from_stdin_streams_the_export_to_stdout() {
+        let temp = TempDir::new().unwrap();
+        let runtime = runtime(&temp);
+
+        let output = run(&["file", "-", "--format", "md"], "A document.", &runtime);
+        assert!(output.starts_with('#'));
+        assert!(output.contains("A document. <ja> <en>"));
+        assert!(!output.contains("File:"));
+    }
+}
@@ -0,0 +1,173 @@
+use std::env;
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::app_paths::AppPaths;
+use crate::settings::AppSettings;
+
+/// Prefix shared by every environment variable the app reads.
+pub const ENV_PREFIX: &str = "TF_";
+
+const REDACTED: &str = "<redacted>";
+const SECRET_MARKERS: [&str; 5] = ["KEY", "TOKEN", "SECRET", "PASSWORD", "CREDENTIAL"];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolvedPath {
+    pub name: &'static str,
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EnvOverride {
+    pub name: String,
+    pub value: String,
+}
+
+/// Fully-resolved configuration: settings after normalization, paths, and `TF_*` overrides.
+#[derive(Debug, Clone, Serialize)]
+pub struct EffectiveConfig {
+    /// Normalized settings as JSON fields, with secret values already redacted.
+    pub settings: Map<String, Value>,
+    pub paths: Vec<ResolvedPath>,
+    pub env_overrides: Vec<EnvOverride>,
+}
+
+impl EffectiveConfig {
+    pub fn resolve(settings: &AppSettings, paths: &AppPaths) -> Self {
+        Self::from_env(settings, paths, env::vars())
+    }
+
+    pub fn from_env(
+        settings: &AppSettings,
+        paths: &AppPaths,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Self {
+        let mut normalized = settings.clone();
+        normalized.normalize();
+        let mut settings = match serde_json::to_value(&normalized) {
+            Ok(Value::Object(fields)) => fields,
+            _ => Map::new(),
+        };
+        for (name, value) in settings.iter_mut() {
+            let is_set = value.as_str().is_some_and(|text| !text.is_empty());
+            if is_secret(name) && is_set {
+                *value = Value::String(REDACTED.to_owned());
+            }
+        }
+
+        let mut env_overrides: Vec<EnvOverride> = vars
+            .into_iter()
+            .filter(|(name, _)| name.starts_with(ENV_PREFIX))
+            .map(|(name, value)| {
+                let value = if is_secret(&name) {
+                    REDACTED.to_owned()
+                } else {
+                    value
+                };
+                EnvOverride { name, value }
+            })
+            .collect();
+        env_overrides.sort_by(|left, right| left.name.cmp(&right.name));
+
+        Self {
+            settings,
+            paths: resolved_paths(paths),
+            env_overrides,
+        }
+    }
+
+    /// Human-readable `key = value` listing grouped by section.
+    pub fn render(&self) -> String {
+        let mut output = String::from("[settings]\n");
+        for (name, value) in &self.settings {
+            output.push_str(&format!("{name} = {value}\n"));
+        }
+
+        output.push_str("\n[paths]\n");
+        for path in &self.paths {
+            output.push_str(&format!("{} = {}\n", path.name, path.path));
+        }
+
+        output.push_str("\n[env]\n");
+        if self.env_overrides.is_empty() {
+            output.push_str("(no TF_* variables set)\n");
+        }
+        for item in &self.env_overrides {
+            output.push_str(&format!("{} = {}\n", item.name, item.value));
+        }
+        output
+    }
+}
+
+fn resolved_paths(paths: &AppPaths) -> Vec<ResolvedPath> {
+    [
+        ("app_root", &paths.app_root),
+        ("data_root", &paths.data_root),
+        ("logs_dir", &paths.logs_dir),
+        ("exports_dir", &paths.exports_dir),
+        ("settings_file", &paths.settings_file),
+        ("memory_db_file", &paths.memory_db_file),
+        ("history_file", &paths.history_file),
+    ]
+    .into_iter()
+    .map(|(name, path)| ResolvedPath {
+        name,
+        path: path.display().to_string(),
+    })
+    .collect()
+}
+
+fn is_secret(name: &str) -> bool {
+    let upper = name.to_ascii_uppercase();
+    SECRET_MARKERS.iter().any(|marker| upper.contains(marker))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn sample_paths() -> AppPaths {
+        let root = PathBuf::from("/tmp/tf");
+        AppPaths {
+            app_root: root.clone(),
+            data_root: root.join("data"),
+            logs_dir: root.join("data/logs"),
+            exports_dir: root.join("data/exports"),
+            settings_file: root.join("data/settings.json"),
+            memory_db_file: root.join("data/translation_memory.db"),
+            history_file: root.join("data/history.json"),
+        }
+    }
+
+    #[test]
+    fn keeps_only_tf_vars_and_redacts_secrets() {
+        let vars = [
+            ("PATH".to_owned(), "/usr/bin".to_owned()),
+            ("TF_APP_HOME".to_owned(), "/srv/tf".to_owned()),
+            ("TF_DEEPL_API_KEY".to_owned(), "abc123".to_owned()),
+        ];
+
+        let settings = AppSettings {
+            deepl_api_key: "secret-key:fx".to_owned(),
+            ..AppSettings::default()
+        };
+        let config = EffectiveConfig::from_env(&settings, &sample_paths(), vars);
+
+        assert_eq!(config.env_overrides.len(), 2);
+        let rendered = config.render();
+        assert!(rendered.contains("TF_APP_HOME = /srv/tf"));
+        assert!(rendered.contains("TF_DEEPL_API_KEY = <redacted>"));
+        assert!(!rendered.contains("abc123"));
+        assert!(rendered.contains("deepl_api_key = \"<redacted>\""));
+        assert!(
+            !serde_json::to_string(&config)
+                .unwrap()
+                .contains("secret-key")
+        );
+        assert!(!rendered.contains("PATH = /usr/bin"));
+        assert!(rendered.contains("intermediate_language = \"ja\""));
+        assert!(rendered.contains("settings_file = /tmp/tf/data/settings.json"));
+    }
+}
@@ -0,0 +1,195 @@
+use serde::Serialize;
+
+use crate::html::escape_html;
+
+/// Upper bound on LCS table cells; larger inputs fall back to a coarse replace.
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffOp<'a> {
+    Equal(&'a str),
+    Insert(&'a str),
+    Delete(&'a str),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct DiffSummary {
+    pub unchanged: usize,
+    pub inserted: usize,
+    pub deleted: usize,
+}
+
+impl DiffSummary {
+    pub fn from_ops(ops: &[DiffOp<'_>]) -> Self {
+        let mut summary = Self::default();
+        for op in ops {
+            match op {
+                DiffOp::Equal(_) => summary.unchanged += 1,
+                DiffOp::Insert(_) => summary.inserted += 1,
+                DiffOp::Delete(_) => summary.deleted += 1,
+            }
+        }
+        summary
+    }
+
+    pub fn has_changes(&self) -> bool {
+        self.inserted > 0 || self.deleted > 0
+    }
+
+    /// Share of words kept unchanged across both sides, in `0.0..=1.0`.
+    pub fn similarity(&self) -> f64 {
+        let total = 2 * self.unchanged + self.inserted + self.deleted;
+        if total == 0 {
+            return 1.0;
+        }
+        (2 * self.unchanged) as f64 / total as f64
+    }
+}
+
+/// Word-level diff of `old` against `new` using a longest-common-subsequence table.
+pub fn diff_words<'a>(old: &'a str, new: &'a str) -> Vec<DiffOp<'a>> {
+    let old_words: Vec<&str> = old.split_whitespace().collect();
+    let new_words: Vec<&str> = new.split_whitespace().collect();
+
+    let prefix = old_words
+        .iter()
+        .zip(&new_words)
+        .take_while(|(left, right)| left == right)
+        .count();
+    let suffix = old_words[prefix..]
+        .iter()
+        .rev()
+        .zip(new_words[prefix..].iter().rev())
+        .take_while(|(left, right)| left == right)
+        .count();
+
+    let old_middle = &old_words[prefix..old_words.len() - suffix];
+    let new_middle = &new_words[prefix..new_words.len() - suffix];
+
+    let mut ops = Vec::with_capacity(old_words.len().max(new_words.len()));
+    ops.extend(old_words[..prefix].iter().map(|word| DiffOp::Equal(word)));
+    diff_middle(old_middle, new_middle, &mut ops);
+    ops.extend(
+        old_words[old_words.len() - suffix..]
+            .iter()
+            .map(|word| DiffOp::Equal(word)),
+    );
+    ops
+}
+
+/// HTML-escaped `new` text with words added since `old` wrapped in `<ins>`
+/// and dropped words in `<del>`; adjacent changes share one tag.
+pub fn diff_html(old: &str, new: &str) -> String {
+    let mut html = String::new();
+    let mut open: Option<&str> = None;
+    for op in diff_words(old, new) {
+        let (tag, word) = match op {
+            DiffOp::Equal(word) => (None, word),
+            DiffOp::Insert(word) => (Some("ins"), word),
+            DiffOp::Delete(word) => (Some("del"), word),
+        };
+        if tag != open {
+            if let Some(previous) = open {
+                html.push_str(&format!("</{previous}>"));
+            }
+            if !html.is_empty() {
+                html.push(' ');
+            }
+            if let Some(next) = tag {
+                html.push_str(&format!("<{next}>"));
+            }
+            open = tag;
+        } else if !html.is_empty() {
+            html.push(' ');
+        }
+        html.push_str(&escape_html(word));
+    }
+    if let Some(previous) = open {
+        html.push_str(&format!("</{previous}>"));
+    }
+    html
+}
+
+fn diff_middle<'a>(old: &[&'a str], new: &[&'a str], ops: &mut Vec<DiffOp<'a>>) {
+    if old.is_empty() || new.is_empty() || old.len() * new.len() > MAX_LCS_CELLS {
+        ops.extend(old.iter().map(|word| DiffOp::Delete(word)));
+        ops.extend(new.iter().map(|word| DiffOp::Insert(word)));
+        return;
+    }
+
+    let width = new.len() + 1;
+    let mut table = vec![0_u32; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            table[i * width + j] = if old[i] == new[j] {
+                table[(i + 1) * width + j + 1] + 1
+            } else {
+                table[(i + 1) * width + j].max(table[i * width + j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            ops.push(DiffOp::Equal(old[i]));
+            i += 1;
+            j += 1;
+        } else if table[(i + 1) * width + j] >= table[i * width + j + 1] {
+            ops.push(DiffOp::Delete(old[i]));
+            i += 1;
+        } else {
+            ops.push(DiffOp::Insert(new[j]));
+            j += 1;
+        }
+    }
+    ops.extend(old[i..].iter().map(|word| DiffOp::Delete(word)));
+    ops.extend(new[j..].iter().map(|word| DiffOp::Insert(word)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn html_diff_marks_only_changed_words() {
+        assert_eq!(
+            diff_html("the quick <brown> fox", "the quick <brown> fox"),
+            "the quick &lt;brown&gt; fox"
+        );
+        assert_eq!(
+            diff_html("the quick brown fox", "the quick red fox"),
+            "the quick <del>brown</del> <ins>red</ins> fox"
+        );
+        assert_eq!(
+            diff_html("a b c", "a x y c"),
+            "a <del>b</del> <ins>x y</ins> c"
+        );
+    }
+
+    #[test]
+    fn identical_text_has_no_changes() {
+        let ops = diff_words("the quick brown fox", "the  quick brown\nfox");
+        assert!(ops.iter().all(|op| matches!(op, DiffOp::Equal(_))));
+        assert_eq!(DiffSummary::from_ops(&ops).similarity(), 1.0);
+    }
+
+    #[test]
+    fn one_word_change_is_a_delete_and_insert() {
+        let ops = diff_words("the quick brown fox", "the quick red fox");
+        assert_eq!(
+            ops,
+            vec![
+                DiffOp::Equal("the"),
+                DiffOp::Equal("quick"),
+                DiffOp::Delete("brown"),
+                DiffOp::Insert("red"),
+                DiffOp::Equal("fox"),
+            ]
+        );
+
+        let summary = DiffSummary::from_ops(&ops);
+        assert_eq!((summary.inserted, summary.deleted), (1, 1));
+        assert!(summary.has_changes());
+    }
+}
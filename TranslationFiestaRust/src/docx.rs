@@ -0,0 +1,231 @@
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use quick_xml::Reader;
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::Event;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive};
+
+use crate::html::escape_xml;
+
+/// One paragraph of a WordprocessingML document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DocxBlock {
+    Title(String),
+    Heading1(String),
+    Heading2(String),
+    /// A bold `label:` run followed by plain text on the same line.
+    Labeled {
+        label: String,
+        value: String,
+    },
+    Paragraph(String),
+}
+
+impl DocxBlock {
+    pub fn labeled(label: impl Into<String>, value: impl Into<String>) -> Self {
+        Self::Labeled {
+            label: label.into(),
+            value: value.into(),
+        }
+    }
+}
+
+/// Appends one paragraph per line of `text`, keeping blank lines.
+pub fn push_paragraphs(blocks: &mut Vec<DocxBlock>, text: &str) {
+    blocks.extend(
+        text.lines()
+            .map(|line| DocxBlock::Paragraph(line.to_owned())),
+    );
+}
+
+/// Writes a minimal DOCX package with a styles part defining the title and
+/// heading styles the blocks refer to.
+pub fn write_document(path: &Path, blocks: &[DocxBlock]) -> Result<()> {
+    let file =
+        File::create(path).with_context(|| format!("failed to create {}", path.display()))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    zip.start_file("[Content_Types].xml", options)?;
+    zip.write_all(CONTENT_TYPES_XML.as_bytes())?;
+
+    zip.start_file("_rels/.rels", options)?;
+    zip.write_all(ROOT_RELATIONSHIPS_XML.as_bytes())?;
+
+    zip.start_file("word/document.xml", options)?;
+    zip.write_all(document_xml(blocks).as_bytes())?;
+
+    zip.start_file("word/_rels/document.xml.rels", options)?;
+    zip.write_all(DOCUMENT_RELATIONSHIPS_XML.as_bytes())?;
+
+    zip.start_file("word/styles.xml", options)?;
+    zip.write_all(STYLES_XML.as_bytes())?;
+
+    zip.finish()
+        .with_context(|| format!("failed to finish document {}", path.display()))?;
+    Ok(())
+}
+
+/// Text of a `.docx` file's main document part, one line per paragraph.
+/// Normalizing collapses whitespace and separates paragraphs with blank lines.
+pub fn extract_text(path: &Path, normalize_whitespace: bool) -> Result<String> {
+    let file = File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+    let mut archive = ZipArchive::new(file)
+        .with_context(|| format!("{} is not a valid DOCX archive", path.display()))?;
+    let mut xml = String::new();
+    archive
+        .by_name("word/document.xml")
+        .with_context(|| format!("{} has no word/document.xml", path.display()))?
+        .read_to_string(&mut xml)
+        .with_context(|| format!("failed to read word/document.xml in {}", path.display()))?;
+
+    let paragraphs = document_paragraphs(&xml)
+        .with_context(|| format!("failed to parse word/document.xml in {}", path.display()))?;
+    if !normalize_whitespace {
+        return Ok(paragraphs.join("\n"));
+    }
+    Ok(paragraphs
+        .iter()
+        .map(|paragraph| paragraph.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|paragraph| !paragraph.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n"))
+}
+
+/// Collects `<w:t>` runs per `<w:p>`, turning tabs and breaks into whitespace.
+fn document_paragraphs(xml: &str) -> Result<Vec<String>> {
+    let mut reader = Reader::from_str(xml);
+    let mut paragraphs = Vec::new();
+    let mut current = String::new();
+    let mut in_text = false;
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(tag) => in_text = tag.local_name().as_ref() == b"t",
+            Event::Empty(tag) => match tag.local_name().as_ref() {
+                b"tab" => current.push('\t'),
+                b"br" | b"cr" => current.push('\n'),
+                b"p" => paragraphs.push(String::new()),
+                _ => {}
+            },
+            Event::Text(text) if in_text => current.push_str(&text.decode()?),
+            Event::GeneralRef(entity) if in_text => {
+                if let Some(ch) = entity.resolve_char_ref()? {
+                    current.push(ch);
+                } else if let Some(value) = resolve_predefined_entity(&entity.decode()?) {
+                    current.push_str(value);
+                }
+            }
+            Event::End(tag) => match tag.local_name().as_ref() {
+                b"t" => in_text = false,
+                b"p" => paragraphs.push(std::mem::take(&mut current)),
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(paragraphs)
+}
+
+const CONTENT_TYPES_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">
+  <Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>
+  <Default Extension="xml" ContentType="application/xml"/>
+  <Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/>
+  <Override PartName="/word/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.styles+xml"/>
+</Types>"#;
+
+const ROOT_RELATIONSHIPS_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+  <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="word/document.xml"/>
+</Relationships>"#;
+
+const DOCUMENT_RELATIONSHIPS_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+  <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles.xml"/>
+</Relationships>"#;
+
+/// Sizes are in half-points: 18pt title, 16pt and 13pt headings, 11pt body.
+const STYLES_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <w:docDefaults><w:rPrDefault><w:rPr><w:rFonts w:ascii="Calibri" w:hAnsi="Calibri" w:eastAsia="Yu Gothic"/><w:sz w:val="22"/></w:rPr></w:rPrDefault></w:docDefaults>
+  <w:style w:type="paragraph" w:default="1" w:styleId="Normal"><w:name w:val="Normal"/><w:pPr><w:spacing w:after="120"/></w:pPr></w:style>
+  <w:style w:type="paragraph" w:styleId="Title"><w:name w:val="Title"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:pPr><w:spacing w:after="240"/></w:pPr><w:rPr><w:b/><w:sz w:val="36"/></w:rPr></w:style>
+  <w:style w:type="paragraph" w:styleId="Heading1"><w:name w:val="heading 1"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:pPr><w:keepNext/><w:spacing w:before="360" w:after="120"/><w:outlineLvl w:val="0"/></w:pPr><w:rPr><w:b/><w:sz w:val="32"/></w:rPr></w:style>
+  <w:style w:type="paragraph" w:styleId="Heading2"><w:name w:val="heading 2"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:pPr><w:keepNext/><w:spacing w:before="240" w:after="80"/><w:outlineLvl w:val="1"/></w:pPr><w:rPr><w:b/><w:sz w:val="26"/></w:rPr></w:style>
+</w:styles>"#;
+
+fn document_xml(blocks: &[DocxBlock]) -> String {
+    let mut body = String::new();
+    for block in blocks {
+        match block {
+            DocxBlock::Title(text) => body.push_str(&styled_paragraph("Title", text)),
+            DocxBlock::Heading1(text) => body.push_str(&styled_paragraph("Heading1", text)),
+            DocxBlock::Heading2(text) => body.push_str(&styled_paragraph("Heading2", text)),
+            DocxBlock::Labeled { label, value } => {
+                body.push_str("<w:p><w:r><w:rPr><w:b/></w:rPr>");
+                body.push_str(&text_element(&format!("{label}: ")));
+                body.push_str("</w:r><w:r>");
+                body.push_str(&text_element(value));
+                body.push_str("</w:r></w:p>");
+            }
+            DocxBlock::Paragraph(text) => {
+                body.push_str("<w:p><w:r>");
+                body.push_str(&text_element(text));
+                body.push_str("</w:r></w:p>");
+            }
+        }
+    }
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?><w:document xmlns:w=\"http://schemas.openxmlformats.org/wordprocessingml/2006/main\"><w:body>{body}<w:sectPr/></w:body></w:document>"
+    )
+}
+
+fn styled_paragraph(style: &str, text: &str) -> String {
+    format!(
+        "<w:p><w:pPr><w:pStyle w:val=\"{style}\"/></w:pPr><w:r>{}</w:r></w:p>",
+        text_element(text)
+    )
+}
+
+fn text_element(text: &str) -> String {
+    format!("<w:t xml:space=\"preserve\">{}</w:t>", escape_xml(text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_the_text_a_written_document_contains() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("sample.docx");
+        write_document(
+            &path,
+            &[
+                DocxBlock::Title("Report".to_owned()),
+                DocxBlock::labeled("Note", "fish & chips"),
+                DocxBlock::Paragraph("Hello   world".to_owned()),
+                DocxBlock::Paragraph(String::new()),
+                DocxBlock::Paragraph("こんにちは".to_owned()),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(
+            extract_text(&path, true).unwrap(),
+            "Report\n\nNote: fish & chips\n\nHello world\n\nこんにちは"
+        );
+        assert_eq!(
+            extract_text(&path, false).unwrap(),
+            "Report\nNote: fish & chips\nHello   world\n\nこんにちは"
+        );
+    }
+}
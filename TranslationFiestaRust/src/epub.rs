@@ -1,22 +1,26 @@
 use std::fs::File;
 use std::io::Read;
+use std::ops::Range;
 use std::path::Path;
 
 use anyhow::{Context, Result};
-use quick_xml::Reader;
-use quick_xml::events::Event;
+use quick_xml::events::{BytesText, Event};
+use quick_xml::{Reader, Writer};
+use tracing::warn;
 use zip::ZipArchive;
 
-use crate::html::extract_text_from_html;
+use crate::html::{escape_xml, extract_text_from_html_with, extract_text_preserving_paragraphs};
 use crate::models::{EpubBook, EpubChapter};
 
-pub fn load_epub(path: &Path) -> Result<EpubBook> {
+pub fn load_epub(path: &Path, normalize_whitespace: bool) -> Result<EpubBook> {
     let file =
         File::open(path).with_context(|| format!("failed to open EPUB file {}", path.display()))?;
     let mut archive = ZipArchive::new(file).context("failed to read EPUB zip archive")?;
 
-    let mut title: Option<String> = None;
-    let mut author: Option<String> = None;
+    let mut metadata = OpfMetadata::default();
+    let mut spine: Vec<String> = Vec::new();
+    let mut opf_path: Option<String> = None;
+    let mut toc: Vec<(String, String)> = Vec::new();
 
     let mut chapters = Vec::new();
 
@@ -33,13 +37,20 @@ pub fn load_epub(path: &Path) -> Result<EpubBook> {
             entry
                 .read_to_string(&mut xml)
                 .with_context(|| format!("failed to read OPF entry {name}"))?;
-            let (parsed_title, parsed_author) = parse_opf_metadata(&xml);
-            if title.is_none() {
-                title = parsed_title;
-            }
-            if author.is_none() {
-                author = parsed_author;
+            metadata.fill_missing(parse_opf_metadata(&xml));
+            if spine.is_empty() {
+                spine = parse_opf_spine(&xml, &name);
             }
+            opf_path.get_or_insert(name);
+            continue;
+        }
+
+        if lower.ends_with(".ncx") {
+            let mut xml = String::new();
+            entry
+                .read_to_string(&mut xml)
+                .with_context(|| format!("failed to read NCX entry {name}"))?;
+            toc.extend(parse_toc(&xml, &name));
             continue;
         }
 
@@ -53,7 +64,14 @@ pub fn load_epub(path: &Path) -> Result<EpubBook> {
             .with_context(|| format!("failed to read chapter entry {name}"))?;
 
         let raw = String::from_utf8_lossy(&bytes).to_string();
-        let text = extract_text_from_html(&raw);
+        if raw.contains("<nav") {
+            toc.extend(parse_toc(&raw, &name));
+        }
+        let text = if normalize_whitespace {
+            extract_text_preserving_paragraphs(&raw)
+        } else {
+            extract_text_from_html_with(&raw, false)
+        };
 
         if text.trim().is_empty() {
             continue;
@@ -71,7 +89,24 @@ pub fn load_epub(path: &Path) -> Result<EpubBook> {
         });
     }
 
-    chapters.sort_by_key(|chapter| chapter.order);
+    // TOC labels are what readers see, so they beat `<title>` and file names.
+    for chapter in &mut chapters {
+        if let Some((_, label)) = toc.iter().find(|(href, _)| *href == chapter.path) {
+            chapter.title = label.clone();
+        }
+    }
+
+    // Zip order is arbitrary; the spine is the canonical reading order. Chapters
+    // missing from the spine keep their discovery order after the spine ones.
+    if !spine.is_empty() {
+        chapters.sort_by_key(|chapter| {
+            let position = spine.iter().position(|href| *href == chapter.path);
+            (position.unwrap_or(usize::MAX), chapter.order)
+        });
+        for (order, chapter) in chapters.iter_mut().enumerate() {
+            chapter.order = order;
+        }
+    }
 
     let fallback_title = path
         .file_stem()
@@ -79,21 +114,182 @@ pub fn load_epub(path: &Path) -> Result<EpubBook> {
         .unwrap_or_else(|| "Untitled EPUB".to_string());
 
     Ok(EpubBook {
-        title: title.unwrap_or(fallback_title),
-        author,
+        title: metadata.title.unwrap_or(fallback_title),
+        author: metadata.author,
+        language: metadata.language,
+        publisher: metadata.publisher,
+        identifier: metadata.identifier,
+        date: metadata.date,
         chapters,
+        source_path: path.to_path_buf(),
+        opf_path,
     })
 }
 
-pub fn extract_text(path: &Path) -> Result<String> {
-    let book = load_epub(path)?;
-    let content = book
-        .chapters
+pub fn extract_text(path: &Path, normalize_whitespace: bool) -> Result<String> {
+    let book = load_epub(path, normalize_whitespace)?;
+    Ok(join_chapters(&book.chapters))
+}
+
+/// Like `extract_text`, but only the chapters at `range`, counted from 0 in
+/// reading order. A range reaching past the last chapter is clamped.
+pub fn extract_text_range(
+    path: &Path,
+    range: Range<usize>,
+    normalize_whitespace: bool,
+) -> Result<String> {
+    let book = load_epub(path, normalize_whitespace)?;
+    let range = clamp_chapter_range(range, book.chapters.len());
+    Ok(join_chapters(&book.chapters[range]))
+}
+
+/// `range` limited to the book's `chapter_count` chapters, with a warning
+/// when it had to shrink.
+pub fn clamp_chapter_range(range: Range<usize>, chapter_count: usize) -> Range<usize> {
+    let end = range.end.min(chapter_count);
+    let clamped = range.start.min(end)..end;
+    if clamped != range {
+        warn!(
+            "chapters {}..{} requested but the book has {chapter_count}; using {}..{}",
+            range.start, range.end, clamped.start, clamped.end
+        );
+    }
+    clamped
+}
+
+fn join_chapters(chapters: &[EpubChapter]) -> String {
+    chapters
         .iter()
         .map(|chapter| chapter.content.as_str())
         .collect::<Vec<_>>()
-        .join("\n\n");
-    Ok(content)
+        .join("\n\n")
+}
+
+/// Options for rebuilding a translated EPUB.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EpubRepackOptions {
+    /// Translate the book title and author along with the chapters.
+    pub translate_metadata: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EpubMetadata {
+    pub title: String,
+    pub author: Option<String>,
+}
+
+/// Title and author for the rebuilt book: translated through `translate` when
+/// `translate_metadata` is on, otherwise copied from `book` unchanged.
+pub fn repack_metadata<E>(
+    book: &EpubBook,
+    options: &EpubRepackOptions,
+    mut translate: impl FnMut(&str) -> std::result::Result<String, E>,
+) -> std::result::Result<EpubMetadata, E> {
+    if !options.translate_metadata {
+        return Ok(EpubMetadata {
+            title: book.title.clone(),
+            author: book.author.clone(),
+        });
+    }
+
+    let title = translate(&book.title)?;
+    let author = match book
+        .author
+        .as_deref()
+        .filter(|value| !value.trim().is_empty())
+    {
+        Some(author) => Some(translate(author)?),
+        None => book.author.clone(),
+    };
+    Ok(EpubMetadata { title, author })
+}
+
+/// Translates every chapter in reading order, one non-empty line at a time so
+/// each provider request stays short. Blank lines are kept as paragraph breaks.
+pub fn translate_chapters<E>(
+    book: &EpubBook,
+    mut translate: impl FnMut(&EpubChapter, &str) -> std::result::Result<String, E>,
+) -> std::result::Result<Vec<String>, E> {
+    let mut translated = Vec::with_capacity(book.chapters.len());
+    for chapter in &book.chapters {
+        let mut lines = Vec::new();
+        for line in chapter.content.lines() {
+            if line.trim().is_empty() {
+                lines.push(String::new());
+            } else {
+                lines.push(translate(chapter, line)?);
+            }
+        }
+        translated.push(lines.join("\n"));
+    }
+    Ok(translated)
+}
+
+/// Replaces everything inside `<body>` with one `<p>` per non-empty line of
+/// `text`, keeping the head (title, stylesheets) and the body's attributes.
+pub fn rewrite_chapter_body(xhtml: &str, text: &str) -> String {
+    let paragraphs: String = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| format!("<p>{}</p>\n", escape_xml(line)))
+        .collect();
+
+    let lower = xhtml.to_ascii_lowercase();
+    let body_open = lower
+        .find("<body")
+        .and_then(|start| lower[start..].find('>').map(|offset| start + offset + 1));
+    let body_close = lower.rfind("</body>");
+    match (body_open, body_close) {
+        (Some(open), Some(close)) if open <= close => {
+            format!("{}\n{paragraphs}{}", &xhtml[..open], &xhtml[close..])
+        }
+        _ => format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<html xmlns=\"http://www.w3.org/1999/xhtml\"><head><title></title></head><body>\n{paragraphs}</body></html>"
+        ),
+    }
+}
+
+/// Replaces the text of the first `dc:title` and `dc:creator` in an OPF package,
+/// leaving every other element and attribute as written.
+pub fn rewrite_opf_metadata(opf_xml: &str, metadata: &EpubMetadata) -> Result<String> {
+    let mut reader = Reader::from_str(opf_xml);
+    let mut writer = Writer::new(Vec::new());
+
+    let mut pending: Option<&str> = None;
+    let mut title_done = false;
+    let mut author_done = metadata.author.is_none();
+
+    loop {
+        let event = reader.read_event().context("failed to parse OPF package")?;
+        match &event {
+            Event::Start(tag) => {
+                let name = String::from_utf8_lossy(tag.name().as_ref()).to_ascii_lowercase();
+                if !title_done && (name == "dc:title" || name.ends_with(":title")) {
+                    title_done = true;
+                    pending = Some(metadata.title.as_str());
+                } else if !author_done && (name == "dc:creator" || name.ends_with(":creator")) {
+                    author_done = true;
+                    pending = metadata.author.as_deref();
+                }
+                writer.write_event(event.borrow())?;
+                continue;
+            }
+            Event::Text(_) | Event::CData(_) if pending.is_some() => {
+                continue;
+            }
+            Event::End(_) => {
+                if let Some(value) = pending.take() {
+                    writer.write_event(Event::Text(BytesText::new(value)))?;
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        writer.write_event(event.borrow())?;
+    }
+
+    String::from_utf8(writer.into_inner()).context("rewritten OPF is not valid UTF-8")
 }
 
 fn parse_html_title(html: &str) -> Option<String> {
@@ -113,20 +309,72 @@ fn infer_title_from_path(path: &str) -> String {
         .unwrap_or_else(|| "Untitled Chapter".to_string())
 }
 
-fn parse_opf_metadata(opf_xml: &str) -> (Option<String>, Option<String>) {
+/// Dublin Core fields of an OPF package; EPUB 3 `<meta property="dcterms:…">`
+/// elements fill in the ones the `dc:` elements leave out.
+#[derive(Debug, Default, PartialEq)]
+struct OpfMetadata {
+    title: Option<String>,
+    author: Option<String>,
+    language: Option<String>,
+    publisher: Option<String>,
+    identifier: Option<String>,
+    date: Option<String>,
+}
+
+impl OpfMetadata {
+    /// Takes `other`'s value for every field still unset, so the first OPF
+    /// in the archive wins.
+    fn fill_missing(&mut self, other: Self) {
+        let fields = [
+            (&mut self.title, other.title),
+            (&mut self.author, other.author),
+            (&mut self.language, other.language),
+            (&mut self.publisher, other.publisher),
+            (&mut self.identifier, other.identifier),
+            (&mut self.date, other.date),
+        ];
+        for (field, value) in fields {
+            if field.is_none() {
+                *field = value;
+            }
+        }
+    }
+}
+
+fn parse_opf_metadata(opf_xml: &str) -> OpfMetadata {
     let mut reader = Reader::from_str(opf_xml);
     reader.config_mut().trim_text(true);
 
+    let mut metadata = OpfMetadata::default();
+    let mut refined = OpfMetadata::default();
     let mut current_tag = String::new();
-    let mut title: Option<String> = None;
-    let mut author: Option<String> = None;
+    let mut current_id = String::new();
+    let mut current_scheme = String::new();
+    let mut current_property = String::new();
+    let mut current_refines = String::new();
+    // (id, scheme, value) of every dc:identifier, and `identifier-type`
+    // refinements keyed by the identifier id they refine.
+    let mut identifiers: Vec<(String, String, String)> = Vec::new();
+    let mut identifier_types: Vec<(String, String)> = Vec::new();
 
     loop {
         match reader.read_event() {
             Ok(Event::Start(tag)) => {
+                let attribute = |name: &str| {
+                    tag.try_get_attribute(name)
+                        .ok()
+                        .flatten()
+                        .and_then(|attr| attr.unescape_value().ok())
+                        .map(|value| value.to_string())
+                        .unwrap_or_default()
+                };
                 current_tag = String::from_utf8_lossy(tag.name().as_ref())
                     .to_ascii_lowercase()
                     .to_string();
+                current_id = attribute("id");
+                current_scheme = attribute("opf:scheme");
+                current_property = attribute("property");
+                current_refines = attribute("refines");
             }
             Ok(Event::Text(text)) => {
                 if let Ok(value) = text.decode() {
@@ -135,17 +383,110 @@ fn parse_opf_metadata(opf_xml: &str) -> (Option<String>, Option<String>) {
                         continue;
                     }
 
-                    if title.is_none()
-                        && (current_tag == "dc:title" || current_tag.ends_with(":title"))
-                    {
-                        title = Some(value.clone());
+                    let element = current_tag.rsplit(':').next().unwrap_or_default();
+                    if element == "meta" {
+                        match current_property.as_str() {
+                            "identifier-type" => identifier_types
+                                .push((current_refines.trim_start_matches('#').to_owned(), value)),
+                            "dcterms:language" => {
+                                refined.language.get_or_insert(value);
+                            }
+                            "dcterms:publisher" => {
+                                refined.publisher.get_or_insert(value);
+                            }
+                            "dcterms:identifier" => {
+                                refined.identifier.get_or_insert(value);
+                            }
+                            "dcterms:date" | "dcterms:issued" => {
+                                refined.date.get_or_insert(value);
+                            }
+                            _ => {}
+                        }
+                        continue;
                     }
 
-                    if author.is_none()
-                        && (current_tag == "dc:creator" || current_tag.ends_with(":creator"))
-                    {
-                        author = Some(value);
+                    let field = match element {
+                        "title" => &mut metadata.title,
+                        "creator" => &mut metadata.author,
+                        "language" => &mut metadata.language,
+                        "publisher" => &mut metadata.publisher,
+                        "date" => &mut metadata.date,
+                        "identifier" => {
+                            identifiers.push((current_id.clone(), current_scheme.clone(), value));
+                            continue;
+                        }
+                        _ => continue,
+                    };
+                    field.get_or_insert(value);
+                }
+            }
+            Ok(Event::Eof) => break,
+            Ok(_) => {}
+            Err(_) => break,
+        }
+    }
+
+    let is_isbn = |(id, scheme, value): &(String, String, String)| {
+        // ONIX list 5 codes 02 and 15 are ISBN-10 and ISBN-13.
+        let refined_type = identifier_types
+            .iter()
+            .find(|(refines, _)| !id.is_empty() && refines == id)
+            .map(|(_, kind)| kind.as_str());
+        scheme.eq_ignore_ascii_case("isbn")
+            || matches!(refined_type, Some("02" | "15"))
+            || refined_type.is_some_and(|kind| kind.eq_ignore_ascii_case("isbn"))
+            || isbn_urn(value).is_some()
+    };
+    metadata.identifier = identifiers
+        .iter()
+        .find(|identifier| is_isbn(identifier))
+        .or(identifiers.first())
+        .map(|(_, _, value)| isbn_urn(value).unwrap_or(value).to_owned());
+
+    metadata.fill_missing(refined);
+    metadata
+}
+
+/// The ISBN inside a `urn:isbn:` identifier.
+fn isbn_urn(value: &str) -> Option<&str> {
+    const PREFIX: &str = "urn:isbn:";
+    value
+        .get(..PREFIX.len())
+        .filter(|prefix| prefix.eq_ignore_ascii_case(PREFIX))
+        .map(|_| &value[PREFIX.len()..])
+}
+
+/// Resolves the spine's `itemref`s through the manifest into archive paths,
+/// relative to the directory holding the OPF.
+fn parse_opf_spine(opf_xml: &str, opf_path: &str) -> Vec<String> {
+    let mut reader = Reader::from_str(opf_xml);
+    reader.config_mut().trim_text(true);
+
+    let mut manifest: Vec<(String, String)> = Vec::new();
+    let mut idrefs: Vec<String> = Vec::new();
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(tag)) | Ok(Event::Empty(tag)) => {
+                let attribute = |name: &str| {
+                    tag.try_get_attribute(name)
+                        .ok()
+                        .flatten()
+                        .and_then(|attr| attr.unescape_value().ok())
+                        .map(|value| value.into_owned())
+                };
+                match tag.local_name().as_ref() {
+                    b"item" => {
+                        if let (Some(id), Some(href)) = (attribute("id"), attribute("href")) {
+                            manifest.push((id, href));
+                        }
+                    }
+                    b"itemref" => {
+                        if let Some(idref) = attribute("idref") {
+                            idrefs.push(idref);
+                        }
                     }
+                    _ => {}
                 }
             }
             Ok(Event::Eof) => break,
@@ -154,7 +495,104 @@ fn parse_opf_metadata(opf_xml: &str) -> (Option<String>, Option<String>) {
         }
     }
 
-    (title, author)
+    let base = opf_path.rsplit_once('/').map_or("", |(dir, _)| dir);
+    idrefs
+        .iter()
+        .filter_map(|idref| {
+            manifest
+                .iter()
+                .find(|(id, _)| id == idref)
+                .map(|(_, href)| resolve_href(base, href))
+        })
+        .collect()
+}
+
+/// `(archive path, label)` for every entry of an EPUB 2 `toc.ncx` or the
+/// `epub:type="toc"` navigation of an EPUB 3 nav document at `toc_path`.
+fn parse_toc(xml: &str, toc_path: &str) -> Vec<(String, String)> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let base = toc_path.rsplit_once('/').map_or("", |(dir, _)| dir);
+    let mut entries = Vec::new();
+    // NCX: the latest `<navLabel><text>`, claimed by the next `<content>`.
+    let mut in_label_text = false;
+    let mut ncx_label: Option<String> = None;
+    // Nav: the open `<a>` inside the TOC `<nav>` and the text read so far.
+    let mut in_toc_nav = false;
+    let mut link: Option<(String, Vec<String>)> = None;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(tag)) | Ok(Event::Empty(tag)) => {
+                let attribute = |name: &str| {
+                    tag.try_get_attribute(name)
+                        .ok()
+                        .flatten()
+                        .and_then(|attr| attr.unescape_value().ok())
+                        .map(|value| value.into_owned())
+                };
+                match tag.local_name().as_ref() {
+                    b"text" => in_label_text = true,
+                    b"content" => {
+                        if let (Some(src), Some(label)) = (attribute("src"), ncx_label.take()) {
+                            entries.push((resolve_href(base, &src), label));
+                        }
+                    }
+                    b"nav" => {
+                        in_toc_nav = attribute("epub:type")
+                            .is_some_and(|kind| kind.split_whitespace().any(|kind| kind == "toc"));
+                    }
+                    b"a" if in_toc_nav => link = attribute("href").map(|href| (href, Vec::new())),
+                    _ => {}
+                }
+            }
+            Ok(Event::Text(text)) => {
+                let Ok(value) = text.decode() else {
+                    continue;
+                };
+                if in_label_text {
+                    ncx_label = Some(value.trim().to_owned());
+                } else if let Some((_, parts)) = &mut link {
+                    parts.push(value.trim().to_owned());
+                }
+            }
+            Ok(Event::End(tag)) => match tag.local_name().as_ref() {
+                b"text" => in_label_text = false,
+                b"nav" => in_toc_nav = false,
+                b"a" => {
+                    if let Some((href, parts)) = link.take() {
+                        let label = parts.join(" ");
+                        if !label.is_empty() {
+                            entries.push((resolve_href(base, &href), label));
+                        }
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Eof) => break,
+            Ok(_) => {}
+            Err(_) => break,
+        }
+    }
+
+    entries.retain(|(_, label)| !label.is_empty());
+    entries
+}
+
+fn resolve_href(base: &str, href: &str) -> String {
+    let href = href.split('#').next().unwrap_or_default();
+    let mut parts: Vec<&str> = base.split('/').filter(|part| !part.is_empty()).collect();
+    for part in href.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            _ => parts.push(part),
+        }
+    }
+    parts.join("/")
 }
 
 #[cfg(test)]
@@ -172,8 +610,190 @@ mod tests {
             </package>
         "#;
 
-        let (title, author) = parse_opf_metadata(xml);
-        assert_eq!(title.as_deref(), Some("Book Title"));
-        assert_eq!(author.as_deref(), Some("Author Name"));
+        let metadata = parse_opf_metadata(xml);
+        assert_eq!(metadata.title.as_deref(), Some("Book Title"));
+        assert_eq!(metadata.author.as_deref(), Some("Author Name"));
+    }
+
+    #[test]
+    fn parses_language_publisher_identifier_and_date() {
+        let xml = r##"
+            <package xmlns:opf="http://www.idpf.org/2007/opf">
+              <metadata>
+                <dc:title>Le Livre</dc:title>
+                <dc:identifier id="uid">urn:uuid:1234</dc:identifier>
+                <dc:identifier id="isbn">9780000000002</dc:identifier>
+                <meta refines="#isbn" property="identifier-type" scheme="onix:codelist5">15</meta>
+                <dc:language>fr-CA</dc:language>
+                <meta property="dcterms:publisher">Maison</meta>
+                <dc:date>2021-03-04</dc:date>
+              </metadata>
+            </package>
+        "##;
+
+        let metadata = parse_opf_metadata(xml);
+        assert_eq!(metadata.language.as_deref(), Some("fr-CA"));
+        assert_eq!(metadata.publisher.as_deref(), Some("Maison"));
+        assert_eq!(metadata.identifier.as_deref(), Some("9780000000002"));
+        assert_eq!(metadata.date.as_deref(), Some("2021-03-04"));
+
+        let epub2 = r#"<package><metadata><dc:identifier>abc</dc:identifier><dc:identifier opf:scheme="ISBN">urn:isbn:0000000000</dc:identifier></metadata></package>"#;
+        assert_eq!(
+            parse_opf_metadata(epub2).identifier.as_deref(),
+            Some("0000000000")
+        );
+    }
+
+    #[test]
+    fn translated_title_is_written_into_opf() {
+        let book = EpubBook {
+            title: "The Cat".to_owned(),
+            author: None,
+            language: None,
+            publisher: None,
+            identifier: None,
+            date: None,
+            chapters: Vec::new(),
+            source_path: "book.epub".into(),
+            opf_path: None,
+        };
+        let options = EpubRepackOptions {
+            translate_metadata: true,
+        };
+
+        let metadata = repack_metadata(&book, &options, |text| {
+            Ok::<_, ()>(if text == "The Cat" {
+                "Le Chat".to_owned()
+            } else {
+                text.to_owned()
+            })
+        })
+        .unwrap();
+        assert_eq!(metadata.title, "Le Chat");
+        assert_eq!(metadata.author, None);
+
+        let opf = r#"<package><metadata><dc:title id="t">The Cat</dc:title><dc:creator>A. Writer</dc:creator></metadata></package>"#;
+        let rewritten = rewrite_opf_metadata(opf, &metadata).unwrap();
+        assert!(rewritten.contains(r#"<dc:title id="t">Le Chat</dc:title>"#));
+        assert!(rewritten.contains("<dc:creator>A. Writer</dc:creator>"));
+    }
+
+    #[test]
+    fn chapters_follow_the_spine_not_zip_order() {
+        use std::io::Write;
+        use zip::write::SimpleFileOptions;
+
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("book.epub");
+        let mut zip = zip::ZipWriter::new(File::create(&path).unwrap());
+        let options = SimpleFileOptions::default();
+        let opf = r#"<package><metadata><dc:title>Spine</dc:title></metadata>
+            <manifest>
+              <item id="z" href="text/a.xhtml" media-type="application/xhtml+xml"/>
+              <item id="y" href="text/b.xhtml" media-type="application/xhtml+xml"/>
+              <item id="x" href="text/c.xhtml" media-type="application/xhtml+xml"/>
+            </manifest>
+            <spine><itemref idref="x"/><itemref idref="z"/><itemref idref="y"/></spine>
+        </package>"#;
+        zip.start_file("OEBPS/content.opf", options).unwrap();
+        zip.write_all(opf.as_bytes()).unwrap();
+        for name in ["a", "b", "c"] {
+            zip.start_file(format!("OEBPS/text/{name}.xhtml"), options)
+                .unwrap();
+            zip.write_all(
+                format!("<html><head><title>{name}</title></head><body><p>Chapter {name}</p></body></html>")
+                    .as_bytes(),
+            )
+            .unwrap();
+        }
+        zip.finish().unwrap();
+
+        let book = load_epub(&path, true).unwrap();
+
+        let titles: Vec<&str> = book
+            .chapters
+            .iter()
+            .map(|chapter| chapter.title.as_str())
+            .collect();
+        assert_eq!(titles, ["c", "a", "b"]);
+        let orders: Vec<usize> = book.chapters.iter().map(|chapter| chapter.order).collect();
+        assert_eq!(orders, [0, 1, 2]);
+    }
+
+    #[test]
+    fn extracts_only_the_requested_chapter_range() {
+        use std::io::Write;
+        use zip::write::SimpleFileOptions;
+
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("book.epub");
+        let mut zip = zip::ZipWriter::new(File::create(&path).unwrap());
+        let options = SimpleFileOptions::default();
+        for name in ["a", "b", "c", "d", "e"] {
+            zip.start_file(format!("OEBPS/{name}.xhtml"), options)
+                .unwrap();
+            zip.write_all(format!("<html><body><p>Chapter {name}</p></body></html>").as_bytes())
+                .unwrap();
+        }
+        zip.finish().unwrap();
+
+        let middle = extract_text_range(&path, 1..3, true).unwrap();
+        assert_eq!(middle, "Chapter b\n\nChapter c");
+        let tail = extract_text_range(&path, 3..40, true).unwrap();
+        assert_eq!(tail, "Chapter d\n\nChapter e");
+        assert_eq!(clamp_chapter_range(7..9, 5), 5..5);
+    }
+
+    #[test]
+    fn toc_labels_name_chapters() {
+        use std::io::Write;
+        use zip::write::SimpleFileOptions;
+
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("book.epub");
+        let mut zip = zip::ZipWriter::new(File::create(&path).unwrap());
+        let options = SimpleFileOptions::default();
+        let ncx = r#"<ncx><docTitle><text>Book</text></docTitle><navMap>
+            <navPoint id="n1"><navLabel><text>Chapter One</text></navLabel><content src="text/ch001.xhtml#start"/></navPoint>
+        </navMap></ncx>"#;
+        zip.start_file("OEBPS/toc.ncx", options).unwrap();
+        zip.write_all(ncx.as_bytes()).unwrap();
+        for name in ["ch001", "ch002"] {
+            zip.start_file(format!("OEBPS/text/{name}.xhtml"), options)
+                .unwrap();
+            zip.write_all(format!("<html><body><p>Text of {name}</p></body></html>").as_bytes())
+                .unwrap();
+        }
+        zip.finish().unwrap();
+
+        let book = load_epub(&path, true).unwrap();
+        let titles: Vec<&str> = book
+            .chapters
+            .iter()
+            .map(|chapter| chapter.title.as_str())
+            .collect();
+        assert_eq!(titles, ["Chapter One", "ch002"]);
+
+        let nav = r#"<html><body><nav epub:type="toc"><ol><li><a href="../text/ch002.xhtml"><span>Chapter</span> Two</a></li></ol></nav></body></html>"#;
+        assert_eq!(
+            parse_toc(nav, "OEBPS/nav/nav.xhtml"),
+            [(
+                "OEBPS/text/ch002.xhtml".to_owned(),
+                "Chapter Two".to_owned()
+            )]
+        );
+    }
+
+    #[test]
+    fn chapter_body_is_replaced_but_head_is_kept() {
+        let xhtml = r#"<html><head><title>One</title><link href="style.css"/></head><body class="c"><h1>One</h1><p>Hello</p></body></html>"#;
+
+        let rewritten = rewrite_chapter_body(xhtml, "Bonjour\n\nA & B");
+
+        assert!(rewritten.starts_with(
+            r#"<html><head><title>One</title><link href="style.css"/></head><body class="c">"#
+        ));
+        assert!(rewritten.contains("<p>Bonjour</p>\n<p>A &amp; B</p>\n</body></html>"));
+        assert!(!rewritten.contains("Hello"));
     }
 }
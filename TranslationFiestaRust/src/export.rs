@@ -1,20 +1,104 @@
 use std::fs::File;
-use std::io::Write;
+use std::io::{BufWriter, Read, Write};
 use std::path::Path;
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use chrono::Utc;
-use csv::Writer;
-use printpdf::{BuiltinFont, Mm, Op, PdfDocument, PdfPage, PdfSaveOptions, Point, Pt, TextItem};
+use csv::{QuoteStyle, Writer, WriterBuilder};
+use printpdf::{
+    BuiltinFont, FontId, Mm, Op, ParsedFont, PdfDocument, PdfPage, PdfSaveOptions, Point, Pt,
+    TextItem,
+};
+use serde::Serialize;
 use serde_json::json;
-use zip::CompressionMethod;
+use tracing::warn;
 use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive};
+
+use crate::build_info;
+use crate::diff::{DiffSummary, diff_html, diff_words};
+use crate::docx::{DocxBlock, push_paragraphs, write_document};
+use crate::epub::{EpubMetadata, rewrite_chapter_body, rewrite_opf_metadata};
+use crate::fonts::load_cjk_font_data;
+use crate::html::{escape_html, escape_xml};
+use crate::models::{
+    BackTranslationResult, BatchErrorKind, BatchItemResult, EpubBook, ExportFormat, ExportMetadata,
+};
+use crate::stats::DurationStats;
+use crate::tmx::{TmxUnit, write_tmx};
+use crate::xlsx::{XlsxCell, XlsxSheet, write_workbook};
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ExportService {
+    highlight_diff: bool,
+    options: ExportOptions,
+}
+
+/// Format knobs for exports that readers' locales or tools disagree on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExportOptions {
+    /// CSV field separator; anything but a single ASCII character other than
+    /// a quote or line break falls back to `,`.
+    pub csv_delimiter: char,
+    /// Quote every CSV field rather than only those containing the
+    /// delimiter, a quote, or a line break.
+    pub csv_always_quote: bool,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            csv_delimiter: ',',
+            csv_always_quote: false,
+        }
+    }
+}
+
+impl ExportOptions {
+    fn csv_delimiter_byte(&self) -> u8 {
+        match u8::try_from(self.csv_delimiter) {
+            Ok(byte) if byte.is_ascii() && !matches!(byte, b'"' | b'\n' | b'\r') => byte,
+            _ => b',',
+        }
+    }
+}
 
-use crate::html::escape_html;
-use crate::models::{BackTranslationResult, BatchItemResult, ExportFormat, ExportMetadata};
+/// Counts and timings for a batch run, without the translated text.
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchSummary<'a> {
+    pub total: usize,
+    pub successful: usize,
+    pub failed: usize,
+    pub duration: DurationStats,
+    pub failures: Vec<BatchFailure<'a>>,
+}
 
-#[derive(Debug, Default, Clone)]
-pub struct ExportService;
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchFailure<'a> {
+    pub file_path: &'a str,
+    pub error: &'a str,
+}
+
+impl<'a> BatchSummary<'a> {
+    pub fn from_results(results: &'a [BatchItemResult]) -> Self {
+        let failures: Vec<_> = results
+            .iter()
+            .filter(|item| !item.success)
+            .map(|item| BatchFailure {
+                file_path: &item.file_path,
+                error: item.error.as_deref().unwrap_or("unknown error"),
+            })
+            .collect();
+
+        Self {
+            total: results.len(),
+            successful: results.len() - failures.len(),
+            failed: failures.len(),
+            duration: DurationStats::from_durations(results.iter().map(|item| item.duration_ms)),
+            failures,
+        }
+    }
+}
 
 #[derive(Debug, Clone, Copy)]
 pub struct BatchExportContext<'a> {
@@ -25,6 +109,48 @@ pub struct BatchExportContext<'a> {
 }
 
 impl ExportService {
+    /// Adds a word-level diff of the original against the back-translation to
+    /// HTML exports, marking insertions with `<ins>` and deletions with `<del>`.
+    pub fn with_highlight_diff(mut self, enabled: bool) -> Self {
+        self.highlight_diff = enabled;
+        self
+    }
+
+    pub fn highlight_diff(&self) -> bool {
+        self.highlight_diff
+    }
+
+    pub fn with_options(mut self, options: ExportOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn options(&self) -> ExportOptions {
+        self.options
+    }
+
+    fn csv_writer(&self) -> WriterBuilder {
+        let mut builder = WriterBuilder::new();
+        // The optional metadata block has two columns while result rows have
+        // more, so records may differ in length.
+        builder
+            .flexible(true)
+            .delimiter(self.options.csv_delimiter_byte());
+        if self.options.csv_always_quote {
+            builder.quote_style(QuoteStyle::Always);
+        }
+        builder
+    }
+
+    fn diff_block(&self, original: &str, back_translated: &str) -> Option<String> {
+        (self.highlight_diff && !original.is_empty()).then(|| {
+            format!(
+                "<div class=\"block diff\">{}</div>",
+                diff_html(original, back_translated)
+            )
+        })
+    }
+
     pub fn export_single(
         &self,
         result: &BackTranslationResult,
@@ -74,6 +200,7 @@ impl ExportService {
                 std::fs::write(output_path, serde_json::to_string_pretty(&payload)?)
                     .with_context(|| format!("failed to write {}", output_path.display()))?;
             }
+            ExportFormat::Jsonl => write_jsonl(output_path, std::slice::from_ref(result))?,
             ExportFormat::Csv => {
                 self.write_single_csv(result, output_path, include_metadata, metadata)?;
             }
@@ -90,11 +217,39 @@ impl ExportService {
             ExportFormat::Docx => {
                 self.write_single_docx(result, output_path, include_metadata, metadata)?;
             }
+            ExportFormat::Tmx => {
+                std::fs::write(output_path, self.single_tmx_content(result))
+                    .with_context(|| format!("failed to write {}", output_path.display()))?;
+            }
+            ExportFormat::Xlsx => self.write_single_xlsx(result, output_path)?,
         }
 
         Ok(())
     }
 
+    /// The bytes `export_single` would write, for destinations without a
+    /// path such as stdout. Binary formats go through a temporary file.
+    pub fn render_single(
+        &self,
+        result: &BackTranslationResult,
+        format: ExportFormat,
+        include_metadata: bool,
+    ) -> Result<Vec<u8>> {
+        let temp_path = std::env::temp_dir().join(format!(
+            "tf-export-{}.{}",
+            uuid::Uuid::new_v4(),
+            format.extension()
+        ));
+        let exported = self
+            .export_single(result, &temp_path, format, include_metadata)
+            .and_then(|()| {
+                std::fs::read(&temp_path)
+                    .with_context(|| format!("failed to read {}", temp_path.display()))
+            });
+        let _ = std::fs::remove_file(&temp_path);
+        exported
+    }
+
     pub fn export_batch(
         &self,
         results: &[BatchItemResult],
@@ -119,6 +274,11 @@ impl ExportService {
             target_language: context.target_language.to_owned(),
             processing_time_seconds: average_secs,
             api_used: context.provider.to_owned(),
+            fidelity_score: None,
+            forward_seconds: None,
+            backward_seconds: None,
+            tool_version: build_info::TOOL_VERSION.to_owned(),
+            build_info: build_info::build_details(),
         };
 
         match format {
@@ -149,6 +309,7 @@ impl ExportService {
                 std::fs::write(output_path, serde_json::to_string_pretty(&payload)?)
                     .with_context(|| format!("failed to write {}", output_path.display()))?;
             }
+            ExportFormat::Jsonl => write_jsonl(output_path, results)?,
             ExportFormat::Csv => {
                 self.write_batch_csv(results, output_path, context.include_metadata, &metadata)?
             }
@@ -165,8 +326,178 @@ impl ExportService {
             ExportFormat::Docx => {
                 self.write_batch_docx(results, output_path, context.include_metadata, &metadata)?
             }
+            ExportFormat::Tmx => {
+                std::fs::write(output_path, self.batch_tmx_content(results, &metadata))
+                    .with_context(|| format!("failed to write {}", output_path.display()))?;
+            }
+            ExportFormat::Xlsx => {
+                self.write_batch_xlsx(results, output_path, context.include_metadata, &metadata)?
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes one `batch.<ext>` entry per format into a single zip archive.
+    pub fn export_bundle(
+        &self,
+        results: &[BatchItemResult],
+        output_path: &Path,
+        formats: &[ExportFormat],
+        context: BatchExportContext<'_>,
+    ) -> Result<()> {
+        if formats.is_empty() {
+            bail!("a bundle needs at least one export format");
+        }
+
+        let file = File::create(output_path)
+            .with_context(|| format!("failed to create {}", output_path.display()))?;
+        let mut zip = zip::ZipWriter::new(file);
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+        let mut written = Vec::new();
+        for &format in formats {
+            if written.contains(&format) {
+                continue;
+            }
+            let temp_path = std::env::temp_dir().join(format!(
+                "tf-bundle-{}.{}",
+                uuid::Uuid::new_v4(),
+                format.extension()
+            ));
+            let exported = self
+                .export_batch(results, &temp_path, format, context)
+                .and_then(|()| {
+                    std::fs::read(&temp_path)
+                        .with_context(|| format!("failed to read {}", temp_path.display()))
+                });
+            let _ = std::fs::remove_file(&temp_path);
+
+            zip.start_file(format!("batch.{}", format.extension()), options)?;
+            zip.write_all(&exported?)?;
+            written.push(format);
+        }
+
+        zip.finish()
+            .with_context(|| format!("failed to finish bundle {}", output_path.display()))?;
+        Ok(())
+    }
+
+    /// Writes the `BatchSummary` of `results` as txt, md, html, or json.
+    pub fn export_batch_summary(
+        &self,
+        results: &[BatchItemResult],
+        output_path: &Path,
+        format: ExportFormat,
+    ) -> Result<()> {
+        let summary = BatchSummary::from_results(results);
+        let content = match format {
+            ExportFormat::Txt => batch_summary_text(&summary, false),
+            ExportFormat::Markdown => batch_summary_text(&summary, true),
+            ExportFormat::Html => batch_summary_html(&summary),
+            ExportFormat::Json => serde_json::to_string_pretty(&summary)?,
+            other => bail!(
+                "batch summaries can be exported as txt, md, html, or json, not {}",
+                other.extension()
+            ),
+        };
+        std::fs::write(output_path, content)
+            .with_context(|| format!("failed to write {}", output_path.display()))
+    }
+
+    /// Rebuilds `book` with each chapter's body replaced by the matching entry of
+    /// `translated_chapters`; the OPF, spine, stylesheets, and images are copied as-is.
+    pub fn export_epub(
+        &self,
+        book: &EpubBook,
+        translated_chapters: &[String],
+        output_path: &Path,
+    ) -> Result<()> {
+        let metadata = EpubMetadata {
+            title: book.title.clone(),
+            author: book.author.clone(),
+        };
+        self.export_epub_with_metadata(book, translated_chapters, &metadata, output_path)
+    }
+
+    /// Like [`Self::export_epub`], but writes `metadata` into the OPF when it
+    /// differs from the book's own title and author.
+    pub fn export_epub_with_metadata(
+        &self,
+        book: &EpubBook,
+        translated_chapters: &[String],
+        metadata: &EpubMetadata,
+        output_path: &Path,
+    ) -> Result<()> {
+        if translated_chapters.len() != book.chapters.len() {
+            bail!(
+                "expected {} translated chapters, got {}",
+                book.chapters.len(),
+                translated_chapters.len()
+            );
+        }
+
+        let source = File::open(&book.source_path)
+            .with_context(|| format!("failed to open EPUB {}", book.source_path.display()))?;
+        let mut archive = ZipArchive::new(source).context("failed to read EPUB zip archive")?;
+        let rewrite_opf = metadata.title != book.title || metadata.author != book.author;
+
+        let file = File::create(output_path)
+            .with_context(|| format!("failed to create {}", output_path.display()))?;
+        let mut zip = zip::ZipWriter::new(file);
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+        // Readers sniff the first entry, so it must be the uncompressed mimetype.
+        zip.start_file(
+            "mimetype",
+            SimpleFileOptions::default().compression_method(CompressionMethod::Stored),
+        )?;
+        zip.write_all(b"application/epub+zip")?;
+
+        let mut has_container = false;
+        for index in 0..archive.len() {
+            let mut entry = archive
+                .by_index(index)
+                .with_context(|| format!("failed to open EPUB entry index {index}"))?;
+            let name = entry.name().to_owned();
+            if name == "mimetype" {
+                continue;
+            }
+            has_container |= name == "META-INF/container.xml";
+
+            let chapter = book
+                .chapters
+                .iter()
+                .position(|chapter| chapter.path == name);
+            if let Some(position) = chapter {
+                let mut xhtml = String::new();
+                entry
+                    .read_to_string(&mut xhtml)
+                    .with_context(|| format!("failed to read chapter entry {name}"))?;
+                zip.start_file(name.as_str(), options)?;
+                zip.write_all(
+                    rewrite_chapter_body(&xhtml, &translated_chapters[position]).as_bytes(),
+                )?;
+            } else if rewrite_opf && book.opf_path.as_deref() == Some(name.as_str()) {
+                let mut opf = String::new();
+                entry
+                    .read_to_string(&mut opf)
+                    .with_context(|| format!("failed to read OPF entry {name}"))?;
+                zip.start_file(name.as_str(), options)?;
+                zip.write_all(rewrite_opf_metadata(&opf, metadata)?.as_bytes())?;
+            } else {
+                zip.raw_copy_file(entry)
+                    .with_context(|| format!("failed to copy EPUB entry {name}"))?;
+            }
         }
 
+        if !has_container && let Some(opf_path) = &book.opf_path {
+            zip.start_file("META-INF/container.xml", options)?;
+            zip.write_all(epub_container_xml(opf_path).as_bytes())?;
+        }
+
+        zip.finish()
+            .with_context(|| format!("failed to finish EPUB {}", output_path.display()))?;
         Ok(())
     }
 
@@ -187,23 +518,18 @@ impl ExportService {
                 "metadata": if include_metadata { serde_json::to_value(&metadata)? } else { json!(null) },
                 "result": result,
             }))?,
+            ExportFormat::Jsonl => format!("{}\n", serde_json::to_string(result)?),
             ExportFormat::Csv => {
-                let mut output = String::new();
-                output.push_str("original_text,intermediate_text,back_translated_text,source_language,target_language,provider_id,duration_ms\n");
-                output.push_str(&format!(
-                    "\"{}\",\"{}\",\"{}\",{},{},{},{}\n",
-                    result.original_text.replace('"', "\"\""),
-                    result.intermediate_text.replace('"', "\"\""),
-                    result.back_translated_text.replace('"', "\"\""),
-                    result.source_language,
-                    result.intermediate_language,
-                    result.provider_id,
-                    result.duration_ms
-                ));
-                output
+                let mut writer = self.csv_writer().from_writer(Vec::new());
+                self.write_single_csv_to(&mut writer, result, include_metadata, &metadata)?;
+                let bytes = writer
+                    .into_inner()
+                    .map_err(|error| anyhow::anyhow!("failed to finish CSV preview: {error}"))?;
+                String::from_utf8(bytes).context("CSV preview is not valid UTF-8")?
             }
             ExportFormat::Xml => self.single_xml_content(result, include_metadata, &metadata),
-            ExportFormat::Pdf | ExportFormat::Docx => {
+            ExportFormat::Tmx => self.single_tmx_content(result),
+            ExportFormat::Pdf | ExportFormat::Docx | ExportFormat::Xlsx => {
                 self.single_markdown_content(result, include_metadata, &metadata)
             }
         };
@@ -220,6 +546,9 @@ impl ExportService {
         let mut output = String::new();
 
         output.push_str("TranslationFiesta Rust - Translation Result\n\n");
+        if let Some(reason) = &result.suspicious_reason {
+            output.push_str(&format!("Warning: {reason}\n\n"));
+        }
         output.push_str("Original Text:\n");
         output.push_str(&result.original_text);
         output.push_str("\n\nIntermediate Translation:\n");
@@ -243,6 +572,12 @@ impl ExportService {
                 "- Processing Time: {:.2}s\n",
                 metadata.processing_time_seconds
             ));
+            if let Some(hops) = metadata.hop_breakdown() {
+                output.push_str(&format!("- Hop Times: {hops}\n"));
+            }
+            if let Some(score) = metadata.fidelity_score {
+                output.push_str(&format!("- Round-trip Fidelity: {:.0}%\n", score * 100.0));
+            }
             output.push_str(&format!("- Timestamp: {}\n", metadata.created_date));
         }
 
@@ -257,6 +592,9 @@ impl ExportService {
     ) -> String {
         let mut output = String::new();
         output.push_str("# Translation Result\n\n");
+        if let Some(reason) = &result.suspicious_reason {
+            output.push_str(&format!("> **Warning:** {reason}\n\n"));
+        }
         output.push_str("## Original Text\n\n");
         output.push_str(&result.original_text);
         output.push_str("\n\n## Intermediate Translation\n\n");
@@ -280,6 +618,12 @@ impl ExportService {
                 "- Processing Time: {:.2}s\n",
                 metadata.processing_time_seconds
             ));
+            if let Some(hops) = metadata.hop_breakdown() {
+                output.push_str(&format!("- Hop Times: {hops}\n"));
+            }
+            if let Some(score) = metadata.fidelity_score {
+                output.push_str(&format!("- Round-trip Fidelity: {:.0}%\n", score * 100.0));
+            }
             output.push_str(&format!("- Timestamp: {}\n", metadata.created_date));
         }
 
@@ -294,25 +638,52 @@ impl ExportService {
     ) -> String {
         let metadata_block = if include_metadata {
             format!(
-                "<section class=\"metadata\"><h2>Metadata</h2><table><tr><th>API Used</th><td>{}</td></tr><tr><th>Source</th><td>{}</td></tr><tr><th>Target</th><td>{}</td></tr><tr><th>Processing Time</th><td>{:.2}s</td></tr><tr><th>Timestamp</th><td>{}</td></tr></table></section>",
+                "<section class=\"metadata\"><h2>Metadata</h2><table><tr><th>API Used</th><td>{}</td></tr><tr><th>Source</th><td>{}</td></tr><tr><th>Target</th><td>{}</td></tr><tr><th>Processing Time</th><td>{:.2}s</td></tr>{}{}<tr><th>Timestamp</th><td>{}</td></tr></table></section>",
                 escape_html(&metadata.api_used),
                 escape_html(&metadata.source_language),
                 escape_html(&metadata.target_language),
                 metadata.processing_time_seconds,
+                metadata
+                    .hop_breakdown()
+                    .map(|hops| format!("<tr><th>Hop Times</th><td>{hops}</td></tr>"))
+                    .unwrap_or_default(),
+                metadata
+                    .fidelity_score
+                    .map(|score| format!(
+                        "<tr><th>Round-trip Fidelity</th><td>{:.0}%</td></tr>",
+                        score * 100.0
+                    ))
+                    .unwrap_or_default(),
                 escape_html(&metadata.created_date.to_rfc3339()),
             )
         } else {
             String::new()
         };
 
+        let warning_block = result
+            .suspicious_reason
+            .as_ref()
+            .map(|reason| {
+                format!(
+                    "<p class=\"warning\"><strong>Warning:</strong> {}</p>",
+                    escape_html(reason)
+                )
+            })
+            .unwrap_or_default();
+
         format!(
-            "<!doctype html><html lang=\"en\"><head><meta charset=\"utf-8\"><meta name=\"viewport\" content=\"width=device-width,initial-scale=1\"><title>{}</title><style>{}</style></head><body><main class=\"container\"><h1>Translation Result</h1><section><h2>Original Text</h2><div class=\"block\">{}</div></section><section><h2>Intermediate Translation</h2><div class=\"block\">{}</div></section><section><h2>Back Translation</h2><div class=\"block\">{}</div></section>{}</main></body></html>",
+            "<!doctype html><html lang=\"en\"><head><meta charset=\"utf-8\"><meta name=\"viewport\" content=\"width=device-width,initial-scale=1\"><title>{}</title><style>{}</style></head><body><main class=\"container\"><h1>Translation Result</h1>{}<section><h2>Original Text</h2><div class=\"block\">{}</div></section><section><h2>Intermediate Translation</h2><div class=\"block\">{}</div></section><section><h2>Back Translation</h2><div class=\"block\">{}</div></section>{}{}</main>{}</body></html>",
             escape_html(&metadata.title),
             base_html_style(),
+            warning_block,
             escape_html(&result.original_text).replace('\n', "<br>"),
             escape_html(&result.intermediate_text).replace('\n', "<br>"),
             escape_html(&result.back_translated_text).replace('\n', "<br>"),
+            self.diff_block(&result.original_text, &result.back_translated_text)
+                .map(|diff| format!("<section><h2>Changes</h2>{diff}</section>"))
+                .unwrap_or_default(),
             metadata_block,
+            html_footer(include_metadata, metadata),
         )
     }
 
@@ -324,13 +695,24 @@ impl ExportService {
     ) -> String {
         let metadata_xml = if include_metadata {
             format!(
-                "<metadata><title>{}</title><apiUsed>{}</apiUsed><sourceLanguage>{}</sourceLanguage><targetLanguage>{}</targetLanguage><processingTimeSeconds>{:.2}</processingTimeSeconds><timestamp>{}</timestamp></metadata>",
-                xml_escape(&metadata.title),
-                xml_escape(&metadata.api_used),
-                xml_escape(&metadata.source_language),
-                xml_escape(&metadata.target_language),
+                "<metadata><title>{}</title><apiUsed>{}</apiUsed><sourceLanguage>{}</sourceLanguage><targetLanguage>{}</targetLanguage><processingTimeSeconds>{:.2}</processingTimeSeconds>{}{}<timestamp>{}</timestamp></metadata>",
+                escape_xml(&metadata.title),
+                escape_xml(&metadata.api_used),
+                escape_xml(&metadata.source_language),
+                escape_xml(&metadata.target_language),
                 metadata.processing_time_seconds,
-                xml_escape(&metadata.created_date.to_rfc3339()),
+                metadata
+                    .forward_seconds
+                    .zip(metadata.backward_seconds)
+                    .map(|(forward, backward)| format!(
+                        "<forwardSeconds>{forward:.2}</forwardSeconds><backwardSeconds>{backward:.2}</backwardSeconds>"
+                    ))
+                    .unwrap_or_default(),
+                metadata
+                    .fidelity_score
+                    .map(|score| format!("<fidelityScore>{score:.4}</fidelityScore>"))
+                    .unwrap_or_default(),
+                escape_xml(&metadata.created_date.to_rfc3339()),
             )
         } else {
             String::new()
@@ -339,9 +721,9 @@ impl ExportService {
         format!(
             "<?xml version=\"1.0\" encoding=\"UTF-8\"?><translationResult>{}<originalText>{}</originalText><intermediateText>{}</intermediateText><backTranslatedText>{}</backTranslatedText></translationResult>",
             metadata_xml,
-            xml_escape(&result.original_text),
-            xml_escape(&result.intermediate_text),
-            xml_escape(&result.back_translated_text),
+            escape_xml(&result.original_text),
+            escape_xml(&result.intermediate_text),
+            escape_xml(&result.back_translated_text),
         )
     }
 
@@ -387,6 +769,9 @@ impl ExportService {
                 if let Some(error) = &result.error {
                     output.push_str(&format!("- Error: {}\n", error));
                 }
+                if let Some(reason) = &result.suspicious_reason {
+                    output.push_str(&format!("- Warning: {}\n", reason));
+                }
                 output.push_str("\n### Intermediate\n\n");
                 output.push_str(&result.intermediate_text);
                 output.push_str("\n\n### Back Translation\n\n");
@@ -403,6 +788,9 @@ impl ExportService {
                 if let Some(error) = &result.error {
                     output.push_str(&format!("Error: {}\n", error));
                 }
+                if let Some(reason) = &result.suspicious_reason {
+                    output.push_str(&format!("Warning: {}\n", reason));
+                }
                 output.push_str("Intermediate:\n");
                 output.push_str(&result.intermediate_text);
                 output.push_str("\nBack Translation:\n");
@@ -435,7 +823,7 @@ impl ExportService {
         body.push_str("<section><h2>Results</h2>");
         for (index, result) in results.iter().enumerate() {
             body.push_str(&format!(
-                "<article class=\"item\"><h3>File {}</h3><p><strong>Path:</strong> {}</p><p><strong>Success:</strong> {}</p><p><strong>Duration:</strong> {:.2}s</p>{}<h4>Intermediate</h4><div class=\"block\">{}</div><h4>Back Translation</h4><div class=\"block\">{}</div></article>",
+                "<article class=\"item\"><h3>File {}</h3><p><strong>Path:</strong> {}</p><p><strong>Success:</strong> {}</p><p><strong>Duration:</strong> {:.2}s</p>{}<h4>Intermediate</h4><div class=\"block\">{}</div><h4>Back Translation</h4><div class=\"block\">{}</div>{}</article>",
                 index + 1,
                 escape_html(&result.file_path),
                 result.success,
@@ -446,16 +834,21 @@ impl ExportService {
                     .map(|error| format!("<p><strong>Error:</strong> {}</p>", escape_html(error)))
                     .unwrap_or_default(),
                 escape_html(&result.intermediate_text).replace('\n', "<br>"),
-                escape_html(&result.back_translated_text).replace('\n', "<br>")
+                escape_html(&result.back_translated_text).replace('\n', "<br>"),
+                self.diff_block(&result.original_text, &result.back_translated_text)
+                    .filter(|_| result.success)
+                    .map(|diff| format!("<h4>Changes</h4>{diff}"))
+                    .unwrap_or_default()
             ));
         }
         body.push_str("</section>");
 
         format!(
-            "<!doctype html><html lang=\"en\"><head><meta charset=\"utf-8\"><meta name=\"viewport\" content=\"width=device-width,initial-scale=1\"><title>{}</title><style>{}</style></head><body><main class=\"container\"><h1>Batch Translation Results</h1>{}</main></body></html>",
+            "<!doctype html><html lang=\"en\"><head><meta charset=\"utf-8\"><meta name=\"viewport\" content=\"width=device-width,initial-scale=1\"><title>{}</title><style>{}</style></head><body><main class=\"container\"><h1>Batch Translation Results</h1>{}</main>{}</body></html>",
             escape_html(&metadata.title),
             base_html_style(),
             body,
+            html_footer(include_metadata, metadata),
         )
     }
 
@@ -471,10 +864,10 @@ impl ExportService {
         if include_metadata {
             xml.push_str(&format!(
                 "<metadata><title>{}</title><apiUsed>{}</apiUsed><sourceLanguage>{}</sourceLanguage><targetLanguage>{}</targetLanguage><averageProcessingTime>{:.2}</averageProcessingTime></metadata>",
-                xml_escape(&metadata.title),
-                xml_escape(&metadata.api_used),
-                xml_escape(&metadata.source_language),
-                xml_escape(&metadata.target_language),
+                escape_xml(&metadata.title),
+                escape_xml(&metadata.api_used),
+                escape_xml(&metadata.source_language),
+                escape_xml(&metadata.target_language),
                 metadata.processing_time_seconds,
             ));
         }
@@ -482,15 +875,18 @@ impl ExportService {
         xml.push_str("<items>");
         for item in results {
             xml.push_str(&format!(
-                "<item><filePath>{}</filePath><success>{}</success><durationMs>{}</durationMs><intermediateText>{}</intermediateText><backTranslatedText>{}</backTranslatedText>{}</item>",
-                xml_escape(&item.file_path),
+                "<item><filePath>{}</filePath><success>{}</success><durationMs>{}</durationMs><intermediateText>{}</intermediateText><backTranslatedText>{}</backTranslatedText>{}{}</item>",
+                escape_xml(&item.file_path),
                 item.success,
                 item.duration_ms,
-                xml_escape(&item.intermediate_text),
-                xml_escape(&item.back_translated_text),
+                escape_xml(&item.intermediate_text),
+                escape_xml(&item.back_translated_text),
                 item.error
                     .as_ref()
-                    .map(|error| format!("<error>{}</error>", xml_escape(error)))
+                    .map(|error| format!("<error>{}</error>", escape_xml(error)))
+                    .unwrap_or_default(),
+                item.error_kind
+                    .map(|kind| format!("<errorKind>{kind}</errorKind>"))
                     .unwrap_or_default()
             ));
         }
@@ -498,6 +894,36 @@ impl ExportService {
         xml
     }
 
+    fn single_tmx_content(&self, result: &BackTranslationResult) -> String {
+        write_tmx(
+            &result.source_language,
+            &result.intermediate_language,
+            &[TmxUnit {
+                source_text: &result.original_text,
+                target_text: &result.intermediate_text,
+                file_path: None,
+            }],
+        )
+    }
+
+    /// Pairs each successful item's original text with its intermediate translation,
+    /// which is the only text in the target language.
+    fn batch_tmx_content(&self, results: &[BatchItemResult], metadata: &ExportMetadata) -> String {
+        let units: Vec<TmxUnit<'_>> = results
+            .iter()
+            .filter(|item| {
+                item.success && !item.original_text.is_empty() && !item.intermediate_text.is_empty()
+            })
+            .map(|item| TmxUnit {
+                source_text: &item.original_text,
+                target_text: &item.intermediate_text,
+                file_path: Some(&item.file_path),
+            })
+            .collect();
+
+        write_tmx(&metadata.source_language, &metadata.target_language, &units)
+    }
+
     fn write_single_csv(
         &self,
         result: &BackTranslationResult,
@@ -505,9 +931,21 @@ impl ExportService {
         include_metadata: bool,
         metadata: &ExportMetadata,
     ) -> Result<()> {
-        let mut writer = Writer::from_path(output_path)
+        let mut writer = self
+            .csv_writer()
+            .from_path(output_path)
             .with_context(|| format!("failed to create CSV {}", output_path.display()))?;
+        self.write_single_csv_to(&mut writer, result, include_metadata, metadata)
+    }
 
+    /// Shared by the file export and the preview so both produce identical CSV.
+    fn write_single_csv_to<W: Write>(
+        &self,
+        writer: &mut Writer<W>,
+        result: &BackTranslationResult,
+        include_metadata: bool,
+        metadata: &ExportMetadata,
+    ) -> Result<()> {
         if include_metadata {
             writer.write_record(["metadata_key", "metadata_value"])?;
             writer.write_record(["title", metadata.title.as_str()])?;
@@ -518,6 +956,15 @@ impl ExportService {
                 "processing_time_seconds",
                 &format!("{:.2}", metadata.processing_time_seconds),
             ])?;
+            if let (Some(forward), Some(backward)) =
+                (metadata.forward_seconds, metadata.backward_seconds)
+            {
+                writer.write_record(["forward_seconds", &format!("{forward:.2}")])?;
+                writer.write_record(["backward_seconds", &format!("{backward:.2}")])?;
+            }
+            if let Some(score) = metadata.fidelity_score {
+                writer.write_record(["fidelity_score", &format!("{score:.4}")])?;
+            }
             writer.write_record(["", ""])?;
         }
 
@@ -552,7 +999,9 @@ impl ExportService {
         include_metadata: bool,
         metadata: &ExportMetadata,
     ) -> Result<()> {
-        let mut writer = Writer::from_path(output_path)
+        let mut writer = self
+            .csv_writer()
+            .from_path(output_path)
             .with_context(|| format!("failed to create CSV {}", output_path.display()))?;
 
         if include_metadata {
@@ -565,6 +1014,9 @@ impl ExportService {
                 "processing_time_seconds",
                 &format!("{:.2}", metadata.processing_time_seconds),
             ])?;
+            if let Some(score) = metadata.fidelity_score {
+                writer.write_record(["fidelity_score", &format!("{score:.4}")])?;
+            }
             writer.write_record(["", ""])?;
         }
 
@@ -575,6 +1027,7 @@ impl ExportService {
             "intermediate_text",
             "back_translated_text",
             "error",
+            "error_kind",
         ])?;
 
         for item in results {
@@ -585,6 +1038,7 @@ impl ExportService {
                 item.intermediate_text.as_str(),
                 item.back_translated_text.as_str(),
                 item.error.as_deref().unwrap_or(""),
+                item.error_kind.map(BatchErrorKind::as_str).unwrap_or(""),
             ])?;
         }
 
@@ -599,7 +1053,8 @@ impl ExportService {
         include_metadata: bool,
         metadata: &ExportMetadata,
     ) -> Result<()> {
-        let content = self.single_markdown_content(result, include_metadata, metadata);
+        let mut content = self.single_markdown_content(result, include_metadata, metadata);
+        content.push_str(&text_footer(include_metadata, metadata));
         write_pdf(output_path, &metadata.title, &content)
     }
 
@@ -610,7 +1065,8 @@ impl ExportService {
         include_metadata: bool,
         metadata: &ExportMetadata,
     ) -> Result<()> {
-        let content = self.batch_text_content(results, include_metadata, metadata, false);
+        let mut content = self.batch_text_content(results, include_metadata, metadata, false);
+        content.push_str(&text_footer(include_metadata, metadata));
         write_pdf(output_path, &metadata.title, &content)
     }
 
@@ -621,8 +1077,52 @@ impl ExportService {
         include_metadata: bool,
         metadata: &ExportMetadata,
     ) -> Result<()> {
-        let content = self.single_txt_content(result, include_metadata, metadata);
-        write_docx(output_path, &content)
+        let mut blocks = vec![DocxBlock::Title(
+            "TranslationFiesta Rust - Translation Result".to_owned(),
+        )];
+        if let Some(reason) = &result.suspicious_reason {
+            blocks.push(DocxBlock::labeled("Warning", reason.as_str()));
+        }
+        for (heading, text) in [
+            ("Original Text", &result.original_text),
+            ("Intermediate Translation", &result.intermediate_text),
+            ("Back Translation", &result.back_translated_text),
+        ] {
+            blocks.push(DocxBlock::Heading1(heading.to_owned()));
+            push_paragraphs(&mut blocks, text);
+        }
+
+        if include_metadata {
+            blocks.push(DocxBlock::Heading1("Metadata".to_owned()));
+            blocks.push(DocxBlock::labeled("API Used", metadata.api_used.as_str()));
+            blocks.push(DocxBlock::labeled(
+                "Source Language",
+                metadata.source_language.as_str(),
+            ));
+            blocks.push(DocxBlock::labeled(
+                "Target Language",
+                metadata.target_language.as_str(),
+            ));
+            blocks.push(DocxBlock::labeled(
+                "Processing Time",
+                format!("{:.2}s", metadata.processing_time_seconds),
+            ));
+            if let Some(hops) = metadata.hop_breakdown() {
+                blocks.push(DocxBlock::labeled("Hop Times", hops));
+            }
+            if let Some(score) = metadata.fidelity_score {
+                blocks.push(DocxBlock::labeled(
+                    "Round-trip Fidelity",
+                    format!("{:.0}%", score * 100.0),
+                ));
+            }
+            blocks.push(DocxBlock::labeled(
+                "Timestamp",
+                metadata.created_date.to_string(),
+            ));
+        }
+
+        write_document(output_path, &blocks)
     }
 
     fn write_batch_docx(
@@ -632,32 +1132,201 @@ impl ExportService {
         include_metadata: bool,
         metadata: &ExportMetadata,
     ) -> Result<()> {
-        let content = self.batch_text_content(results, include_metadata, metadata, false);
-        write_docx(output_path, &content)
+        let mut blocks = vec![DocxBlock::Title("Batch Translation Results".to_owned())];
+        if include_metadata {
+            blocks.push(DocxBlock::labeled("API Used", metadata.api_used.as_str()));
+            blocks.push(DocxBlock::labeled(
+                "Source Language",
+                metadata.source_language.as_str(),
+            ));
+            blocks.push(DocxBlock::labeled(
+                "Target Language",
+                metadata.target_language.as_str(),
+            ));
+            blocks.push(DocxBlock::labeled(
+                "Average Processing Time",
+                format!("{:.2}s", metadata.processing_time_seconds),
+            ));
+        }
+
+        for result in results {
+            blocks.push(DocxBlock::Heading1(result.file_path.clone()));
+            blocks.push(DocxBlock::labeled("Success", result.success.to_string()));
+            blocks.push(DocxBlock::labeled(
+                "Duration",
+                format!("{:.2}s", result.duration_ms as f64 / 1000.0),
+            ));
+            if let Some(error) = &result.error {
+                blocks.push(DocxBlock::labeled("Error", error.as_str()));
+            }
+            if let Some(reason) = &result.suspicious_reason {
+                blocks.push(DocxBlock::labeled("Warning", reason.as_str()));
+            }
+            blocks.push(DocxBlock::Heading2("Intermediate Translation".to_owned()));
+            push_paragraphs(&mut blocks, &result.intermediate_text);
+            blocks.push(DocxBlock::Heading2("Back Translation".to_owned()));
+            push_paragraphs(&mut blocks, &result.back_translated_text);
+        }
+
+        write_document(output_path, &blocks)
+    }
+
+    fn write_single_xlsx(&self, result: &BackTranslationResult, output_path: &Path) -> Result<()> {
+        let mut sheet = XlsxSheet::new(
+            "Result",
+            &[
+                "original_text",
+                "intermediate_text",
+                "back_translated_text",
+                "source_language",
+                "target_language",
+                "provider_id",
+                "duration_ms",
+                "similarity",
+            ],
+        );
+        sheet.push_row(vec![
+            result.original_text.as_str().into(),
+            result.intermediate_text.as_str().into(),
+            result.back_translated_text.as_str().into(),
+            result.source_language.as_str().into(),
+            result.intermediate_language.as_str().into(),
+            result.provider_id.as_str().into(),
+            XlsxCell::Number(result.duration_ms as f64),
+            XlsxCell::Number(round_trip_similarity(
+                &result.original_text,
+                &result.back_translated_text,
+            )),
+        ]);
+        write_workbook(output_path, &[sheet])
+    }
+
+    /// Writes a "Summary" sheet of run-level figures and a "Results" sheet with one
+    /// row per file.
+    fn write_batch_xlsx(
+        &self,
+        results: &[BatchItemResult],
+        output_path: &Path,
+        include_metadata: bool,
+        metadata: &ExportMetadata,
+    ) -> Result<()> {
+        let successful: Vec<&BatchItemResult> =
+            results.iter().filter(|item| item.success).collect();
+        let similarities: Vec<Option<f64>> = results
+            .iter()
+            .map(|item| {
+                (item.success && !item.original_text.is_empty())
+                    .then(|| round_trip_similarity(&item.original_text, &item.back_translated_text))
+            })
+            .collect();
+        let scored: Vec<f64> = similarities.iter().flatten().copied().collect();
+
+        let mut summary = XlsxSheet::new("Summary", &["metric", "value"]);
+        let mut metric = |name: &str, value: XlsxCell| summary.push_row(vec![name.into(), value]);
+        metric("total_files", XlsxCell::Number(results.len() as f64));
+        metric("successful", XlsxCell::Number(successful.len() as f64));
+        metric(
+            "failed",
+            XlsxCell::Number((results.len() - successful.len()) as f64),
+        );
+        metric(
+            "success_rate",
+            XlsxCell::Number(if results.is_empty() {
+                0.0
+            } else {
+                successful.len() as f64 / results.len() as f64
+            }),
+        );
+        metric(
+            "average_duration_ms",
+            XlsxCell::Number(metadata.processing_time_seconds * 1000.0),
+        );
+        if !scored.is_empty() {
+            metric(
+                "average_similarity",
+                XlsxCell::Number(scored.iter().sum::<f64>() / scored.len() as f64),
+            );
+        }
+        if include_metadata {
+            metric("source_language", metadata.source_language.as_str().into());
+            metric("target_language", metadata.target_language.as_str().into());
+            metric("provider", metadata.api_used.as_str().into());
+            metric("created", metadata.created_date.to_rfc3339().into());
+            metric("generated_by", generated_by(metadata).into());
+        }
+
+        let mut details = XlsxSheet::new(
+            "Results",
+            &[
+                "file_path",
+                "success",
+                "duration_ms",
+                "similarity",
+                "original_text",
+                "intermediate_text",
+                "back_translated_text",
+                "error",
+            ],
+        )
+        .with_frozen_header();
+        for (item, similarity) in results.iter().zip(similarities) {
+            details.push_row(vec![
+                item.file_path.as_str().into(),
+                XlsxCell::Bool(item.success),
+                XlsxCell::Number(item.duration_ms as f64),
+                similarity.map_or(XlsxCell::Text(String::new()), XlsxCell::Number),
+                item.original_text.as_str().into(),
+                item.intermediate_text.as_str().into(),
+                item.back_translated_text.as_str().into(),
+                item.error.as_deref().unwrap_or_default().into(),
+            ]);
+        }
+
+        write_workbook(output_path, &[summary, details])
     }
 }
 
+/// Round-trip similarity between the original and the back-translation.
+fn round_trip_similarity(original: &str, back_translated: &str) -> f64 {
+    DiffSummary::from_ops(&diff_words(original, back_translated)).similarity()
+}
+
 /// Maximum lines per A4 page (11pt font, 14pt line height, 12mm margins).
 const PDF_LINES_PER_PAGE: usize = 55;
 
-fn build_pdf_page(lines: &[&str]) -> PdfPage {
+/// Helvetica only covers Latin text, so anything else is set in an embedded font.
+#[derive(Clone)]
+enum PdfFont {
+    Builtin(BuiltinFont),
+    Embedded(FontId),
+}
+
+fn build_pdf_page(lines: &[&str], font: &PdfFont) -> PdfPage {
+    let size = Pt(11.0);
     let mut ops = vec![
         Op::StartTextSection,
         Op::SetTextCursor {
             pos: Point::new(Mm(12.0), Mm(285.0)),
         },
-        Op::SetFontSizeBuiltinFont {
-            size: Pt(11.0),
-            font: BuiltinFont::Helvetica,
+        match font {
+            PdfFont::Builtin(font) => Op::SetFontSizeBuiltinFont { size, font: *font },
+            PdfFont::Embedded(font) => Op::SetFontSize {
+                size,
+                font: font.clone(),
+            },
         },
         Op::SetLineHeight { lh: Pt(14.0) },
     ];
 
     for line in lines {
         let safe_line = if line.is_empty() { " " } else { line };
-        ops.push(Op::WriteTextBuiltinFont {
-            items: vec![TextItem::Text(safe_line.to_owned())],
-            font: BuiltinFont::Helvetica,
+        let items = vec![TextItem::Text(safe_line.to_owned())];
+        ops.push(match font {
+            PdfFont::Builtin(font) => Op::WriteTextBuiltinFont { items, font: *font },
+            PdfFont::Embedded(font) => Op::WriteText {
+                items,
+                font: font.clone(),
+            },
         });
         ops.push(Op::AddLineBreak);
     }
@@ -666,15 +1335,37 @@ fn build_pdf_page(lines: &[&str]) -> PdfPage {
     PdfPage::new(Mm(210.0), Mm(297.0), ops)
 }
 
+/// Embeds a system CJK font when `text` is not pure ASCII, falling back to
+/// Helvetica (with a warning) when none can be loaded.
+fn pdf_font(doc: &mut PdfDocument, text: &str) -> PdfFont {
+    if text.is_ascii() {
+        return PdfFont::Builtin(BuiltinFont::Helvetica);
+    }
+
+    let Some((_, bytes, font_path)) = load_cjk_font_data() else {
+        warn!("no CJK-capable font found; non-Latin text may be missing from the PDF");
+        return PdfFont::Builtin(BuiltinFont::Helvetica);
+    };
+
+    match ParsedFont::from_bytes(&bytes, 0, &mut Vec::new()) {
+        Some(font) => PdfFont::Embedded(doc.add_font(&font)),
+        None => {
+            warn!("failed to parse PDF font {}", font_path.display());
+            PdfFont::Builtin(BuiltinFont::Helvetica)
+        }
+    }
+}
+
 fn write_pdf(path: &Path, title: &str, text: &str) -> Result<()> {
     let mut doc = PdfDocument::new(title);
+    let font = pdf_font(&mut doc, text);
     let all_lines: Vec<&str> = text.lines().collect();
     let pages: Vec<PdfPage> = if all_lines.is_empty() {
-        vec![build_pdf_page(&[])]
+        vec![build_pdf_page(&[], &font)]
     } else {
         all_lines
             .chunks(PDF_LINES_PER_PAGE)
-            .map(build_pdf_page)
+            .map(|lines| build_pdf_page(lines, &font))
             .collect()
     };
 
@@ -688,63 +1379,126 @@ fn write_pdf(path: &Path, title: &str, text: &str) -> Result<()> {
     Ok(())
 }
 
-fn write_docx(path: &Path, text: &str) -> Result<()> {
-    let file =
-        File::create(path).with_context(|| format!("failed to create {}", path.display()))?;
-    let mut zip = zip::ZipWriter::new(file);
-    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+/// The batch summary as plain text, or as Markdown when `markdown` is set.
+fn batch_summary_text(summary: &BatchSummary<'_>, markdown: bool) -> String {
+    let seconds = |ms: f64| ms / 1000.0;
+    let bullet = if markdown { "- " } else { "" };
+    let mut output = String::new();
 
-    zip.start_file("[Content_Types].xml", options)?;
-    zip.write_all(content_types_xml().as_bytes())?;
-
-    zip.start_file("_rels/.rels", options)?;
-    zip.write_all(root_relationships_xml().as_bytes())?;
-
-    zip.start_file("word/document.xml", options)?;
-    zip.write_all(document_xml(text).as_bytes())?;
+    output.push_str(if markdown {
+        "# Batch Summary\n\n"
+    } else {
+        "Batch Summary\n\n"
+    });
+    output.push_str(&format!("{bullet}Total Files: {}\n", summary.total));
+    output.push_str(&format!("{bullet}Successful: {}\n", summary.successful));
+    output.push_str(&format!("{bullet}Failed: {}\n", summary.failed));
+    output.push_str(&format!(
+        "{bullet}Average Duration: {:.2}s\n",
+        seconds(summary.duration.average_ms)
+    ));
+    output.push_str(&format!(
+        "{bullet}Median Duration: {:.2}s\n",
+        seconds(summary.duration.median_ms as f64)
+    ));
+    output.push_str(&format!(
+        "{bullet}95th Percentile Duration: {:.2}s\n",
+        seconds(summary.duration.p95_ms as f64)
+    ));
+
+    if !summary.failures.is_empty() {
+        output.push_str(if markdown {
+            "\n## Failed Files\n\n"
+        } else {
+            "\nFailed Files:\n"
+        });
+        for failure in &summary.failures {
+            if markdown {
+                output.push_str(&format!("- `{}`: {}\n", failure.file_path, failure.error));
+            } else {
+                output.push_str(&format!("{}: {}\n", failure.file_path, failure.error));
+            }
+        }
+    }
+    output
+}
 
-    zip.start_file("word/_rels/document.xml.rels", options)?;
-    zip.write_all(document_relationships_xml().as_bytes())?;
+fn batch_summary_html(summary: &BatchSummary<'_>) -> String {
+    let mut body = format!(
+        "<section class=\"metadata\"><table><tr><th>Total Files</th><td>{}</td></tr><tr><th>Successful</th><td>{}</td></tr><tr><th>Failed</th><td>{}</td></tr><tr><th>Average Duration</th><td>{:.2}s</td></tr><tr><th>Median Duration</th><td>{:.2}s</td></tr><tr><th>95th Percentile Duration</th><td>{:.2}s</td></tr></table></section>",
+        summary.total,
+        summary.successful,
+        summary.failed,
+        summary.duration.average_ms / 1000.0,
+        summary.duration.median_ms as f64 / 1000.0,
+        summary.duration.p95_ms as f64 / 1000.0,
+    );
+
+    if !summary.failures.is_empty() {
+        body.push_str("<section><h2>Failed Files</h2><table>");
+        for failure in &summary.failures {
+            body.push_str(&format!(
+                "<tr><th>{}</th><td>{}</td></tr>",
+                escape_html(failure.file_path),
+                escape_html(failure.error)
+            ));
+        }
+        body.push_str("</table></section>");
+    }
 
-    zip.finish()?;
-    Ok(())
+    format!(
+        "<!doctype html><html lang=\"en\"><head><meta charset=\"utf-8\"><meta name=\"viewport\" content=\"width=device-width,initial-scale=1\"><title>Batch Summary</title><style>{}</style></head><body><main class=\"container\"><h1>Batch Summary</h1>{}</main></body></html>",
+        base_html_style(),
+        body,
+    )
 }
 
-fn content_types_xml() -> &'static str {
-    r#"<?xml version="1.0" encoding="UTF-8"?>
-<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">
-  <Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>
-  <Default Extension="xml" ContentType="application/xml"/>
-  <Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/>
-</Types>"#
+/// Streams `items` as JSON Lines. There is no metadata line, so every line
+/// has the same shape.
+fn write_jsonl<T: Serialize>(output_path: &Path, items: &[T]) -> Result<()> {
+    let file = File::create(output_path)
+        .with_context(|| format!("failed to create {}", output_path.display()))?;
+    let mut writer = BufWriter::new(file);
+    for item in items {
+        serde_json::to_writer(&mut writer, item)?;
+        writer.write_all(b"\n")?;
+    }
+    writer
+        .flush()
+        .with_context(|| format!("failed to write {}", output_path.display()))
 }
 
-fn root_relationships_xml() -> &'static str {
-    r#"<?xml version="1.0" encoding="UTF-8"?>
-<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
-  <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="word/document.xml"/>
-</Relationships>"#
+fn epub_container_xml(opf_path: &str) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?><container version=\"1.0\" xmlns=\"urn:oasis:names:tc:opendocument:xmlns:container\"><rootfiles><rootfile full-path=\"{}\" media-type=\"application/oebps-package+xml\"/></rootfiles></container>",
+        escape_xml(opf_path)
+    )
 }
 
-fn document_relationships_xml() -> &'static str {
-    r#"<?xml version="1.0" encoding="UTF-8"?>
-<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"/>"#
+fn generated_by(metadata: &ExportMetadata) -> String {
+    format!(
+        "Generated by TranslationFiesta Rust {} ({})",
+        metadata.tool_version, metadata.build_info
+    )
 }
 
-fn document_xml(text: &str) -> String {
-    let mut body = String::new();
-    for line in text.lines() {
-        body.push_str("<w:p><w:r><w:t xml:space=\"preserve\">");
-        body.push_str(&xml_escape(line));
-        body.push_str("</w:t></w:r></w:p>");
+fn html_footer(include_metadata: bool, metadata: &ExportMetadata) -> String {
+    if !include_metadata {
+        return String::new();
     }
-
     format!(
-        "<?xml version=\"1.0\" encoding=\"UTF-8\"?><w:document xmlns:w=\"http://schemas.openxmlformats.org/wordprocessingml/2006/main\"><w:body>{}<w:sectPr/></w:body></w:document>",
-        body
+        "<footer class=\"build-info\">{}</footer>",
+        escape_html(&generated_by(metadata))
     )
 }
 
+fn text_footer(include_metadata: bool, metadata: &ExportMetadata) -> String {
+    if !include_metadata {
+        return String::new();
+    }
+    format!("\n{}\n", generated_by(metadata))
+}
+
 fn base_html_style() -> &'static str {
     r#"
       :root {
@@ -777,6 +1531,19 @@ fn base_html_style() -> &'static str {
         white-space: pre-wrap;
         line-height: 1.5;
       }
+      .warning {
+        background: rgba(214, 158, 46, 0.16);
+        border: 1px solid rgba(214, 158, 46, 0.5);
+        border-radius: 12px;
+        padding: 10px 14px;
+      }
+      .diff ins {
+        background: rgba(62, 180, 110, 0.28);
+        text-decoration: none;
+      }
+      .diff del {
+        background: rgba(224, 82, 82, 0.28);
+      }
       .metadata table {
         width: 100%;
         border-collapse: collapse;
@@ -792,18 +1559,15 @@ fn base_html_style() -> &'static str {
         padding-top: 16px;
         margin-top: 16px;
       }
+      .build-info {
+        max-width: 980px;
+        margin: 12px auto 0;
+        font-size: 12px;
+        opacity: 0.6;
+      }
     "#
 }
 
-fn xml_escape(value: &str) -> String {
-    value
-        .replace('&', "&amp;")
-        .replace('<', "&lt;")
-        .replace('>', "&gt;")
-        .replace('"', "&quot;")
-        .replace('\'', "&apos;")
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -825,7 +1589,7 @@ mod tests {
 
     #[test]
     fn exports_single_json() {
-        let service = ExportService;
+        let service = ExportService::default();
         let result = sample_result();
         let temp = TempDir::new().unwrap();
         let output = temp.path().join("result.json");
@@ -837,11 +1601,161 @@ mod tests {
         let content = std::fs::read_to_string(output).unwrap();
         assert!(content.contains("Hello world"));
         assert!(content.contains("metadata"));
+        assert!(content.contains(&format!(
+            "\"tool_version\": \"{}\"",
+            build_info::TOOL_VERSION
+        )));
+        assert!(content.contains("\"fidelity_score\": 1.0"));
+    }
+
+    #[test]
+    fn html_export_has_build_footer_only_with_metadata() {
+        let service = ExportService::default();
+        let result = sample_result();
+
+        let with_metadata = service
+            .preview_single(&result, ExportFormat::Html, true)
+            .unwrap();
+        assert!(with_metadata.contains(&build_info::build_details()));
+
+        let without_metadata = service
+            .preview_single(&result, ExportFormat::Html, false)
+            .unwrap();
+        assert!(!without_metadata.contains("build-info\">"));
+    }
+
+    #[test]
+    fn html_export_highlights_changes_only_when_enabled() {
+        let mut result = sample_result();
+        result.back_translated_text = "Hello there world".to_owned();
+
+        let plain = ExportService::default()
+            .preview_single(&result, ExportFormat::Html, false)
+            .unwrap();
+        assert!(!plain.contains("<ins>"));
+
+        let highlighted = ExportService::default()
+            .with_highlight_diff(true)
+            .preview_single(&result, ExportFormat::Html, false)
+            .unwrap();
+        assert!(highlighted.contains("<h2>Changes</h2>"));
+        assert!(highlighted.contains("Hello <ins>there</ins> world"));
+    }
+
+    #[test]
+    fn csv_preview_matches_exported_file() {
+        let service = ExportService::default();
+        let result = sample_result();
+        let temp = TempDir::new().unwrap();
+        let output = temp.path().join("result.csv");
+
+        service
+            .export_single(&result, &output, ExportFormat::Csv, true)
+            .unwrap();
+        let preview = service
+            .preview_single(&result, ExportFormat::Csv, true)
+            .unwrap();
+
+        assert_eq!(preview, std::fs::read_to_string(output).unwrap());
+        assert!(preview.starts_with("metadata_key,metadata_value\n"));
+    }
+
+    #[test]
+    fn batch_jsonl_has_one_result_per_line() {
+        let temp = TempDir::new().unwrap();
+        let output = temp.path().join("batch.jsonl");
+        let item = |path: &str, error: Option<&str>| BatchItemResult {
+            file_path: path.to_owned(),
+            success: error.is_none(),
+            original_text: "Hello\nworld".to_owned(),
+            intermediate_text: "こんにちは".to_owned(),
+            back_translated_text: "Hello world".to_owned(),
+            error: error.map(str::to_owned),
+            error_kind: error.map(|_| BatchErrorKind::Network),
+            detected_source: None,
+            suspicious_reason: None,
+            duration_ms: 120,
+        };
+        let results = [item("a.txt", None), item("b.txt", Some("timed out"))];
+
+        ExportService::default()
+            .export_batch(
+                &results,
+                &output,
+                ExportFormat::Jsonl,
+                BatchExportContext {
+                    include_metadata: true,
+                    source_language: "en",
+                    target_language: "ja",
+                    provider: "google_unofficial",
+                },
+            )
+            .unwrap();
+
+        let content = std::fs::read_to_string(&output).unwrap();
+        let parsed: Vec<BatchItemResult> = content
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].original_text, "Hello\nworld");
+        assert_eq!(parsed[1].error_kind, Some(BatchErrorKind::Network));
+        assert_eq!("ndjson".parse(), Ok(ExportFormat::Jsonl));
+    }
+
+    #[test]
+    fn csv_exports_use_the_configured_delimiter_and_quoting() {
+        let service = ExportService::default().with_options(ExportOptions {
+            csv_delimiter: ';',
+            csv_always_quote: false,
+        });
+        let temp = TempDir::new().unwrap();
+        let single = temp.path().join("result.csv");
+        service
+            .export_single(&sample_result(), &single, ExportFormat::Csv, false)
+            .unwrap();
+        let single = std::fs::read_to_string(single).unwrap();
+        assert!(single.starts_with("original_text;intermediate_text;back_translated_text;"));
+
+        let results = [BatchItemResult {
+            file_path: "a.txt".to_owned(),
+            success: true,
+            original_text: "Hello, world".to_owned(),
+            intermediate_text: "こんにちは".to_owned(),
+            back_translated_text: "Hello; world".to_owned(),
+            error: None,
+            error_kind: None,
+            detected_source: None,
+            suspicious_reason: None,
+            duration_ms: 250,
+        }];
+        let context = BatchExportContext {
+            include_metadata: false,
+            source_language: "en",
+            target_language: "ja",
+            provider: "google_unofficial",
+        };
+        let batch = temp.path().join("batch.csv");
+        service
+            .export_batch(&results, &batch, ExportFormat::Csv, context)
+            .unwrap();
+        let batch = std::fs::read_to_string(batch).unwrap();
+        assert!(batch.starts_with("file_path;success;duration_ms;"));
+        assert!(batch.contains("\"Hello; world\""));
+
+        let quoted = ExportService::default().with_options(ExportOptions {
+            csv_delimiter: ',',
+            csv_always_quote: true,
+        });
+        let preview = quoted
+            .preview_single(&sample_result(), ExportFormat::Csv, false)
+            .unwrap();
+        assert!(preview.starts_with("\"original_text\",\"intermediate_text\","));
     }
 
     #[test]
     fn exports_single_docx() {
-        let service = ExportService;
+        let service = ExportService::default();
         let result = sample_result();
         let temp = TempDir::new().unwrap();
         let output = temp.path().join("result.docx");
@@ -850,13 +1764,258 @@ mod tests {
             .export_single(&result, &output, ExportFormat::Docx, true)
             .unwrap();
 
-        assert!(output.exists());
-        assert!(std::fs::metadata(output).unwrap().len() > 64);
+        let mut archive = zip::ZipArchive::new(File::open(&output).unwrap()).unwrap();
+        assert!(archive.by_name("word/styles.xml").is_ok());
+        let mut document = String::new();
+        archive
+            .by_name("word/document.xml")
+            .unwrap()
+            .read_to_string(&mut document)
+            .unwrap();
+        assert!(document.contains("<w:pStyle w:val=\"Heading1\"/></w:pPr><w:r><w:t xml:space=\"preserve\">Original Text</w:t>"));
+        assert!(document.contains("<w:b/></w:rPr><w:t xml:space=\"preserve\">API Used: </w:t>"));
+    }
+
+    #[test]
+    fn batch_tmx_skips_failed_items() {
+        let temp = TempDir::new().unwrap();
+        let output = temp.path().join("batch.tmx");
+        let item = |path: &str, success: bool| BatchItemResult {
+            file_path: path.to_owned(),
+            success,
+            original_text: "Hello world".to_owned(),
+            intermediate_text: if success {
+                "こんにちは世界".to_owned()
+            } else {
+                String::new()
+            },
+            back_translated_text: String::new(),
+            error: None,
+            error_kind: None,
+            detected_source: None,
+            suspicious_reason: None,
+            duration_ms: 10,
+        };
+
+        ExportService::default()
+            .export_batch(
+                &[item("ok.txt", true), item("failed.txt", false)],
+                &output,
+                ExportFormat::Tmx,
+                BatchExportContext {
+                    include_metadata: true,
+                    source_language: "en",
+                    target_language: "ja",
+                    provider: "google_unofficial",
+                },
+            )
+            .unwrap();
+
+        let xml = std::fs::read_to_string(&output).unwrap();
+        assert_eq!(xml.matches("<tu>").count(), 1);
+        assert!(xml.contains("<tuv xml:lang=\"ja\"><seg>こんにちは世界</seg></tuv>"));
+        assert!(!xml.contains("failed.txt"));
+    }
+
+    #[test]
+    fn batch_summary_counts_and_lists_failures() {
+        let temp = TempDir::new().unwrap();
+        let item = |path: &str, duration_ms: u128, error: Option<&str>| BatchItemResult {
+            file_path: path.to_owned(),
+            success: error.is_none(),
+            original_text: "Hello world".to_owned(),
+            intermediate_text: String::new(),
+            back_translated_text: String::new(),
+            error: error.map(str::to_owned),
+            error_kind: None,
+            detected_source: None,
+            suspicious_reason: None,
+            duration_ms,
+        };
+        let results = [
+            item("a.txt", 100, None),
+            item("b.txt", 300, None),
+            item("broken.txt", 200, Some("Rate limited")),
+        ];
+
+        let json_path = temp.path().join("summary.json");
+        ExportService::default()
+            .export_batch_summary(&results, &json_path, ExportFormat::Json)
+            .unwrap();
+        let summary: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(summary["total"], 3);
+        assert_eq!(summary["successful"], 2);
+        assert_eq!(summary["failed"], 1);
+        assert_eq!(summary["duration"]["median_ms"], 200);
+        assert_eq!(summary["failures"][0]["file_path"], "broken.txt");
+
+        let md_path = temp.path().join("summary.md");
+        ExportService::default()
+            .export_batch_summary(&results, &md_path, ExportFormat::Markdown)
+            .unwrap();
+        let markdown = std::fs::read_to_string(&md_path).unwrap();
+        assert!(markdown.contains("- Failed: 1\n"));
+        assert!(markdown.contains("- `broken.txt`: Rate limited\n"));
+        assert!(!markdown.contains("a.txt"));
+
+        assert!(
+            ExportService::default()
+                .export_batch_summary(&results, &temp.path().join("s.pdf"), ExportFormat::Pdf)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn batch_xlsx_has_summary_and_results_sheets() {
+        let temp = TempDir::new().unwrap();
+        let output = temp.path().join("batch.xlsx");
+        let results = [BatchItemResult {
+            file_path: "a.txt".to_owned(),
+            success: true,
+            original_text: "Hello world".to_owned(),
+            intermediate_text: "こんにちは世界".to_owned(),
+            back_translated_text: "Hello world".to_owned(),
+            error: None,
+            error_kind: None,
+            detected_source: None,
+            suspicious_reason: None,
+            duration_ms: 250,
+        }];
+
+        ExportService::default()
+            .export_batch(
+                &results,
+                &output,
+                ExportFormat::Xlsx,
+                BatchExportContext {
+                    include_metadata: true,
+                    source_language: "en",
+                    target_language: "ja",
+                    provider: "google_unofficial",
+                },
+            )
+            .unwrap();
+
+        let mut archive = zip::ZipArchive::new(File::open(&output).unwrap()).unwrap();
+        let mut workbook = String::new();
+        archive
+            .by_name("xl/workbook.xml")
+            .unwrap()
+            .read_to_string(&mut workbook)
+            .unwrap();
+        assert!(workbook.contains("name=\"Summary\""));
+        assert!(workbook.contains("name=\"Results\""));
+
+        let mut results_sheet = String::new();
+        archive
+            .by_name("xl/worksheets/sheet2.xml")
+            .unwrap()
+            .read_to_string(&mut results_sheet)
+            .unwrap();
+        assert!(results_sheet.contains("こんにちは世界"));
+    }
+
+    #[test]
+    fn batch_xlsx_unzips_with_only_the_results_sheet_filtered() {
+        let temp = TempDir::new().unwrap();
+        let output = temp.path().join("batch.xlsx");
+        let results = [BatchItemResult {
+            file_path: "broken.txt".to_owned(),
+            success: false,
+            original_text: String::new(),
+            intermediate_text: String::new(),
+            back_translated_text: String::new(),
+            error: Some("boom".to_owned()),
+            error_kind: Some(BatchErrorKind::Io),
+            detected_source: None,
+            suspicious_reason: None,
+            duration_ms: 5,
+        }];
+        ExportService::default()
+            .export_batch(
+                &results,
+                &output,
+                ExportFormat::Xlsx,
+                BatchExportContext {
+                    include_metadata: false,
+                    source_language: "en",
+                    target_language: "ja",
+                    provider: "google_unofficial",
+                },
+            )
+            .unwrap();
+
+        let mut archive = zip::ZipArchive::new(File::open(&output).unwrap()).unwrap();
+        let mut sheet = |name: &str| {
+            let mut xml = String::new();
+            archive
+                .by_name(name)
+                .unwrap()
+                .read_to_string(&mut xml)
+                .unwrap();
+            xml
+        };
+        let summary = sheet("xl/worksheets/sheet1.xml");
+        assert!(!summary.contains("<autoFilter"), "{summary}");
+        let details = sheet("xl/worksheets/sheet2.xml");
+        assert!(details.contains("state=\"frozen\""));
+        assert!(details.contains("<autoFilter ref=\"A1:H2\"/>"));
+    }
+
+    #[test]
+    fn bundle_has_one_entry_per_requested_format() {
+        let temp = TempDir::new().unwrap();
+        let output = temp.path().join("batch.zip");
+        let results = [BatchItemResult {
+            file_path: "a.txt".to_owned(),
+            success: true,
+            original_text: "Hello world".to_owned(),
+            intermediate_text: "こんにちは世界".to_owned(),
+            back_translated_text: "Hello world".to_owned(),
+            error: None,
+            error_kind: None,
+            detected_source: None,
+            suspicious_reason: None,
+            duration_ms: 250,
+        }];
+
+        ExportService::default()
+            .export_bundle(
+                &results,
+                &output,
+                &[
+                    ExportFormat::Json,
+                    ExportFormat::Csv,
+                    ExportFormat::Html,
+                    ExportFormat::Json,
+                ],
+                BatchExportContext {
+                    include_metadata: true,
+                    source_language: "en",
+                    target_language: "ja",
+                    provider: "google_unofficial",
+                },
+            )
+            .unwrap();
+
+        let mut archive = zip::ZipArchive::new(File::open(&output).unwrap()).unwrap();
+        let mut names: Vec<&str> = archive.file_names().collect();
+        names.sort_unstable();
+        assert_eq!(names, ["batch.csv", "batch.html", "batch.json"]);
+
+        let mut report = String::new();
+        archive
+            .by_name("batch.json")
+            .unwrap()
+            .read_to_string(&mut report)
+            .unwrap();
+        assert!(report.contains("こんにちは世界"));
     }
 
     #[test]
     fn exports_single_pdf() {
-        let service = ExportService;
+        let service = ExportService::default();
         let result = sample_result();
         let temp = TempDir::new().unwrap();
         let output = temp.path().join("result.pdf");
@@ -869,6 +2028,38 @@ mod tests {
         assert!(std::fs::metadata(output).unwrap().len() > 64);
     }
 
+    #[test]
+    #[ignore = "needs a CJK system font"]
+    fn pdf_embeds_a_font_for_japanese_text() {
+        assert!(
+            load_cjk_font_data().is_some(),
+            "no CJK-capable system font installed"
+        );
+        let service = ExportService::default();
+        let temp = TempDir::new().unwrap();
+        let export = |intermediate: &str, name: &str| {
+            let result = BackTranslationResult::new(
+                "Hello".to_owned(),
+                intermediate.to_owned(),
+                "Hello".to_owned(),
+                "en".to_owned(),
+                "ja".to_owned(),
+                ProviderId::GoogleUnofficial,
+                Duration::from_millis(10),
+            );
+            let output = temp.path().join(name);
+            service
+                .export_single(&result, &output, ExportFormat::Pdf, false)
+                .unwrap();
+            std::fs::metadata(output).unwrap().len()
+        };
+
+        let ascii = export("Konnichiwa", "ascii.pdf");
+        let japanese = export("こんにちは", "japanese.pdf");
+
+        assert!(japanese > ascii, "{japanese} <= {ascii}");
+    }
+
     #[test]
     fn exports_pdf_multipage_without_truncation() {
         let temp = TempDir::new().unwrap();
@@ -884,4 +2075,56 @@ mod tests {
         assert!(output.exists());
         assert!(std::fs::metadata(output).unwrap().len() > 512);
     }
+
+    #[test]
+    fn rebuilt_epub_keeps_structure_and_swaps_chapter_text() {
+        let temp = TempDir::new().unwrap();
+        let source = temp.path().join("book.epub");
+        let mut zip = zip::ZipWriter::new(File::create(&source).unwrap());
+        let deflated = SimpleFileOptions::default();
+        zip.start_file("mimetype", deflated).unwrap();
+        zip.write_all(b"application/epub+zip").unwrap();
+        zip.start_file("META-INF/container.xml", deflated).unwrap();
+        zip.write_all(epub_container_xml("OEBPS/content.opf").as_bytes())
+            .unwrap();
+        zip.start_file("OEBPS/content.opf", deflated).unwrap();
+        zip.write_all(br#"<package><metadata><dc:title>Book</dc:title></metadata><manifest><item id="c1" href="ch1.xhtml"/></manifest><spine><itemref idref="c1"/></spine></package>"#)
+            .unwrap();
+        zip.start_file("OEBPS/style.css", deflated).unwrap();
+        zip.write_all(b"p { margin: 0 }").unwrap();
+        zip.start_file("OEBPS/ch1.xhtml", deflated).unwrap();
+        zip.write_all(b"<html><head><title>One</title></head><body><p>Hello</p></body></html>")
+            .unwrap();
+        zip.finish().unwrap();
+
+        let book = crate::epub::load_epub(&source, true).unwrap();
+        let output = temp.path().join("translated.epub");
+        ExportService::default()
+            .export_epub(&book, &["Bonjour".to_owned()], &output)
+            .unwrap();
+
+        let mut archive = ZipArchive::new(File::open(&output).unwrap()).unwrap();
+        let first = archive.by_index(0).unwrap();
+        assert_eq!(first.name(), "mimetype");
+        assert_eq!(first.compression(), CompressionMethod::Stored);
+        drop(first);
+
+        let mut chapter = String::new();
+        archive
+            .by_name("OEBPS/ch1.xhtml")
+            .unwrap()
+            .read_to_string(&mut chapter)
+            .unwrap();
+        assert!(chapter.contains("<title>One</title>"));
+        assert!(chapter.contains("<p>Bonjour</p>"));
+        assert!(!chapter.contains("Hello"));
+        assert!(archive.by_name("OEBPS/style.css").is_ok());
+        assert!(archive.by_name("META-INF/container.xml").is_ok());
+
+        assert!(
+            ExportService::default()
+                .export_epub(&book, &[], &temp.path().join("bad.epub"))
+                .is_err()
+        );
+    }
 }
@@ -1,11 +1,22 @@
+use std::borrow::Cow;
 use std::fs;
+use std::io::Read;
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result, bail};
+use chardetng::{EncodingDetector, Iso2022JpDetection, Utf8Detection};
+use encoding_rs::{Encoding, UTF_8};
+use tracing::{info, warn};
 use walkdir::WalkDir;
 
+use crate::docx;
 use crate::epub;
-use crate::html::extract_text_from_html;
+use crate::glob::GlobPattern;
+use crate::html::{extract_text_from_html_with, extract_text_preserving_paragraphs};
+use crate::rtf::rtf_to_text;
+use crate::structured::StructuredDocument;
+use crate::subtitle;
+use crate::translation::nfc;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SupportedFileType {
@@ -13,6 +24,10 @@ pub enum SupportedFileType {
     Markdown,
     Html,
     Epub,
+    Srt,
+    Pdf,
+    Docx,
+    Rtf,
 }
 
 impl SupportedFileType {
@@ -23,16 +38,46 @@ impl SupportedFileType {
             "md" | "markdown" => Some(Self::Markdown),
             "html" | "htm" => Some(Self::Html),
             "epub" => Some(Self::Epub),
+            "srt" => Some(Self::Srt),
+            "pdf" => Some(Self::Pdf),
+            "docx" => Some(Self::Docx),
+            "rtf" => Some(Self::Rtf),
             _ => None,
         }
     }
 
     pub fn supported_extensions() -> &'static [&'static str] {
-        &["txt", "md", "html", "htm", "epub"]
+        &[
+            "txt", "md", "html", "htm", "epub", "srt", "pdf", "docx", "rtf",
+        ]
+    }
+}
+
+/// Largest input file read by default.
+pub const DEFAULT_MAX_INPUT_FILE_BYTES: u64 = 50 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextLoadOptions {
+    /// Collapse runs of whitespace during extraction; disable for formatting-sensitive inputs.
+    pub normalize_whitespace: bool,
+    /// Inputs larger than this are rejected before they are read.
+    pub max_file_bytes: u64,
+}
+
+impl Default for TextLoadOptions {
+    fn default() -> Self {
+        Self {
+            normalize_whitespace: true,
+            max_file_bytes: DEFAULT_MAX_INPUT_FILE_BYTES,
+        }
     }
 }
 
 pub fn load_text(path: &Path) -> Result<String> {
+    load_text_with_options(path, TextLoadOptions::default())
+}
+
+pub fn load_text_with_options(path: &Path, options: TextLoadOptions) -> Result<String> {
     if !path.exists() {
         bail!("file does not exist: {}", path.display());
     }
@@ -40,16 +85,63 @@ pub fn load_text(path: &Path) -> Result<String> {
     let file_type = SupportedFileType::detect(path)
         .ok_or_else(|| anyhow::anyhow!("unsupported file type for {}", path.display()))?;
 
+    let metadata = fs::metadata(path)
+        .with_context(|| format!("failed to read metadata for {}", path.display()))?;
+    if metadata.len() > options.max_file_bytes {
+        bail!(
+            "file too large: {} ({} bytes exceeds the configured limit of {} bytes)",
+            path.display(),
+            metadata.len(),
+            options.max_file_bytes
+        );
+    }
+
     match file_type {
-        SupportedFileType::Txt | SupportedFileType::Markdown => read_text(path),
+        SupportedFileType::Txt | SupportedFileType::Markdown => read_text(path, options),
         SupportedFileType::Html => {
-            let raw = read_text(path)?;
-            Ok(extract_text_from_html(&raw))
+            let raw = read_text(path, options)?;
+            Ok(if options.normalize_whitespace {
+                extract_text_preserving_paragraphs(&raw)
+            } else {
+                extract_text_from_html_with(&raw, false)
+            })
+        }
+        SupportedFileType::Epub => epub::extract_text(path, options.normalize_whitespace),
+        SupportedFileType::Srt => {
+            let cues = subtitle::parse_srt(&read_decoded_text(path)?)
+                .with_context(|| format!("failed to parse subtitles {}", path.display()))?;
+            Ok(subtitle::cues_to_text(&cues))
         }
-        SupportedFileType::Epub => epub::extract_text(path),
+        SupportedFileType::Pdf => extract_pdf_text(path, options.normalize_whitespace),
+        SupportedFileType::Docx => docx::extract_text(path, options.normalize_whitespace),
+        SupportedFileType::Rtf => Ok(rtf_to_text(
+            &read_decoded_text(path)?,
+            options.normalize_whitespace,
+        )),
     }
 }
 
+/// Reads `path` as a document whose markup is kept through translation
+/// instead of being flattened to plain text. Only Markdown is supported.
+pub fn load_structured(path: &Path, options: TextLoadOptions) -> Result<StructuredDocument> {
+    match SupportedFileType::detect(path) {
+        Some(SupportedFileType::Markdown) => {}
+        _ => bail!(
+            "keeping markup is only supported for Markdown files, not {}",
+            path.display()
+        ),
+    }
+    // Whitespace is part of the markup, so it is never collapsed here.
+    let raw = load_text_with_options(
+        path,
+        TextLoadOptions {
+            normalize_whitespace: false,
+            ..options
+        },
+    )?;
+    Ok(StructuredDocument::parse_markdown(&raw))
+}
+
 pub fn save_text(path: &Path, content: &str) -> Result<()> {
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)
@@ -60,7 +152,58 @@ pub fn save_text(path: &Path, content: &str) -> Result<()> {
         .with_context(|| format!("failed to write file {}", path.display()))
 }
 
+/// Which files a directory scan keeps, by their path relative to the root.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScanOptions {
+    /// When non-empty, a file must match at least one of these.
+    pub include: Vec<GlobPattern>,
+    /// Files matching any of these are skipped.
+    pub exclude: Vec<GlobPattern>,
+    /// How many directory levels to descend; 1 scans only the top level.
+    pub max_depth: Option<usize>,
+}
+
+impl ScanOptions {
+    pub fn with_patterns(include: &[String], exclude: &[String]) -> Result<Self> {
+        let compile = |patterns: &[String]| {
+            patterns
+                .iter()
+                .map(|pattern| GlobPattern::new(pattern))
+                .collect::<Result<Vec<_>>>()
+        };
+        Ok(Self {
+            include: compile(include)?,
+            exclude: compile(exclude)?,
+            max_depth: None,
+        })
+    }
+
+    pub fn with_max_depth(mut self, max_depth: Option<usize>) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    fn keeps(&self, relative_path: &Path) -> bool {
+        (self.include.is_empty()
+            || self
+                .include
+                .iter()
+                .any(|pattern| pattern.matches(relative_path)))
+            && !self
+                .exclude
+                .iter()
+                .any(|pattern| pattern.matches(relative_path))
+    }
+}
+
 pub fn list_supported_files_in_directory(directory: &Path) -> Result<Vec<PathBuf>> {
+    list_supported_files_with_options(directory, &ScanOptions::default())
+}
+
+pub fn list_supported_files_with_options(
+    directory: &Path,
+    options: &ScanOptions,
+) -> Result<Vec<PathBuf>> {
     if !directory.exists() {
         bail!("directory does not exist: {}", directory.display());
     }
@@ -68,15 +211,20 @@ pub fn list_supported_files_in_directory(directory: &Path) -> Result<Vec<PathBuf
         bail!("path is not a directory: {}", directory.display());
     }
 
+    let mut walker = WalkDir::new(directory).follow_links(false);
+    if let Some(depth) = options.max_depth {
+        walker = walker.max_depth(depth);
+    }
+
     let mut files = Vec::new();
-    for entry in WalkDir::new(directory)
-        .follow_links(false)
+    for entry in walker
         .into_iter()
         .filter_map(Result::ok)
         .filter(|entry| entry.file_type().is_file())
     {
         let path = entry.path();
-        if SupportedFileType::detect(path).is_some() {
+        let relative = path.strip_prefix(directory).unwrap_or(path);
+        if SupportedFileType::detect(path).is_some() && options.keeps(relative) {
             files.push(path.to_path_buf());
         }
     }
@@ -85,28 +233,114 @@ pub fn list_supported_files_in_directory(directory: &Path) -> Result<Vec<PathBuf
     Ok(files)
 }
 
-fn read_text(path: &Path) -> Result<String> {
-    const MAX_FILE_BYTES: u64 = 50 * 1024 * 1024;
-
-    let metadata = fs::metadata(path)
-        .with_context(|| format!("failed to read metadata for {}", path.display()))?;
-
-    if metadata.len() > MAX_FILE_BYTES {
+/// Reads and decodes plain text from a stream such as stdin.
+pub fn read_text_from(reader: impl Read, options: TextLoadOptions) -> Result<String> {
+    let mut raw = Vec::new();
+    reader
+        .take(options.max_file_bytes.saturating_add(1))
+        .read_to_end(&mut raw)
+        .context("failed to read input stream")?;
+    if raw.len() as u64 > options.max_file_bytes {
         bail!(
-            "file too large: {} ({} bytes exceeds {} bytes)",
-            path.display(),
-            metadata.len(),
-            MAX_FILE_BYTES
+            "input stream too large: exceeds the configured limit of {} bytes",
+            options.max_file_bytes
         );
     }
+    let (text, encoding) = decode_text(&raw);
+    if encoding != UTF_8 {
+        info!("decoded input stream as {}", encoding.name());
+    }
+    if options.normalize_whitespace {
+        Ok(text.trim().to_owned())
+    } else {
+        Ok(text)
+    }
+}
+
+/// Text of every page in order, pages separated by blank lines. Scanned PDFs
+/// without a text layer come back empty.
+fn extract_pdf_text(path: &Path, normalize_whitespace: bool) -> Result<String> {
+    let document = lopdf::Document::load(path)
+        .with_context(|| format!("failed to open PDF {}", path.display()))?;
+    let mut pages = Vec::new();
+    for page_number in document.get_pages().into_keys() {
+        let text = document.extract_text(&[page_number]).with_context(|| {
+            format!(
+                "failed to extract text from page {page_number} of {}",
+                path.display()
+            )
+        })?;
+        pages.push(if normalize_whitespace {
+            text.lines()
+                .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+                .filter(|line| !line.is_empty())
+                .collect::<Vec<_>>()
+                .join("\n")
+        } else {
+            text
+        });
+    }
+    Ok(pages.join("\n\n").trim().to_owned())
+}
 
+/// Decoded file contents with whitespace left untouched.
+pub(crate) fn read_decoded_text(path: &Path) -> Result<String> {
+    read_text(
+        path,
+        TextLoadOptions {
+            normalize_whitespace: false,
+            ..TextLoadOptions::default()
+        },
+    )
+}
+
+fn read_text(path: &Path, options: TextLoadOptions) -> Result<String> {
     let raw = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
-    Ok(String::from_utf8_lossy(&raw).trim().to_owned())
+    let (text, encoding) = decode_text(&raw);
+    if encoding != UTF_8 {
+        info!("decoded {} as {}", path.display(), encoding.name());
+    }
+    let text = match nfc(&text) {
+        Cow::Borrowed(_) => text,
+        Cow::Owned(composed) => composed,
+    };
+    if options.normalize_whitespace {
+        Ok(text.trim().to_owned())
+    } else {
+        Ok(text)
+    }
+}
+
+/// Decodes `raw` using its BOM, else UTF-8 when valid, else the charset
+/// `chardetng` guesses (Shift_JIS, EUC-JP, windows-1252, ...).
+fn decode_text(raw: &[u8]) -> (String, &'static Encoding) {
+    let encoding = match Encoding::for_bom(raw) {
+        Some((encoding, _)) => encoding,
+        None if std::str::from_utf8(raw).is_ok() => UTF_8,
+        None => {
+            let mut detector = EncodingDetector::new(Iso2022JpDetection::Allow);
+            detector.feed(raw, true);
+            detector.guess(None, Utf8Detection::Allow)
+        }
+    };
+
+    let (text, _, had_errors) = encoding.decode(raw);
+    if had_errors {
+        warn!(
+            "input contained bytes that are invalid in {}; they were replaced",
+            encoding.name()
+        );
+    }
+    (text.into_owned(), encoding)
 }
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use super::*;
+    use crate::export::ExportService;
+    use crate::models::{BackTranslationResult, ExportFormat, ProviderId};
 
     #[test]
     fn detects_supported_extensions() {
@@ -116,4 +350,156 @@ mod tests {
         );
         assert!(SupportedFileType::detect(Path::new("image.png")).is_none());
     }
+
+    #[test]
+    fn loads_text_from_exported_pdf_and_docx() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let result = BackTranslationResult::new(
+            "Hello world".to_owned(),
+            "Bonjour le monde".to_owned(),
+            "Hello world".to_owned(),
+            "en".to_owned(),
+            "fr".to_owned(),
+            ProviderId::GoogleUnofficial,
+            Duration::from_millis(10),
+        );
+
+        for format in [ExportFormat::Pdf, ExportFormat::Docx] {
+            let path = temp.path().join(format!("result.{}", format.extension()));
+            ExportService::default()
+                .export_single(&result, &path, format, false)
+                .unwrap();
+
+            let text = load_text(&path).unwrap();
+            assert!(text.contains("Bonjour le monde"), "{format}: {text}");
+        }
+        assert_eq!(
+            list_supported_files_in_directory(temp.path())
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn load_text_preserves_spacing_when_normalization_is_disabled() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("page.html");
+        fs::write(&path, "<p>Keep  two  spaces</p>").unwrap();
+
+        let preserved = load_text_with_options(
+            &path,
+            TextLoadOptions {
+                normalize_whitespace: false,
+                ..TextLoadOptions::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(preserved, "Keep  two  spaces");
+        assert_eq!(load_text(&path).unwrap(), "Keep two spaces");
+    }
+
+    #[test]
+    fn directory_scan_applies_include_and_exclude_globs() {
+        let temp = tempfile::TempDir::new().unwrap();
+        for relative in [
+            "chapters/one.html",
+            "chapters/draft_two.html",
+            "chapters/notes/extra.html",
+            "readme.md",
+        ] {
+            let path = temp.path().join(relative);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "text").unwrap();
+        }
+        let relative_names = |options: &ScanOptions| {
+            list_supported_files_with_options(temp.path(), options)
+                .unwrap()
+                .iter()
+                .map(|path| {
+                    path.strip_prefix(temp.path())
+                        .unwrap()
+                        .to_string_lossy()
+                        .replace('\\', "/")
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let options =
+            ScanOptions::with_patterns(&["chapters/*.html".to_owned()], &["draft_*".to_owned()])
+                .unwrap();
+        assert_eq!(relative_names(&options), ["chapters/one.html"]);
+
+        let options = ScanOptions::with_patterns(&[], &["chapters/**".to_owned()]).unwrap();
+        assert_eq!(relative_names(&options), ["readme.md"]);
+    }
+
+    #[test]
+    fn depth_one_scan_ignores_files_in_subfolders() {
+        let temp = tempfile::TempDir::new().unwrap();
+        fs::create_dir_all(temp.path().join("sub/deeper")).unwrap();
+        fs::write(temp.path().join("top.txt"), "top").unwrap();
+        fs::write(temp.path().join("sub/inner.txt"), "inner").unwrap();
+        fs::write(temp.path().join("sub/deeper/deep.txt"), "deep").unwrap();
+        let count = |depth| {
+            let options = ScanOptions::default().with_max_depth(depth);
+            list_supported_files_with_options(temp.path(), &options)
+                .unwrap()
+                .len()
+        };
+
+        assert_eq!(count(Some(1)), 1);
+        assert_eq!(count(Some(2)), 2);
+        assert_eq!(count(None), 3);
+    }
+
+    #[test]
+    fn rejects_files_over_the_configured_size_limit() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("dump.txt");
+        fs::write(&path, "x".repeat(2048)).unwrap();
+        let limited = |max_file_bytes| TextLoadOptions {
+            max_file_bytes,
+            ..TextLoadOptions::default()
+        };
+
+        let error = load_text_with_options(&path, limited(1024)).unwrap_err();
+        assert!(
+            error.to_string().contains("configured limit of 1024 bytes"),
+            "{error}"
+        );
+        assert_eq!(
+            load_text_with_options(&path, limited(4096)).unwrap().len(),
+            2048
+        );
+        assert!(read_text_from("x".repeat(2048).as_bytes(), limited(1024)).is_err());
+    }
+
+    #[test]
+    fn decodes_shift_jis_text() {
+        let (encoded, _, _) =
+            encoding_rs::SHIFT_JIS.encode("こんにちは、世界。日本語のテキストです。");
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("sjis.txt");
+        fs::write(&path, &encoded).unwrap();
+
+        assert_eq!(
+            load_text(&path).unwrap(),
+            "こんにちは、世界。日本語のテキストです。"
+        );
+    }
+
+    #[test]
+    fn decodes_windows_1252_text() {
+        let (encoded, _, _) =
+            encoding_rs::WINDOWS_1252.encode("Café crème — “déjà vu” for naïve façades");
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("cp1252.txt");
+        fs::write(&path, &encoded).unwrap();
+
+        assert_eq!(
+            load_text(&path).unwrap(),
+            "Café crème — “déjà vu” for naïve façades"
+        );
+    }
 }
@@ -0,0 +1,40 @@
+use std::fs;
+use std::path::PathBuf;
+
+/// First readable CJK-capable system font as `(font name, bytes, path)`.
+pub fn load_cjk_font_data() -> Option<(String, Vec<u8>, PathBuf)> {
+    for candidate in cjk_font_candidates() {
+        let path = PathBuf::from(candidate);
+        let bytes = match fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(_) => continue,
+        };
+
+        let stem = path
+            .file_stem()
+            .and_then(|name| name.to_str())
+            .unwrap_or("cjk-fallback");
+        let font_name = format!("cjk-{stem}");
+        return Some((font_name, bytes, path));
+    }
+    None
+}
+
+fn cjk_font_candidates() -> &'static [&'static str] {
+    &[
+        "/System/Library/Fonts/Supplemental/Arial Unicode.ttf",
+        "/System/Library/Fonts/Supplemental/Hiragino Sans GB.ttc",
+        "/System/Library/Fonts/Supplemental/Songti.ttc",
+        "/Library/Fonts/Arial Unicode.ttf",
+        r"C:\Windows\Fonts\YuGothM.ttc",
+        r"C:\Windows\Fonts\YuGothR.ttc",
+        r"C:\Windows\Fonts\Meiryo.ttc",
+        r"C:\Windows\Fonts\msgothic.ttc",
+        "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+        "/usr/share/fonts/opentype/noto/NotoSerifCJK-Regular.ttc",
+        "/usr/share/fonts/truetype/noto/NotoSansCJK-Regular.ttc",
+        "/usr/share/fonts/truetype/noto/NotoSansJP-Regular.otf",
+        "/usr/share/fonts/truetype/noto/NotoSansJP-Regular.ttf",
+        "/usr/local/share/fonts/NotoSansCJK-Regular.ttc",
+    ]
+}
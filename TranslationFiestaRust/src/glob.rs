@@ -0,0 +1,141 @@
+use std::path::Path;
+
+use anyhow::{Result, bail};
+
+/// A shell-style pattern over `/`-separated relative paths: `*` and `?` stay
+/// within one path segment, `**` spans any number of them, and `[a-z]` /
+/// `[!0-9]` match one character from a set. Patterns without a `/` are
+/// matched against the file name alone.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlobPattern {
+    tokens: Vec<char>,
+    whole_path: bool,
+}
+
+impl GlobPattern {
+    pub fn new(pattern: &str) -> Result<Self> {
+        let trimmed = pattern.trim().trim_start_matches("./");
+        if trimmed.is_empty() {
+            bail!("glob pattern cannot be empty");
+        }
+        let tokens: Vec<char> = trimmed.replace('\\', "/").chars().collect();
+        let mut index = 0;
+        while index < tokens.len() {
+            if tokens[index] == '[' {
+                let Some(end) = class_end(&tokens, index) else {
+                    bail!("unclosed `[` in glob pattern: {pattern}");
+                };
+                index = end;
+            }
+            index += 1;
+        }
+
+        Ok(Self {
+            whole_path: tokens.contains(&'/'),
+            tokens,
+        })
+    }
+
+    pub fn matches(&self, relative_path: &Path) -> bool {
+        let normalized = relative_path.to_string_lossy().replace('\\', "/");
+        let candidate = if self.whole_path {
+            normalized.as_str()
+        } else {
+            normalized.rsplit('/').next().unwrap_or_default()
+        };
+        let text: Vec<char> = candidate.chars().collect();
+        match_from(&self.tokens, &text)
+    }
+}
+
+fn match_from(pattern: &[char], text: &[char]) -> bool {
+    let Some(&first) = pattern.first() else {
+        return text.is_empty();
+    };
+
+    match first {
+        '*' if pattern.get(1) == Some(&'*') => {
+            let rest = &pattern[2..];
+            if let Some(after_slash) = rest.strip_prefix(&['/']) {
+                // `**/` matches zero or more whole directories.
+                match_from(after_slash, text)
+                    || (0..text.len())
+                        .filter(|&index| text[index] == '/')
+                        .any(|index| match_from(after_slash, &text[index + 1..]))
+            } else {
+                (0..=text.len()).any(|index| match_from(rest, &text[index..]))
+            }
+        }
+        '*' => {
+            let rest = &pattern[1..];
+            let segment_end = text.iter().position(|&ch| ch == '/').unwrap_or(text.len());
+            (0..=segment_end).any(|index| match_from(rest, &text[index..]))
+        }
+        '?' => text
+            .first()
+            .is_some_and(|&ch| ch != '/' && match_from(&pattern[1..], &text[1..])),
+        '[' => {
+            let end = class_end(pattern, 0).unwrap_or(pattern.len() - 1);
+            text.first().is_some_and(|&ch| {
+                ch != '/'
+                    && class_matches(&pattern[1..end], ch)
+                    && match_from(&pattern[end + 1..], &text[1..])
+            })
+        }
+        literal => text.first() == Some(&literal) && match_from(&pattern[1..], &text[1..]),
+    }
+}
+
+/// Index of the `]` closing the class opened at `start`; a `]` right after
+/// the opening bracket (or `!`) is part of the set.
+fn class_end(pattern: &[char], start: usize) -> Option<usize> {
+    let mut index = start + 1;
+    if pattern.get(index) == Some(&'!') {
+        index += 1;
+    }
+    if pattern.get(index) == Some(&']') {
+        index += 1;
+    }
+    (index..pattern.len()).find(|&position| pattern[position] == ']')
+}
+
+fn class_matches(class: &[char], ch: char) -> bool {
+    let (negated, members) = match class.split_first() {
+        Some(('!', rest)) => (true, rest),
+        _ => (false, class),
+    };
+
+    let mut found = false;
+    let mut index = 0;
+    while index < members.len() {
+        if index + 2 < members.len() && members[index + 1] == '-' {
+            found |= (members[index]..=members[index + 2]).contains(&ch);
+            index += 3;
+        } else {
+            found |= members[index] == ch;
+            index += 1;
+        }
+    }
+    found != negated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, path: &str) -> bool {
+        GlobPattern::new(pattern).unwrap().matches(Path::new(path))
+    }
+
+    #[test]
+    fn matches_segments_directories_and_classes() {
+        assert!(matches("chapters/*.xhtml", "chapters/one.xhtml"));
+        assert!(!matches("chapters/*.xhtml", "chapters/part/one.xhtml"));
+        assert!(matches("chapters/**/*.xhtml", "chapters/part/one.xhtml"));
+        assert!(matches("**/*.md", "notes.md"));
+        assert!(matches("draft_*", "nested/dir/draft_02.txt"));
+        assert!(matches("ch[0-9]?.txt", "ch17.txt"));
+        assert!(!matches("ch[!0-9]*.txt", "ch1.txt"));
+        assert!(GlobPattern::new("ch[0-9.txt").is_err());
+    }
+}
@@ -0,0 +1,284 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+
+const TOKEN_PREFIX: &str = "__TF";
+const TOKEN_SUFFIX: &str = "__";
+
+/// A protected term. Without a `translation` the term is kept as written.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct GlossaryEntry {
+    pub term: String,
+    #[serde(default)]
+    pub translation: Option<String>,
+    #[serde(default = "default_case_sensitive")]
+    pub case_sensitive: bool,
+}
+
+fn default_case_sensitive() -> bool {
+    true
+}
+
+/// Terms swapped for placeholder tokens before a provider sees the text and
+/// swapped back afterwards, so names and product terms survive translation.
+#[derive(Debug, Clone, Default)]
+pub struct Glossary {
+    entries: Vec<GlossaryEntry>,
+}
+
+/// Text with its protected terms replaced by tokens, plus what each token
+/// restores to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProtectedText {
+    pub text: String,
+    pub replacements: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum GlossaryFile {
+    /// `{"VibeTranslate": null, "backtranslation": "逆翻訳"}`
+    Map(BTreeMap<String, Option<String>>),
+    List(Vec<GlossaryEntry>),
+}
+
+impl Glossary {
+    pub fn new(entries: Vec<GlossaryEntry>) -> Self {
+        let mut entries: Vec<GlossaryEntry> = entries
+            .into_iter()
+            .filter(|entry| !entry.term.trim().is_empty())
+            .map(|entry| GlossaryEntry {
+                term: entry.term.trim().to_owned(),
+                translation: entry.translation.filter(|value| !value.trim().is_empty()),
+                ..entry
+            })
+            .collect();
+        // Longest terms first so overlapping entries prefer the longer match.
+        entries.sort_by_key(|entry| Reverse(entry.term.len()));
+        Self { entries }
+    }
+
+    /// Reads a `.csv` (`term,translation[,case_sensitive]`, blank translation
+    /// meaning "do not translate") or JSON glossary.
+    pub fn load(path: &Path) -> Result<Self> {
+        let raw = fs::read_to_string(path)
+            .with_context(|| format!("failed to read glossary {}", path.display()))?;
+        let is_csv = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+        let entries = if is_csv {
+            parse_csv(&raw)
+        } else {
+            parse_json(&raw)
+        }
+        .with_context(|| format!("failed to parse glossary {}", path.display()))?;
+        Ok(Self::new(entries))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Replaces every protected term with a `__TF<n>__` token.
+    pub fn apply(&self, text: &str) -> ProtectedText {
+        let mut protected = String::with_capacity(text.len());
+        let mut replacements = Vec::new();
+        let mut position = 0;
+
+        while position < text.len() {
+            let found = self
+                .entries
+                .iter()
+                .find_map(|entry| match_at(text, position, entry).map(|end| (entry, end)));
+
+            match found {
+                Some((entry, end)) => {
+                    let restored = entry
+                        .translation
+                        .clone()
+                        .unwrap_or_else(|| text[position..end].to_owned());
+                    protected.push_str(&token(replacements.len()));
+                    replacements.push(restored);
+                    position = end;
+                }
+                None => {
+                    let ch = text[position..].chars().next().unwrap_or_default();
+                    protected.push(ch);
+                    position += ch.len_utf8();
+                }
+            }
+        }
+
+        ProtectedText {
+            text: protected,
+            replacements,
+        }
+    }
+
+    /// Puts the protected terms back into translated text. Tokens are matched
+    /// case-insensitively because some providers change their casing.
+    pub fn restore(&self, translated: &str, replacements: &[String]) -> String {
+        let mut restored = translated.to_owned();
+        // Highest index first so `__TF1__` never matches inside `__TF10__`.
+        for (index, value) in replacements.iter().enumerate().rev() {
+            let token = token(index);
+            while let Some(start) = find_ignore_ascii_case(&restored, &token) {
+                restored.replace_range(start..start + token.len(), value);
+            }
+        }
+        restored
+    }
+}
+
+fn token(index: usize) -> String {
+    format!("{TOKEN_PREFIX}{index}{TOKEN_SUFFIX}")
+}
+
+/// End of `entry.term` matched at `position`, if it is there and is not part
+/// of a longer ASCII word.
+fn match_at(text: &str, position: usize, entry: &GlossaryEntry) -> Option<usize> {
+    let end = position + entry.term.len();
+    let candidate = text.get(position..end)?;
+    let matches = if entry.case_sensitive {
+        candidate == entry.term
+    } else {
+        candidate.to_lowercase() == entry.term.to_lowercase()
+    };
+    if !matches {
+        return None;
+    }
+
+    let before = text[..position].chars().next_back();
+    let after = text[end..].chars().next();
+    let first = entry.term.chars().next();
+    let last = entry.term.chars().next_back();
+    let joins = |outer: Option<char>, inner: Option<char>| {
+        outer.is_some_and(|ch| ch.is_ascii_alphanumeric())
+            && inner.is_some_and(|ch| ch.is_ascii_alphanumeric())
+    };
+    if joins(before, first) || joins(after, last) {
+        return None;
+    }
+    Some(end)
+}
+
+fn find_ignore_ascii_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack
+        .as_bytes()
+        .windows(needle.len())
+        .position(|window| window.eq_ignore_ascii_case(needle.as_bytes()))
+}
+
+fn parse_json(raw: &str) -> Result<Vec<GlossaryEntry>> {
+    let file: GlossaryFile = serde_json::from_str(raw)?;
+    Ok(match file {
+        GlossaryFile::Map(map) => map
+            .into_iter()
+            .map(|(term, translation)| GlossaryEntry {
+                term,
+                translation,
+                case_sensitive: true,
+            })
+            .collect(),
+        GlossaryFile::List(entries) => entries,
+    })
+}
+
+fn parse_csv(raw: &str) -> Result<Vec<GlossaryEntry>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(raw.as_bytes());
+
+    let mut entries = Vec::new();
+    for (index, record) in reader.records().enumerate() {
+        let record = record?;
+        let term = record.get(0).unwrap_or_default();
+        if index == 0 && term.eq_ignore_ascii_case("term") {
+            continue;
+        }
+        let case_sensitive = match record.get(2).unwrap_or_default() {
+            "" | "true" | "yes" | "1" => true,
+            "false" | "no" | "0" => false,
+            other => bail!(
+                "invalid case_sensitive value on line {}: {other}",
+                index + 1
+            ),
+        };
+        entries.push(GlossaryEntry {
+            term: term.to_owned(),
+            translation: record.get(1).map(ToOwned::to_owned),
+            case_sensitive,
+        });
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(term: &str, translation: Option<&str>, case_sensitive: bool) -> GlossaryEntry {
+        GlossaryEntry {
+            term: term.to_owned(),
+            translation: translation.map(ToOwned::to_owned),
+            case_sensitive,
+        }
+    }
+
+    #[test]
+    fn overlapping_terms_prefer_the_longest_match() {
+        let glossary = Glossary::new(vec![
+            entry("Vibe", None, true),
+            entry("VibeTranslate Pro", Some("VTP"), true),
+        ]);
+
+        let protected = glossary.apply("Try VibeTranslate Pro or Vibe, not Vibes.");
+        assert_eq!(protected.text, "Try __TF0__ or __TF1__, not Vibes.");
+        assert_eq!(protected.replacements, vec!["VTP", "Vibe"]);
+
+        let translated = "__tf0__ か __TF1__ を試して";
+        assert_eq!(
+            glossary.restore(translated, &protected.replacements),
+            "VTP か Vibe を試して"
+        );
+    }
+
+    #[test]
+    fn case_sensitivity_is_per_entry() {
+        let glossary = Glossary::new(vec![entry("Rust", None, true), entry("cargo", None, false)]);
+
+        let protected = glossary.apply("rust and Rust, Cargo and cargo");
+        assert_eq!(protected.text, "rust and __TF0__, __TF1__ and __TF2__");
+        assert_eq!(protected.replacements, vec!["Rust", "Cargo", "cargo"]);
+    }
+
+    #[test]
+    fn loads_csv_and_json_glossaries() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let csv_path = temp.path().join("terms.csv");
+        fs::write(
+            &csv_path,
+            "term,translation\nVibeTranslate,\nbacktranslation,逆翻訳,false\n",
+        )
+        .unwrap();
+        let json_path = temp.path().join("terms.json");
+        fs::write(&json_path, r#"{"VibeTranslate": null, "memory": "メモリ"}"#).unwrap();
+
+        let from_csv = Glossary::load(&csv_path).unwrap();
+        assert_eq!(from_csv.len(), 2);
+        let protected = from_csv.apply("Backtranslation in VibeTranslate");
+        assert_eq!(protected.replacements, vec!["逆翻訳", "VibeTranslate"]);
+
+        assert_eq!(Glossary::load(&json_path).unwrap().len(), 2);
+    }
+}
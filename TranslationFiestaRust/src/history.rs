@@ -0,0 +1,145 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use tracing::warn;
+
+use crate::export::BatchExportContext;
+use crate::models::{BackTranslationResult, BatchItemResult};
+
+/// Completed translations, newest first. A missing or unreadable file starts
+/// an empty history.
+pub fn load_history(path: &Path) -> Vec<BackTranslationResult> {
+    if !path.exists() {
+        return Vec::new();
+    }
+
+    match fs::read_to_string(path)
+        .with_context(|| format!("failed to read history from {}", path.display()))
+        .and_then(|content| {
+            serde_json::from_str(&content)
+                .with_context(|| format!("failed to parse history from {}", path.display()))
+        }) {
+        Ok(entries) => entries,
+        Err(error) => {
+            warn!("failed to load history from {}: {error}", path.display());
+            Vec::new()
+        }
+    }
+}
+
+pub fn save_history(path: &Path, entries: &[BackTranslationResult]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create history directory {}", parent.display()))?;
+    }
+
+    let json = serde_json::to_string_pretty(entries).context("failed to serialize history")?;
+    fs::write(path, json).with_context(|| format!("failed to write history to {}", path.display()))
+}
+
+/// Puts `result` at the front and drops the oldest entries beyond `limit`.
+pub fn record_history(
+    entries: &mut Vec<BackTranslationResult>,
+    result: BackTranslationResult,
+    limit: usize,
+) {
+    entries.insert(0, result);
+    entries.truncate(limit.max(1));
+}
+
+/// History entries shaped as batch rows so `ExportService::export_batch` can
+/// write them; each row is labelled with its timestamp and language pair.
+pub fn history_as_batch_items(entries: &[BackTranslationResult]) -> Vec<BatchItemResult> {
+    entries
+        .iter()
+        .map(|entry| BatchItemResult {
+            file_path: history_label(entry),
+            success: true,
+            original_text: entry.original_text.clone(),
+            intermediate_text: entry.intermediate_text.clone(),
+            back_translated_text: entry.back_translated_text.clone(),
+            error: None,
+            error_kind: None,
+            detected_source: None,
+            suspicious_reason: entry.suspicious_reason.clone(),
+            duration_ms: entry.duration_ms,
+        })
+        .collect()
+}
+
+/// Export context for a history dump; languages and provider read `mixed`
+/// when the entries disagree.
+pub fn history_export_context(
+    entries: &[BackTranslationResult],
+    include_metadata: bool,
+) -> BatchExportContext<'_> {
+    fn shared<'a>(
+        entries: &'a [BackTranslationResult],
+        field: impl Fn(&'a BackTranslationResult) -> &'a str,
+    ) -> &'a str {
+        match entries.first().map(&field) {
+            Some(first) if entries.iter().all(|entry| field(entry) == first) => first,
+            Some(_) => "mixed",
+            None => "",
+        }
+    }
+
+    BatchExportContext {
+        include_metadata,
+        source_language: shared(entries, |entry| &entry.source_language),
+        target_language: shared(entries, |entry| &entry.intermediate_language),
+        provider: shared(entries, |entry| &entry.provider_id),
+    }
+}
+
+pub fn history_label(entry: &BackTranslationResult) -> String {
+    format!(
+        "{} {} -> {}",
+        entry.created_at.format("%Y-%m-%d %H:%M:%S"),
+        entry.source_language,
+        entry.intermediate_language
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::models::ProviderId;
+
+    fn result(text: &str) -> BackTranslationResult {
+        BackTranslationResult::new(
+            text.to_owned(),
+            "中間".to_owned(),
+            text.to_owned(),
+            "en".to_owned(),
+            "ja".to_owned(),
+            ProviderId::GoogleUnofficial,
+            Duration::from_millis(10),
+        )
+    }
+
+    #[test]
+    fn keeps_the_newest_entries_up_to_the_limit() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("data/history.json");
+        let mut entries = load_history(&path);
+        for text in ["one", "two", "three"] {
+            record_history(&mut entries, result(text), 2);
+        }
+        save_history(&path, &entries).unwrap();
+
+        let loaded = load_history(&path);
+        let texts: Vec<_> = loaded
+            .iter()
+            .map(|entry| entry.original_text.as_str())
+            .collect();
+        assert_eq!(texts, ["three", "two"]);
+
+        let context = history_export_context(&loaded, false);
+        assert_eq!(context.source_language, "en");
+        assert_eq!(history_as_batch_items(&loaded)[0].original_text, "three");
+    }
+}
@@ -1,14 +1,117 @@
-use scraper::{Html, Selector};
+use ego_tree::NodeId;
+use scraper::{Html, Node, Selector};
+
+/// Elements whose text and attributes are never extracted or translated.
+const SKIPPED_ELEMENTS: [&str; 6] = ["script", "style", "code", "pre", "noscript", "iframe"];
+
+/// Elements treated as separate paragraphs by `extract_blocks_from_html`.
+const BLOCK_ELEMENTS: [&str; 9] = ["p", "h1", "h2", "h3", "h4", "h5", "h6", "li", "blockquote"];
+
+/// Attributes that carry human-readable text.
+pub const DEFAULT_TRANSLATABLE_ATTRIBUTES: [&str; 4] =
+    ["alt", "title", "aria-label", "placeholder"];
+
+#[derive(Debug, Clone)]
+pub struct HtmlTranslateOptions {
+    /// Also translate the values of `attributes`, not just text nodes.
+    pub translate_attributes: bool,
+    pub attributes: Vec<String>,
+}
+
+impl Default for HtmlTranslateOptions {
+    fn default() -> Self {
+        Self {
+            translate_attributes: false,
+            attributes: DEFAULT_TRANSLATABLE_ATTRIBUTES
+                .iter()
+                .map(|name| (*name).to_owned())
+                .collect(),
+        }
+    }
+}
 
 pub fn extract_text_from_html(html_content: &str) -> String {
+    extract_text_from_html_with(html_content, true)
+}
+
+/// With `normalize` off, text nodes are concatenated as-is so
+/// intentional spacing survives; only leading/trailing whitespace is trimmed.
+pub fn extract_text_from_html_with(html_content: &str, normalize: bool) -> String {
     if html_content.trim().is_empty() {
         return String::new();
     }
 
+    let document = parse_without_skipped(html_content);
+
+    if !normalize {
+        return document
+            .root_element()
+            .text()
+            .collect::<String>()
+            .trim()
+            .to_owned();
+    }
+
+    let text = document
+        .root_element()
+        .text()
+        .map(str::trim)
+        .filter(|chunk| !chunk.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    normalize_whitespace(&text)
+}
+
+/// One whitespace-normalized string per block element (`p`, `h1`-`h6`,
+/// `li`, `blockquote`), in document order. Blocks nested inside another
+/// block are part of their outermost block. Documents without any block
+/// elements yield their whole text as a single block.
+pub fn extract_blocks_from_html(html_content: &str) -> Vec<String> {
+    if html_content.trim().is_empty() {
+        return Vec::new();
+    }
+
+    let document = parse_without_skipped(html_content);
+    let Ok(selector) = Selector::parse(&BLOCK_ELEMENTS.join(",")) else {
+        return Vec::new();
+    };
+
+    let blocks: Vec<String> = document
+        .select(&selector)
+        .filter(|element| {
+            !element.ancestors().any(|ancestor| {
+                ancestor
+                    .value()
+                    .as_element()
+                    .is_some_and(|parent| BLOCK_ELEMENTS.contains(&parent.name()))
+            })
+        })
+        .map(|element| normalize_whitespace(&element.text().collect::<String>()))
+        .filter(|block| !block.is_empty())
+        .collect();
+
+    if blocks.is_empty() {
+        let text = extract_text_from_html(html_content);
+        return if text.is_empty() {
+            Vec::new()
+        } else {
+            vec![text]
+        };
+    }
+    blocks
+}
+
+/// Like `extract_text_from_html`, but keeps paragraphs and headings apart
+/// by joining blocks with a blank line.
+pub fn extract_text_preserving_paragraphs(html_content: &str) -> String {
+    extract_blocks_from_html(html_content).join("\n\n")
+}
+
+fn parse_without_skipped(html_content: &str) -> Html {
     let mut document = Html::parse_document(html_content);
 
-    let blocklist = ["script", "style", "code", "pre", "noscript", "iframe"];
-    for tag in blocklist {
+    for tag in SKIPPED_ELEMENTS {
         if let Ok(selector) = Selector::parse(tag) {
             let elements: Vec<_> = document.select(&selector).map(|el| el.id()).collect();
             for element in elements {
@@ -18,16 +121,85 @@ pub fn extract_text_from_html(html_content: &str) -> String {
             }
         }
     }
+    document
+}
 
-    let text = document
-        .root_element()
-        .text()
-        .map(str::trim)
-        .filter(|chunk| !chunk.is_empty())
-        .collect::<Vec<_>>()
-        .join(" ");
+/// Translates text nodes (and optionally attributes) in place, keeping the
+/// markup intact. Whitespace around each text node is kept as-is.
+pub fn translate_html_preserving<E>(
+    html_content: &str,
+    options: &HtmlTranslateOptions,
+    mut translate: impl FnMut(&str) -> Result<String, E>,
+) -> Result<String, E> {
+    let mut document = Html::parse_document(html_content);
 
-    normalize_whitespace(&text)
+    let mut text_nodes: Vec<(NodeId, String)> = Vec::new();
+    let mut attribute_nodes: Vec<(NodeId, usize, String)> = Vec::new();
+    for node in document.tree.root().descendants() {
+        let skipped = node.ancestors().chain(std::iter::once(node)).any(|item| {
+            item.value()
+                .as_element()
+                .is_some_and(|element| SKIPPED_ELEMENTS.contains(&element.name()))
+        });
+        if skipped {
+            continue;
+        }
+
+        match node.value() {
+            Node::Text(text) if !text.trim().is_empty() => {
+                text_nodes.push((node.id(), text.to_string()));
+            }
+            Node::Element(element) if options.translate_attributes => {
+                for (index, (name, value)) in element.attrs.iter().enumerate() {
+                    let translatable = options
+                        .attributes
+                        .iter()
+                        .any(|attribute| attribute.eq_ignore_ascii_case(&name.local));
+                    if translatable && !value.trim().is_empty() {
+                        attribute_nodes.push((node.id(), index, value.to_string()));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for (id, original) in text_nodes {
+        let translated = translate_padded(&original, &mut translate)?;
+        if let Some(mut node) = document.tree.get_mut(id)
+            && let Node::Text(text) = node.value()
+        {
+            text.text = translated.as_str().into();
+        }
+    }
+
+    for (id, index, original) in attribute_nodes {
+        let translated = translate(original.trim())?;
+        if let Some(mut node) = document.tree.get_mut(id)
+            && let Node::Element(element) = node.value()
+            && let Some((_, value)) = element.attrs.get_mut(index)
+        {
+            *value = translated.as_str().into();
+        }
+    }
+
+    Ok(document.html())
+}
+
+fn translate_padded<E>(
+    original: &str,
+    translate: &mut impl FnMut(&str) -> Result<String, E>,
+) -> Result<String, E> {
+    let trimmed = original.trim();
+    let start = original.len() - original.trim_start().len();
+    let end = start + trimmed.len();
+    let translated = translate(trimmed)?;
+    Ok(format!(
+        "{}{}{}",
+        &original[..start],
+        translated,
+        &original[end..]
+    ))
 }
 
 pub fn escape_html(value: &str) -> String {
@@ -39,6 +211,67 @@ pub fn escape_html(value: &str) -> String {
         .replace('\'', "&#39;")
 }
 
+/// Decodes the entities providers put in their output: the five XML ones,
+/// `&nbsp;`, and numeric references. One left-to-right pass, so `&amp;lt;`
+/// becomes `&lt;` rather than `<`; anything unrecognized is kept as written.
+pub fn unescape_html(value: &str) -> String {
+    if !value.contains('&') {
+        return value.to_owned();
+    }
+
+    let mut output = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('&') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest[1..]
+            .char_indices()
+            .take(10)
+            .find(|&(_, ch)| ch == ';')
+            .and_then(|(end, _)| Some((decode_entity(&rest[1..end + 1])?, end + 2)));
+        match decoded {
+            Some((ch, len)) => {
+                output.push(ch);
+                rest = &rest[len..];
+            }
+            None => {
+                output.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+fn decode_entity(name: &str) -> Option<char> {
+    let code = match name.strip_prefix('#') {
+        Some(hex) if hex.starts_with(['x', 'X']) => u32::from_str_radix(&hex[1..], 16).ok()?,
+        Some(decimal) => decimal.parse().ok()?,
+        None => {
+            return match name {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some('\u{a0}'),
+                _ => None,
+            };
+        }
+    };
+    char::from_u32(code)
+}
+
+pub fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
 pub fn normalize_whitespace(value: &str) -> String {
     value
         .split_whitespace()
@@ -68,6 +301,62 @@ mod tests {
         assert_eq!(result, "Hello world");
     }
 
+    #[test]
+    fn keeps_paragraphs_on_separate_lines() {
+        let html = r#"
+            <html><body>
+              <h1>Title</h1>
+              <p>First   paragraph.</p>
+              <p>Second <em>paragraph</em>.</p>
+              <blockquote><p>Quoted.</p></blockquote>
+            </body></html>
+        "#;
+
+        assert_eq!(
+            extract_blocks_from_html(html),
+            vec!["Title", "First paragraph.", "Second paragraph.", "Quoted."]
+        );
+        let text = extract_text_preserving_paragraphs(html);
+        let lines: Vec<&str> = text.lines().filter(|line| !line.is_empty()).collect();
+        assert_eq!(lines[1], "First paragraph.");
+        assert_eq!(lines[2], "Second paragraph.");
+        assert_eq!(
+            extract_text_preserving_paragraphs("<div>Just text</div>"),
+            "Just text"
+        );
+    }
+
+    #[test]
+    fn preserves_spacing_when_normalization_is_disabled() {
+        let html = "<html><body><p>Hello  <strong>world</strong>\n\tagain</p></body></html>";
+
+        assert_eq!(extract_text_from_html_with(html, true), "Hello world again");
+        assert_eq!(
+            extract_text_from_html_with(html, false),
+            "Hello  world\n\tagain"
+        );
+    }
+
+    #[test]
+    fn translates_alt_text_but_not_href() {
+        let html = r#"<html><body><p>Hello <a href="/home" title="Go home">home</a></p><img src="cat.png" alt="A cat"><code title="Keep">x</code></body></html>"#;
+        let options = HtmlTranslateOptions {
+            translate_attributes: true,
+            ..HtmlTranslateOptions::default()
+        };
+
+        let result =
+            translate_html_preserving(html, &options, |text| Ok::<_, ()>(text.to_uppercase()))
+                .unwrap();
+
+        assert!(result.contains(r#"alt="A CAT""#));
+        assert!(result.contains(r#"title="GO HOME""#));
+        assert!(result.contains(r#"href="/home""#));
+        assert!(result.contains(r#"src="cat.png""#));
+        assert!(result.contains("<p>HELLO <a"));
+        assert!(result.contains(r#"<code title="Keep">x</code>"#));
+    }
+
     #[test]
     fn escapes_html_entities() {
         assert_eq!(
@@ -75,4 +364,17 @@ mod tests {
             "&lt;hi&gt; &amp; &quot;bye&quot;"
         );
     }
+
+    #[test]
+    fn unescapes_entities_once() {
+        assert_eq!(unescape_html("don&#39;t &amp; won&#039;t"), "don't & won't");
+        assert_eq!(
+            unescape_html("&lt;b&gt; &#x263A; &quot;q&quot;"),
+            "<b> ☺ \"q\""
+        );
+        assert_eq!(unescape_html("&amp;lt; stays &lt;"), "&lt; stays <");
+        assert_eq!(unescape_html("R&D & AT&T;"), "R&D & AT&T;");
+        let original = "Tom & \"Jerry\" <3 '";
+        assert_eq!(unescape_html(&escape_html(original)), original);
+    }
 }
@@ -1,3 +1,5 @@
+use serde::Serialize;
+
 pub fn is_supported_language_code(code: &str) -> bool {
     let trimmed = code.trim();
     if trimmed.is_empty() {
@@ -33,6 +35,207 @@ pub fn normalize_language_code(code: &str) -> Option<String> {
     Some(code.trim().to_ascii_lowercase())
 }
 
+/// Standard BCP-47 casing (`pt-BR`, `zh-Hans`), the form providers expect.
+/// Settings store lowercased codes, so this restores region and script case.
+pub fn canonical_language_code(code: &str) -> Option<String> {
+    if !is_supported_language_code(code) {
+        return None;
+    }
+
+    let mut segments = code.trim().split('-');
+    let mut canonical = segments.next().unwrap_or_default().to_ascii_lowercase();
+    for segment in segments {
+        canonical.push('-');
+        let is_alpha = segment.chars().all(|ch| ch.is_ascii_alphabetic());
+        match segment.len() {
+            2 if is_alpha => canonical.push_str(&segment.to_ascii_uppercase()),
+            3 if segment.chars().all(|ch| ch.is_ascii_digit()) => canonical.push_str(segment),
+            4 if is_alpha => {
+                canonical.push_str(&segment[..1].to_ascii_uppercase());
+                canonical.push_str(&segment[1..].to_ascii_lowercase());
+            }
+            _ => canonical.push_str(&segment.to_ascii_lowercase()),
+        }
+    }
+    Some(canonical)
+}
+
+/// Result of language detection over a text sample.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LanguageGuess {
+    /// Two-letter code, e.g. `ja`.
+    pub code: String,
+    /// whatlang's confidence in `0.0..=1.0`.
+    pub confidence: f64,
+    /// Whether whatlang considers the guess trustworthy.
+    pub reliable: bool,
+}
+
+pub fn guess_language(text: &str) -> Option<LanguageGuess> {
+    let info = whatlang::detect(text.trim())?;
+    let code = iso639_1_code(info.lang().code())?;
+    Some(LanguageGuess {
+        code: code.to_owned(),
+        confidence: info.confidence(),
+        reliable: info.is_reliable(),
+    })
+}
+
+/// Maps whatlang's ISO 639-3 codes to the ISO 639-1 codes providers take.
+fn iso639_1_code(iso639_3: &str) -> Option<&'static str> {
+    Some(match iso639_3 {
+        "epo" => "eo",
+        "eng" => "en",
+        "rus" => "ru",
+        "cmn" => "zh",
+        "spa" => "es",
+        "por" => "pt",
+        "ita" => "it",
+        "ben" => "bn",
+        "fra" => "fr",
+        "deu" => "de",
+        "ukr" => "uk",
+        "kat" => "ka",
+        "ara" => "ar",
+        "hin" => "hi",
+        "jpn" => "ja",
+        "heb" => "he",
+        "yid" => "yi",
+        "pol" => "pl",
+        "amh" => "am",
+        "jav" => "jv",
+        "kor" => "ko",
+        "nob" => "nb",
+        "dan" => "da",
+        "swe" => "sv",
+        "fin" => "fi",
+        "tur" => "tr",
+        "nld" => "nl",
+        "hun" => "hu",
+        "ces" => "cs",
+        "ell" => "el",
+        "bul" => "bg",
+        "bel" => "be",
+        "mar" => "mr",
+        "kan" => "kn",
+        "ron" => "ro",
+        "slv" => "sl",
+        "hrv" => "hr",
+        "srp" => "sr",
+        "mkd" => "mk",
+        "lit" => "lt",
+        "lav" => "lv",
+        "est" => "et",
+        "tam" => "ta",
+        "vie" => "vi",
+        "urd" => "ur",
+        "tha" => "th",
+        "guj" => "gu",
+        "uzb" => "uz",
+        "pan" => "pa",
+        "aze" => "az",
+        "ind" => "id",
+        "tel" => "te",
+        "pes" => "fa",
+        "mal" => "ml",
+        "ori" => "or",
+        "mya" => "my",
+        "nep" => "ne",
+        "sin" => "si",
+        "khm" => "km",
+        "tuk" => "tk",
+        "aka" => "ak",
+        "zul" => "zu",
+        "sna" => "sn",
+        "afr" => "af",
+        "lat" => "la",
+        "slk" => "sk",
+        "cat" => "ca",
+        "tgl" => "tl",
+        "hye" => "hy",
+        _ => return None,
+    })
+}
+
+/// Languages offered in the desktop pickers, as `(code, English name)` with
+/// codes in the lowercased form settings store.
+const COMMON_LANGUAGES: &[(&str, &str)] = &[
+    ("ar", "Arabic"),
+    ("bg", "Bulgarian"),
+    ("cs", "Czech"),
+    ("da", "Danish"),
+    ("de", "German"),
+    ("el", "Greek"),
+    ("en", "English"),
+    ("es", "Spanish"),
+    ("fa", "Persian"),
+    ("fi", "Finnish"),
+    ("fr", "French"),
+    ("he", "Hebrew"),
+    ("hi", "Hindi"),
+    ("hu", "Hungarian"),
+    ("id", "Indonesian"),
+    ("it", "Italian"),
+    ("ja", "Japanese"),
+    ("ko", "Korean"),
+    ("nl", "Dutch"),
+    ("no", "Norwegian"),
+    ("pl", "Polish"),
+    ("pt", "Portuguese"),
+    ("pt-br", "Portuguese (Brazil)"),
+    ("ro", "Romanian"),
+    ("ru", "Russian"),
+    ("sv", "Swedish"),
+    ("th", "Thai"),
+    ("tr", "Turkish"),
+    ("uk", "Ukrainian"),
+    ("vi", "Vietnamese"),
+    ("zh-cn", "Chinese (Simplified)"),
+    ("zh-tw", "Chinese (Traditional)"),
+];
+
+pub fn common_languages() -> &'static [(&'static str, &'static str)] {
+    COMMON_LANGUAGES
+}
+
+/// English name of `code` when it is one of `common_languages`.
+pub fn language_name(code: &str) -> Option<&'static str> {
+    let code = code.trim();
+    COMMON_LANGUAGES
+        .iter()
+        .find(|(known, _)| known.eq_ignore_ascii_case(code))
+        .map(|(_, name)| *name)
+}
+
+/// Whether the language separates words with spaces. Scripts such as Japanese,
+/// Chinese, and Thai are written without inter-word spacing.
+pub fn uses_word_spacing(code: &str) -> bool {
+    let primary = code
+        .trim()
+        .split('-')
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+
+    !matches!(
+        primary.as_str(),
+        "ja" | "jpn"
+            | "zh"
+            | "zho"
+            | "yue"
+            | "th"
+            | "tha"
+            | "lo"
+            | "lao"
+            | "km"
+            | "khm"
+            | "my"
+            | "mya"
+            | "bo"
+            | "bod"
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -54,4 +257,48 @@ mod tests {
         assert!(!is_supported_language_code("en_au"));
         assert!(!is_supported_language_code("en-"));
     }
+
+    #[test]
+    fn common_languages_are_valid_normalized_codes() {
+        for (code, _) in common_languages() {
+            assert_eq!(normalize_language_code(code).as_deref(), Some(*code));
+        }
+        assert_eq!(language_name("PT-BR"), Some("Portuguese (Brazil)"));
+        assert_eq!(language_name("tlh"), None);
+    }
+
+    #[test]
+    fn detects_languages_as_two_letter_codes() {
+        let guess =
+            guess_language("これは日本語で書かれた文章です。今日は天気がいいですね。").unwrap();
+        assert_eq!(guess.code, "ja");
+        assert!(guess.confidence > 0.5);
+        assert_eq!(
+            guess_language("The weather is lovely today, so we walked to the park.")
+                .unwrap()
+                .code,
+            "en"
+        );
+        assert_eq!(guess_language("   "), None);
+    }
+
+    #[test]
+    fn canonicalizes_bcp47_casing() {
+        assert_eq!(canonical_language_code(" pt-br ").as_deref(), Some("pt-BR"));
+        assert_eq!(
+            canonical_language_code("ZH-HANS").as_deref(),
+            Some("zh-Hans")
+        );
+        assert_eq!(canonical_language_code("es-419").as_deref(), Some("es-419"));
+        assert_eq!(canonical_language_code("en_au"), None);
+    }
+
+    #[test]
+    fn detects_languages_without_word_spacing() {
+        assert!(uses_word_spacing("en"));
+        assert!(uses_word_spacing("pt-BR"));
+        assert!(!uses_word_spacing("ja"));
+        assert!(!uses_word_spacing("zh-Hans"));
+        assert!(!uses_word_spacing("th"));
+    }
 }
@@ -1,25 +1,46 @@
 pub mod app_paths;
 pub mod batch;
+pub mod batch_diff;
+pub mod build_info;
 pub mod cli;
+pub mod config;
+pub mod diff;
+pub mod docx;
 pub mod epub;
 pub mod export;
 pub mod file_service;
+pub mod fonts;
+pub mod glob;
+pub mod glossary;
+pub mod history;
 pub mod html;
 pub mod language;
 pub mod logger;
 pub mod memory;
 pub mod models;
+pub mod providers;
+pub mod quality;
+pub mod rtf;
 pub mod settings;
+pub mod stats;
+pub mod structured;
+pub mod subtitle;
+pub mod text_stats;
+pub mod tmx;
 pub mod translation;
 pub mod ui;
+pub mod xlsx;
 
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::Result;
+use tracing::{info, warn};
 
 use app_paths::AppPaths;
 use batch::BatchProcessor;
 use export::ExportService;
+use glossary::Glossary;
 use memory::TranslationMemory;
 use settings::{AppSettings, load_settings};
 use translation::TranslationService;
@@ -36,15 +57,59 @@ pub struct RuntimeServices {
 
 pub fn initialize_runtime(paths: AppPaths) -> Result<RuntimeServices> {
     let settings = load_settings(&paths.settings_file);
+    initialize_runtime_with_settings(paths, settings)
+}
+
+/// `initialize_runtime` for settings the caller already loaded, e.g. to
+/// configure logging first.
+pub fn initialize_runtime_with_settings(
+    paths: AppPaths,
+    settings: AppSettings,
+) -> Result<RuntimeServices> {
+    let memory = Arc::new(
+        TranslationMemory::new(
+            &paths.memory_db_file,
+            settings.translation_memory_max_entries,
+        )?
+        .with_metrics_tracking(settings.track_memory_metrics)
+        .with_eviction_policy(settings.eviction())
+        .with_cache_normalization(settings.cache_normalization())
+        .with_entry_ttl_days(settings.entry_ttl_days)
+        .with_cache_namespace(settings.cache_namespace.clone()),
+    );
 
-    let memory = Arc::new(TranslationMemory::new(
-        &paths.memory_db_file,
-        settings.translation_memory_max_entries,
-    )?);
+    match memory.purge_expired() {
+        Ok(0) => {}
+        Ok(removed) => info!("purged {removed} expired translation memory entries"),
+        Err(error) => warn!("failed to purge expired translation memory entries: {error:#}"),
+    }
 
-    let translator = TranslationService::new(Arc::clone(&memory))?;
+    let translator = TranslationService::new(Arc::clone(&memory))?
+        .with_retry_policy(settings.retry_max_attempts, settings.retry_base_delay_ms)
+        .with_max_total_retry(Duration::from_millis(settings.max_total_retry_ms))
+        .with_blocked_retry_policy(
+            settings.blocked_retry_attempts,
+            Duration::from_secs(settings.blocked_cooldown_minutes * 60),
+        )
+        .with_min_request_interval(Duration::from_millis(settings.min_request_interval_ms))
+        .with_request_timeout(settings.request_timeout())?
+        .with_quality_thresholds(settings.quality_thresholds())
+        .with_deepl_api_key(settings.resolved_deepl_api_key())
+        .with_google_api_key(settings.resolved_google_api_key())
+        .with_libretranslate_url(&settings.libretranslate_url)
+        .with_segment_cache(settings.segment_cache);
+    let translator = match settings.glossary_file() {
+        Some(path) => match Glossary::load(&path) {
+            Ok(glossary) => translator.with_glossary(glossary),
+            Err(error) => {
+                warn!("glossary not loaded: {error:#}");
+                translator
+            }
+        },
+        None => translator,
+    };
     let batch = BatchProcessor::new(translator.clone());
-    let export = ExportService;
+    let export = ExportService::default().with_options(settings.export_options());
 
     Ok(RuntimeServices {
         paths,
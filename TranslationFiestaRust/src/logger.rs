@@ -1,33 +1,263 @@
+use std::fmt;
 use std::fs;
 use std::path::Path;
 
 use anyhow::{Context, Result};
-use tracing_subscriber::EnvFilter;
+use chrono::Utc;
+use serde_json::{Map, Value};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber, info, warn};
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
 
-pub fn init_logger(log_file: &Path) -> Result<()> {
-    if let Some(parent) = log_file.parent() {
-        fs::create_dir_all(parent)
-            .with_context(|| format!("failed to create log directory {}", parent.display()))?;
+use crate::build_info;
+
+const DEFAULT_LOG_LEVEL: &str = "info";
+
+/// Line format of the log file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// `tracing_subscriber`'s human-readable lines.
+    #[default]
+    Text,
+    /// One JSON object per event, for log shippers and observability tooling.
+    Json,
+}
+
+impl LogFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Text => "text",
+            Self::Json => "json",
+        }
     }
 
-    let file_appender = tracing_appender::rolling::never(
-        log_file
-            .parent()
-            .ok_or_else(|| anyhow::anyhow!("log file parent not found"))?,
-        log_file
-            .file_name()
-            .ok_or_else(|| anyhow::anyhow!("log file name missing"))?,
-    );
+    pub fn normalize(value: &str) -> Self {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "json" => Self::Json,
+            _ => Self::Text,
+        }
+    }
+}
 
-    let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+#[derive(Debug, Clone)]
+pub struct LogOptions {
+    /// `tracing` filter used when `RUST_LOG` is unset.
+    pub level: String,
+    /// Daily log files kept before the oldest is deleted.
+    pub retention_days: usize,
+    pub format: LogFormat,
+    /// Filter for an extra stderr copy of the log; `None` logs to the file only.
+    pub console_level: Option<String>,
+}
 
-    tracing_subscriber::fmt()
-        .with_env_filter(env_filter)
+impl Default for LogOptions {
+    fn default() -> Self {
+        Self {
+            level: DEFAULT_LOG_LEVEL.to_owned(),
+            retention_days: 14,
+            format: LogFormat::default(),
+            console_level: None,
+        }
+    }
+}
+
+/// Logs to `log_file` with the date inserted before its extension
+/// (`app.2026-01-31.log`), starting a new file each day and deleting all but
+/// the newest `retention_days` files. `RUST_LOG` takes precedence over the
+/// configured level. With a `console_level`, events passing that filter are
+/// also printed to stderr.
+pub fn init_logger(log_file: &Path, options: &LogOptions) -> Result<()> {
+    let file_appender = rolling_appender(log_file, options.retention_days)?;
+
+    let (env_filter, invalid_level) = match EnvFilter::try_from_default_env() {
+        Ok(filter) => (filter, None),
+        Err(_) => match EnvFilter::try_new(&options.level) {
+            Ok(filter) => (filter, None),
+            Err(error) => (EnvFilter::new(DEFAULT_LOG_LEVEL), Some(error)),
+        },
+    };
+
+    let file_layer = tracing_subscriber::fmt::layer()
         .with_writer(file_appender)
         .with_ansi(false)
-        .with_target(false)
+        .with_target(false);
+    let file_layer = match options.format {
+        LogFormat::Text => file_layer.boxed(),
+        LogFormat::Json => file_layer.event_format(JsonLines).boxed(),
+    };
+
+    let (console_filter, invalid_console_level) = match options.console_level.as_deref() {
+        Some(level) => match EnvFilter::try_new(level) {
+            Ok(filter) => (Some(filter), None),
+            Err(error) => (
+                Some(EnvFilter::new(DEFAULT_LOG_LEVEL)),
+                Some((level, error)),
+            ),
+        },
+        None => (None, None),
+    };
+    let console_layer = console_filter.map(|filter| {
+        tracing_subscriber::fmt::layer()
+            .with_writer(std::io::stderr)
+            .with_target(false)
+            .with_filter(filter)
+    });
+
+    tracing_subscriber::registry()
+        .with(file_layer.with_filter(env_filter))
+        .with(console_layer)
         .try_init()
         .map_err(|err| anyhow::anyhow!(err.to_string()))?;
 
+    info!("starting {}", build_info::build_label());
+    if let Some(error) = invalid_level {
+        warn!(
+            "invalid log_level {:?} ({error}); using {DEFAULT_LOG_LEVEL}",
+            options.level
+        );
+    }
+    if let Some((level, error)) = invalid_console_level {
+        warn!("invalid console log level {level:?} ({error}); using {DEFAULT_LOG_LEVEL}");
+    }
+
     Ok(())
 }
+
+fn rolling_appender(log_file: &Path, retention_days: usize) -> Result<RollingFileAppender> {
+    let directory = log_file
+        .parent()
+        .ok_or_else(|| anyhow::anyhow!("log file parent not found"))?;
+    fs::create_dir_all(directory)
+        .with_context(|| format!("failed to create log directory {}", directory.display()))?;
+
+    let prefix = log_file
+        .file_stem()
+        .ok_or_else(|| anyhow::anyhow!("log file name missing"))?
+        .to_string_lossy();
+    let mut builder = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(prefix)
+        .max_log_files(retention_days.max(1));
+    if let Some(extension) = log_file.extension() {
+        builder = builder.filename_suffix(extension.to_string_lossy());
+    }
+    builder
+        .build(directory)
+        .with_context(|| format!("failed to open log files in {}", directory.display()))
+}
+
+/// Writes each event as `{"timestamp", "level", "message", "spans", ...fields}`;
+/// event fields such as `provider` or `duration_ms` keep their JSON types.
+struct JsonLines;
+
+impl<S, N> FormatEvent<S, N> for JsonLines
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let mut line = Map::new();
+        line.insert("timestamp".to_owned(), Utc::now().to_rfc3339().into());
+        line.insert("level".to_owned(), event.metadata().level().as_str().into());
+        if let Some(scope) = ctx.event_scope() {
+            let spans: Vec<Value> = scope.from_root().map(|span| span.name().into()).collect();
+            line.insert("spans".to_owned(), spans.into());
+        }
+        event.record(&mut JsonFields(&mut line));
+
+        writeln!(writer, "{}", Value::Object(line))
+    }
+}
+
+struct JsonFields<'a>(&'a mut Map<String, Value>);
+
+impl Visit for JsonFields<'_> {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_owned(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_owned(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_owned(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_owned(), value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_owned(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_owned(), format!("{value:?}").into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotating_logger_prunes_files_beyond_retention() {
+        let temp = tempfile::TempDir::new().unwrap();
+        for day in 1..=3 {
+            fs::write(temp.path().join(format!("app.2020-01-0{day}.log")), "old").unwrap();
+        }
+        fs::write(temp.path().join("unrelated.txt"), "keep").unwrap();
+
+        rolling_appender(&temp.path().join("app.log"), 2).unwrap();
+
+        let mut logs: Vec<String> = fs::read_dir(temp.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .filter(|name| name.starts_with("app.") && name.ends_with(".log"))
+            .collect();
+        logs.sort();
+        assert_eq!(logs.len(), 2, "{logs:?}");
+        assert!(!logs.iter().all(|name| name.starts_with("app.2020-")));
+        assert!(temp.path().join("unrelated.txt").exists());
+    }
+
+    #[test]
+    fn json_logger_writes_one_object_per_event() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let options = LogOptions {
+            format: LogFormat::Json,
+            ..LogOptions::default()
+        };
+        init_logger(&temp.path().join("app.log"), &options).unwrap();
+
+        info!(provider = "deepl", duration_ms = 42_u64, "json smoke test");
+
+        let log_path = fs::read_dir(temp.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .find(|path| path.extension().is_some_and(|ext| ext == "log"))
+            .unwrap();
+        let line = fs::read_to_string(log_path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(line).unwrap())
+            .find(|line| line["message"] == "json smoke test")
+            .unwrap();
+        assert_eq!(line["level"], "INFO");
+        assert_eq!(line["provider"], "deepl");
+        assert_eq!(line["duration_ms"], 42);
+    }
+}
@@ -1,33 +1,50 @@
 use std::error::Error;
+use std::process::ExitCode;
 
 use clap::Parser;
 use eframe::egui;
 
 use translation_fiesta_rust::app_paths::AppPaths;
-use translation_fiesta_rust::cli::{CliArgs, CliRuntime, execute};
-use translation_fiesta_rust::initialize_runtime;
+use translation_fiesta_rust::batch::BatchProcessor;
+use translation_fiesta_rust::cli::{CliArgs, CliOutcome, CliRuntime, execute};
+use translation_fiesta_rust::initialize_runtime_with_settings;
 use translation_fiesta_rust::logger::init_logger;
+use translation_fiesta_rust::settings::load_settings;
 use translation_fiesta_rust::ui::TranslationFiestaApp;
 
-fn main() -> Result<(), Box<dyn Error>> {
+fn main() -> Result<ExitCode, Box<dyn Error>> {
     let args = CliArgs::parse();
 
-    let paths = AppPaths::discover()?;
+    let paths = AppPaths::discover(args.portable)?;
     let log_path = paths.logs_dir.join("translationfiestarust.log");
-    init_logger(&log_path)?;
+    let settings = load_settings(&paths.settings_file);
+    let mut log_options = settings.log_options();
+    log_options.console_level = args.console_log_level().map(str::to_owned);
+    init_logger(&log_path, &log_options)?;
 
-    let runtime = initialize_runtime(paths.clone())?;
+    let mut runtime = initialize_runtime_with_settings(paths.clone(), settings)?;
+    if let Some(seed) = args.seed {
+        runtime.translator = runtime.translator.with_seed(seed);
+    }
+    if args.no_cache {
+        runtime.translator = runtime.translator.with_bypass_cache(true);
+    }
+    if args.seed.is_some() || args.no_cache {
+        runtime.batch = BatchProcessor::new(runtime.translator.clone());
+    }
 
     let cli_runtime = CliRuntime {
         paths: runtime.paths.clone(),
+        settings: runtime.settings.clone(),
         translator: runtime.translator.clone(),
         batch: runtime.batch.clone(),
-        export: runtime.export.clone(),
+        export: runtime.export,
         memory: runtime.memory.clone(),
     };
 
-    if execute(&args, &cli_runtime)? {
-        return Ok(());
+    match execute(&args, &cli_runtime)? {
+        CliOutcome::Gui => {}
+        outcome => return Ok(ExitCode::from(outcome.exit_code(args.fail_on))),
     }
 
     let window_size = egui::vec2(
@@ -35,10 +52,15 @@ fn main() -> Result<(), Box<dyn Error>> {
         runtime.settings.window_height,
     );
 
+    let mut viewport = egui::ViewportBuilder::default()
+        .with_inner_size(window_size)
+        .with_min_inner_size(egui::vec2(900.0, 620.0));
+    if let Some((x, y)) = runtime.settings.window_position() {
+        viewport = viewport.with_position(egui::pos2(x, y));
+    }
+
     let native_options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_inner_size(window_size)
-            .with_min_inner_size(egui::vec2(900.0, 620.0)),
+        viewport,
         ..Default::default()
     };
 
@@ -57,5 +79,5 @@ fn main() -> Result<(), Box<dyn Error>> {
         Box::new(|_cc| Ok(Box::new(app))),
     )?;
 
-    Ok(())
+    Ok(ExitCode::SUCCESS)
 }
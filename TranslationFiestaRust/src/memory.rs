@@ -1,17 +1,154 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::io::Write;
 use std::path::Path;
 use std::sync::{Arc, Mutex};
 use std::time::Instant;
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use chrono::{DateTime, Utc};
 use rusqlite::{Connection, OptionalExtension, params};
+use tracing::warn;
 
-use crate::models::{MemoryEntry, MemoryStats};
+use crate::models::{ExportFormat, GroupedMemoryStats, MemoryEntry, MemoryRecord, MemoryStats};
+
+const INDEX_NAMES: [&str; 2] = ["idx_cache_key", "idx_last_accessed"];
+
+const INDEX_DEFINITIONS: &str =
+    "CREATE INDEX IF NOT EXISTS idx_cache_key ON translation_cache(cache_key);
+     CREATE INDEX IF NOT EXISTS idx_last_accessed ON translation_cache(last_accessed);";
+
+/// External-content FTS5 index over both text columns. The trigram tokenizer
+/// keeps substring semantics and works for scripts without word spacing.
+/// Triggers keep it in sync with every insert, upsert, prune, and clear.
+const FTS_DEFINITIONS: &str = "CREATE VIRTUAL TABLE IF NOT EXISTS translation_cache_fts USING fts5(
+         source_text, translated_text,
+         content='translation_cache', content_rowid='id', tokenize='trigram'
+     );
+     CREATE TRIGGER IF NOT EXISTS translation_cache_fts_insert AFTER INSERT ON translation_cache BEGIN
+         INSERT INTO translation_cache_fts(rowid, source_text, translated_text)
+         VALUES (new.id, new.source_text, new.translated_text);
+     END;
+     CREATE TRIGGER IF NOT EXISTS translation_cache_fts_delete AFTER DELETE ON translation_cache BEGIN
+         INSERT INTO translation_cache_fts(translation_cache_fts, rowid, source_text, translated_text)
+         VALUES ('delete', old.id, old.source_text, old.translated_text);
+     END;
+     CREATE TRIGGER IF NOT EXISTS translation_cache_fts_update AFTER UPDATE ON translation_cache BEGIN
+         INSERT INTO translation_cache_fts(translation_cache_fts, rowid, source_text, translated_text)
+         VALUES ('delete', old.id, old.source_text, old.translated_text);
+         INSERT INTO translation_cache_fts(rowid, source_text, translated_text)
+         VALUES (new.id, new.source_text, new.translated_text);
+     END;";
+
+/// Trigram FTS cannot match terms shorter than this.
+const MIN_FTS_TERM_CHARS: usize = 3;
+
+/// Which rows go first when the memory grows past `max_entries`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EvictionPolicy {
+    /// Least recently used.
+    #[default]
+    Lru,
+    /// Least frequently used, ties broken by recency.
+    Lfu,
+}
+
+impl EvictionPolicy {
+    pub fn all() -> [Self; 2] {
+        [Self::Lru, Self::Lfu]
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Lru => "lru",
+            Self::Lfu => "lfu",
+        }
+    }
+
+    pub fn display_name(self) -> &'static str {
+        match self {
+            Self::Lru => "Least recently used",
+            Self::Lfu => "Least frequently used",
+        }
+    }
+
+    pub fn normalize(value: &str) -> Self {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "lfu" | "frequency" | "least_frequently_used" => Self::Lfu,
+            _ => Self::Lru,
+        }
+    }
+
+    fn order_by(self) -> &'static str {
+        match self {
+            Self::Lru => "last_accessed ASC",
+            Self::Lfu => "access_count ASC, last_accessed ASC",
+        }
+    }
+}
+
+/// How source text is folded before it becomes part of a cache key. Only the
+/// key changes; entries keep the source text as it was first stored.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CacheNormalization {
+    /// The exact text is the key.
+    #[default]
+    Off,
+    /// Trims and collapses runs of whitespace to one space.
+    Whitespace,
+    /// Like `Whitespace`, and lowercases the text too.
+    WhitespaceAndCase,
+}
+
+impl CacheNormalization {
+    pub fn all() -> [Self; 3] {
+        [Self::Off, Self::Whitespace, Self::WhitespaceAndCase]
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Whitespace => "whitespace",
+            Self::WhitespaceAndCase => "whitespace_and_case",
+        }
+    }
+
+    pub fn display_name(self) -> &'static str {
+        match self {
+            Self::Off => "Exact text",
+            Self::Whitespace => "Ignore whitespace",
+            Self::WhitespaceAndCase => "Ignore whitespace and case",
+        }
+    }
+
+    pub fn normalize(value: &str) -> Self {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "whitespace" => Self::Whitespace,
+            "whitespace_and_case" | "case" => Self::WhitespaceAndCase,
+            _ => Self::Off,
+        }
+    }
+
+    pub fn apply(self, text: &str) -> Cow<'_, str> {
+        let collapse = || text.split_whitespace().collect::<Vec<_>>().join(" ");
+        match self {
+            Self::Off => Cow::Borrowed(text),
+            Self::Whitespace => Cow::Owned(collapse()),
+            Self::WhitespaceAndCase => Cow::Owned(collapse().to_lowercase()),
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct TranslationMemory {
     conn: Arc<Mutex<Connection>>,
     max_entries: usize,
+    track_metrics: bool,
+    namespace: String,
+    normalization: CacheNormalization,
+    eviction_policy: EvictionPolicy,
+    entry_ttl: Option<chrono::Duration>,
+    full_text_search: bool,
 }
 
 impl TranslationMemory {
@@ -26,18 +163,113 @@ impl TranslationMemory {
         }
         let conn = Connection::open(db_path)
             .with_context(|| format!("failed to open sqlite db {}", db_path.display()))?;
-        let memory = Self {
+        // WAL lets lookups proceed while a store is being written; NORMAL sync is
+        // durable enough for a cache and avoids an fsync on every commit.
+        conn.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get::<_, String>(0))
+            .context("failed to enable WAL journal mode")?;
+        conn.pragma_update(None, "synchronous", "NORMAL")
+            .context("failed to set synchronous pragma")?;
+        let mut memory = Self {
             conn: Arc::new(Mutex::new(conn)),
             max_entries,
+            track_metrics: true,
+            namespace: String::new(),
+            normalization: CacheNormalization::default(),
+            eviction_policy: EvictionPolicy::default(),
+            entry_ttl: None,
+            full_text_search: false,
         };
         memory.init_schema()?;
+        memory.full_text_search = memory.init_full_text_search();
         Ok(memory)
     }
 
+    /// When disabled, lookups skip the `memory_metrics` write and `stats()` reports zero hit/miss data.
+    pub fn with_metrics_tracking(mut self, enabled: bool) -> Self {
+        self.track_metrics = enabled;
+        self
+    }
+
+    /// Mixes `namespace` into every cache key, so a new value starts a fresh cache
+    /// generation; entries from older generations are never hit again and age out
+    /// through normal pruning. An empty namespace keeps the original keys.
+    pub fn with_cache_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = namespace.into().trim().to_owned();
+        self
+    }
+
+    /// Applied to the source text in every lookup, store, and import key.
+    /// Entries stored under another mode keep their keys and stop matching
+    /// until they are stored again.
+    pub fn with_cache_normalization(mut self, normalization: CacheNormalization) -> Self {
+        self.normalization = normalization;
+        self
+    }
+
+    pub fn with_eviction_policy(mut self, policy: EvictionPolicy) -> Self {
+        self.eviction_policy = policy;
+        self
+    }
+
+    pub fn eviction_policy(&self) -> EvictionPolicy {
+        self.eviction_policy
+    }
+
+    /// Entries created more than `days` ago miss on lookup and are removed by
+    /// `purge_expired`. Zero keeps entries forever.
+    pub fn with_entry_ttl_days(mut self, days: u64) -> Self {
+        self.entry_ttl = (days > 0).then(|| chrono::Duration::days(days as i64));
+        self
+    }
+
+    /// Deletes entries older than the TTL and returns how many were removed.
+    pub fn purge_expired(&self) -> Result<usize> {
+        let Some(cutoff) = self.expiry_cutoff() else {
+            return Ok(0);
+        };
+        let conn = self.conn.lock().expect("translation memory lock poisoned");
+        let removed = conn
+            .execute(
+                "DELETE FROM translation_cache WHERE created_at < ?1",
+                params![cutoff],
+            )
+            .context("failed to purge expired translation memory entries")?;
+        Ok(removed)
+    }
+
+    /// Oldest `created_at` still considered fresh, when a TTL is set.
+    fn expiry_cutoff(&self) -> Option<String> {
+        self.entry_ttl.map(|ttl| (Utc::now() - ttl).to_rfc3339())
+    }
+
+    pub fn cache_namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    fn key(
+        &self,
+        source_text: &str,
+        source_language: &str,
+        target_language: &str,
+        provider_id: &str,
+    ) -> String {
+        cache_key(
+            &self.namespace,
+            &self.normalization.apply(source_text),
+            source_language,
+            target_language,
+            provider_id,
+        )
+    }
+
     pub fn max_entries(&self) -> usize {
         self.max_entries
     }
 
+    pub fn tracks_metrics(&self) -> bool {
+        self.track_metrics
+    }
+
     pub fn lookup(
         &self,
         source_text: &str,
@@ -46,7 +278,7 @@ impl TranslationMemory {
         provider_id: &str,
     ) -> Result<Option<String>> {
         let started_at = Instant::now();
-        let key = cache_key(source_text, source_language, target_language, provider_id);
+        let key = self.key(source_text, source_language, target_language, provider_id);
         let now = Utc::now().to_rfc3339();
 
         let mut conn = self.conn.lock().expect("translation memory lock poisoned");
@@ -54,10 +286,13 @@ impl TranslationMemory {
             .transaction()
             .context("failed to begin lookup transaction")?;
 
+        // Without a TTL the cutoff is empty, which every timestamp sorts after.
+        let cutoff = self.expiry_cutoff().unwrap_or_default();
         let maybe_translation: Option<String> = tx
             .query_row(
-                "SELECT translated_text FROM translation_cache WHERE cache_key = ?1",
-                params![key.as_str()],
+                "SELECT translated_text FROM translation_cache
+                 WHERE cache_key = ?1 AND created_at >= ?2",
+                params![key.as_str(), cutoff],
                 |row| row.get(0),
             )
             .optional()
@@ -72,9 +307,14 @@ impl TranslationMemory {
                 params![now, key.as_str()],
             )
             .context("failed to update translation memory access info")?;
-            bump_metrics(&tx, true, started_at.elapsed().as_secs_f64() * 1000.0)?;
-        } else {
-            bump_metrics(&tx, false, started_at.elapsed().as_secs_f64() * 1000.0)?;
+        }
+
+        if self.track_metrics {
+            bump_metrics(
+                &tx,
+                maybe_translation.is_some(),
+                started_at.elapsed().as_secs_f64() * 1000.0,
+            )?;
         }
 
         tx.commit().context("failed to commit lookup transaction")?;
@@ -90,7 +330,7 @@ impl TranslationMemory {
         provider_id: &str,
     ) -> Result<()> {
         let now = Utc::now().to_rfc3339();
-        let key = cache_key(source_text, source_language, target_language, provider_id);
+        let key = self.key(source_text, source_language, target_language, provider_id);
 
         let mut conn = self.conn.lock().expect("translation memory lock poisoned");
         let tx = conn
@@ -112,6 +352,7 @@ impl TranslationMemory {
              ON CONFLICT(cache_key) DO UPDATE SET
                 translated_text = excluded.translated_text,
                 access_count = translation_cache.access_count + 1,
+                created_at = excluded.created_at,
                 last_accessed = excluded.last_accessed",
             params![
                 key.as_str(),
@@ -125,13 +366,54 @@ impl TranslationMemory {
         )
         .context("failed to store translation memory entry")?;
 
-        prune_oldest(&tx, self.max_entries)?;
+        prune_overflow(&tx, self.max_entries, self.eviction_policy)?;
 
         tx.commit().context("failed to commit store transaction")?;
         Ok(())
     }
 
+    /// Whether `search` can use the FTS5 index; false when the linked SQLite
+    /// lacks FTS5, in which case it falls back to `LIKE`.
+    pub fn has_full_text_search(&self) -> bool {
+        self.full_text_search
+    }
+
+    /// Entries whose source or translation contains any of the query's
+    /// words, best matches first. Queries without a word long enough for the
+    /// FTS index use substring matching ordered by recency instead.
     pub fn search(&self, query: &str, limit: usize) -> Result<Vec<MemoryEntry>> {
+        if self.full_text_search
+            && let Some(fts_query) = fts_query(query)
+        {
+            return self.search_full_text(&fts_query, limit);
+        }
+        self.search_like(query, limit)
+    }
+
+    fn search_full_text(&self, fts_query: &str, limit: usize) -> Result<Vec<MemoryEntry>> {
+        let conn = self.conn.lock().expect("translation memory lock poisoned");
+        let mut statement = conn.prepare(
+            "SELECT c.source_text, c.translated_text, c.source_language, c.target_language, c.provider_id, c.access_count, c.last_accessed, c.cache_key, bm25(translation_cache_fts)
+             FROM translation_cache_fts
+             JOIN translation_cache c ON c.id = translation_cache_fts.rowid
+             WHERE translation_cache_fts MATCH ?1
+             ORDER BY bm25(translation_cache_fts), c.last_accessed DESC
+             LIMIT ?2",
+        )?;
+
+        let rows = statement.query_map(params![fts_query, limit as i64], |row| {
+            let rank: f64 = row.get(8)?;
+            memory_entry(row, Some(-rank))
+        })?;
+
+        let mut entries = Vec::new();
+        for item in rows {
+            entries.push(item?);
+        }
+        Ok(entries)
+    }
+
+    fn search_like(&self, query: &str, limit: usize) -> Result<Vec<MemoryEntry>> {
         let conn = self.conn.lock().expect("translation memory lock poisoned");
 
         let escaped_query = query
@@ -141,7 +423,7 @@ impl TranslationMemory {
             .replace('_', "\\_");
         let like_query = format!("%{}%", escaped_query);
         let mut statement = conn.prepare(
-            "SELECT source_text, translated_text, source_language, target_language, provider_id, access_count, last_accessed
+            "SELECT source_text, translated_text, source_language, target_language, provider_id, access_count, last_accessed, cache_key
              FROM translation_cache
              WHERE source_text LIKE ?1 ESCAPE '\\' OR translated_text LIKE ?1 ESCAPE '\\'
              ORDER BY last_accessed DESC
@@ -149,28 +431,190 @@ impl TranslationMemory {
         )?;
 
         let rows = statement.query_map(params![like_query, limit as i64], |row| {
-            let last_accessed_raw: String = row.get(6)?;
-            let last_accessed = DateTime::parse_from_rfc3339(&last_accessed_raw)
-                .map(|dt| dt.with_timezone(&Utc))
-                .unwrap_or_else(|_| Utc::now());
+            memory_entry(row, None)
+        })?;
+
+        let mut entries = Vec::new();
+        for item in rows {
+            entries.push(item?);
+        }
+
+        Ok(entries)
+    }
+
+    /// Every row as a JSON array or CSV table. Prefer `export_to` for large
+    /// memories; this buffers the whole export.
+    pub fn export_all(&self, format: ExportFormat) -> Result<String> {
+        let mut buffer = Vec::new();
+        self.export_to(format, &mut buffer)?;
+        String::from_utf8(buffer).context("translation memory export is not valid UTF-8")
+    }
 
-            Ok(MemoryEntry {
+    /// Streams every row to `writer` in JSON or CSV, oldest first, and
+    /// returns the number of rows written.
+    pub fn export_to<W: Write>(&self, format: ExportFormat, writer: W) -> Result<usize> {
+        if !matches!(format, ExportFormat::Json | ExportFormat::Csv) {
+            bail!("translation memory can only be exported as json or csv, not {format}");
+        }
+
+        let conn = self.conn.lock().expect("translation memory lock poisoned");
+        let mut statement = conn.prepare(
+            "SELECT source_text, translated_text, source_language, target_language, provider_id, access_count, created_at, last_accessed
+             FROM translation_cache
+             ORDER BY id",
+        )?;
+        let rows = statement.query_map([], |row| {
+            Ok(MemoryRecord {
                 source_text: row.get(0)?,
                 translated_text: row.get(1)?,
                 source_language: row.get(2)?,
                 target_language: row.get(3)?,
                 provider_id: row.get(4)?,
                 access_count: row.get(5)?,
-                last_accessed,
+                created_at: row.get(6)?,
+                last_accessed: row.get(7)?,
             })
         })?;
 
-        let mut entries = Vec::new();
-        for item in rows {
-            entries.push(item?);
+        let mut count = 0;
+        match format {
+            ExportFormat::Csv => {
+                let mut csv_writer = csv::Writer::from_writer(writer);
+                for record in rows {
+                    csv_writer.serialize(record?)?;
+                    count += 1;
+                }
+                if count == 0 {
+                    csv_writer.write_record([
+                        "source_text",
+                        "translated_text",
+                        "source_language",
+                        "target_language",
+                        "provider_id",
+                        "access_count",
+                        "created_at",
+                        "last_accessed",
+                    ])?;
+                }
+                csv_writer
+                    .flush()
+                    .context("failed to write translation memory csv")?;
+            }
+            _ => {
+                let mut writer = writer;
+                writer.write_all(b"[")?;
+                for record in rows {
+                    writer.write_all(if count == 0 { b"\n  " } else { b",\n  " })?;
+                    serde_json::to_writer(&mut writer, &record?)?;
+                    count += 1;
+                }
+                writer.write_all(b"\n]\n")?;
+                writer
+                    .flush()
+                    .context("failed to write translation memory json")?;
+            }
         }
+        Ok(count)
+    }
 
-        Ok(entries)
+    /// Loads a `.json` or `.csv` file written by `export_to` and returns the
+    /// number of rows read. Cache keys are recomputed under the current
+    /// namespace; rows that already exist add their access counts together.
+    /// Without `merge` the memory is cleared first.
+    pub fn import(&self, path: &Path, merge: bool) -> Result<usize> {
+        let file = std::fs::File::open(path)
+            .with_context(|| format!("failed to open {}", path.display()))?;
+        let reader = std::io::BufReader::new(file);
+        let records: Vec<MemoryRecord> = match ExportFormat::from_path(path) {
+            Some(ExportFormat::Json) => serde_json::from_reader(reader)
+                .with_context(|| format!("failed to parse {}", path.display()))?,
+            Some(ExportFormat::Csv) => csv::Reader::from_reader(reader)
+                .deserialize()
+                .collect::<std::result::Result<_, _>>()
+                .with_context(|| format!("failed to parse {}", path.display()))?,
+            _ => bail!(
+                "translation memory can only be imported from .json or .csv, not {}",
+                path.display()
+            ),
+        };
+
+        let mut conn = self.conn.lock().expect("translation memory lock poisoned");
+        let tx = conn
+            .transaction()
+            .context("failed to begin import transaction")?;
+        if !merge {
+            clear_all(&tx)?;
+        }
+        {
+            let mut statement = tx.prepare(
+                "INSERT INTO translation_cache (
+                    cache_key,
+                    source_text,
+                    translated_text,
+                    source_language,
+                    target_language,
+                    provider_id,
+                    access_count,
+                    created_at,
+                    last_accessed
+                 ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+                 ON CONFLICT(cache_key) DO UPDATE SET
+                    translated_text = excluded.translated_text,
+                    access_count = translation_cache.access_count + excluded.access_count,
+                    last_accessed = MAX(translation_cache.last_accessed, excluded.last_accessed)",
+            )?;
+            for record in &records {
+                let key = self.key(
+                    &record.source_text,
+                    &record.source_language,
+                    &record.target_language,
+                    &record.provider_id,
+                );
+                statement
+                    .execute(params![
+                        key.as_str(),
+                        record.source_text,
+                        record.translated_text,
+                        record.source_language,
+                        record.target_language,
+                        record.provider_id,
+                        record.access_count.max(1),
+                        record.created_at,
+                        record.last_accessed,
+                    ])
+                    .context("failed to import translation memory entry")?;
+            }
+        }
+
+        prune_overflow(&tx, self.max_entries, self.eviction_policy)?;
+
+        tx.commit().context("failed to commit import transaction")?;
+        Ok(records.len())
+    }
+
+    /// Removes the entry stored under `key`; false when there was none.
+    pub fn delete(&self, key: &str) -> Result<bool> {
+        let conn = self.conn.lock().expect("translation memory lock poisoned");
+        let removed = conn
+            .execute(
+                "DELETE FROM translation_cache WHERE cache_key = ?1",
+                params![key],
+            )
+            .context("failed to delete translation memory entry")?;
+        Ok(removed > 0)
+    }
+
+    /// Replaces the cached translation under `key`, e.g. to correct a bad
+    /// provider result; false when there was no such entry.
+    pub fn update_translation(&self, key: &str, translated_text: &str) -> Result<bool> {
+        let conn = self.conn.lock().expect("translation memory lock poisoned");
+        let updated = conn
+            .execute(
+                "UPDATE translation_cache SET translated_text = ?2 WHERE cache_key = ?1",
+                params![key, translated_text],
+            )
+            .context("failed to update translation memory entry")?;
+        Ok(updated > 0)
     }
 
     pub fn clear(&self) -> Result<()> {
@@ -178,25 +622,48 @@ impl TranslationMemory {
         let tx = conn
             .transaction()
             .context("failed to begin clear transaction")?;
-
-        tx.execute("DELETE FROM translation_cache", [])
-            .context("failed to clear translation cache")?;
-        tx.execute(
-            "UPDATE memory_metrics
-             SET hits = 0,
-                 misses = 0,
-                 total_lookups = 0,
-                 total_lookup_time_ms = 0.0,
-                 last_persisted = ?1
-             WHERE id = 1",
-            params![Utc::now().to_rfc3339()],
-        )
-        .context("failed to clear memory metrics")?;
-
+        clear_all(&tx)?;
         tx.commit().context("failed to commit clear transaction")?;
         Ok(())
     }
 
+    /// Entry and access totals keyed by provider id.
+    pub fn stats_by_provider(&self) -> Result<BTreeMap<String, GroupedMemoryStats>> {
+        self.grouped_stats("provider_id")
+    }
+
+    /// Entry and access totals keyed by `source->target` language pair.
+    pub fn stats_by_language_pair(&self) -> Result<BTreeMap<String, GroupedMemoryStats>> {
+        self.grouped_stats("source_language || '->' || target_language")
+    }
+
+    fn grouped_stats(&self, group: &str) -> Result<BTreeMap<String, GroupedMemoryStats>> {
+        let conn = self.conn.lock().expect("translation memory lock poisoned");
+        let mut statement = conn.prepare(&format!(
+            "SELECT {group}, COUNT(*), COALESCE(SUM(access_count), 0)
+             FROM translation_cache
+             GROUP BY 1"
+        ))?;
+        let rows = statement.query_map([], |row| {
+            let entries: i64 = row.get(1)?;
+            let accesses: i64 = row.get(2)?;
+            Ok((
+                row.get::<_, String>(0)?,
+                GroupedMemoryStats {
+                    entries: entries.max(0) as usize,
+                    total_accesses: accesses.max(0) as u64,
+                },
+            ))
+        })?;
+
+        let mut groups = BTreeMap::new();
+        for row in rows {
+            let (key, stats) = row.context("failed to read grouped memory stats")?;
+            groups.insert(key, stats);
+        }
+        Ok(groups)
+    }
+
     pub fn stats(&self) -> Result<MemoryStats> {
         let conn = self.conn.lock().expect("translation memory lock poisoned");
 
@@ -207,6 +674,14 @@ impl TranslationMemory {
             })
             .context("failed to count translation memory entries")?;
 
+        if !self.track_metrics {
+            return Ok(MemoryStats {
+                total_entries,
+                max_entries: self.max_entries,
+                ..MemoryStats::default()
+            });
+        }
+
         let (hits, misses, total_lookups, total_lookup_time_ms): (i64, i64, i64, f64) = conn
             .query_row(
                 "SELECT hits, misses, total_lookups, total_lookup_time_ms
@@ -241,6 +716,65 @@ impl TranslationMemory {
         })
     }
 
+    /// Drops and recreates the defined indexes, then rebuilds every index on the cache table.
+    pub fn reindex(&self) -> Result<()> {
+        let mut conn = self.conn.lock().expect("translation memory lock poisoned");
+        let tx = conn
+            .transaction()
+            .context("failed to begin reindex transaction")?;
+
+        for name in INDEX_NAMES {
+            tx.execute_batch(&format!("DROP INDEX IF EXISTS {name}"))
+                .with_context(|| format!("failed to drop index {name}"))?;
+        }
+        tx.execute_batch(INDEX_DEFINITIONS)
+            .context("failed to recreate translation memory indexes")?;
+        tx.execute_batch("REINDEX translation_cache")
+            .context("failed to reindex translation cache")?;
+
+        tx.commit()
+            .context("failed to commit reindex transaction")?;
+        Ok(())
+    }
+
+    /// Reclaims free pages left behind by pruning and clearing.
+    pub fn compact(&self) -> Result<()> {
+        let conn = self.conn.lock().expect("translation memory lock poisoned");
+        conn.execute_batch("VACUUM")
+            .context("failed to compact translation memory")?;
+        Ok(())
+    }
+
+    /// Creates the FTS index and its triggers, backfilling it when the
+    /// table is new. Returns false when FTS5 is unavailable.
+    fn init_full_text_search(&self) -> bool {
+        let mut conn = self.conn.lock().expect("translation memory lock poisoned");
+        let result = (|| -> rusqlite::Result<()> {
+            let tx = conn.transaction()?;
+            let existed: bool = tx.query_row(
+                "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE name = 'translation_cache_fts')",
+                [],
+                |row| row.get(0),
+            )?;
+            tx.execute_batch(FTS_DEFINITIONS)?;
+            if !existed {
+                tx.execute(
+                    "INSERT INTO translation_cache_fts(translation_cache_fts) VALUES ('rebuild')",
+                    [],
+                )?;
+            }
+            tx.commit()
+        })();
+
+        match result {
+            Ok(()) => true,
+            Err(error) => {
+                warn!("full-text search unavailable, falling back to LIKE: {error}");
+                false
+            }
+        }
+    }
+
     fn init_schema(&self) -> Result<()> {
         let mut conn = self.conn.lock().expect("translation memory lock poisoned");
         let tx = conn
@@ -260,8 +794,6 @@ impl TranslationMemory {
                 created_at TEXT NOT NULL,
                 last_accessed TEXT NOT NULL
             );
-            CREATE INDEX IF NOT EXISTS idx_cache_key ON translation_cache(cache_key);
-            CREATE INDEX IF NOT EXISTS idx_last_accessed ON translation_cache(last_accessed);
             CREATE TABLE IF NOT EXISTS memory_metrics (
                 id INTEGER PRIMARY KEY CHECK(id = 1),
                 hits INTEGER NOT NULL DEFAULT 0,
@@ -272,6 +804,8 @@ impl TranslationMemory {
             );",
         )
         .context("failed to initialize translation memory schema")?;
+        tx.execute_batch(INDEX_DEFINITIONS)
+            .context("failed to initialize translation memory indexes")?;
 
         tx.execute(
             "INSERT OR IGNORE INTO memory_metrics (id, hits, misses, total_lookups, total_lookup_time_ms, last_persisted)
@@ -280,7 +814,7 @@ impl TranslationMemory {
         )
         .context("failed to initialize memory metrics row")?;
 
-        prune_oldest(&tx, self.max_entries)?;
+        prune_overflow(&tx, self.max_entries, self.eviction_policy)?;
 
         tx.commit()
             .context("failed to commit init_schema transaction")?;
@@ -288,6 +822,36 @@ impl TranslationMemory {
     }
 }
 
+fn memory_entry(row: &rusqlite::Row<'_>, score: Option<f64>) -> rusqlite::Result<MemoryEntry> {
+    let last_accessed_raw: String = row.get(6)?;
+    let last_accessed = DateTime::parse_from_rfc3339(&last_accessed_raw)
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now());
+
+    Ok(MemoryEntry {
+        cache_key: row.get(7)?,
+        source_text: row.get(0)?,
+        translated_text: row.get(1)?,
+        source_language: row.get(2)?,
+        target_language: row.get(3)?,
+        provider_id: row.get(4)?,
+        access_count: row.get(5)?,
+        last_accessed,
+        score,
+    })
+}
+
+/// ORs together each query word long enough for the trigram index, quoted
+/// so FTS5 operators in user input are matched literally.
+fn fts_query(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .filter(|term| term.chars().count() >= MIN_FTS_TERM_CHARS)
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" OR "))
+}
+
 fn bump_metrics(conn: &Connection, hit: bool, lookup_ms: f64) -> Result<()> {
     if hit {
         conn.execute(
@@ -313,7 +877,24 @@ fn bump_metrics(conn: &Connection, hit: bool, lookup_ms: f64) -> Result<()> {
     Ok(())
 }
 
-fn prune_oldest(conn: &Connection, max_entries: usize) -> Result<()> {
+fn clear_all(conn: &Connection) -> Result<()> {
+    conn.execute("DELETE FROM translation_cache", [])
+        .context("failed to clear translation cache")?;
+    conn.execute(
+        "UPDATE memory_metrics
+         SET hits = 0,
+             misses = 0,
+             total_lookups = 0,
+             total_lookup_time_ms = 0.0,
+             last_persisted = ?1
+         WHERE id = 1",
+        params![Utc::now().to_rfc3339()],
+    )
+    .context("failed to clear memory metrics")?;
+    Ok(())
+}
+
+fn prune_overflow(conn: &Connection, max_entries: usize, policy: EvictionPolicy) -> Result<()> {
     let current_size: usize = conn
         .query_row("SELECT COUNT(*) FROM translation_cache", [], |row| {
             let value: i64 = row.get(0)?;
@@ -327,12 +908,15 @@ fn prune_oldest(conn: &Connection, max_entries: usize) -> Result<()> {
 
     let overflow = current_size - max_entries;
     conn.execute(
-        "DELETE FROM translation_cache
-         WHERE id IN (
-            SELECT id FROM translation_cache
-            ORDER BY last_accessed ASC
-            LIMIT ?1
-         )",
+        &format!(
+            "DELETE FROM translation_cache
+             WHERE id IN (
+                SELECT id FROM translation_cache
+                ORDER BY {}
+                LIMIT ?1
+             )",
+            policy.order_by()
+        ),
         params![overflow as i64],
     )
     .context("failed to prune translation memory")?;
@@ -341,12 +925,17 @@ fn prune_oldest(conn: &Connection, max_entries: usize) -> Result<()> {
 }
 
 fn cache_key(
+    namespace: &str,
     source_text: &str,
     source_language: &str,
     target_language: &str,
     provider_id: &str,
 ) -> String {
     let mut key = String::new();
+    if !namespace.is_empty() {
+        key.push('@');
+        append_cache_key_part(&mut key, namespace);
+    }
     append_cache_key_part(&mut key, provider_id);
     append_cache_key_part(&mut key, source_language);
     append_cache_key_part(&mut key, target_language);
@@ -386,10 +975,435 @@ mod tests {
         assert_eq!(stats.total_hits, 1);
     }
 
+    #[test]
+    fn deletes_and_edits_single_entries_by_cache_key() {
+        let temp_dir = TempDir::new().unwrap();
+        let memory = TranslationMemory::new(&temp_dir.path().join("memory.db"), 100).unwrap();
+        memory
+            .store("hello", "こんにちは", "en", "ja", "google_unofficial")
+            .unwrap();
+        memory
+            .store("goodbye", "さようなら", "en", "ja", "google_unofficial")
+            .unwrap();
+
+        let hello = memory.search("hello", 10).unwrap().remove(0);
+        assert!(memory.delete(&hello.cache_key).unwrap());
+        assert!(!memory.delete(&hello.cache_key).unwrap());
+
+        assert_eq!(
+            memory
+                .lookup("hello", "en", "ja", "google_unofficial")
+                .unwrap(),
+            None
+        );
+        let goodbye = memory.search("goodbye", 10).unwrap().remove(0);
+        assert_eq!(goodbye.translated_text, "さようなら");
+
+        assert!(
+            memory
+                .update_translation(&goodbye.cache_key, "じゃあね")
+                .unwrap()
+        );
+        assert_eq!(
+            memory
+                .lookup("goodbye", "en", "ja", "google_unofficial")
+                .unwrap()
+                .as_deref(),
+            Some("じゃあね")
+        );
+        assert_eq!(memory.stats().unwrap().total_entries, 1);
+    }
+
+    #[test]
+    fn groups_stats_by_provider_and_language_pair() {
+        let temp_dir = TempDir::new().unwrap();
+        let memory = TranslationMemory::new(&temp_dir.path().join("memory.db"), 100).unwrap();
+        memory
+            .store("hello", "こんにちは", "en", "ja", "google_unofficial")
+            .unwrap();
+        memory
+            .store("bye", "さようなら", "en", "ja", "google_unofficial")
+            .unwrap();
+        memory
+            .store("hello", "bonjour", "en", "fr", "deepl")
+            .unwrap();
+        memory
+            .lookup("hello", "en", "ja", "google_unofficial")
+            .unwrap();
+
+        let by_provider = memory.stats_by_provider().unwrap();
+        assert_eq!(
+            by_provider["google_unofficial"],
+            GroupedMemoryStats {
+                entries: 2,
+                total_accesses: 3,
+            }
+        );
+        assert_eq!(by_provider["google_unofficial"].reuses(), 1);
+        assert_eq!(by_provider["deepl"].entries, 1);
+
+        let by_pair = memory.stats_by_language_pair().unwrap();
+        assert_eq!(by_pair.keys().collect::<Vec<_>>(), ["en->fr", "en->ja"]);
+        assert_eq!(by_pair["en->ja"].entries, 2);
+    }
+
+    #[test]
+    fn exports_round_trip_through_json_and_csv() {
+        let temp_dir = TempDir::new().unwrap();
+        let memory = TranslationMemory::new(&temp_dir.path().join("memory.db"), 100).unwrap();
+        memory
+            .store("hello", "こんにちは", "en", "ja", "google_unofficial")
+            .unwrap();
+        memory
+            .store("a, \"quoted\"\nline", "引用", "en", "ja", "deepl")
+            .unwrap();
+
+        let from_json: Vec<MemoryRecord> =
+            serde_json::from_str(&memory.export_all(ExportFormat::Json).unwrap()).unwrap();
+        assert_eq!(from_json.len(), 2);
+        assert_eq!(from_json[0].translated_text, "こんにちは");
+        assert_eq!(from_json[1].source_text, "a, \"quoted\"\nline");
+        assert_eq!(from_json[1].provider_id, "deepl");
+        assert_eq!(from_json[1].access_count, 1);
+
+        let csv = memory.export_all(ExportFormat::Csv).unwrap();
+        let from_csv: Vec<MemoryRecord> = csv::Reader::from_reader(csv.as_bytes())
+            .deserialize()
+            .collect::<std::result::Result<_, _>>()
+            .unwrap();
+        assert_eq!(from_csv, from_json);
+
+        assert!(memory.export_all(ExportFormat::Pdf).is_err());
+    }
+
+    #[test]
+    fn imports_an_export_into_an_empty_memory() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = TranslationMemory::new(&temp_dir.path().join("source.db"), 100).unwrap();
+        source
+            .store("hello", "こんにちは", "en", "ja", "google_unofficial")
+            .unwrap();
+        source
+            .store("goodbye", "さようなら", "en", "ja", "google_unofficial")
+            .unwrap();
+        let dump = temp_dir.path().join("memory.json");
+        std::fs::write(&dump, source.export_all(ExportFormat::Json).unwrap()).unwrap();
+
+        let target = TranslationMemory::new(&temp_dir.path().join("target.db"), 100).unwrap();
+        assert_eq!(target.import(&dump, true).unwrap(), 2);
+        assert_eq!(target.stats().unwrap().total_entries, 2);
+        assert_eq!(
+            target
+                .lookup("hello", "en", "ja", "google_unofficial")
+                .unwrap()
+                .as_deref(),
+            Some("こんにちは")
+        );
+
+        target.import(&dump, true).unwrap();
+        let merged = target.search("goodbye", 1).unwrap();
+        assert_eq!(merged[0].access_count, 2);
+
+        target.import(&dump, false).unwrap();
+        assert_eq!(target.stats().unwrap().total_entries, 2);
+        assert_eq!(target.search("goodbye", 1).unwrap()[0].access_count, 1);
+    }
+
+    #[test]
+    fn failed_replace_import_keeps_the_existing_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("memory.db");
+        let memory = TranslationMemory::new(&db_path, 100).unwrap();
+        memory
+            .store("hello", "こんにちは", "en", "ja", "google_unofficial")
+            .unwrap();
+        let dump = temp_dir.path().join("memory.json");
+        std::fs::write(&dump, memory.export_all(ExportFormat::Json).unwrap()).unwrap();
+
+        Connection::open(&db_path)
+            .unwrap()
+            .execute_batch(
+                "CREATE TRIGGER reject_import BEFORE INSERT ON translation_cache
+                 BEGIN SELECT RAISE(ABORT, 'rejected'); END;",
+            )
+            .unwrap();
+
+        assert!(memory.import(&dump, false).is_err());
+        assert_eq!(memory.stats().unwrap().total_entries, 1);
+        assert_eq!(
+            memory
+                .lookup("hello", "en", "ja", "google_unofficial")
+                .unwrap()
+                .as_deref(),
+            Some("こんにちは")
+        );
+    }
+
+    #[test]
+    fn full_text_search_ranks_multi_word_matches_first() {
+        let temp_dir = TempDir::new().unwrap();
+        let memory = TranslationMemory::new(&temp_dir.path().join("memory.db"), 100).unwrap();
+        assert!(memory.has_full_text_search());
+        for (source, translated) in [
+            ("brown bread for breakfast", "朝食の黒パン"),
+            ("the quick brown fox jumps", "素早い茶色の狐が跳ぶ"),
+            ("a quick lunch", "簡単な昼食"),
+            ("nothing relevant", "無関係"),
+        ] {
+            memory
+                .store(source, translated, "en", "ja", "google_unofficial")
+                .unwrap();
+        }
+
+        let results = memory.search("quick brown fox", 10).unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].source_text, "the quick brown fox jumps");
+        let scores: Vec<f64> = results.iter().map(|entry| entry.score.unwrap()).collect();
+        assert!(scores.windows(2).all(|pair| pair[0] >= pair[1]));
+
+        let japanese = memory.search("茶色の狐", 10).unwrap();
+        assert_eq!(japanese.len(), 1);
+
+        memory.clear().unwrap();
+        assert!(memory.search("quick brown fox", 10).unwrap().is_empty());
+
+        let short = memory.search("a", 10).unwrap();
+        assert!(short.is_empty());
+    }
+
+    fn surviving_sources(policy: EvictionPolicy) -> Vec<String> {
+        let temp_dir = TempDir::new().unwrap();
+        let memory = TranslationMemory::new(&temp_dir.path().join("memory.db"), 2)
+            .unwrap()
+            .with_eviction_policy(policy);
+        memory
+            .store("popular", "人気", "en", "ja", "deepl")
+            .unwrap();
+        for _ in 0..3 {
+            memory.lookup("popular", "en", "ja", "deepl").unwrap();
+        }
+        memory.store("recent", "最近", "en", "ja", "deepl").unwrap();
+        memory.store("newest", "最新", "en", "ja", "deepl").unwrap();
+
+        let mut sources: Vec<String> = memory
+            .search_like("", 10)
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.source_text)
+            .collect();
+        sources.sort();
+        sources
+    }
+
+    #[test]
+    fn lru_evicts_the_least_recently_used_entry() {
+        assert_eq!(surviving_sources(EvictionPolicy::Lru), ["newest", "recent"]);
+    }
+
+    #[test]
+    fn lfu_evicts_the_least_frequently_used_entry() {
+        assert_eq!(
+            surviving_sources(EvictionPolicy::Lfu),
+            ["newest", "popular"]
+        );
+        assert_eq!(EvictionPolicy::normalize("LFU"), EvictionPolicy::Lfu);
+        assert_eq!(EvictionPolicy::normalize("other"), EvictionPolicy::Lru);
+    }
+
+    #[test]
+    fn expired_entries_miss_and_are_purged() {
+        let temp_dir = TempDir::new().unwrap();
+        let memory = TranslationMemory::new(&temp_dir.path().join("memory.db"), 100)
+            .unwrap()
+            .with_entry_ttl_days(30);
+        memory.store("old", "古い", "en", "ja", "deepl").unwrap();
+        memory
+            .store("fresh", "新しい", "en", "ja", "deepl")
+            .unwrap();
+        let backdated = (Utc::now() - chrono::Duration::days(45)).to_rfc3339();
+        memory
+            .conn
+            .lock()
+            .unwrap()
+            .execute(
+                "UPDATE translation_cache SET created_at = ?1 WHERE source_text = 'old'",
+                params![backdated],
+            )
+            .unwrap();
+
+        assert_eq!(memory.lookup("old", "en", "ja", "deepl").unwrap(), None);
+        assert_eq!(
+            memory
+                .lookup("fresh", "en", "ja", "deepl")
+                .unwrap()
+                .as_deref(),
+            Some("新しい")
+        );
+
+        assert_eq!(memory.purge_expired().unwrap(), 1);
+        assert_eq!(memory.stats().unwrap().total_entries, 1);
+        assert_eq!(memory.with_entry_ttl_days(0).purge_expired().unwrap(), 0);
+    }
+
+    #[test]
+    fn disabled_metrics_skip_metric_writes() {
+        let temp_dir = TempDir::new().unwrap();
+        let memory = TranslationMemory::new(&temp_dir.path().join("memory.db"), 100)
+            .unwrap()
+            .with_metrics_tracking(false);
+
+        memory
+            .store("hello", "こんにちは", "en", "ja", "google_unofficial")
+            .unwrap();
+        memory
+            .lookup("hello", "en", "ja", "google_unofficial")
+            .unwrap();
+        memory
+            .lookup("missing", "en", "ja", "google_unofficial")
+            .unwrap();
+
+        let total_lookups: i64 = memory
+            .conn
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT total_lookups FROM memory_metrics WHERE id = 1",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(total_lookups, 0);
+
+        let stats = memory.stats().unwrap();
+        assert_eq!(stats.total_entries, 1);
+        assert_eq!(stats.total_hits, 0);
+        assert_eq!(stats.total_misses, 0);
+    }
+
+    #[test]
+    fn reindex_and_compact_succeed_on_populated_db() {
+        let temp_dir = TempDir::new().unwrap();
+        let memory = TranslationMemory::new(&temp_dir.path().join("memory.db"), 100).unwrap();
+
+        for index in 0..20 {
+            memory
+                .store(
+                    &format!("hello {index}"),
+                    &format!("こんにちは {index}"),
+                    "en",
+                    "ja",
+                    "google_unofficial",
+                )
+                .unwrap();
+        }
+
+        memory.reindex().unwrap();
+        memory.compact().unwrap();
+
+        let hit = memory
+            .lookup("hello 7", "en", "ja", "google_unofficial")
+            .unwrap();
+        assert_eq!(hit.as_deref(), Some("こんにちは 7"));
+        assert_eq!(memory.stats().unwrap().total_entries, 20);
+    }
+
     #[test]
     fn cache_key_is_collision_resistant_for_delimited_values() {
-        let left = cache_key("hello:world", "en", "ja", "google");
-        let right = cache_key("world", "en", "ja:hello", "google");
+        let left = cache_key("", "hello:world", "en", "ja", "google");
+        let right = cache_key("", "world", "en", "ja:hello", "google");
         assert_ne!(left, right);
     }
+
+    #[test]
+    fn normalized_keys_match_whitespace_and_case_variants() {
+        let temp_dir = TempDir::new().unwrap();
+        let memory = TranslationMemory::new(&temp_dir.path().join("memory.db"), 100)
+            .unwrap()
+            .with_cache_normalization(CacheNormalization::Whitespace);
+        memory
+            .store(
+                " Hello \n world ",
+                "こんにちは世界",
+                "en",
+                "ja",
+                "google_unofficial",
+            )
+            .unwrap();
+
+        let hit = memory
+            .lookup("Hello world", "en", "ja", "google_unofficial")
+            .unwrap();
+        assert_eq!(hit.as_deref(), Some("こんにちは世界"));
+        let miss = memory
+            .lookup("hello world", "en", "ja", "google_unofficial")
+            .unwrap();
+        assert_eq!(miss, None);
+
+        let folded = memory
+            .clone()
+            .with_cache_normalization(CacheNormalization::WhitespaceAndCase);
+        folded
+            .store(
+                "HELLO  World",
+                "こんにちは世界",
+                "en",
+                "ja",
+                "google_unofficial",
+            )
+            .unwrap();
+        folded
+            .store(
+                "hello world",
+                "こんにちは世界",
+                "en",
+                "ja",
+                "google_unofficial",
+            )
+            .unwrap();
+        let entries = memory.search("", 10).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(
+            entries
+                .iter()
+                .any(|entry| entry.source_text == "HELLO  World")
+        );
+    }
+
+    #[test]
+    fn changing_namespace_misses_previously_cached_text() {
+        let temp_dir = TempDir::new().unwrap();
+        let memory = TranslationMemory::new(&temp_dir.path().join("memory.db"), 100).unwrap();
+        memory
+            .store("hello", "こんにちは", "en", "ja", "google_unofficial")
+            .unwrap();
+
+        let bumped = memory.clone().with_cache_namespace("v2");
+        let miss = bumped
+            .lookup("hello", "en", "ja", "google_unofficial")
+            .unwrap();
+        assert_eq!(miss, None);
+        assert_eq!(bumped.stats().unwrap().total_entries, 1);
+
+        let hit = memory
+            .lookup("hello", "en", "ja", "google_unofficial")
+            .unwrap();
+        assert_eq!(hit.as_deref(), Some("こんにちは"));
+    }
+
+    #[test]
+    fn opens_with_wal_journal_and_normal_sync() {
+        let temp = TempDir::new().unwrap();
+        let memory = TranslationMemory::new(&temp.path().join("memory.db"), 10).unwrap();
+        let conn = memory.conn.lock().unwrap();
+
+        let journal_mode: String = conn
+            .query_row("PRAGMA journal_mode", [], |row| row.get(0))
+            .unwrap();
+        let synchronous: i64 = conn
+            .query_row("PRAGMA synchronous", [], |row| row.get(0))
+            .unwrap();
+
+        assert_eq!(journal_mode, "wal");
+        assert_eq!(synchronous, 1);
+    }
 }
@@ -1,5 +1,5 @@
 use std::fmt::{Display, Formatter};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::time::Duration;
 
@@ -7,23 +7,47 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::build_info;
+use crate::quality::fidelity_score;
+
 pub const GOOGLE_UNOFFICIAL_PROVIDER: &str = "google_unofficial";
+pub const GOOGLE_OFFICIAL_PROVIDER: &str = "google_official";
+pub const DEEPL_PROVIDER: &str = "deepl";
+pub const LIBRETRANSLATE_PROVIDER: &str = "libretranslate";
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ProviderId {
     GoogleUnofficial,
+    GoogleOfficial,
+    DeepL,
+    LibreTranslate,
 }
 
 impl ProviderId {
+    pub fn all() -> [Self; 4] {
+        [
+            Self::GoogleUnofficial,
+            Self::GoogleOfficial,
+            Self::DeepL,
+            Self::LibreTranslate,
+        ]
+    }
+
     pub fn as_str(self) -> &'static str {
         match self {
             Self::GoogleUnofficial => GOOGLE_UNOFFICIAL_PROVIDER,
+            Self::GoogleOfficial => GOOGLE_OFFICIAL_PROVIDER,
+            Self::DeepL => DEEPL_PROVIDER,
+            Self::LibreTranslate => LIBRETRANSLATE_PROVIDER,
         }
     }
 
     pub fn display_name(self) -> &'static str {
         match self {
             Self::GoogleUnofficial => "Google Translate (Unofficial / Free)",
+            Self::GoogleOfficial => "Google Cloud Translation (API key)",
+            Self::DeepL => "DeepL API (Free, API key)",
+            Self::LibreTranslate => "LibreTranslate (self-hosted)",
         }
     }
 
@@ -32,6 +56,9 @@ impl ProviderId {
             "google_unofficial" | "unofficial" | "google_free" | "googletranslate" => {
                 Self::GoogleUnofficial
             }
+            "google_official" | "official" | "google_cloud" => Self::GoogleOfficial,
+            "deepl" | "deepl_free" => Self::DeepL,
+            "libretranslate" | "libre_translate" | "libre" => Self::LibreTranslate,
             _ => Self::GoogleUnofficial,
         }
     }
@@ -49,10 +76,14 @@ pub enum ExportFormat {
     Markdown,
     Html,
     Json,
+    /// One compact JSON object per line, for streaming into other tools.
+    Jsonl,
     Csv,
     Xml,
     Pdf,
     Docx,
+    Tmx,
+    Xlsx,
 }
 
 impl ExportFormat {
@@ -62,10 +93,13 @@ impl ExportFormat {
             Self::Markdown => "md",
             Self::Html => "html",
             Self::Json => "json",
+            Self::Jsonl => "jsonl",
             Self::Csv => "csv",
             Self::Xml => "xml",
             Self::Pdf => "pdf",
             Self::Docx => "docx",
+            Self::Tmx => "tmx",
+            Self::Xlsx => "xlsx",
         }
     }
 
@@ -75,10 +109,13 @@ impl ExportFormat {
             Self::Markdown => "Markdown (.md)",
             Self::Html => "HTML (.html)",
             Self::Json => "JSON (.json)",
+            Self::Jsonl => "JSON Lines (.jsonl)",
             Self::Csv => "CSV (.csv)",
             Self::Xml => "XML (.xml)",
             Self::Pdf => "PDF (.pdf)",
             Self::Docx => "DOCX (.docx)",
+            Self::Tmx => "TMX (.tmx)",
+            Self::Xlsx => "Excel (.xlsx)",
         }
     }
 
@@ -87,16 +124,19 @@ impl ExportFormat {
         Self::from_str(ext.as_str()).ok()
     }
 
-    pub fn all() -> [Self; 8] {
+    pub fn all() -> [Self; 11] {
         [
             Self::Txt,
             Self::Markdown,
             Self::Html,
             Self::Json,
+            Self::Jsonl,
             Self::Csv,
             Self::Xml,
             Self::Pdf,
             Self::Docx,
+            Self::Tmx,
+            Self::Xlsx,
         ]
     }
 }
@@ -116,10 +156,13 @@ impl FromStr for ExportFormat {
             "md" | "markdown" => Ok(Self::Markdown),
             "html" | "htm" => Ok(Self::Html),
             "json" => Ok(Self::Json),
+            "jsonl" | "ndjson" => Ok(Self::Jsonl),
             "csv" => Ok(Self::Csv),
             "xml" => Ok(Self::Xml),
             "pdf" => Ok(Self::Pdf),
             "docx" | "doc" => Ok(Self::Docx),
+            "tmx" => Ok(Self::Tmx),
+            "xlsx" => Ok(Self::Xlsx),
             _ => Err(format!("unsupported format: {s}")),
         }
     }
@@ -136,6 +179,20 @@ pub struct BackTranslationResult {
     pub provider_id: String,
     pub created_at: DateTime<Utc>,
     pub duration_ms: u128,
+    /// Time spent on the forward (source to intermediate) and backward hops;
+    /// both 0 for results recorded before hops were timed.
+    #[serde(default)]
+    pub forward_ms: u128,
+    #[serde(default)]
+    pub backward_ms: u128,
+    /// Similarity of the back-translation to the original, in `0.0..=1.0`.
+    #[serde(default)]
+    pub fidelity_score: f64,
+    /// Set when the round trip looks like a silent provider failure.
+    #[serde(default)]
+    pub suspicious: bool,
+    #[serde(default)]
+    pub suspicious_reason: Option<String>,
 }
 
 impl BackTranslationResult {
@@ -148,6 +205,7 @@ impl BackTranslationResult {
         provider_id: ProviderId,
         duration: Duration,
     ) -> Self {
+        let fidelity_score = fidelity_score(&original_text, &back_translated_text);
         Self {
             id: Uuid::new_v4(),
             original_text,
@@ -158,20 +216,113 @@ impl BackTranslationResult {
             provider_id: provider_id.as_str().to_owned(),
             created_at: Utc::now(),
             duration_ms: duration.as_millis(),
+            forward_ms: 0,
+            backward_ms: 0,
+            fidelity_score,
+            suspicious: false,
+            suspicious_reason: None,
         }
     }
+
+    pub fn with_hop_durations(mut self, forward: Duration, backward: Duration) -> Self {
+        self.forward_ms = forward.as_millis();
+        self.backward_ms = backward.as_millis();
+        self
+    }
+
+    /// `forward 0.40s, back 0.35s`, or `None` when the hops were not timed.
+    pub fn hop_breakdown(&self) -> Option<String> {
+        hop_breakdown(
+            self.forward_ms as f64 / 1000.0,
+            self.backward_ms as f64 / 1000.0,
+        )
+    }
+
+    pub fn flag_suspicious(&mut self, reason: String) {
+        self.suspicious = true;
+        self.suspicious_reason = Some(reason);
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BatchItemResult {
     pub file_path: String,
     pub success: bool,
+    #[serde(default)]
+    pub original_text: String,
     pub intermediate_text: String,
     pub back_translated_text: String,
     pub error: Option<String>,
+    #[serde(default)]
+    pub error_kind: Option<BatchErrorKind>,
+    /// Source language detected for this file when the batch ran without
+    /// one; `None` when the source was given.
+    #[serde(default)]
+    pub detected_source: Option<String>,
+    #[serde(default)]
+    pub suspicious_reason: Option<String>,
     pub duration_ms: u128,
 }
 
+/// Why a batch item failed, coarse enough to choose which failures to retry.
+/// Timeouts and unexpected provider responses count as `Network`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchErrorKind {
+    Cancelled,
+    RateLimited,
+    Network,
+    InvalidInput,
+    Blocked,
+    /// Reading the input file or writing its per-file output failed.
+    Io,
+}
+
+impl BatchErrorKind {
+    pub fn all() -> [Self; 6] {
+        [
+            Self::Cancelled,
+            Self::RateLimited,
+            Self::Network,
+            Self::InvalidInput,
+            Self::Blocked,
+            Self::Io,
+        ]
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Cancelled => "cancelled",
+            Self::RateLimited => "rate_limited",
+            Self::Network => "network",
+            Self::InvalidInput => "invalid_input",
+            Self::Blocked => "blocked",
+            Self::Io => "io",
+        }
+    }
+}
+
+impl Display for BatchErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for BatchErrorKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalized = s.trim().to_ascii_lowercase().replace('-', "_");
+        Self::all()
+            .into_iter()
+            .find(|kind| kind.as_str() == normalized)
+            .ok_or_else(|| {
+                let kinds: Vec<_> = Self::all().iter().map(|kind| kind.as_str()).collect();
+                format!("unknown error kind: {s} (expected {})", kinds.join(", "))
+            })
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExportMetadata {
     pub title: String,
@@ -183,10 +334,24 @@ pub struct ExportMetadata {
     pub target_language: String,
     pub processing_time_seconds: f64,
     pub api_used: String,
+    /// Round-trip fidelity of a single result; batch exports leave it unset.
+    #[serde(default)]
+    pub fidelity_score: Option<f64>,
+    /// Forward and backward hop times of a single result; unset for batches
+    /// and for results recorded before hops were timed.
+    #[serde(default)]
+    pub forward_seconds: Option<f64>,
+    #[serde(default)]
+    pub backward_seconds: Option<f64>,
+    #[serde(default)]
+    pub tool_version: String,
+    #[serde(default)]
+    pub build_info: String,
 }
 
 impl ExportMetadata {
     pub fn from_result(result: &BackTranslationResult) -> Self {
+        let timed = result.forward_ms + result.backward_ms > 0;
         Self {
             title: "Translation Results".to_string(),
             author: "TranslationFiesta Rust".to_string(),
@@ -197,12 +362,28 @@ impl ExportMetadata {
             target_language: result.intermediate_language.clone(),
             processing_time_seconds: result.duration_ms as f64 / 1000.0,
             api_used: result.provider_id.clone(),
+            fidelity_score: Some(result.fidelity_score),
+            forward_seconds: timed.then(|| result.forward_ms as f64 / 1000.0),
+            backward_seconds: timed.then(|| result.backward_ms as f64 / 1000.0),
+            tool_version: build_info::TOOL_VERSION.to_owned(),
+            build_info: build_info::build_details(),
         }
     }
+
+    pub fn hop_breakdown(&self) -> Option<String> {
+        hop_breakdown(self.forward_seconds?, self.backward_seconds?)
+    }
 }
 
-#[derive(Debug, Clone)]
+fn hop_breakdown(forward_seconds: f64, backward_seconds: f64) -> Option<String> {
+    (forward_seconds + backward_seconds > 0.0)
+        .then(|| format!("forward {forward_seconds:.2}s, back {backward_seconds:.2}s"))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MemoryEntry {
+    /// Identifies the row for `TranslationMemory::delete` and `update_translation`.
+    pub cache_key: String,
     pub source_text: String,
     pub translated_text: String,
     pub source_language: String,
@@ -210,9 +391,25 @@ pub struct MemoryEntry {
     pub provider_id: String,
     pub access_count: i64,
     pub last_accessed: DateTime<Utc>,
+    /// Full-text relevance (higher is better); `None` when the search fell
+    /// back to substring matching.
+    pub score: Option<f64>,
+}
+
+/// One translation memory row as written by `TranslationMemory::export_to`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MemoryRecord {
+    pub source_text: String,
+    pub translated_text: String,
+    pub source_language: String,
+    pub target_language: String,
+    pub provider_id: String,
+    pub access_count: i64,
+    pub created_at: String,
+    pub last_accessed: String,
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct MemoryStats {
     pub total_entries: usize,
     pub max_entries: usize,
@@ -223,6 +420,22 @@ pub struct MemoryStats {
     pub avg_lookup_ms: f64,
 }
 
+/// Entry and access totals for one provider or language pair. Misses are
+/// only tracked globally, so groups report reuse rather than a hit rate.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GroupedMemoryStats {
+    pub entries: usize,
+    /// Stores plus lookup hits across the group's entries.
+    pub total_accesses: u64,
+}
+
+impl GroupedMemoryStats {
+    /// Accesses beyond each entry's first store, i.e. translations served from memory.
+    pub fn reuses(&self) -> u64 {
+        self.total_accesses.saturating_sub(self.entries as u64)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct EpubChapter {
     pub title: String,
@@ -235,7 +448,17 @@ pub struct EpubChapter {
 pub struct EpubBook {
     pub title: String,
     pub author: Option<String>,
+    /// `dc:language` as written in the OPF, e.g. `en` or `fr-CA`.
+    pub language: Option<String>,
+    pub publisher: Option<String>,
+    /// The ISBN when the OPF marks one, otherwise its first `dc:identifier`.
+    pub identifier: Option<String>,
+    pub date: Option<String>,
     pub chapters: Vec<EpubChapter>,
+    /// Archive the book was read from; rebuilding copies its other entries.
+    pub source_path: PathBuf,
+    /// Archive path of the OPF package, when one was found.
+    pub opf_path: Option<String>,
 }
 
 #[cfg(test)]
@@ -256,6 +479,15 @@ mod tests {
             ProviderId::normalize("unknown"),
             ProviderId::GoogleUnofficial
         );
+        assert_eq!(ProviderId::normalize("DeepL"), ProviderId::DeepL);
+        assert_eq!(
+            ProviderId::normalize("google_cloud"),
+            ProviderId::GoogleOfficial
+        );
+        assert_eq!(
+            ProviderId::normalize("libretranslate"),
+            ProviderId::LibreTranslate
+        );
     }
 
     #[test]
@@ -0,0 +1,556 @@
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+
+use reqwest::StatusCode;
+use reqwest::blocking::{Client, Response};
+use serde_json::{Value, json};
+
+use crate::html::unescape_html;
+use crate::language::uses_word_spacing;
+use crate::translation::TranslationError;
+
+pub const GOOGLE_UNOFFICIAL_ENDPOINT: &str = "https://translate.googleapis.com/translate_a/single";
+pub const GOOGLE_OFFICIAL_ENDPOINT: &str =
+    "https://translation.googleapis.com/language/translate/v2";
+pub const DEEPL_FREE_ENDPOINT: &str = "https://api-free.deepl.com/v2/translate";
+/// DeepL's "quota exceeded" status.
+const DEEPL_QUOTA_EXCEEDED: u16 = 456;
+
+/// One translation backend. Implementations make a single attempt per call;
+/// `TranslationService` owns caching, throttling, and retries, so a provider
+/// only has to map its failures onto `RateLimited`, `Blocked`, or `Network`
+/// for them to be retried.
+pub trait TranslationProvider: Debug + Send + Sync {
+    fn translate(
+        &self,
+        text: &str,
+        source_language: &str,
+        target_language: &str,
+        cancel_flag: Option<&AtomicBool>,
+    ) -> Result<String, TranslationError>;
+
+    /// The same backend sending its requests through `client`, used when the
+    /// service rebuilds its HTTP client. Providers without a client keep the
+    /// default and are reused as they are.
+    fn with_client(&self, _client: Client) -> Option<Arc<dyn TranslationProvider>> {
+        None
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GoogleUnofficialProvider {
+    client: Client,
+    endpoint: String,
+    user_agent: Option<String>,
+}
+
+impl GoogleUnofficialProvider {
+    pub fn new(client: Client, endpoint: impl Into<String>) -> Self {
+        let user_agent = std::env::var("TF_UNOFFICIAL_USER_AGENT")
+            .ok()
+            .map(|agent| agent.trim().to_owned())
+            .filter(|agent| !agent.is_empty());
+
+        Self {
+            client,
+            endpoint: endpoint.into(),
+            user_agent,
+        }
+    }
+}
+
+impl TranslationProvider for GoogleUnofficialProvider {
+    fn with_client(&self, client: Client) -> Option<Arc<dyn TranslationProvider>> {
+        Some(Arc::new(Self {
+            client,
+            ..self.clone()
+        }))
+    }
+
+    fn translate(
+        &self,
+        text: &str,
+        source_language: &str,
+        target_language: &str,
+        _cancel_flag: Option<&AtomicBool>,
+    ) -> Result<String, TranslationError> {
+        let encoded = urlencoding::encode(text);
+        let url = format!(
+            "{}?client=gtx&sl={source_language}&tl={target_language}&dt=t&q={encoded}",
+            self.endpoint
+        );
+        let mut request = self
+            .client
+            .get(url)
+            .header("Accept", "application/json,text/plain,*/*");
+
+        if let Some(agent) = &self.user_agent {
+            request = request.header("User-Agent", agent);
+        }
+
+        let (status, body) = read_response(request.send())?;
+
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            return Err(TranslationError::RateLimited);
+        }
+
+        if status == StatusCode::FORBIDDEN {
+            return Err(TranslationError::Blocked);
+        }
+
+        if !status.is_success() {
+            return Err(TranslationError::InvalidResponse(format!(
+                "HTTP {}",
+                status.as_u16()
+            )));
+        }
+
+        if body.trim().is_empty() {
+            return Err(TranslationError::InvalidResponse(
+                "empty response body".to_owned(),
+            ));
+        }
+
+        let lower = body.to_ascii_lowercase();
+        if lower.contains("<html") || lower.contains("captcha") {
+            return Err(TranslationError::Blocked);
+        }
+
+        parse_unofficial_google_response(&body, target_language)
+    }
+}
+
+/// Google Cloud Translation v2, authenticated with an API key.
+#[derive(Debug, Clone)]
+pub struct GoogleOfficialProvider {
+    client: Client,
+    endpoint: String,
+    api_key: Option<String>,
+}
+
+impl GoogleOfficialProvider {
+    /// A blank key leaves the provider unconfigured; requests then fail with `InvalidInput`.
+    pub fn new(client: Client, endpoint: impl Into<String>, api_key: Option<String>) -> Self {
+        Self {
+            client,
+            endpoint: endpoint.into(),
+            api_key: api_key
+                .map(|key| key.trim().to_owned())
+                .filter(|key| !key.is_empty()),
+        }
+    }
+}
+
+impl TranslationProvider for GoogleOfficialProvider {
+    fn with_client(&self, client: Client) -> Option<Arc<dyn TranslationProvider>> {
+        Some(Arc::new(Self {
+            client,
+            ..self.clone()
+        }))
+    }
+
+    fn translate(
+        &self,
+        text: &str,
+        source_language: &str,
+        target_language: &str,
+        _cancel_flag: Option<&AtomicBool>,
+    ) -> Result<String, TranslationError> {
+        let Some(api_key) = &self.api_key else {
+            return Err(TranslationError::InvalidInput(
+                "Google Cloud API key is not configured; set google_api_key in settings or TF_GOOGLE_API_KEY"
+                    .to_owned(),
+            ));
+        };
+
+        let request = self
+            .client
+            .post(format!(
+                "{}?key={}",
+                self.endpoint,
+                urlencoding::encode(api_key)
+            ))
+            .form(&[
+                ("q", text),
+                ("source", source_language),
+                ("target", target_language),
+                ("format", "text"),
+            ]);
+        let (status, body) = read_response(request.send())?;
+
+        if status.is_success() {
+            return parse_google_official_response(&body);
+        }
+
+        // Quota errors come back as 403 with a `...RateLimitExceeded` reason.
+        let error = serde_json::from_str::<Value>(&body)
+            .ok()
+            .and_then(|value| value.get("error").cloned());
+        let reason = error
+            .as_ref()
+            .and_then(|error| error.pointer("/errors/0/reason")?.as_str())
+            .unwrap_or_default();
+        let message = error
+            .as_ref()
+            .and_then(|error| error.get("message")?.as_str())
+            .map(ToOwned::to_owned)
+            .unwrap_or_else(|| format!("HTTP {}", status.as_u16()));
+
+        if status == StatusCode::TOO_MANY_REQUESTS || reason.ends_with("RateLimitExceeded") {
+            return Err(TranslationError::RateLimited);
+        }
+        if matches!(status.as_u16(), 400 | 401 | 403) {
+            return Err(TranslationError::InvalidInput(format!(
+                "Google Cloud rejected the request: {message}"
+            )));
+        }
+        Err(TranslationError::InvalidResponse(message))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DeepLProvider {
+    client: Client,
+    endpoint: String,
+    api_key: Option<String>,
+}
+
+impl DeepLProvider {
+    /// A blank key leaves the provider unconfigured; requests then fail with `InvalidInput`.
+    pub fn new(client: Client, endpoint: impl Into<String>, api_key: Option<String>) -> Self {
+        Self {
+            client,
+            endpoint: endpoint.into(),
+            api_key: api_key
+                .map(|key| key.trim().to_owned())
+                .filter(|key| !key.is_empty()),
+        }
+    }
+}
+
+impl TranslationProvider for DeepLProvider {
+    fn with_client(&self, client: Client) -> Option<Arc<dyn TranslationProvider>> {
+        Some(Arc::new(Self {
+            client,
+            ..self.clone()
+        }))
+    }
+
+    fn translate(
+        &self,
+        text: &str,
+        source_language: &str,
+        target_language: &str,
+        _cancel_flag: Option<&AtomicBool>,
+    ) -> Result<String, TranslationError> {
+        let Some(api_key) = &self.api_key else {
+            return Err(TranslationError::InvalidInput(
+                "DeepL API key is not configured; set deepl_api_key in settings or TF_DEEPL_API_KEY"
+                    .to_owned(),
+            ));
+        };
+
+        let request = self
+            .client
+            .post(&self.endpoint)
+            .header("Authorization", format!("DeepL-Auth-Key {api_key}"))
+            .form(&[
+                ("text", text),
+                ("source_lang", &deepl_language_code(source_language, false)),
+                ("target_lang", &deepl_language_code(target_language, true)),
+            ]);
+        let (status, body) = read_response(request.send())?;
+
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            return Err(TranslationError::RateLimited);
+        }
+        if status == StatusCode::FORBIDDEN {
+            return Err(TranslationError::InvalidInput(
+                "DeepL rejected the API key".to_owned(),
+            ));
+        }
+        if status.as_u16() == DEEPL_QUOTA_EXCEEDED {
+            return Err(TranslationError::InvalidResponse(
+                "DeepL character quota exceeded".to_owned(),
+            ));
+        }
+        if !status.is_success() {
+            return Err(TranslationError::InvalidResponse(format!(
+                "HTTP {}",
+                status.as_u16()
+            )));
+        }
+
+        parse_deepl_response(&body)
+    }
+}
+
+/// Self-hosted LibreTranslate server, addressed by its base URL.
+#[derive(Debug, Clone)]
+pub struct LibreTranslateProvider {
+    client: Client,
+    base_url: String,
+}
+
+impl LibreTranslateProvider {
+    pub fn new(client: Client, base_url: impl Into<String>) -> Self {
+        Self {
+            client,
+            base_url: base_url.into().trim().trim_end_matches('/').to_owned(),
+        }
+    }
+}
+
+impl TranslationProvider for LibreTranslateProvider {
+    fn with_client(&self, client: Client) -> Option<Arc<dyn TranslationProvider>> {
+        Some(Arc::new(Self {
+            client,
+            ..self.clone()
+        }))
+    }
+
+    fn translate(
+        &self,
+        text: &str,
+        source_language: &str,
+        target_language: &str,
+        _cancel_flag: Option<&AtomicBool>,
+    ) -> Result<String, TranslationError> {
+        let request = self
+            .client
+            .post(format!("{}/translate", self.base_url))
+            .json(&json!({
+                "q": text,
+                "source": source_language,
+                "target": target_language,
+                "format": "text",
+            }));
+        let (status, body) = read_response(request.send())?;
+
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            return Err(TranslationError::RateLimited);
+        }
+        if status == StatusCode::FORBIDDEN {
+            return Err(TranslationError::Blocked);
+        }
+        if !status.is_success() {
+            let detail = serde_json::from_str::<Value>(&body)
+                .ok()
+                .and_then(|value| value.get("error")?.as_str().map(ToOwned::to_owned))
+                .unwrap_or_else(|| format!("HTTP {}", status.as_u16()));
+            return Err(TranslationError::InvalidResponse(detail));
+        }
+
+        parse_libretranslate_response(&body)
+    }
+}
+
+/// Maps transport failures to `Network` so the service retries them.
+fn read_response(
+    response: reqwest::Result<Response>,
+) -> Result<(StatusCode, String), TranslationError> {
+    let response = response.map_err(|err| TranslationError::Network(err.to_string()))?;
+    let status = response.status();
+    let body = response
+        .text()
+        .map_err(|err| TranslationError::Network(err.to_string()))?;
+    Ok((status, body))
+}
+
+/// Joins the response segments, inserting a space between segments for
+/// space-delimited target languages when the provider did not include one.
+/// Entities the endpoint sometimes leaves in (`don&#39;t`) are decoded.
+pub fn parse_unofficial_google_response(
+    body: &str,
+    target_language: &str,
+) -> Result<String, TranslationError> {
+    let parsed: Value = serde_json::from_str(body)
+        .map_err(|err| TranslationError::InvalidResponse(err.to_string()))?;
+
+    let root = parsed
+        .as_array()
+        .ok_or_else(|| TranslationError::InvalidResponse("root is not an array".to_owned()))?;
+
+    let segments = root.first().and_then(Value::as_array).ok_or_else(|| {
+        TranslationError::InvalidResponse("missing translation segments".to_owned())
+    })?;
+
+    let insert_spaces = uses_word_spacing(target_language);
+    let mut result = String::new();
+    for sentence in segments {
+        if let Some(parts) = sentence.as_array()
+            && let Some(part) = parts.first().and_then(Value::as_str)
+        {
+            if insert_spaces && needs_separator(&result, part) {
+                result.push(' ');
+            }
+            result.push_str(part);
+        }
+    }
+
+    if result.trim().is_empty() {
+        return Err(TranslationError::InvalidResponse(
+            "no translation segments returned".to_owned(),
+        ));
+    }
+
+    Ok(unescape_html(&result))
+}
+
+/// Reads `data.translations[0].translatedText` from a Cloud Translation v2
+/// response. The API returns HTML entities even for `format=text`, so they
+/// are decoded.
+pub fn parse_google_official_response(body: &str) -> Result<String, TranslationError> {
+    let parsed: Value = serde_json::from_str(body)
+        .map_err(|err| TranslationError::InvalidResponse(err.to_string()))?;
+
+    parsed
+        .pointer("/data/translations/0/translatedText")
+        .and_then(Value::as_str)
+        .filter(|text| !text.trim().is_empty())
+        .map(unescape_html)
+        .ok_or_else(|| {
+            TranslationError::InvalidResponse("missing Google Cloud translation".to_owned())
+        })
+}
+
+/// Reads `translations[0].text` from a DeepL `/v2/translate` response,
+/// decoding any HTML entities.
+pub fn parse_deepl_response(body: &str) -> Result<String, TranslationError> {
+    let parsed: Value = serde_json::from_str(body)
+        .map_err(|err| TranslationError::InvalidResponse(err.to_string()))?;
+
+    parsed
+        .get("translations")
+        .and_then(Value::as_array)
+        .and_then(|translations| translations.first())
+        .and_then(|translation| translation.get("text"))
+        .and_then(Value::as_str)
+        .filter(|text| !text.trim().is_empty())
+        .map(unescape_html)
+        .ok_or_else(|| TranslationError::InvalidResponse("missing DeepL translation".to_owned()))
+}
+
+/// Reads `translatedText` from a LibreTranslate `/translate` response,
+/// decoding any HTML entities.
+pub fn parse_libretranslate_response(body: &str) -> Result<String, TranslationError> {
+    let parsed: Value = serde_json::from_str(body)
+        .map_err(|err| TranslationError::InvalidResponse(err.to_string()))?;
+
+    parsed
+        .get("translatedText")
+        .and_then(Value::as_str)
+        .filter(|text| !text.trim().is_empty())
+        .map(unescape_html)
+        .ok_or_else(|| {
+            TranslationError::InvalidResponse("missing LibreTranslate translation".to_owned())
+        })
+}
+
+/// DeepL wants upper-case codes; only a few target variants keep their region.
+fn deepl_language_code(code: &str, is_target: bool) -> String {
+    let upper = code.trim().to_ascii_uppercase().replace('_', "-");
+    if is_target && matches!(upper.as_str(), "EN-GB" | "EN-US" | "PT-BR" | "PT-PT") {
+        return upper;
+    }
+    upper.split('-').next().unwrap_or_default().to_owned()
+}
+
+fn needs_separator(joined: &str, next: &str) -> bool {
+    match (joined.chars().next_back(), next.chars().next()) {
+        (Some(last), Some(first)) => !last.is_whitespace() && !first.is_whitespace(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_deepl_response_text() {
+        let body = r#"{"translations":[{"detected_source_language":"EN","text":"Hallo Welt"}]}"#;
+        assert_eq!(parse_deepl_response(body).unwrap(), "Hallo Welt");
+        assert!(parse_deepl_response(r#"{"translations":[]}"#).is_err());
+        assert_eq!(deepl_language_code("zh-CN", true), "ZH");
+        assert_eq!(deepl_language_code("pt-br", true), "PT-BR");
+    }
+
+    #[test]
+    fn parses_google_official_response_and_decodes_entities() {
+        let body = r#"{"data":{"translations":[{"translatedText":"I don&#39;t know &quot;why&quot; &amp; how"}]}}"#;
+        assert_eq!(
+            parse_google_official_response(body).unwrap(),
+            "I don't know \"why\" & how"
+        );
+        let plain = r#"{"data":{"translations":[{"translatedText":"こんにちは世界"}]}}"#;
+        assert_eq!(
+            parse_google_official_response(plain).unwrap(),
+            "こんにちは世界"
+        );
+        assert!(parse_google_official_response(r#"{"data":{"translations":[]}}"#).is_err());
+    }
+
+    #[test]
+    fn parses_libretranslate_response_text() {
+        let body = r#"{"translatedText":"こんにちは世界","detectedLanguage":{"confidence":90,"language":"en"}}"#;
+        assert_eq!(
+            parse_libretranslate_response(body).unwrap(),
+            "こんにちは世界"
+        );
+        assert!(parse_libretranslate_response(r#"{"error":"boom"}"#).is_err());
+    }
+
+    #[test]
+    fn parses_unofficial_response_segments() {
+        let body = r#"[[["こんにちは", "hello", null, null, 1],["！","!",null,null,1]] ]"#;
+        let parsed = parse_unofficial_google_response(body, "ja").unwrap();
+        assert_eq!(parsed, "こんにちは！");
+    }
+
+    #[test]
+    fn inserts_spaces_between_segments_for_english_targets() {
+        let body = r#"[[["Hello there.","こんにちは。",null,null,1],["How are you?","元気？",null,null,1],[" Fine.","元気。",null,null,1]]]"#;
+        let parsed = parse_unofficial_google_response(body, "en").unwrap();
+        assert_eq!(parsed, "Hello there. How are you? Fine.");
+    }
+
+    #[test]
+    fn joins_japanese_segments_without_spaces() {
+        let body = r#"[[["こんにちは。","Hello.",null,null,1],["元気ですか？","How are you?",null,null,1]]]"#;
+        let parsed = parse_unofficial_google_response(body, "ja").unwrap();
+        assert_eq!(parsed, "こんにちは。元気ですか？");
+    }
+
+    #[test]
+    fn decodes_entities_in_every_provider_response() {
+        let unofficial = r#"[[["it&#39;s &amp; that","それ",null,null,1]]]"#;
+        assert_eq!(
+            parse_unofficial_google_response(unofficial, "en").unwrap(),
+            "it's & that"
+        );
+        let deepl = r#"{"translations":[{"text":"it&#39;s &amp; that"}]}"#;
+        assert_eq!(parse_deepl_response(deepl).unwrap(), "it's & that");
+        let libre = r#"{"translatedText":"it&#39;s &amp; that"}"#;
+        assert_eq!(parse_libretranslate_response(libre).unwrap(), "it's & that");
+
+        // Clean text is untouched, and decoding happens once.
+        let clean = r#"[[["Fish & chips; 5 < 6","",null,null,1]]]"#;
+        assert_eq!(
+            parse_unofficial_google_response(clean, "en").unwrap(),
+            "Fish & chips; 5 < 6"
+        );
+        let nested = r#"{"translatedText":"write &amp;lt; for <"}"#;
+        assert_eq!(
+            parse_libretranslate_response(nested).unwrap(),
+            "write &lt; for <"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_response_shape() {
+        let error = parse_unofficial_google_response("{}", "ja").unwrap_err();
+        assert!(matches!(error, TranslationError::InvalidResponse(_)));
+    }
+}
@@ -0,0 +1,165 @@
+/// Upper bound on edit-distance cells; longer inputs are scored by bigram overlap.
+const MAX_EDIT_CELLS: usize = 4_000_000;
+
+/// Similarity levels at or above which a round trip is flagged as suspicious.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QualityThresholds {
+    /// Original vs intermediate: the provider likely echoed the input untranslated.
+    pub echo_similarity: f64,
+    /// Back-translation vs intermediate: the back hop likely returned its input.
+    pub back_similarity: f64,
+}
+
+impl Default for QualityThresholds {
+    fn default() -> Self {
+        Self {
+            echo_similarity: 0.98,
+            back_similarity: 0.95,
+        }
+    }
+}
+
+/// Case- and whitespace-insensitive similarity in `0.0..=1.0`, compared per
+/// character so CJK text without spaces is scored meaningfully.
+pub fn similarity(left: &str, right: &str) -> f64 {
+    let left = comparable_chars(left);
+    let right = comparable_chars(right);
+    if left.is_empty() && right.is_empty() {
+        return 1.0;
+    }
+    if left.is_empty() || right.is_empty() {
+        return 0.0;
+    }
+
+    if left.len().saturating_mul(right.len()) > MAX_EDIT_CELLS {
+        return bigram_overlap(&left, &right);
+    }
+
+    let distance = levenshtein(&left, &right);
+    1.0 - distance as f64 / left.len().max(right.len()) as f64
+}
+
+/// How closely the back-translation reproduces the original, in `0.0..=1.0`.
+pub fn fidelity_score(original: &str, back_translated: &str) -> f64 {
+    similarity(original, back_translated)
+}
+
+/// Returns why a round trip looks like a silent provider failure, if it does.
+pub fn suspicious_reason(
+    original: &str,
+    intermediate: &str,
+    back_translated: &str,
+    thresholds: &QualityThresholds,
+) -> Option<String> {
+    let echo = similarity(original, intermediate);
+    if echo >= thresholds.echo_similarity {
+        return Some(format!(
+            "intermediate text is {:.0}% identical to the original; the provider may have echoed the input",
+            echo * 100.0
+        ));
+    }
+
+    let back = similarity(back_translated, intermediate);
+    if back >= thresholds.back_similarity {
+        return Some(format!(
+            "back translation is {:.0}% identical to the intermediate text; the back hop may not have translated",
+            back * 100.0
+        ));
+    }
+
+    None
+}
+
+fn comparable_chars(value: &str) -> Vec<char> {
+    value
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn levenshtein(left: &[char], right: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=right.len()).collect();
+    let mut current = vec![0; right.len() + 1];
+
+    for (i, left_char) in left.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, right_char) in right.iter().enumerate() {
+            let substitution = previous[j] + usize::from(left_char != right_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[right.len()]
+}
+
+/// Dice coefficient over character bigrams, used when edit distance is too costly.
+fn bigram_overlap(left: &[char], right: &[char]) -> f64 {
+    use std::collections::HashMap;
+
+    let mut counts: HashMap<(char, char), isize> = HashMap::new();
+    for pair in left.windows(2) {
+        *counts.entry((pair[0], pair[1])).or_default() += 1;
+    }
+
+    let mut shared = 0;
+    for pair in right.windows(2) {
+        if let Some(count) = counts.get_mut(&(pair[0], pair[1]))
+            && *count > 0
+        {
+            *count -= 1;
+            shared += 1;
+        }
+    }
+
+    let total = left.len().saturating_sub(1) + right.len().saturating_sub(1);
+    if total == 0 {
+        return if left == right { 1.0 } else { 0.0 };
+    }
+    (2 * shared) as f64 / total as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_echoed_input_as_suspicious() {
+        let thresholds = QualityThresholds::default();
+
+        let echoed = suspicious_reason("Hello world", "Hello  world", "Hello world", &thresholds);
+        assert!(echoed.unwrap().contains("echoed"));
+
+        let healthy =
+            suspicious_reason("Hello world", "こんにちは世界", "Hello world", &thresholds);
+        assert_eq!(healthy, None);
+    }
+
+    #[test]
+    fn flags_untranslated_back_hop() {
+        let reason = suspicious_reason(
+            "Hello world",
+            "こんにちは世界",
+            "こんにちは世界",
+            &QualityThresholds::default(),
+        );
+        assert!(reason.unwrap().contains("back hop"));
+    }
+
+    #[test]
+    fn fidelity_score_spans_identical_to_unrelated_text() {
+        assert_eq!(fidelity_score("Hello world", "hello   world"), 1.0);
+        assert!(fidelity_score("abcdefgh", "zyxwvuts") < 0.05);
+    }
+
+    #[test]
+    fn fidelity_score_compares_cjk_per_character() {
+        assert_eq!(fidelity_score("こんにちは世界", "こんにちは世界"), 1.0);
+        let score = fidelity_score("こんにちは世界", "こんにちは地球");
+        assert!((score - 5.0 / 7.0).abs() < 1e-9, "score was {score}");
+        assert_eq!(fidelity_score("日本語", ""), 0.0);
+    }
+}
@@ -0,0 +1,201 @@
+use encoding_rs::WINDOWS_1252;
+
+/// Groups whose content is formatting or metadata rather than document text.
+const SKIPPED_DESTINATIONS: &[&str] = &[
+    "fonttbl",
+    "colortbl",
+    "stylesheet",
+    "info",
+    "pict",
+    "header",
+    "headerl",
+    "headerr",
+    "footer",
+    "footerl",
+    "footerr",
+    "footnote",
+    "object",
+    "themedata",
+    "datastore",
+    "latentstyles",
+    "listtable",
+    "listoverridetable",
+    "rsidtbl",
+    "generator",
+    "xmlnstbl",
+];
+
+#[derive(Debug, Clone, Copy)]
+struct GroupState {
+    skip: bool,
+    /// Fallback characters that follow each `\uN` escape (`\ucN`).
+    unicode_skip: usize,
+}
+
+/// Plain text of an RTF document: control words and non-text groups are
+/// dropped, `\'hh` and `\uN` escapes decoded, and `\par`/`\line` become line
+/// breaks. Normalizing collapses whitespace and separates paragraphs with
+/// blank lines.
+pub fn rtf_to_text(rtf: &str, normalize_whitespace: bool) -> String {
+    let text = decode(rtf);
+    if !normalize_whitespace {
+        return text;
+    }
+    text.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn decode(rtf: &str) -> String {
+    let chars: Vec<char> = rtf.chars().collect();
+    let mut output = String::new();
+    let mut state = GroupState {
+        skip: false,
+        unicode_skip: 1,
+    };
+    let mut stack = Vec::new();
+    // Fallback characters still to drop after a `\uN` escape.
+    let mut pending_skip = 0;
+    let mut index = 0;
+
+    while index < chars.len() {
+        let ch = chars[index];
+        match ch {
+            '{' => {
+                stack.push(state);
+                index += 1;
+                // `{\*\dest ...}` marks a destination readers may ignore.
+                if chars.get(index) == Some(&'\\') && chars.get(index + 1) == Some(&'*') {
+                    state.skip = true;
+                }
+            }
+            '}' => {
+                state = stack.pop().unwrap_or(state);
+                pending_skip = 0;
+                index += 1;
+            }
+            '\\' => {
+                let next = chars.get(index + 1).copied().unwrap_or_default();
+                if next.is_ascii_alphabetic() {
+                    let (word, parameter, end) = control_word(&chars, index + 1);
+                    index = end;
+                    if pending_skip > 0 {
+                        pending_skip -= 1;
+                        continue;
+                    }
+                    if SKIPPED_DESTINATIONS.contains(&word.as_str()) {
+                        state.skip = true;
+                    }
+                    if state.skip {
+                        continue;
+                    }
+                    match word.as_str() {
+                        "par" | "line" | "sect" | "page" | "row" => output.push('\n'),
+                        "tab" | "cell" => output.push('\t'),
+                        "emdash" => output.push('—'),
+                        "endash" => output.push('–'),
+                        "lquote" => output.push('‘'),
+                        "rquote" => output.push('’'),
+                        "ldblquote" => output.push('“'),
+                        "rdblquote" => output.push('”'),
+                        "bullet" => output.push('•'),
+                        "uc" => state.unicode_skip = parameter.unwrap_or(1).max(0) as usize,
+                        "u" => {
+                            if let Some(value) = parameter {
+                                // Code points above 32767 are written as negative numbers.
+                                let code = if value < 0 { value + 65_536 } else { value };
+                                output.extend(char::from_u32(code as u32));
+                                pending_skip = state.unicode_skip;
+                            }
+                        }
+                        _ => {}
+                    }
+                } else if next == '\'' {
+                    let hex: String = chars.iter().skip(index + 2).take(2).collect();
+                    index += 2 + hex.len();
+                    if pending_skip > 0 {
+                        pending_skip -= 1;
+                        continue;
+                    }
+                    if !state.skip
+                        && let Ok(byte) = u8::from_str_radix(&hex, 16)
+                    {
+                        output.push_str(&WINDOWS_1252.decode_without_bom_handling(&[byte]).0);
+                    }
+                } else {
+                    index += 2;
+                    if pending_skip > 0 {
+                        pending_skip -= 1;
+                        continue;
+                    }
+                    if state.skip {
+                        continue;
+                    }
+                    match next {
+                        '\\' | '{' | '}' => output.push(next),
+                        '~' => output.push('\u{a0}'),
+                        '_' => output.push('-'),
+                        '\n' | '\r' => output.push('\n'),
+                        _ => {}
+                    }
+                }
+            }
+            // Raw line breaks in the source are formatting, not text.
+            '\r' | '\n' => index += 1,
+            _ => {
+                index += 1;
+                if pending_skip > 0 {
+                    pending_skip -= 1;
+                } else if !state.skip {
+                    output.push(ch);
+                }
+            }
+        }
+    }
+
+    output
+}
+
+/// Reads the control word starting at `start`, returning its name, numeric
+/// parameter, and the index after its delimiter.
+fn control_word(chars: &[char], start: usize) -> (String, Option<i32>, usize) {
+    let mut index = start;
+    while chars.get(index).is_some_and(char::is_ascii_alphabetic) {
+        index += 1;
+    }
+    let word: String = chars[start..index].iter().collect();
+
+    let number_start = index;
+    if chars.get(index) == Some(&'-') {
+        index += 1;
+    }
+    while chars.get(index).is_some_and(char::is_ascii_digit) {
+        index += 1;
+    }
+    let parameter = chars[number_start..index]
+        .iter()
+        .collect::<String>()
+        .parse()
+        .ok();
+
+    // A single space delimits the control word and belongs to it.
+    if chars.get(index) == Some(&' ') {
+        index += 1;
+    }
+    (word, parameter, index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_text_with_escaped_accents() {
+        let rtf = "{\\rtf1\\ansi\\deff0{\\fonttbl{\\f0 Arial;}}{\\*\\generator Writer;}\r\n\\f0\\fs24 Caf\\'e9 na\\u239?ve \\{ok\\}\\par\r\n{\\b Second}   line\\par}";
+
+        assert_eq!(rtf_to_text(rtf, true), "Café naïve {ok}\n\nSecond line");
+        assert_eq!(rtf_to_text(rtf, false), "Café naïve {ok}\nSecond   line\n");
+    }
+}
@@ -1,24 +1,145 @@
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use tracing::warn;
 
+use crate::export::ExportOptions;
+use crate::file_service::{DEFAULT_MAX_INPUT_FILE_BYTES, TextLoadOptions};
 use crate::language::normalize_language_code;
+use crate::logger::{LogFormat, LogOptions};
+use crate::memory::{CacheNormalization, EvictionPolicy};
 use crate::models::{ExportFormat, ProviderId};
+use crate::quality::QualityThresholds;
+use crate::translation::{DEFAULT_REQUEST_TIMEOUT, request_timeout_from_env};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Upper bound on parallel batch workers, matching the CLI `--concurrency` range.
+pub const MAX_BATCH_CONCURRENCY: usize = 32;
+pub const DEFAULT_LIBRETRANSLATE_URL: &str = "http://localhost:5000";
+/// Smallest accepted `max_input_file_bytes`, so a typo cannot reject every file.
+pub const MIN_INPUT_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Window width and title-bar height kept on screen when a saved position is
+/// clamped, so the window can still be grabbed and dragged.
+const VISIBLE_WINDOW_WIDTH: f32 = 120.0;
+const VISIBLE_TITLE_BAR_HEIGHT: f32 = 40.0;
+
+/// Desktop colour scheme; `System` follows the OS light/dark preference.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+    System,
+}
+
+impl Theme {
+    pub fn all() -> [Self; 3] {
+        [Self::Dark, Self::Light, Self::System]
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Dark => "dark",
+            Self::Light => "light",
+            Self::System => "system",
+        }
+    }
+
+    pub fn display_name(self) -> &'static str {
+        match self {
+            Self::Dark => "Dark",
+            Self::Light => "Light",
+            Self::System => "Match system",
+        }
+    }
+
+    pub fn normalize(value: &str) -> Self {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "light" => Self::Light,
+            "system" | "auto" => Self::System,
+            _ => Self::Dark,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AppSettings {
     pub provider_id: String,
     pub source_language: String,
     pub intermediate_language: String,
     pub output_format: String,
+    /// Field separator for CSV exports, e.g. `;` for locales that use a
+    /// decimal comma.
+    pub csv_delimiter: char,
+    /// Quote every CSV field, not just those that need it.
+    pub csv_always_quote: bool,
     pub window_width: f32,
     pub window_height: f32,
+    /// Outer window position in points; unset until the window has been shown once.
+    pub window_pos_x: Option<f32>,
+    pub window_pos_y: Option<f32>,
+    /// `dark`, `light`, or `system`; see `Theme`.
+    pub theme: String,
     pub last_file_path: String,
     pub last_save_path: String,
     pub translation_memory_max_entries: usize,
+    /// Completed translations kept in the History tab.
+    pub history_max_entries: usize,
+    pub track_memory_metrics: bool,
+    /// Cache and translate each sentence on its own, so documents that share
+    /// most of their sentences reuse the cached ones.
+    pub segment_cache: bool,
+    /// `lru` or `lfu`; see `EvictionPolicy`.
+    pub eviction_policy: String,
+    /// `off`, `whitespace`, or `whitespace_and_case`; see `CacheNormalization`.
+    pub cache_normalize: String,
+    /// Memory entries older than this many days are purged; 0 keeps them forever.
+    pub entry_ttl_days: u64,
+    /// Attempts per request before a rate-limit or network error is returned.
+    pub retry_max_attempts: usize,
+    /// Base of the exponential backoff between attempts.
+    pub retry_base_delay_ms: u64,
+    /// Longest time one request may spend retrying before its last error is
+    /// returned; 0 leaves only `retry_max_attempts` as a limit.
+    pub max_total_retry_ms: u64,
+    pub blocked_retry_attempts: usize,
+    pub blocked_cooldown_minutes: u64,
+    /// Minimum gap between provider requests, shared by batch workers; 0 disables it.
+    pub min_request_interval_ms: u64,
+    /// HTTP timeout per provider request; `TF_UNOFFICIAL_TIMEOUT_SECONDS` takes precedence.
+    pub request_timeout_seconds: u64,
+    pub normalize_whitespace: bool,
+    /// `tracing` filter used when `RUST_LOG` is unset, e.g. `debug` or `warn`.
+    pub log_level: String,
+    /// Daily log files kept before the oldest is deleted.
+    pub log_retention_days: usize,
+    /// `text` or `json`; see `LogFormat`.
+    pub log_format: String,
+    /// Input files larger than this are rejected; at least `MIN_INPUT_FILE_BYTES`.
+    pub max_input_file_bytes: u64,
+    /// Files translated in parallel by the Batch tab.
+    pub batch_concurrency: usize,
+    /// Shell command run after a batch completes without being cancelled; empty disables it.
+    pub on_complete_command: String,
+    /// Original-vs-intermediate similarity that flags an echoed input.
+    pub suspicious_echo_similarity: f64,
+    /// Back-translation-vs-intermediate similarity that flags an untranslated back hop.
+    pub suspicious_back_similarity: f64,
+    /// Translation memory generation; changing it starts a fresh cache without deleting rows.
+    pub cache_namespace: String,
+    /// DeepL API key; when blank, `TF_DEEPL_API_KEY` is used instead.
+    pub deepl_api_key: String,
+    /// Google Cloud Translation API key; when blank, `TF_GOOGLE_API_KEY` is used instead.
+    pub google_api_key: String,
+    /// Base URL of the LibreTranslate server; requests go to `<url>/translate`.
+    pub libretranslate_url: String,
+    /// JSON or CSV glossary of protected terms; empty disables it.
+    pub glossary_path: String,
 }
 
 impl Default for AppSettings {
@@ -28,11 +149,43 @@ impl Default for AppSettings {
             source_language: "en".to_owned(),
             intermediate_language: "ja".to_owned(),
             output_format: ExportFormat::Html.extension().to_owned(),
+            csv_delimiter: ExportOptions::default().csv_delimiter,
+            csv_always_quote: false,
             window_width: 1260.0,
             window_height: 860.0,
+            window_pos_x: None,
+            window_pos_y: None,
+            theme: Theme::default().as_str().to_owned(),
             last_file_path: String::new(),
             last_save_path: String::new(),
             translation_memory_max_entries: 1000,
+            history_max_entries: 200,
+            track_memory_metrics: true,
+            segment_cache: false,
+            eviction_policy: EvictionPolicy::default().as_str().to_owned(),
+            cache_normalize: CacheNormalization::default().as_str().to_owned(),
+            entry_ttl_days: 0,
+            retry_max_attempts: 4,
+            retry_base_delay_ms: 300,
+            max_total_retry_ms: 60_000,
+            blocked_retry_attempts: 0,
+            blocked_cooldown_minutes: 5,
+            min_request_interval_ms: 0,
+            request_timeout_seconds: DEFAULT_REQUEST_TIMEOUT.as_secs(),
+            normalize_whitespace: true,
+            log_level: "info".to_owned(),
+            log_retention_days: 14,
+            log_format: LogFormat::default().as_str().to_owned(),
+            max_input_file_bytes: DEFAULT_MAX_INPUT_FILE_BYTES,
+            batch_concurrency: 1,
+            on_complete_command: String::new(),
+            suspicious_echo_similarity: QualityThresholds::default().echo_similarity,
+            suspicious_back_similarity: QualityThresholds::default().back_similarity,
+            cache_namespace: String::new(),
+            deepl_api_key: String::new(),
+            google_api_key: String::new(),
+            libretranslate_url: DEFAULT_LIBRETRANSLATE_URL.to_owned(),
+            glossary_path: String::new(),
         }
     }
 }
@@ -42,10 +195,125 @@ impl AppSettings {
         ProviderId::normalize(&self.provider_id)
     }
 
+    pub fn window_position(&self) -> Option<(f32, f32)> {
+        Some((self.window_pos_x?, self.window_pos_y?))
+    }
+
+    pub fn log_options(&self) -> LogOptions {
+        LogOptions {
+            level: self.log_level.clone(),
+            retention_days: self.log_retention_days,
+            format: LogFormat::normalize(&self.log_format),
+            console_level: None,
+        }
+    }
+
+    pub fn ui_theme(&self) -> Theme {
+        Theme::normalize(&self.theme)
+    }
+
+    pub fn eviction(&self) -> EvictionPolicy {
+        EvictionPolicy::normalize(&self.eviction_policy)
+    }
+
+    pub fn cache_normalization(&self) -> CacheNormalization {
+        CacheNormalization::normalize(&self.cache_normalize)
+    }
+
     pub fn export_format(&self) -> ExportFormat {
         self.output_format.parse().unwrap_or(ExportFormat::Html)
     }
 
+    pub fn export_options(&self) -> ExportOptions {
+        ExportOptions {
+            csv_delimiter: self.csv_delimiter,
+            csv_always_quote: self.csv_always_quote,
+        }
+    }
+
+    pub fn text_load_options(&self) -> TextLoadOptions {
+        TextLoadOptions {
+            normalize_whitespace: self.normalize_whitespace,
+            max_file_bytes: self.max_input_file_bytes,
+        }
+    }
+
+    pub fn request_timeout(&self) -> Duration {
+        request_timeout_from_env()
+            .unwrap_or_else(|| Duration::from_secs(self.request_timeout_seconds))
+    }
+
+    pub fn resolved_deepl_api_key(&self) -> String {
+        if !self.deepl_api_key.trim().is_empty() {
+            return self.deepl_api_key.trim().to_owned();
+        }
+        std::env::var("TF_DEEPL_API_KEY").unwrap_or_default()
+    }
+
+    pub fn resolved_google_api_key(&self) -> String {
+        if !self.google_api_key.trim().is_empty() {
+            return self.google_api_key.trim().to_owned();
+        }
+        std::env::var("TF_GOOGLE_API_KEY").unwrap_or_default()
+    }
+
+    pub fn glossary_file(&self) -> Option<PathBuf> {
+        let path = self.glossary_path.trim();
+        (!path.is_empty()).then(|| PathBuf::from(path))
+    }
+
+    pub fn quality_thresholds(&self) -> QualityThresholds {
+        QualityThresholds {
+            echo_similarity: self.suspicious_echo_similarity,
+            back_similarity: self.suspicious_back_similarity,
+        }
+    }
+
+    /// Names accepted by `get` and `set`, sorted.
+    pub fn keys() -> Vec<String> {
+        Self::default()
+            .fields()
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect()
+    }
+
+    pub fn get(&self, key: &str) -> Result<Value> {
+        match self.fields().remove(key) {
+            Some(value) => Ok(value),
+            None => bail!(unknown_key(key)),
+        }
+    }
+
+    /// Replaces `key` with `raw` and normalizes the result, so out-of-range
+    /// values come back clamped. String settings take `raw` as written; the
+    /// others parse it as JSON (`5`, `true`, `null`).
+    pub fn set(&mut self, key: &str, raw: &str) -> Result<()> {
+        let mut fields = self.fields();
+        let Some(current) = fields.get_mut(key) else {
+            bail!(unknown_key(key));
+        };
+        *current = if current.is_string() {
+            Value::String(raw.to_owned())
+        } else {
+            serde_json::from_str(raw.trim())
+                .with_context(|| format!("invalid value for {key}: {raw:?}"))?
+        };
+
+        let mut updated: Self = serde_json::from_value(Value::Object(fields))
+            .with_context(|| format!("invalid value for {key}: {raw:?}"))?;
+        updated.normalize();
+        *self = updated;
+        Ok(())
+    }
+
+    fn fields(&self) -> Map<String, Value> {
+        match serde_json::to_value(self) {
+            Ok(Value::Object(fields)) => fields,
+            _ => Map::new(),
+        }
+    }
+
     pub fn normalize(&mut self) {
         self.provider_id = self.provider().as_str().to_owned();
 
@@ -55,6 +323,13 @@ impl AppSettings {
             normalize_language_code(&self.intermediate_language).unwrap_or_else(|| "ja".to_owned());
 
         self.output_format = self.export_format().extension().to_owned();
+        if !self.csv_delimiter.is_ascii() || matches!(self.csv_delimiter, '"' | '\n' | '\r') {
+            self.csv_delimiter = ExportOptions::default().csv_delimiter;
+        }
+        self.eviction_policy = self.eviction().as_str().to_owned();
+        self.cache_normalize = self.cache_normalization().as_str().to_owned();
+        self.theme = self.ui_theme().as_str().to_owned();
+        self.entry_ttl_days = self.entry_ttl_days.min(3650);
 
         if self.window_width < 900.0 {
             self.window_width = 900.0;
@@ -62,10 +337,74 @@ impl AppSettings {
         if self.window_height < 620.0 {
             self.window_height = 620.0;
         }
+        self.window_pos_x = self.window_pos_x.filter(|value| value.is_finite());
+        self.window_pos_y = self.window_pos_y.filter(|value| value.is_finite());
 
         if self.translation_memory_max_entries == 0 {
             self.translation_memory_max_entries = 1000;
         }
+        self.history_max_entries = self.history_max_entries.clamp(1, 10_000);
+
+        self.max_input_file_bytes = self.max_input_file_bytes.max(MIN_INPUT_FILE_BYTES);
+        self.batch_concurrency = self.batch_concurrency.clamp(1, MAX_BATCH_CONCURRENCY);
+        self.retry_max_attempts = self.retry_max_attempts.clamp(1, 10);
+        self.retry_base_delay_ms = self.retry_base_delay_ms.clamp(50, 5000);
+        self.max_total_retry_ms = self.max_total_retry_ms.min(600_000);
+        self.blocked_retry_attempts = self.blocked_retry_attempts.min(10);
+        self.blocked_cooldown_minutes = self.blocked_cooldown_minutes.clamp(1, 60);
+        self.min_request_interval_ms = self.min_request_interval_ms.min(60_000);
+        self.request_timeout_seconds = self.request_timeout_seconds.clamp(5, 120);
+        self.on_complete_command = self.on_complete_command.trim().to_owned();
+        self.log_level = self.log_level.trim().to_ascii_lowercase();
+        if self.log_level.is_empty() {
+            self.log_level = "info".to_owned();
+        }
+        self.log_retention_days = self.log_retention_days.clamp(1, 365);
+        self.log_format = LogFormat::normalize(&self.log_format).as_str().to_owned();
+        self.cache_namespace = self.cache_namespace.trim().to_owned();
+        self.glossary_path = self.glossary_path.trim().to_owned();
+        self.libretranslate_url = self
+            .libretranslate_url
+            .trim()
+            .trim_end_matches('/')
+            .to_owned();
+        if self.libretranslate_url.is_empty() {
+            self.libretranslate_url = DEFAULT_LIBRETRANSLATE_URL.to_owned();
+        }
+
+        let defaults = QualityThresholds::default();
+        self.suspicious_echo_similarity =
+            clamp_threshold(self.suspicious_echo_similarity, defaults.echo_similarity);
+        self.suspicious_back_similarity =
+            clamp_threshold(self.suspicious_back_similarity, defaults.back_similarity);
+    }
+}
+
+/// Moves a window at `position` so its title bar stays on a `screen` of the
+/// given size, e.g. after the monitor it was saved on is disconnected.
+pub fn clamp_window_position(
+    position: (f32, f32),
+    window_size: (f32, f32),
+    screen_size: (f32, f32),
+) -> (f32, f32) {
+    let min_x = VISIBLE_WINDOW_WIDTH.min(window_size.0) - window_size.0;
+    let max_x = (screen_size.0 - VISIBLE_WINDOW_WIDTH).max(min_x);
+    let max_y = (screen_size.1 - VISIBLE_TITLE_BAR_HEIGHT).max(0.0);
+    (position.0.clamp(min_x, max_x), position.1.clamp(0.0, max_y))
+}
+
+fn unknown_key(key: &str) -> String {
+    format!(
+        "unknown setting {key:?}; valid keys: {}",
+        AppSettings::keys().join(", ")
+    )
+}
+
+fn clamp_threshold(value: f64, fallback: f64) -> f64 {
+    if value.is_finite() {
+        value.clamp(0.5, 1.0)
+    } else {
+        fallback
     }
 }
 
@@ -130,4 +469,91 @@ mod tests {
         assert_eq!(settings.source_language, "pt-br");
         assert_eq!(settings.intermediate_language, "zh-hans");
     }
+
+    #[test]
+    fn clamps_retry_policy_bounds() {
+        let mut settings = AppSettings {
+            retry_max_attempts: 0,
+            retry_base_delay_ms: 60_000,
+            min_request_interval_ms: 600_000,
+            request_timeout_seconds: 1,
+            max_total_retry_ms: u64::MAX,
+            ..AppSettings::default()
+        };
+
+        settings.normalize();
+
+        assert_eq!(settings.retry_max_attempts, 1);
+        assert_eq!(settings.retry_base_delay_ms, 5000);
+        assert_eq!(settings.min_request_interval_ms, 60_000);
+        assert_eq!(settings.request_timeout_seconds, 5);
+        assert_eq!(settings.max_total_retry_ms, 600_000);
+    }
+
+    #[test]
+    fn set_then_get_round_trips_and_normalizes() {
+        let mut settings = AppSettings::default();
+
+        settings.set("intermediate_language", " FR ").unwrap();
+        settings.set("retry_max_attempts", "99").unwrap();
+        settings.set("normalize_whitespace", "false").unwrap();
+        settings.set("csv_delimiter", ";").unwrap();
+
+        assert_eq!(settings.get("intermediate_language").unwrap(), "fr");
+        assert_eq!(settings.get("retry_max_attempts").unwrap(), 10);
+        assert_eq!(settings.get("normalize_whitespace").unwrap(), false);
+        assert_eq!(settings.export_options().csv_delimiter, ';');
+        assert!(settings.set("csv_delimiter", ";;").is_err());
+        assert!(settings.set("retry_max_attempts", "many").is_err());
+        let error = settings.get("retry_attempts").unwrap_err().to_string();
+        assert!(error.contains("valid keys: batch_concurrency, "), "{error}");
+    }
+
+    #[test]
+    fn normalizes_logging_settings() {
+        let mut settings = AppSettings {
+            log_level: "  ".to_owned(),
+            log_retention_days: 0,
+            ..AppSettings::default()
+        };
+
+        settings.normalize();
+
+        assert_eq!(settings.log_level, "info");
+        assert_eq!(settings.log_retention_days, 1);
+    }
+
+    #[test]
+    fn unknown_themes_fall_back_to_dark() {
+        let mut settings = AppSettings {
+            theme: " Light ".to_owned(),
+            ..AppSettings::default()
+        };
+        settings.normalize();
+        assert_eq!(settings.ui_theme(), Theme::Light);
+        assert_eq!(settings.theme, "light");
+
+        settings.theme = "sepia".to_owned();
+        settings.normalize();
+        assert_eq!(settings.theme, "dark");
+    }
+
+    #[test]
+    fn off_screen_window_positions_are_pulled_back() {
+        let window = (1260.0, 860.0);
+        let screen = (1920.0, 1080.0);
+
+        assert_eq!(
+            clamp_window_position((200.0, 100.0), window, screen),
+            (200.0, 100.0)
+        );
+        assert_eq!(
+            clamp_window_position((3000.0, 1500.0), window, screen),
+            (1800.0, 1040.0)
+        );
+        assert_eq!(
+            clamp_window_position((-2000.0, -50.0), window, screen),
+            (-1140.0, 0.0)
+        );
+    }
 }
@@ -0,0 +1,55 @@
+use serde::Serialize;
+
+/// Average, median, and 95th percentile of a set of durations.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct DurationStats {
+    pub average_ms: f64,
+    pub median_ms: u128,
+    pub p95_ms: u128,
+}
+
+impl DurationStats {
+    /// All zero for an empty set.
+    pub fn from_durations(durations: impl IntoIterator<Item = u128>) -> Self {
+        let mut sorted: Vec<u128> = durations.into_iter().collect();
+        if sorted.is_empty() {
+            return Self::default();
+        }
+        sorted.sort_unstable();
+
+        Self {
+            average_ms: sorted.iter().sum::<u128>() as f64 / sorted.len() as f64,
+            median_ms: percentile(&sorted, 50.0),
+            p95_ms: percentile(&sorted, 95.0),
+        }
+    }
+}
+
+/// Nearest-rank percentile of already sorted values; `percent` is clamped to
+/// 0–100 and an empty slice gives 0.
+pub fn percentile(sorted: &[u128], percent: f64) -> u128 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (percent.clamp(0.0, 100.0) / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn computes_nearest_rank_percentiles() {
+        let values: Vec<u128> = (1..=20).map(|value| value * 10).collect();
+        assert_eq!(percentile(&values, 50.0), 100);
+        assert_eq!(percentile(&values, 95.0), 190);
+        assert_eq!(percentile(&values, 0.0), 10);
+        assert_eq!(percentile(&[], 95.0), 0);
+
+        let stats = DurationStats::from_durations([300, 100, 200]);
+        assert_eq!(stats.median_ms, 200);
+        assert_eq!(stats.p95_ms, 300);
+        assert!((stats.average_ms - 200.0).abs() < f64::EPSILON);
+    }
+}
@@ -0,0 +1,365 @@
+use std::fmt;
+
+/// A document split into markup, reproduced verbatim, and the text between
+/// it, which is all that gets translated.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StructuredDocument {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Markup(String),
+    Text(String),
+}
+
+impl StructuredDocument {
+    /// Splits Markdown into text and markup. Block prefixes (headings,
+    /// quotes, list markers), emphasis delimiters, link targets, inline and
+    /// fenced code, HTML tags, and bare URLs are markup; the lines of one
+    /// paragraph stay in a single text segment.
+    pub fn parse_markdown(source: &str) -> Self {
+        let mut builder = Builder::default();
+        let mut fence: Option<&str> = None;
+        let mut in_paragraph = false;
+        let mut pending_newline = String::new();
+
+        for line in source.split_inclusive('\n') {
+            let body = line.trim_end_matches(['\n', '\r']);
+            let newline = &line[body.len()..];
+            let trimmed = body.trim_start();
+
+            if let Some(marker) = fence {
+                builder.markup(&std::mem::take(&mut pending_newline));
+                builder.markup(body);
+                pending_newline.push_str(newline);
+                if trimmed.starts_with(marker) {
+                    fence = None;
+                }
+                continue;
+            }
+
+            let (prefix, content) = body.split_at(block_prefix_len(body));
+            let starts_block = !prefix.trim().is_empty();
+            let verbatim = trimmed.is_empty() || is_verbatim_line(trimmed);
+            if !verbatim && !starts_block && in_paragraph {
+                // A continuation line: the line break is part of the paragraph.
+                builder.text(&std::mem::take(&mut pending_newline));
+                builder.text(prefix);
+            } else {
+                builder.markup(&std::mem::take(&mut pending_newline));
+                builder.markup(prefix);
+            }
+
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                fence = Some(&trimmed[..3]);
+                builder.markup(content);
+                in_paragraph = false;
+            } else if verbatim {
+                builder.markup(content);
+                in_paragraph = false;
+            } else {
+                tokenize_inline(content, &mut builder);
+                in_paragraph = !trimmed.starts_with('#');
+            }
+            pending_newline.push_str(newline);
+        }
+        builder.markup(&pending_newline);
+
+        Self {
+            segments: builder.segments,
+        }
+    }
+
+    /// Text segments worth translating, in document order.
+    pub fn texts(&self) -> impl Iterator<Item = &str> {
+        self.segments.iter().filter_map(|segment| match segment {
+            Segment::Text(text) if is_translatable(text) => Some(text.as_str()),
+            _ => None,
+        })
+    }
+
+    /// The text segments alone, for language detection and quality checks.
+    pub fn plain_text(&self) -> String {
+        self.segments
+            .iter()
+            .filter_map(|segment| match segment {
+                Segment::Text(text) => Some(text.as_str()),
+                Segment::Markup(_) => None,
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// The same markup around `translate`'s output for every text segment.
+    /// Whitespace around each segment is kept as it was, and segments
+    /// without letters or digits are copied without calling `translate`.
+    pub fn try_map_text<E>(
+        &self,
+        mut translate: impl FnMut(&str) -> Result<String, E>,
+    ) -> Result<Self, E> {
+        let segments = self
+            .segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Text(text) if is_translatable(text) => {
+                    let core = text.trim();
+                    let leading = &text[..text.len() - text.trim_start().len()];
+                    let trailing = &text[text.trim_end().len()..];
+                    let translated = translate(core)?;
+                    Ok(Segment::Text(format!(
+                        "{leading}{}{trailing}",
+                        translated.trim()
+                    )))
+                }
+                other => Ok(other.clone()),
+            })
+            .collect::<Result<_, E>>()?;
+        Ok(Self { segments })
+    }
+}
+
+impl fmt::Display for StructuredDocument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for segment in &self.segments {
+            match segment {
+                Segment::Markup(text) | Segment::Text(text) => f.write_str(text)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Appends segments, merging neighbours of the same kind.
+#[derive(Default)]
+struct Builder {
+    segments: Vec<Segment>,
+}
+
+impl Builder {
+    fn markup(&mut self, value: &str) {
+        if value.is_empty() {
+            return;
+        }
+        match self.segments.last_mut() {
+            Some(Segment::Markup(last)) => last.push_str(value),
+            _ => self.segments.push(Segment::Markup(value.to_owned())),
+        }
+    }
+
+    fn text(&mut self, value: &str) {
+        if value.is_empty() {
+            return;
+        }
+        match self.segments.last_mut() {
+            Some(Segment::Text(last)) => last.push_str(value),
+            _ => self.segments.push(Segment::Text(value.to_owned())),
+        }
+    }
+}
+
+fn is_translatable(text: &str) -> bool {
+    text.chars().any(char::is_alphanumeric)
+}
+
+/// Lines copied as they are: horizontal rules and setext underlines, table
+/// separators, HTML blocks, and link reference definitions.
+fn is_verbatim_line(trimmed: &str) -> bool {
+    let rule = trimmed
+        .chars()
+        .all(|ch| matches!(ch, '-' | '=' | '*' | '_' | ' ' | '|' | ':'));
+    let reference = trimmed.starts_with('[')
+        && trimmed
+            .find("]:")
+            .is_some_and(|end| !trimmed[1..end].contains(']'));
+    rule || trimmed.starts_with('<') || reference
+}
+
+/// Length of the indentation, quote markers, heading hashes, list marker,
+/// and task checkbox that open `line`.
+fn block_prefix_len(line: &str) -> usize {
+    let bytes = line.as_bytes();
+    let mut index = 0;
+    let skip_spaces = |index: &mut usize| {
+        while bytes
+            .get(*index)
+            .is_some_and(|byte| matches!(byte, b' ' | b'\t'))
+        {
+            *index += 1;
+        }
+    };
+
+    skip_spaces(&mut index);
+    while bytes.get(index) == Some(&b'>') {
+        index += 1;
+        skip_spaces(&mut index);
+    }
+
+    let hashes = bytes[index..]
+        .iter()
+        .take_while(|&&byte| byte == b'#')
+        .count();
+    if (1..=6).contains(&hashes) && bytes.get(index + hashes) == Some(&b' ') {
+        index += hashes;
+        skip_spaces(&mut index);
+        return index;
+    }
+
+    let digits = bytes[index..]
+        .iter()
+        .take_while(|byte| byte.is_ascii_digit())
+        .count();
+    let marker_len = match bytes.get(index) {
+        Some(b'-' | b'*' | b'+') => 1,
+        Some(_)
+            if (1..=9).contains(&digits)
+                && matches!(bytes.get(index + digits), Some(b'.' | b')')) =>
+        {
+            digits + 1
+        }
+        _ => 0,
+    };
+    if marker_len > 0 && bytes.get(index + marker_len) == Some(&b' ') {
+        index += marker_len;
+        skip_spaces(&mut index);
+        let rest = &line[index..];
+        if ["[ ] ", "[x] ", "[X] "]
+            .iter()
+            .any(|task| rest.starts_with(task))
+        {
+            index += 4;
+        }
+    }
+    index
+}
+
+fn tokenize_inline(content: &str, builder: &mut Builder) {
+    let mut rest = content;
+    while let Some(ch) = rest.chars().next() {
+        let markup_len = match ch {
+            '\\' => rest[1..]
+                .chars()
+                .next()
+                .filter(char::is_ascii_punctuation)
+                .map_or(0, |_| 2),
+            '`' => {
+                let run = rest.chars().take_while(|&ch| ch == '`').count();
+                rest[run..]
+                    .find(&rest[..run])
+                    .map_or(run, |close| run + close + run)
+            }
+            '*' | '~' | '|' => rest.chars().take_while(|&next| next == ch).count(),
+            // Underscores inside words (snake_case) are text.
+            '_' => {
+                let run = rest.chars().take_while(|&next| next == '_').count();
+                let before = content[..content.len() - rest.len()].chars().next_back();
+                let after = rest[run..].chars().next();
+                let intraword = before.is_some_and(char::is_alphanumeric)
+                    && after.is_some_and(char::is_alphanumeric);
+                if intraword { 0 } else { run }
+            }
+            '[' | '!' => {
+                let open = if ch == '!' { 2 } else { 1 };
+                if ch == '!' && !rest[1..].starts_with('[') {
+                    0
+                } else if let Some((label_end, link_end)) = link_spans(&rest[open - 1..]) {
+                    builder.markup(&rest[..open]);
+                    tokenize_inline(&rest[open..open - 1 + label_end], builder);
+                    builder.markup(&rest[open - 1 + label_end..open - 1 + link_end]);
+                    rest = &rest[open - 1 + link_end..];
+                    continue;
+                } else {
+                    0
+                }
+            }
+            '<' => {
+                let tag_like = rest[1..]
+                    .chars()
+                    .next()
+                    .is_some_and(|next| next.is_ascii_alphabetic() || next == '/' || next == '!');
+                match rest.find('>') {
+                    Some(end) if tag_like => end + 1,
+                    _ => 0,
+                }
+            }
+            'h' if rest.starts_with("http://") || rest.starts_with("https://") => rest
+                .find(|next: char| next.is_whitespace() || matches!(next, ')' | '>'))
+                .unwrap_or(rest.len()),
+            _ => 0,
+        };
+
+        if markup_len > 0 {
+            builder.markup(&rest[..markup_len]);
+            rest = &rest[markup_len..];
+        } else {
+            let len = ch.len_utf8();
+            builder.text(&rest[..len]);
+            rest = &rest[len..];
+        }
+    }
+}
+
+/// For `[label](target)` or `[label][ref]` at the start of `text`, the byte
+/// offsets of the closing `]` and of the end of the whole link.
+fn link_spans(text: &str) -> Option<(usize, usize)> {
+    let label_end = matching(text, '[', ']')?;
+    let after = &text[label_end + 1..];
+    let close = match after.chars().next()? {
+        '(' => ')',
+        '[' => ']',
+        _ => return None,
+    };
+    let open = if close == ')' { '(' } else { '[' };
+    let target_end = matching(after, open, close)?;
+    Some((label_end, label_end + 1 + target_end + 1))
+}
+
+/// Offset of the `close` matching the `open` that starts `text`.
+fn matching(text: &str, open: char, close: char) -> Option<usize> {
+    let mut depth = 0;
+    let mut escaped = false;
+    for (index, ch) in text.char_indices() {
+        match ch {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            _ if ch == open => depth += 1,
+            _ if ch == close => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(index);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shout(source: &str) -> String {
+        StructuredDocument::parse_markdown(source)
+            .try_map_text(|text| Ok::<_, ()>(text.to_uppercase()))
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn translates_text_and_keeps_markdown_markup() {
+        assert_eq!(shout("Some **bold** text"), "SOME **BOLD** TEXT");
+        assert_eq!(
+            shout("# Title\n\n- [a link](https://example.com/a_b) and `code_here`\n"),
+            "# TITLE\n\n- [A LINK](https://example.com/a_b) AND `code_here`\n"
+        );
+        assert_eq!(
+            shout("> quoted _emphasis_ with snake_case\n```rust\nlet x = 1;\n```\n"),
+            "> QUOTED _EMPHASIS_ WITH SNAKE_CASE\n```rust\nlet x = 1;\n```\n"
+        );
+
+        let wrapped = StructuredDocument::parse_markdown("first line\nsecond line\n\n1. item\n");
+        let texts: Vec<_> = wrapped.texts().collect();
+        assert_eq!(texts, ["first line\nsecond line", "item"]);
+    }
+}
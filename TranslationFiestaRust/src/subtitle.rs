@@ -0,0 +1,138 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+
+use crate::file_service::read_decoded_text;
+
+/// Separates cue texts in the translatable text; providers keep blank lines.
+const CUE_SEPARATOR: &str = "\n\n";
+
+/// One SubRip caption: its sequence number, verbatim timecodes, and text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SrtCue {
+    pub index: usize,
+    pub start: String,
+    pub end: String,
+    pub text: String,
+}
+
+pub fn is_srt_path(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("srt"))
+}
+
+pub fn parse_srt(raw: &str) -> Result<Vec<SrtCue>> {
+    let normalized = raw.trim_start_matches('\u{feff}').replace("\r\n", "\n");
+    let mut cues = Vec::new();
+
+    for block in normalized
+        .split("\n\n")
+        .filter(|block| !block.trim().is_empty())
+    {
+        let mut lines = block.trim_matches('\n').lines();
+        let index_line = lines.next().unwrap_or_default().trim();
+        let index = index_line
+            .parse::<usize>()
+            .with_context(|| format!("invalid subtitle index: {index_line}"))?;
+        let timing = lines.next().unwrap_or_default();
+        let Some((start, end)) = timing.split_once("-->") else {
+            bail!("cue {index} has no `start --> end` timing line");
+        };
+        cues.push(SrtCue {
+            index,
+            start: start.trim().to_owned(),
+            end: end.trim().to_owned(),
+            text: lines.collect::<Vec<_>>().join("\n"),
+        });
+    }
+
+    Ok(cues)
+}
+
+/// The captions alone, one blank-line separated block per cue, ready to
+/// translate.
+pub fn cues_to_text(cues: &[SrtCue]) -> String {
+    cues.iter()
+        .map(|cue| cue.text.as_str())
+        .collect::<Vec<_>>()
+        .join(CUE_SEPARATOR)
+}
+
+/// Pairs each block of `translated` with the timing of the matching cue.
+pub fn render_srt(cues: &[SrtCue], translated: &str) -> Result<String> {
+    let normalized = translated.replace("\r\n", "\n");
+    let blocks: Vec<&str> = normalized
+        .split(CUE_SEPARATOR)
+        .map(|block| block.trim_matches('\n'))
+        .filter(|block| !block.trim().is_empty())
+        .collect();
+    if blocks.len() != cues.len() {
+        bail!(
+            "translation has {} caption blocks but the subtitles have {} cues",
+            blocks.len(),
+            cues.len()
+        );
+    }
+
+    let mut output = String::new();
+    for (cue, text) in cues.iter().zip(blocks) {
+        output.push_str(&format!(
+            "{}\n{} --> {}\n{}\n\n",
+            cue.index,
+            cue.start,
+            cue.end,
+            text.trim()
+        ));
+    }
+    Ok(output)
+}
+
+/// Writes `translated` as an SRT file reusing the timecodes of `source_path`.
+pub fn export_srt(source_path: &Path, translated: &str, output_path: &Path) -> Result<()> {
+    let cues = parse_srt(&read_decoded_text(source_path)?)
+        .with_context(|| format!("failed to parse subtitles {}", source_path.display()))?;
+    let rendered = render_srt(&cues, translated)?;
+    fs::write(output_path, rendered)
+        .with_context(|| format!("failed to write {}", output_path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "1\r\n00:00:01,000 --> 00:00:02,500\r\nHello there.\r\n\r\n2\r\n00:00:03,000 --> 00:00:05,000\r\nHow are you?\r\nFine, thanks.\r\n\r\n3\r\n00:00:06,000 --> 00:00:07,000\r\nGoodbye.\r\n";
+
+    #[test]
+    fn parses_cues_and_extracts_caption_text() {
+        let cues = parse_srt(SAMPLE).unwrap();
+        assert_eq!(cues.len(), 3);
+        assert_eq!(
+            cues[1],
+            SrtCue {
+                index: 2,
+                start: "00:00:03,000".to_owned(),
+                end: "00:00:05,000".to_owned(),
+                text: "How are you?\nFine, thanks.".to_owned(),
+            }
+        );
+        assert_eq!(
+            cues_to_text(&cues),
+            "Hello there.\n\nHow are you?\nFine, thanks.\n\nGoodbye."
+        );
+    }
+
+    #[test]
+    fn reassembles_translated_text_against_original_timings() {
+        let cues = parse_srt(SAMPLE).unwrap();
+        let rendered = render_srt(&cues, "Hi.\n\nHow are you doing?\nGood.\n\nBye.").unwrap();
+        assert_eq!(
+            rendered,
+            "1\n00:00:01,000 --> 00:00:02,500\nHi.\n\n\
+             2\n00:00:03,000 --> 00:00:05,000\nHow are you doing?\nGood.\n\n\
+             3\n00:00:06,000 --> 00:00:07,000\nBye.\n\n"
+        );
+
+        assert!(render_srt(&cues, "Hi.\n\nBye.").is_err());
+    }
+}
@@ -0,0 +1,70 @@
+use std::fmt;
+
+use serde::Serialize;
+
+/// Size of a text in the units people and providers care about.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct TextStats {
+    pub chars: usize,
+    /// Whitespace-separated words, with each CJK character counted as a word
+    /// since those scripts are written without spaces.
+    pub words: usize,
+    pub bytes: usize,
+}
+
+impl fmt::Display for TextStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} words, {} characters", self.words, self.chars)
+    }
+}
+
+pub fn text_stats(text: &str) -> TextStats {
+    let words = text
+        .split_whitespace()
+        .map(|token| {
+            let ideographs = token.chars().filter(|&ch| is_cjk(ch)).count();
+            let has_other = token.chars().any(|ch| !is_cjk(ch) && ch.is_alphanumeric());
+            ideographs + usize::from(has_other)
+        })
+        .sum();
+
+    TextStats {
+        chars: text.chars().count(),
+        words,
+        bytes: text.len(),
+    }
+}
+
+fn is_cjk(ch: char) -> bool {
+    matches!(
+        ch,
+        '\u{3040}'..='\u{30ff}' // hiragana and katakana
+            | '\u{3400}'..='\u{4dbf}'
+            | '\u{4e00}'..='\u{9fff}'
+            | '\u{f900}'..='\u{faff}'
+            | '\u{ff66}'..='\u{ff9f}' // half-width katakana
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_words_in_spaced_and_cjk_text() {
+        assert_eq!(
+            text_stats("Hello, big  world!\n"),
+            TextStats {
+                chars: 19,
+                words: 3,
+                bytes: 19,
+            }
+        );
+
+        let japanese = text_stats("今日は晴れ。 OK");
+        assert_eq!(japanese.words, 6);
+        assert_eq!(japanese.chars, 9);
+        assert_eq!(japanese.bytes, 21);
+        assert_eq!(text_stats("  ").words, 0);
+    }
+}
@@ -0,0 +1,76 @@
+use chrono::Utc;
+
+use crate::build_info;
+use crate::html::escape_xml;
+
+/// One bilingual translation unit, written as a TMX `<tu>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TmxUnit<'a> {
+    pub source_text: &'a str,
+    pub target_text: &'a str,
+    /// Optional origin written as a `<prop type="x-file">` on the unit.
+    pub file_path: Option<&'a str>,
+}
+
+/// Renders a TMX 1.4 document with every unit sharing the same language pair.
+pub fn write_tmx(source_language: &str, target_language: &str, units: &[TmxUnit<'_>]) -> String {
+    let source_lang = escape_xml(source_language);
+    let target_lang = escape_xml(target_language);
+    let mut xml = String::new();
+
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<tmx version=\"1.4\">\n");
+    xml.push_str(&format!(
+        "  <header creationtool=\"TranslationFiesta Rust\" creationtoolversion=\"{}\" datatype=\"plaintext\" segtype=\"paragraph\" adminlang=\"en\" srclang=\"{}\" o-tmf=\"TranslationFiesta\" creationdate=\"{}\"/>\n",
+        escape_xml(build_info::TOOL_VERSION),
+        source_lang,
+        Utc::now().format("%Y%m%dT%H%M%SZ"),
+    ));
+    xml.push_str("  <body>\n");
+
+    for unit in units {
+        xml.push_str("    <tu>\n");
+        if let Some(file_path) = unit.file_path {
+            xml.push_str(&format!(
+                "      <prop type=\"x-file\">{}</prop>\n",
+                escape_xml(file_path)
+            ));
+        }
+        xml.push_str(&format!(
+            "      <tuv xml:lang=\"{}\"><seg>{}</seg></tuv>\n",
+            source_lang,
+            escape_xml(unit.source_text)
+        ));
+        xml.push_str(&format!(
+            "      <tuv xml:lang=\"{}\"><seg>{}</seg></tuv>\n",
+            target_lang,
+            escape_xml(unit.target_text)
+        ));
+        xml.push_str("    </tu>\n");
+    }
+
+    xml.push_str("  </body>\n");
+    xml.push_str("</tmx>\n");
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_language_tagged_units() {
+        let units = [TmxUnit {
+            source_text: "Fish & chips",
+            target_text: "フィッシュ＆チップス",
+            file_path: Some("menu.txt"),
+        }];
+
+        let xml = write_tmx("en", "ja", &units);
+
+        assert!(xml.contains("srclang=\"en\""));
+        assert!(xml.contains("<tuv xml:lang=\"en\"><seg>Fish &amp; chips</seg></tuv>"));
+        assert!(xml.contains("<tuv xml:lang=\"ja\"><seg>フィッシュ＆チップス</seg></tuv>"));
+        assert!(xml.contains("<prop type=\"x-file\">menu.txt</prop>"));
+    }
+}
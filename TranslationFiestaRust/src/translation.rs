@@ -1,24 +1,55 @@
-use std::sync::Arc;
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::{Duration, Instant};
 
 use anyhow::Result;
-use rand::Rng;
-use reqwest::StatusCode;
-use reqwest::blocking::{Client, Response};
-use serde_json::Value;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use reqwest::blocking::Client;
 use thiserror::Error;
 use tracing::{debug, info, warn};
+use unicode_normalization::{UnicodeNormalization, is_nfc};
 
-use crate::language::is_supported_language_code;
+use crate::glossary::Glossary;
+use crate::language::{LanguageGuess, canonical_language_code, guess_language};
 use crate::memory::TranslationMemory;
-use crate::models::{BackTranslationResult, ProviderId};
+use crate::models::{BackTranslationResult, BatchErrorKind, ProviderId};
+use crate::providers::{
+    DEEPL_FREE_ENDPOINT, DeepLProvider, GOOGLE_OFFICIAL_ENDPOINT, GOOGLE_UNOFFICIAL_ENDPOINT,
+    GoogleOfficialProvider, GoogleUnofficialProvider, LibreTranslateProvider, TranslationProvider,
+};
+use crate::quality::{QualityThresholds, suspicious_reason};
+use crate::settings::DEFAULT_LIBRETRANSLATE_URL;
+use crate::structured::StructuredDocument;
+
+/// `text` in Unicode normalization form C, borrowed when it already is.
+pub(crate) fn nfc(text: &str) -> Cow<'_, str> {
+    if is_nfc(text) {
+        Cow::Borrowed(text)
+    } else {
+        Cow::Owned(text.nfc().collect())
+    }
+}
+
+/// Longest input sent in one provider request; the unofficial Google
+/// endpoint truncates or rejects much longer `q` parameters.
+pub const DEFAULT_MAX_CHUNK_CHARS: usize = 1800;
+/// HTTP timeout when neither settings nor `TF_UNOFFICIAL_TIMEOUT_SECONDS` set one.
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Detection confidence below which an auto-detected source language is
+/// logged as a guess.
+const LOW_DETECTION_CONFIDENCE: f64 = 0.2;
 
 #[derive(Debug, Error, Clone)]
 pub enum TranslationError {
     #[error("cancelled")]
     Cancelled,
+    #[error("timed out after {0} ms")]
+    TimedOut(u64),
     #[error("provider rate limited")]
     RateLimited,
     #[error("provider blocked or captcha detected")]
@@ -29,58 +60,351 @@ pub enum TranslationError {
     Network(String),
     #[error("{0}")]
     InvalidInput(String),
+    /// The forward hop succeeded (and was cached) but the back hop failed.
+    #[error("back translation failed: {error}")]
+    BackHopFailed {
+        intermediate: String,
+        error: Box<TranslationError>,
+    },
+    /// A hop of a multi-pivot chain failed; `completed` holds the hops before it.
+    #[error("hop {hop} ({from} -> {to}) failed: {error}")]
+    ChainHopFailed {
+        hop: usize,
+        from: String,
+        to: String,
+        completed: Vec<(String, String)>,
+        error: Box<TranslationError>,
+    },
+}
+
+impl TranslationError {
+    /// The failure class a batch item records; hop failures report the
+    /// error of the hop that failed.
+    pub fn batch_error_kind(&self) -> BatchErrorKind {
+        match self {
+            Self::Cancelled => BatchErrorKind::Cancelled,
+            Self::RateLimited => BatchErrorKind::RateLimited,
+            Self::Blocked => BatchErrorKind::Blocked,
+            Self::TimedOut(_) | Self::InvalidResponse(_) | Self::Network(_) => {
+                BatchErrorKind::Network
+            }
+            Self::InvalidInput(_) => BatchErrorKind::InvalidInput,
+            Self::BackHopFailed { error, .. } | Self::ChainHopFailed { error, .. } => {
+                error.batch_error_kind()
+            }
+        }
+    }
+
+    /// Forward translation that was recovered before the failure, if any.
+    pub fn partial_intermediate(&self) -> Option<&str> {
+        match self {
+            Self::BackHopFailed { intermediate, .. } => Some(intermediate),
+            Self::ChainHopFailed { completed, .. } => {
+                completed.last().map(|(_, text)| text.as_str())
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Attempts per request and the base of the exponential backoff between them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub max_retries: usize,
+    pub base_retry_delay_ms: u64,
+}
+
+impl RetryPolicy {
+    pub fn new(max_retries: usize, base_retry_delay_ms: u64) -> Self {
+        Self {
+            max_retries: max_retries.max(1),
+            base_retry_delay_ms: base_retry_delay_ms.max(50),
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(4, 300)
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct TranslationService {
     client: Client,
+    request_timeout: Duration,
     memory: Arc<TranslationMemory>,
-    max_retries: usize,
-    base_retry_delay_ms: u64,
+    providers: HashMap<ProviderId, Arc<dyn TranslationProvider>>,
+    retry_policy: Arc<Mutex<RetryPolicy>>,
+    max_total_retry: Duration,
+    blocked_retries: usize,
+    blocked_cooldown: Duration,
+    min_request_interval: Duration,
+    next_request_at: Arc<Mutex<Option<Instant>>>,
+    rng: Arc<Mutex<StdRng>>,
+    quality_thresholds: QualityThresholds,
+    max_chunk_chars: usize,
+    glossary: Option<Arc<Glossary>>,
+    bypass_cache: bool,
+    segment_cache: bool,
 }
 
 impl TranslationService {
     pub fn new(memory: Arc<TranslationMemory>) -> Result<Self> {
-        let timeout = std::env::var("TF_UNOFFICIAL_TIMEOUT_SECONDS")
-            .ok()
-            .and_then(|value| value.parse::<u64>().ok())
-            .unwrap_or(20);
+        let request_timeout = request_timeout_from_env().unwrap_or(DEFAULT_REQUEST_TIMEOUT);
+        let client = build_client(request_timeout)?;
 
-        let client = Client::builder()
-            .timeout(Duration::from_secs(timeout))
-            .build()?;
+        let mut providers: HashMap<ProviderId, Arc<dyn TranslationProvider>> = HashMap::new();
+        providers.insert(
+            ProviderId::GoogleUnofficial,
+            Arc::new(GoogleUnofficialProvider::new(
+                client.clone(),
+                GOOGLE_UNOFFICIAL_ENDPOINT,
+            )),
+        );
+        providers.insert(
+            ProviderId::GoogleOfficial,
+            Arc::new(GoogleOfficialProvider::new(
+                client.clone(),
+                GOOGLE_OFFICIAL_ENDPOINT,
+                None,
+            )),
+        );
+        providers.insert(
+            ProviderId::DeepL,
+            Arc::new(DeepLProvider::new(
+                client.clone(),
+                DEEPL_FREE_ENDPOINT,
+                None,
+            )),
+        );
+
+        providers.insert(
+            ProviderId::LibreTranslate,
+            Arc::new(LibreTranslateProvider::new(
+                client.clone(),
+                DEFAULT_LIBRETRANSLATE_URL,
+            )),
+        );
 
         Ok(Self {
             client,
+            request_timeout,
             memory,
-            max_retries: 4,
-            base_retry_delay_ms: 300,
+            providers,
+            retry_policy: Arc::new(Mutex::new(RetryPolicy::default())),
+            max_total_retry: Duration::ZERO,
+            blocked_retries: 0,
+            blocked_cooldown: Duration::from_secs(300),
+            min_request_interval: Duration::ZERO,
+            next_request_at: Arc::new(Mutex::new(None)),
+            rng: Arc::new(Mutex::new(StdRng::from_entropy())),
+            quality_thresholds: QualityThresholds::default(),
+            max_chunk_chars: DEFAULT_MAX_CHUNK_CHARS,
+            glossary: None,
+            bypass_cache: false,
+            segment_cache: false,
         })
     }
 
     pub fn with_retry_policy(mut self, max_retries: usize, base_retry_delay_ms: u64) -> Self {
-        self.max_retries = max_retries.max(1);
-        self.base_retry_delay_ms = base_retry_delay_ms.max(50);
+        self.retry_policy = Arc::new(Mutex::new(RetryPolicy::new(
+            max_retries,
+            base_retry_delay_ms,
+        )));
         self
     }
 
-    pub fn detect_language(&self, text: &str) -> String {
-        let sample = text.trim();
-        if sample.is_empty() {
-            return "en".to_owned();
-        }
-
-        match whatlang::detect(sample) {
-            Some(info) => {
-                let code = info.lang().code();
-                if code.len() == 2 {
-                    code.to_owned()
-                } else {
-                    "en".to_owned()
-                }
+    /// Replaces the retry policy for every clone of this service; requests
+    /// already in their retry loop pick it up on their next attempt.
+    pub fn set_retry_policy(&self, max_retries: usize, base_retry_delay_ms: u64) {
+        *self
+            .retry_policy
+            .lock()
+            .expect("retry policy lock poisoned") =
+            RetryPolicy::new(max_retries, base_retry_delay_ms);
+    }
+
+    pub fn retry_policy(&self) -> RetryPolicy {
+        *self
+            .retry_policy
+            .lock()
+            .expect("retry policy lock poisoned")
+    }
+
+    /// Stops retrying rate-limit and network errors once the next backoff
+    /// would take the time spent on a request past `budget`, returning the
+    /// last error. Zero leaves only the attempt count as a limit.
+    pub fn with_max_total_retry(mut self, budget: Duration) -> Self {
+        self.max_total_retry = budget;
+        self
+    }
+
+    /// Opt-in: treat `Blocked` (captcha) as a severe rate limit and retry after a
+    /// long cooldown. Zero retries keeps the default fail-fast behavior.
+    pub fn with_blocked_retry_policy(mut self, max_retries: usize, cooldown: Duration) -> Self {
+        self.blocked_retries = max_retries;
+        self.blocked_cooldown = cooldown;
+        self
+    }
+
+    /// Spaces provider requests at least `interval` apart across every clone of
+    /// this service, so concurrent batch workers share one budget.
+    pub fn with_min_request_interval(mut self, interval: Duration) -> Self {
+        self.min_request_interval = interval;
+        self
+    }
+
+    /// Rebuilds the HTTP client with `timeout` and hands it to every built-in
+    /// provider, keeping their endpoints and keys; registered stubs stay as
+    /// they are.
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Result<Self> {
+        if timeout == self.request_timeout {
+            return Ok(self);
+        }
+
+        let client = build_client(timeout)?;
+        for provider in self.providers.values_mut() {
+            if let Some(rebuilt) = provider.with_client(client.clone()) {
+                *provider = rebuilt;
             }
-            None => "en".to_owned(),
         }
+        self.client = client;
+        self.request_timeout = timeout;
+        Ok(self)
+    }
+
+    pub fn request_timeout(&self) -> Duration {
+        self.request_timeout
+    }
+
+    /// Seeds the retry jitter so delay sequences are reproducible.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = Arc::new(Mutex::new(StdRng::seed_from_u64(seed)));
+        self
+    }
+
+    pub fn with_quality_thresholds(mut self, thresholds: QualityThresholds) -> Self {
+        self.quality_thresholds = thresholds;
+        self
+    }
+
+    /// Inputs longer than `max_chars` are split by `chunk_text` and
+    /// translated one chunk per request.
+    pub fn with_max_chunk_chars(mut self, max_chars: usize) -> Self {
+        self.max_chunk_chars = max_chars.max(1);
+        self
+    }
+
+    /// Provider requests a back-translation of `text` makes without cache
+    /// hits: one per chunk on each hop, assuming the intermediate text splits
+    /// into as many chunks as the original.
+    pub fn estimated_round_trip_requests(&self, text: &str) -> usize {
+        if text.trim().is_empty() {
+            return 0;
+        }
+        let chunks = if !self.segment_cache && text.chars().count() <= self.max_chunk_chars {
+            1
+        } else {
+            self.request_chunks(text)
+                .iter()
+                .filter(|chunk| !chunk.trim().is_empty())
+                .count()
+        };
+        chunks * 2
+    }
+
+    /// Skips translation memory lookups so every segment is fetched again;
+    /// the fresh translations still replace the cached ones.
+    pub fn with_bypass_cache(mut self, bypass: bool) -> Self {
+        self.bypass_cache = bypass;
+        self
+    }
+
+    /// Sends and caches every sentence as its own request, so a text that
+    /// differs from an earlier one by a sentence only fetches that sentence.
+    pub fn with_segment_cache(mut self, enabled: bool) -> Self {
+        self.segment_cache = enabled;
+        self
+    }
+
+    /// Protects the glossary's terms from every provider request.
+    pub fn with_glossary(mut self, glossary: Glossary) -> Self {
+        self.glossary = Some(Arc::new(glossary)).filter(|glossary| !glossary.is_empty());
+        self
+    }
+
+    /// Registers (or replaces) the backend used for `id`. Caching, throttling,
+    /// and retries stay in the service, so stubs only answer single requests.
+    pub fn with_provider(
+        mut self,
+        id: ProviderId,
+        provider: impl TranslationProvider + 'static,
+    ) -> Self {
+        self.providers.insert(id, Arc::new(provider));
+        self
+    }
+
+    /// Points the unofficial Google provider at a different endpoint.
+    pub fn with_endpoint(self, endpoint: impl Into<String>) -> Self {
+        let provider = GoogleUnofficialProvider::new(self.client.clone(), endpoint);
+        self.with_provider(ProviderId::GoogleUnofficial, provider)
+    }
+
+    /// A blank key leaves DeepL unconfigured, so DeepL requests fail with `InvalidInput`.
+    pub fn with_deepl_api_key(self, api_key: impl Into<String>) -> Self {
+        let provider = DeepLProvider::new(
+            self.client.clone(),
+            DEEPL_FREE_ENDPOINT,
+            Some(api_key.into()),
+        );
+        self.with_provider(ProviderId::DeepL, provider)
+    }
+
+    /// A blank key leaves the official Google provider unconfigured, so its
+    /// requests fail with `InvalidInput`.
+    pub fn with_google_api_key(self, api_key: impl Into<String>) -> Self {
+        let provider = GoogleOfficialProvider::new(
+            self.client.clone(),
+            GOOGLE_OFFICIAL_ENDPOINT,
+            Some(api_key.into()),
+        );
+        self.with_provider(ProviderId::GoogleOfficial, provider)
+    }
+
+    pub fn with_libretranslate_url(self, base_url: impl Into<String>) -> Self {
+        let provider = LibreTranslateProvider::new(self.client.clone(), base_url);
+        self.with_provider(ProviderId::LibreTranslate, provider)
+    }
+
+    /// Two-letter code of the text's language, falling back to `en` when
+    /// nothing can be detected.
+    pub fn detect_language(&self, text: &str) -> String {
+        self.detect_language_detailed(text).code
+    }
+
+    /// Like [`Self::detect_language`], keeping whatlang's confidence. An
+    /// undetectable text yields `en` with zero confidence.
+    pub fn detect_language_detailed(&self, text: &str) -> LanguageGuess {
+        guess_language(text).unwrap_or_else(|| LanguageGuess {
+            code: "en".to_owned(),
+            confidence: 0.0,
+            reliable: false,
+        })
+    }
+
+    /// Detected source language for a backtranslation, warning when the guess
+    /// is too weak to trust.
+    fn detect_source_language(&self, text: &str) -> String {
+        let guess = self.detect_language_detailed(text);
+        if guess.confidence < LOW_DETECTION_CONFIDENCE {
+            warn!(
+                "source language detection is unsure (guessed {} with confidence {:.2}); \
+                 pass an explicit source language if the result looks wrong",
+                guess.code, guess.confidence
+            );
+        }
+        guess.code
     }
 
     pub fn translate_text(
@@ -99,30 +423,123 @@ impl TranslationService {
             return Ok(String::new());
         }
 
-        validate_language_code(source_language)?;
-        validate_language_code(target_language)?;
+        let source_language = &validate_language_code(source_language)?;
+        let target_language = &validate_language_code(target_language)?;
+        // Decomposed and composed forms of the same text share a cache entry.
+        let text = &*nfc(text);
 
+        let Some(glossary) = &self.glossary else {
+            return self.translate_chunked(
+                text,
+                source_language,
+                target_language,
+                provider_id,
+                cancel_flag,
+            );
+        };
+        let protected = glossary.apply(text);
+        let translated = self.translate_chunked(
+            &protected.text,
+            source_language,
+            target_language,
+            provider_id,
+            cancel_flag,
+        )?;
+        Ok(glossary.restore(&translated, &protected.replacements))
+    }
+
+    fn translate_chunked(
+        &self,
+        text: &str,
+        source_language: &str,
+        target_language: &str,
+        provider_id: ProviderId,
+        cancel_flag: Option<&AtomicBool>,
+    ) -> std::result::Result<String, TranslationError> {
+        if !self.segment_cache && text.chars().count() <= self.max_chunk_chars {
+            return self.translate_segment(
+                text,
+                source_language,
+                target_language,
+                provider_id,
+                cancel_flag,
+            );
+        }
+
+        let chunks = self.request_chunks(text);
+        debug!(
+            "translating {} chars in {} chunks",
+            text.len(),
+            chunks.len()
+        );
+        let mut translated = String::with_capacity(text.len());
+        for chunk in chunks {
+            let trimmed = chunk.trim();
+            if trimmed.is_empty() {
+                translated.push_str(&chunk);
+                continue;
+            }
+            let start = chunk.len() - chunk.trim_start().len();
+            let end = start + trimmed.len();
+            translated.push_str(&chunk[..start]);
+            translated.push_str(&self.translate_segment(
+                trimmed,
+                source_language,
+                target_language,
+                provider_id,
+                cancel_flag,
+            )?);
+            translated.push_str(&chunk[end..]);
+        }
+        Ok(translated)
+    }
+
+    /// The pieces of `text` sent as separate requests: single sentences with
+    /// the segment cache, otherwise chunks of up to `max_chunk_chars`.
+    fn request_chunks(&self, text: &str) -> Vec<String> {
+        if !self.segment_cache {
+            return chunk_text(text, self.max_chunk_chars);
+        }
+        sentence_segments(text)
+            .into_iter()
+            .flat_map(|sentence| chunk_text(sentence, self.max_chunk_chars))
+            .collect()
+    }
+
+    /// One cached, retried provider request for already-validated codes.
+    fn translate_segment(
+        &self,
+        text: &str,
+        source_language: &str,
+        target_language: &str,
+        provider_id: ProviderId,
+        cancel_flag: Option<&AtomicBool>,
+    ) -> std::result::Result<String, TranslationError> {
         let normalized_provider = provider_id.as_str();
 
-        if let Ok(Some(cached)) =
-            self.memory
-                .lookup(text, source_language, target_language, normalized_provider)
+        if !self.bypass_cache
+            && let Ok(Some(cached)) =
+                self.memory
+                    .lookup(text, source_language, target_language, normalized_provider)
         {
             info!(
-                "translation memory hit ({} -> {})",
-                source_language, target_language
+                provider = normalized_provider,
+                source = source_language,
+                target = target_language,
+                "translation memory hit"
             );
             return Ok(cached);
         }
 
-        let encoded = urlencoding::encode(text);
-        let url = format!(
-            "https://translate.googleapis.com/translate_a/single?client=gtx&sl={source_language}&tl={target_language}&dt=t&q={encoded}"
-        );
-
-        let user_agent = std::env::var("TF_UNOFFICIAL_USER_AGENT").ok();
+        let provider = self.providers.get(&provider_id).ok_or_else(|| {
+            TranslationError::InvalidInput(format!(
+                "no translation provider registered for {normalized_provider}"
+            ))
+        })?;
 
         let mut attempt = 0;
+        let mut blocked_attempts = 0;
+        let mut retrying_since = Instant::now();
         loop {
             attempt += 1;
             if is_cancelled(cancel_flag) {
@@ -130,68 +547,85 @@ impl TranslationService {
             }
 
             debug!(
-                "translation attempt {attempt} ({} -> {})",
-                source_language, target_language
+                provider = normalized_provider,
+                source = source_language,
+                target = target_language,
+                attempt,
+                "translation attempt"
             );
 
-            let result = self.send_request(&url, user_agent.as_deref());
-            match result {
-                Ok(response) => match self.handle_response(response) {
-                    Ok(translated) => {
-                        if let Err(store_error) = self.memory.store(
-                            text,
-                            &translated,
-                            source_language,
-                            target_language,
-                            normalized_provider,
-                        ) {
-                            warn!("failed to persist translation memory entry: {store_error}");
-                        }
-                        return Ok(translated);
-                    }
-                    Err(error @ TranslationError::RateLimited) => {
-                        if attempt < self.max_retries {
-                            let delay = self.retry_delay(attempt);
-                            warn!("rate limited on attempt {attempt}, retrying in {delay:?}");
-                            sleep_with_cancel(delay, cancel_flag)?;
-                            continue;
-                        }
-                        return Err(error);
+            self.wait_for_request_slot(cancel_flag)?;
+            match provider.translate(text, source_language, target_language, cancel_flag) {
+                Ok(translated) => {
+                    if let Err(store_error) = self.memory.store(
+                        text,
+                        &translated,
+                        source_language,
+                        target_language,
+                        normalized_provider,
+                    ) {
+                        warn!("failed to persist translation memory entry: {store_error}");
                     }
-                    Err(error @ TranslationError::Network(_)) => {
-                        if attempt < self.max_retries {
-                            let delay = self.retry_delay(attempt);
-                            warn!("network error on attempt {attempt}, retrying in {delay:?}");
-                            sleep_with_cancel(delay, cancel_flag)?;
-                            continue;
+                    return Ok(translated);
+                }
+                Err(error @ TranslationError::RateLimited) => {
+                    if attempt < self.retry_policy().max_retries {
+                        let delay = self.retry_delay(attempt);
+                        if !self.within_retry_budget(retrying_since, delay) {
+                            warn!("rate limited on attempt {attempt}, retry budget exhausted");
+                            return Err(error);
                         }
-                        return Err(error);
+                        warn!("rate limited on attempt {attempt}, retrying in {delay:?}");
+                        sleep_with_cancel(delay, cancel_flag)?;
+                        continue;
                     }
-                    Err(error) => return Err(error),
-                },
-                Err(error) => {
-                    if attempt < self.max_retries {
-                        let delay = self.retry_delay(attempt);
+                    return Err(error);
+                }
+                Err(error @ TranslationError::Blocked) => {
+                    if blocked_attempts < self.blocked_retries {
+                        blocked_attempts += 1;
                         warn!(
-                            "request failed on attempt {attempt}, retrying in {delay:?}: {error}"
+                            "provider blocked request ({blocked_attempts}/{}), cooling down for {:?}",
+                            self.blocked_retries, self.blocked_cooldown
                         );
+                        sleep_with_cancel(self.blocked_cooldown, cancel_flag)?;
+                        // A cooldown starts a fresh budget for transient failures.
+                        attempt = 0;
+                        retrying_since = Instant::now();
+                        continue;
+                    }
+                    return Err(error);
+                }
+                Err(error @ TranslationError::Network(_)) => {
+                    if attempt < self.retry_policy().max_retries {
+                        let delay = self.retry_delay(attempt);
+                        if !self.within_retry_budget(retrying_since, delay) {
+                            warn!(
+                                "network error on attempt {attempt}, retry budget exhausted: {error}"
+                            );
+                            return Err(error);
+                        }
+                        warn!("network error on attempt {attempt}, retrying in {delay:?}: {error}");
                         sleep_with_cancel(delay, cancel_flag)?;
                         continue;
                     }
-                    return Err(TranslationError::Network(error.to_string()));
+                    return Err(error);
                 }
+                Err(error) => return Err(error),
             }
         }
     }
 
-    pub fn back_translate(
+    /// The checks `back_translate` makes before its first request: non-empty
+    /// text, valid language codes, and a registered provider. Returns the
+    /// source language the run would use, detecting it when none is given.
+    pub fn validate_back_translation(
         &self,
         text: &str,
         source_language: Option<&str>,
         intermediate_language: &str,
         provider_id: ProviderId,
-        cancel_flag: Option<&AtomicBool>,
-    ) -> std::result::Result<BackTranslationResult, TranslationError> {
+    ) -> std::result::Result<String, TranslationError> {
         let input = text.trim();
         if input.is_empty() {
             return Err(TranslationError::InvalidInput(
@@ -200,18 +634,48 @@ impl TranslationService {
         }
 
         validate_language_code(intermediate_language)?;
+        if !self.providers.contains_key(&provider_id) {
+            return Err(TranslationError::InvalidInput(format!(
+                "no translation provider registered for {}",
+                provider_id.as_str()
+            )));
+        }
 
-        let source = source_language
+        match source_language
             .map(str::trim)
             .filter(|value| !value.is_empty())
-            .map(ToOwned::to_owned)
-            .unwrap_or_else(|| self.detect_language(input));
+        {
+            Some(source) => {
+                validate_language_code(source)?;
+                Ok(source.to_owned())
+            }
+            None => Ok(self.detect_source_language(input)),
+        }
+    }
+
+    pub fn back_translate(
+        &self,
+        text: &str,
+        source_language: Option<&str>,
+        intermediate_language: &str,
+        provider_id: ProviderId,
+        cancel_flag: Option<&AtomicBool>,
+    ) -> std::result::Result<BackTranslationResult, TranslationError> {
+        let input = text.trim();
+        let source = self.validate_back_translation(
+            input,
+            source_language,
+            intermediate_language,
+            provider_id,
+        )?;
 
         let started_at = Instant::now();
 
         info!(
-            "starting backtranslation {} -> {} -> {}",
-            source, intermediate_language, source
+            provider = provider_id.as_str(),
+            source = %source,
+            target = intermediate_language,
+            "starting backtranslation"
         );
 
         let intermediate = self.translate_text(
@@ -221,20 +685,39 @@ impl TranslationService {
             provider_id,
             cancel_flag,
         )?;
+        let forward = started_at.elapsed();
 
         if is_cancelled(cancel_flag) {
             return Err(TranslationError::Cancelled);
         }
 
-        let back_translated = self.translate_text(
+        let back_started_at = Instant::now();
+        let back_translated = match self.translate_text(
             &intermediate,
             intermediate_language,
             &source,
             provider_id,
             cancel_flag,
-        )?;
+        ) {
+            Ok(back_translated) => back_translated,
+            Err(TranslationError::Cancelled) => return Err(TranslationError::Cancelled),
+            Err(error) => {
+                warn!("back hop failed after a successful forward hop: {error}");
+                return Err(TranslationError::BackHopFailed {
+                    intermediate,
+                    error: Box::new(error),
+                });
+            }
+        };
+        let backward = back_started_at.elapsed();
 
-        Ok(BackTranslationResult::new(
+        let suspicion = suspicious_reason(
+            input,
+            &intermediate,
+            &back_translated,
+            &self.quality_thresholds,
+        );
+        let mut result = BackTranslationResult::new(
             input.to_owned(),
             intermediate,
             back_translated,
@@ -242,63 +725,258 @@ impl TranslationService {
             intermediate_language.to_owned(),
             provider_id,
             started_at.elapsed(),
-        ))
+        )
+        .with_hop_durations(forward, backward);
+        if let Some(reason) = suspicion {
+            warn!("suspicious backtranslation: {reason}");
+            result.flag_suspicious(reason);
+        }
+        info!(
+            provider = provider_id.as_str(),
+            source = %result.source_language,
+            target = intermediate_language,
+            duration_ms = result.duration_ms as u64,
+            "backtranslation completed"
+        );
+
+        Ok(result)
     }
 
-    fn send_request(&self, url: &str, user_agent: Option<&str>) -> reqwest::Result<Response> {
-        let mut request = self
-            .client
-            .get(url)
-            .header("Accept", "application/json,text/plain,*/*");
+    /// Like `back_translate`, but only the document's text segments are sent to
+    /// the provider, so its markup comes back unchanged in both the
+    /// intermediate and the back-translated text. Quality checks look at the
+    /// text segments alone.
+    pub fn back_translate_structured(
+        &self,
+        document: &StructuredDocument,
+        source_language: Option<&str>,
+        intermediate_language: &str,
+        provider_id: ProviderId,
+        cancel_flag: Option<&AtomicBool>,
+    ) -> std::result::Result<BackTranslationResult, TranslationError> {
+        let plain = document.plain_text();
+        let source = self.validate_back_translation(
+            &plain,
+            source_language,
+            intermediate_language,
+            provider_id,
+        )?;
 
-        if let Some(agent) = user_agent
-            && !agent.trim().is_empty()
-        {
-            request = request.header("User-Agent", agent.trim());
-        }
+        let started_at = Instant::now();
+        info!(
+            provider = provider_id.as_str(),
+            source = %source,
+            target = intermediate_language,
+            segments = document.texts().count(),
+            "starting structured backtranslation"
+        );
 
-        request.send()
-    }
+        let intermediate = document.try_map_text(|text| {
+            self.translate_text(
+                text,
+                &source,
+                intermediate_language,
+                provider_id,
+                cancel_flag,
+            )
+        })?;
+        let forward = started_at.elapsed();
 
-    fn handle_response(&self, response: Response) -> std::result::Result<String, TranslationError> {
-        let status = response.status();
-        let body = response
-            .text()
-            .map_err(|err| TranslationError::Network(err.to_string()))?;
+        if is_cancelled(cancel_flag) {
+            return Err(TranslationError::Cancelled);
+        }
 
-        if status == StatusCode::TOO_MANY_REQUESTS {
-            return Err(TranslationError::RateLimited);
+        let back_started_at = Instant::now();
+        let back_translated = match intermediate.try_map_text(|text| {
+            self.translate_text(
+                text,
+                intermediate_language,
+                &source,
+                provider_id,
+                cancel_flag,
+            )
+        }) {
+            Ok(back_translated) => back_translated,
+            Err(TranslationError::Cancelled) => return Err(TranslationError::Cancelled),
+            Err(error) => {
+                warn!("back hop failed after a successful forward hop: {error}");
+                return Err(TranslationError::BackHopFailed {
+                    intermediate: intermediate.to_string(),
+                    error: Box::new(error),
+                });
+            }
+        };
+        let backward = back_started_at.elapsed();
+
+        let suspicion = suspicious_reason(
+            &plain,
+            &intermediate.plain_text(),
+            &back_translated.plain_text(),
+            &self.quality_thresholds,
+        );
+        let mut result = BackTranslationResult::new(
+            document.to_string(),
+            intermediate.to_string(),
+            back_translated.to_string(),
+            source,
+            intermediate_language.to_owned(),
+            provider_id,
+            started_at.elapsed(),
+        )
+        .with_hop_durations(forward, backward);
+        if let Some(reason) = suspicion {
+            warn!("suspicious backtranslation: {reason}");
+            result.flag_suspicious(reason);
         }
 
-        if status == StatusCode::FORBIDDEN {
-            return Err(TranslationError::Blocked);
+        Ok(result)
+    }
+
+    /// Translates through each pivot in order and finally back to the source,
+    /// returning the result plus every hop's `(language, text)`. The result's
+    /// intermediate is the last pivot, since that is the text translated back.
+    pub fn back_translate_chain(
+        &self,
+        text: &str,
+        source_language: Option<&str>,
+        pivots: &[&str],
+        provider_id: ProviderId,
+        cancel_flag: Option<&AtomicBool>,
+    ) -> std::result::Result<(BackTranslationResult, Vec<(String, String)>), TranslationError> {
+        let input = text.trim();
+        if input.is_empty() {
+            return Err(TranslationError::InvalidInput(
+                "text cannot be empty".to_owned(),
+            ));
+        }
+        if pivots.is_empty() {
+            return Err(TranslationError::InvalidInput(
+                "at least one pivot language is required".to_owned(),
+            ));
+        }
+        for pivot in pivots {
+            validate_language_code(pivot)?;
         }
 
-        if !status.is_success() {
-            return Err(TranslationError::InvalidResponse(format!(
-                "HTTP {}",
-                status.as_u16()
-            )));
+        let source = source_language
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(ToOwned::to_owned)
+            .unwrap_or_else(|| self.detect_source_language(input));
+
+        let started_at = Instant::now();
+        info!(
+            provider = provider_id.as_str(),
+            source = %source,
+            pivots = %pivots.join(","),
+            "starting chained backtranslation"
+        );
+
+        let mut hops: Vec<(String, String)> = Vec::with_capacity(pivots.len() + 1);
+        let mut current_language = source.clone();
+        let mut current_text = input.to_owned();
+        let mut hop_started_at = started_at;
+        for (index, target) in pivots
+            .iter()
+            .map(|pivot| pivot.trim())
+            .chain(std::iter::once(source.as_str()))
+            .enumerate()
+        {
+            if is_cancelled(cancel_flag) {
+                return Err(TranslationError::Cancelled);
+            }
+
+            hop_started_at = Instant::now();
+            match self.translate_text(
+                &current_text,
+                &current_language,
+                target,
+                provider_id,
+                cancel_flag,
+            ) {
+                Ok(translated) => {
+                    hops.push((target.to_owned(), translated.clone()));
+                    current_language = target.to_owned();
+                    current_text = translated;
+                }
+                Err(TranslationError::Cancelled) => return Err(TranslationError::Cancelled),
+                Err(error) => {
+                    warn!("chain hop {} failed: {error}", index + 1);
+                    return Err(TranslationError::ChainHopFailed {
+                        hop: index + 1,
+                        from: current_language,
+                        to: target.to_owned(),
+                        completed: hops,
+                        error: Box::new(error),
+                    });
+                }
+            }
         }
 
-        if body.trim().is_empty() {
-            return Err(TranslationError::InvalidResponse(
-                "empty response body".to_owned(),
-            ));
+        // Every hop up to the last pivot counts as forward.
+        let forward = hop_started_at.duration_since(started_at);
+        let backward = hop_started_at.elapsed();
+        let back_translated = current_text;
+        let (intermediate_language, intermediate) = hops[hops.len() - 2].clone();
+        let suspicion = suspicious_reason(
+            input,
+            &intermediate,
+            &back_translated,
+            &self.quality_thresholds,
+        );
+        let mut result = BackTranslationResult::new(
+            input.to_owned(),
+            intermediate,
+            back_translated,
+            source,
+            intermediate_language,
+            provider_id,
+            started_at.elapsed(),
+        )
+        .with_hop_durations(forward, backward);
+        if let Some(reason) = suspicion {
+            warn!("suspicious backtranslation: {reason}");
+            result.flag_suspicious(reason);
         }
 
-        let lower = body.to_ascii_lowercase();
-        if lower.contains("<html") || lower.contains("captcha") {
-            return Err(TranslationError::Blocked);
+        Ok((result, hops))
+    }
+
+    fn wait_for_request_slot(
+        &self,
+        cancel_flag: Option<&AtomicBool>,
+    ) -> std::result::Result<(), TranslationError> {
+        if self.min_request_interval.is_zero() {
+            return Ok(());
         }
 
-        parse_unofficial_google_response(&body)
+        let wait = {
+            let mut next_request_at = self
+                .next_request_at
+                .lock()
+                .expect("request throttle lock poisoned");
+            let now = Instant::now();
+            let slot = next_request_at.map_or(now, |at| at.max(now));
+            *next_request_at = Some(slot + self.min_request_interval);
+            slot - now
+        };
+
+        sleep_with_cancel(wait, cancel_flag)
+    }
+
+    fn within_retry_budget(&self, retrying_since: Instant, delay: Duration) -> bool {
+        self.max_total_retry.is_zero() || retrying_since.elapsed() + delay <= self.max_total_retry
     }
 
     fn retry_delay(&self, attempt: usize) -> Duration {
-        let jitter_ms: u64 = rand::thread_rng().gen_range(50..=220);
+        let jitter_ms: u64 = self
+            .rng
+            .lock()
+            .expect("retry jitter lock poisoned")
+            .gen_range(50..=220);
         let exp = (2_u64).saturating_pow(attempt.saturating_sub(1) as u32);
         let delay_ms = self
+            .retry_policy()
             .base_retry_delay_ms
             .saturating_mul(exp)
             .saturating_add(jitter_ms)
@@ -307,15 +985,116 @@ impl TranslationService {
     }
 }
 
-fn validate_language_code(code: &str) -> std::result::Result<(), TranslationError> {
-    let trimmed = code.trim();
-    if is_supported_language_code(trimmed) {
-        return Ok(());
+/// Splits `text` into chunks of at most `max_chars` characters, breaking
+/// after paragraph and sentence ends where possible, then between words, and
+/// only mid-word as a last resort. Concatenating the chunks gives back
+/// `text` exactly, so whitespace between chunks is kept.
+pub fn chunk_text(text: &str, max_chars: usize) -> Vec<String> {
+    let max_chars = max_chars.max(1);
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut current_chars = 0;
+
+    for segment in sentence_segments(text) {
+        let segment_chars = segment.chars().count();
+        if current_chars + segment_chars <= max_chars {
+            current.push_str(segment);
+            current_chars += segment_chars;
+            continue;
+        }
+        if !current.is_empty() {
+            chunks.push(std::mem::take(&mut current));
+            current_chars = 0;
+        }
+        if segment_chars <= max_chars {
+            current.push_str(segment);
+            current_chars = segment_chars;
+            continue;
+        }
+
+        for word in segment.split_inclusive(char::is_whitespace) {
+            for piece in split_at_char_limit(word, max_chars) {
+                let piece_chars = piece.chars().count();
+                if current_chars + piece_chars > max_chars && !current.is_empty() {
+                    chunks.push(std::mem::take(&mut current));
+                    current_chars = 0;
+                }
+                current.push_str(piece);
+                current_chars += piece_chars;
+            }
+        }
     }
 
-    Err(TranslationError::InvalidInput(format!(
-        "invalid language code: {code}"
-    )))
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// Sentences (or lines) with their trailing whitespace attached.
+fn sentence_segments(text: &str) -> Vec<&str> {
+    let mut segments = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((_, ch)) = chars.next() {
+        let ends_sentence = matches!(ch, '.' | '!' | '?' | '\n' | '。' | '！' | '？');
+        if !ends_sentence {
+            continue;
+        }
+        let at_break = match chars.peek() {
+            Some((_, next)) => next.is_whitespace() || !ch.is_ascii(),
+            None => true,
+        };
+        if !at_break {
+            continue;
+        }
+        while let Some((_, next)) = chars.peek() {
+            if !next.is_whitespace() {
+                break;
+            }
+            chars.next();
+        }
+        let end = chars.peek().map_or(text.len(), |(index, _)| *index);
+        segments.push(&text[start..end]);
+        start = end;
+    }
+    if start < text.len() {
+        segments.push(&text[start..]);
+    }
+    segments
+}
+
+fn split_at_char_limit(text: &str, max_chars: usize) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut rest = text;
+    while rest.chars().count() > max_chars {
+        let split = rest
+            .char_indices()
+            .nth(max_chars)
+            .map_or(rest.len(), |(index, _)| index);
+        pieces.push(&rest[..split]);
+        rest = &rest[split..];
+    }
+    pieces.push(rest);
+    pieces
+}
+
+/// Returns the canonical BCP-47 form of `code`, which is what providers receive.
+fn validate_language_code(code: &str) -> std::result::Result<String, TranslationError> {
+    canonical_language_code(code)
+        .ok_or_else(|| TranslationError::InvalidInput(format!("invalid language code: {code}")))
+}
+
+/// `TF_UNOFFICIAL_TIMEOUT_SECONDS`, which overrides the configured timeout.
+pub fn request_timeout_from_env() -> Option<Duration> {
+    std::env::var("TF_UNOFFICIAL_TIMEOUT_SECONDS")
+        .ok()
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+}
+
+fn build_client(timeout: Duration) -> Result<Client> {
+    Ok(Client::builder().timeout(timeout).build()?)
 }
 
 fn sleep_with_cancel(
@@ -338,53 +1117,179 @@ fn is_cancelled(cancel_flag: Option<&AtomicBool>) -> bool {
         .unwrap_or(false)
 }
 
-pub fn parse_unofficial_google_response(
-    body: &str,
-) -> std::result::Result<String, TranslationError> {
-    let parsed: Value = serde_json::from_str(body)
-        .map_err(|err| TranslationError::InvalidResponse(err.to_string()))?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::atomic::AtomicUsize;
+
+    use tempfile::TempDir;
+
+    /// Serves the given raw HTTP responses, one per connection, and returns the endpoint URL.
+    fn serve_responses(responses: Vec<String>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            for response in responses {
+                let Ok((mut stream, _)) = listener.accept() else {
+                    return;
+                };
+                let mut request = Vec::new();
+                let mut buffer = [0_u8; 1024];
+                while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+                    match stream.read(&mut buffer) {
+                        Ok(0) | Err(_) => break,
+                        Ok(read) => request.extend_from_slice(&buffer[..read]),
+                    }
+                }
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        format!("http://{address}/translate_a/single")
+    }
 
-    let root = parsed
-        .as_array()
-        .ok_or_else(|| TranslationError::InvalidResponse("root is not an array".to_owned()))?;
+    fn http_response(status: &str, body: &str) -> String {
+        format!(
+            "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+    }
 
-    let segments = root.first().and_then(Value::as_array).ok_or_else(|| {
-        TranslationError::InvalidResponse("missing translation segments".to_owned())
-    })?;
+    fn service_with_endpoint(temp: &TempDir, endpoint: String) -> TranslationService {
+        let memory = Arc::new(TranslationMemory::new(&temp.path().join("memory.db"), 100).unwrap());
+        TranslationService::new(memory)
+            .unwrap()
+            .with_endpoint(endpoint)
+    }
 
-    let mut result = String::new();
-    for sentence in segments {
-        if let Some(parts) = sentence.as_array()
-            && let Some(part) = parts.first().and_then(Value::as_str)
-        {
-            result.push_str(part);
+    /// A service whose Google provider fails at once, so a test that forgets
+    /// to register its own provider never reaches the network.
+    fn offline_service(temp: &TempDir) -> TranslationService {
+        let memory = Arc::new(TranslationMemory::new(&temp.path().join("memory.db"), 100).unwrap());
+        TranslationService::new(memory).unwrap().with_provider(
+            ProviderId::GoogleUnofficial,
+            FailingProvider {
+                calls: Arc::new(AtomicUsize::new(0)),
+            },
+        )
+    }
+
+    #[test]
+    fn errors_map_to_batch_error_kinds() {
+        let cases = [
+            (TranslationError::Cancelled, BatchErrorKind::Cancelled),
+            (TranslationError::TimedOut(500), BatchErrorKind::Network),
+            (TranslationError::RateLimited, BatchErrorKind::RateLimited),
+            (TranslationError::Blocked, BatchErrorKind::Blocked),
+            (
+                TranslationError::InvalidResponse("HTTP 500".to_owned()),
+                BatchErrorKind::Network,
+            ),
+            (
+                TranslationError::Network("reset".to_owned()),
+                BatchErrorKind::Network,
+            ),
+            (
+                TranslationError::InvalidInput("empty".to_owned()),
+                BatchErrorKind::InvalidInput,
+            ),
+            (
+                TranslationError::BackHopFailed {
+                    intermediate: "こんにちは".to_owned(),
+                    error: Box::new(TranslationError::RateLimited),
+                },
+                BatchErrorKind::RateLimited,
+            ),
+            (
+                TranslationError::ChainHopFailed {
+                    hop: 2,
+                    from: "ja".to_owned(),
+                    to: "de".to_owned(),
+                    completed: Vec::new(),
+                    error: Box::new(TranslationError::Blocked),
+                },
+                BatchErrorKind::Blocked,
+            ),
+        ];
+        for (error, kind) in cases {
+            assert_eq!(error.batch_error_kind(), kind, "{error}");
         }
+        assert_eq!(
+            "rate-limited".parse::<BatchErrorKind>(),
+            Ok(BatchErrorKind::RateLimited)
+        );
+        assert!("flaky".parse::<BatchErrorKind>().is_err());
     }
 
-    if result.trim().is_empty() {
-        return Err(TranslationError::InvalidResponse(
-            "no translation segments returned".to_owned(),
-        ));
+    #[test]
+    fn seeded_services_produce_identical_retry_delays() {
+        let temp = TempDir::new().unwrap();
+        let delays = |service: &TranslationService| {
+            (1..=5)
+                .map(|attempt| service.retry_delay(attempt))
+                .collect::<Vec<_>>()
+        };
+
+        let first = offline_service(&temp).with_seed(42);
+        let second = offline_service(&temp).with_seed(42);
+
+        assert_eq!(delays(&first), delays(&second));
     }
 
-    Ok(result)
-}
+    #[test]
+    fn deepl_without_key_is_rejected_before_any_request() {
+        let temp = TempDir::new().unwrap();
+        let service = offline_service(&temp);
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let error = service
+            .translate_text("hello", "en", "de", ProviderId::DeepL, None)
+            .unwrap_err();
+
+        assert!(
+            matches!(error, TranslationError::InvalidInput(message) if message.contains("DeepL API key"))
+        );
+    }
 
     #[test]
-    fn parses_unofficial_response_segments() {
-        let body = r#"[[["こんにちは", "hello", null, null, 1],["！","!",null,null,1]] ]"#;
-        let parsed = parse_unofficial_google_response(body).unwrap();
-        assert_eq!(parsed, "こんにちは！");
+    fn deepl_request_uses_configured_endpoint() {
+        let temp = TempDir::new().unwrap();
+        let endpoint = serve_responses(vec![http_response(
+            "200 OK",
+            r#"{"translations":[{"text":"Hallo"}]}"#,
+        )]);
+        let service = offline_service(&temp);
+        let provider = DeepLProvider::new(
+            service.client.clone(),
+            endpoint,
+            Some("test-key:fx".to_owned()),
+        );
+        let service = service.with_provider(ProviderId::DeepL, provider);
+
+        let translated = service
+            .translate_text("hello", "en", "de", ProviderId::DeepL, None)
+            .unwrap();
+
+        assert_eq!(translated, "Hallo");
     }
 
     #[test]
-    fn rejects_invalid_response_shape() {
-        let error = parse_unofficial_google_response("{}").unwrap_err();
-        assert!(matches!(error, TranslationError::InvalidResponse(_)));
+    fn libretranslate_rate_limit_is_retried() {
+        let temp = TempDir::new().unwrap();
+        let endpoint = serve_responses(vec![
+            http_response("429 Too Many Requests", ""),
+            http_response("200 OK", r#"{"translatedText":"Hallo"}"#),
+        ]);
+        let base_url = endpoint.trim_end_matches("/translate_a/single").to_owned();
+        let service = offline_service(&temp)
+            .with_retry_policy(2, 50)
+            .with_libretranslate_url(base_url);
+
+        let translated = service
+            .translate_text("hello", "en", "de", ProviderId::LibreTranslate, None)
+            .unwrap();
+
+        assert_eq!(translated, "Hallo");
     }
 
     #[test]
@@ -395,4 +1300,647 @@ mod tests {
         assert!(validate_language_code("eng").is_ok());
         assert!(validate_language_code("english").is_err());
     }
+
+    #[test]
+    fn blocked_fails_fast_by_default() {
+        let temp = TempDir::new().unwrap();
+        let endpoint = serve_responses(vec![http_response("403 Forbidden", "")]);
+        let service = service_with_endpoint(&temp, endpoint);
+
+        let error = service
+            .translate_text("hello", "en", "ja", ProviderId::GoogleUnofficial, None)
+            .unwrap_err();
+        assert!(matches!(error, TranslationError::Blocked));
+    }
+
+    #[test]
+    fn blocked_retry_waits_for_cooldown_then_succeeds() {
+        let temp = TempDir::new().unwrap();
+        let endpoint = serve_responses(vec![
+            http_response("403 Forbidden", ""),
+            http_response("200 OK", r#"[[["こんにちは","hello",null,null,1]]]"#),
+        ]);
+        let service = service_with_endpoint(&temp, endpoint)
+            .with_blocked_retry_policy(1, Duration::from_millis(80));
+
+        let started = Instant::now();
+        let translated = service
+            .translate_text("hello", "en", "ja", ProviderId::GoogleUnofficial, None)
+            .unwrap();
+
+        assert_eq!(translated, "こんにちは");
+        assert!(started.elapsed() >= Duration::from_millis(80));
+    }
+
+    #[test]
+    fn back_hop_failure_keeps_the_intermediate_text() {
+        let temp = TempDir::new().unwrap();
+        let service = offline_service(&temp).with_retry_policy(1, 50);
+        service
+            .memory
+            .store("hello", "こんにちは", "en", "ja", "google_unofficial")
+            .unwrap();
+
+        let error = service
+            .back_translate(
+                "hello",
+                Some("en"),
+                "ja",
+                ProviderId::GoogleUnofficial,
+                None,
+            )
+            .unwrap_err();
+
+        assert!(matches!(error, TranslationError::BackHopFailed { .. }));
+        assert_eq!(error.partial_intermediate(), Some("こんにちは"));
+    }
+
+    #[derive(Debug)]
+    struct CountingProvider {
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl TranslationProvider for CountingProvider {
+        fn translate(
+            &self,
+            text: &str,
+            _source_language: &str,
+            target_language: &str,
+            _cancel_flag: Option<&AtomicBool>,
+        ) -> std::result::Result<String, TranslationError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(format!("[{target_language}] {text}"))
+        }
+    }
+
+    #[derive(Debug)]
+    struct FailingProvider {
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl TranslationProvider for FailingProvider {
+        fn translate(
+            &self,
+            _text: &str,
+            _source_language: &str,
+            _target_language: &str,
+            _cancel_flag: Option<&AtomicBool>,
+        ) -> std::result::Result<String, TranslationError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Err(TranslationError::Network("connection reset".to_owned()))
+        }
+    }
+
+    #[test]
+    fn retries_stop_once_the_total_budget_is_spent() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let temp = TempDir::new().unwrap();
+        let service = offline_service(&temp)
+            .with_provider(
+                ProviderId::GoogleUnofficial,
+                FailingProvider {
+                    calls: Arc::clone(&calls),
+                },
+            )
+            .with_retry_policy(10, 100)
+            .with_max_total_retry(Duration::from_millis(600));
+
+        let started = Instant::now();
+        let error = service
+            .translate_text("hello", "en", "ja", ProviderId::GoogleUnofficial, None)
+            .unwrap_err();
+
+        assert!(matches!(error, TranslationError::Network(_)));
+        assert!(started.elapsed() < Duration::from_millis(900));
+        let calls = calls.load(Ordering::SeqCst);
+        assert!((2..10).contains(&calls), "{calls} attempts");
+    }
+
+    #[test]
+    fn registered_provider_is_used_and_cached_by_the_service() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let temp = TempDir::new().unwrap();
+        let service = offline_service(&temp).with_provider(
+            ProviderId::GoogleUnofficial,
+            CountingProvider {
+                calls: Arc::clone(&calls),
+            },
+        );
+
+        let first = service
+            .back_translate(
+                "hello",
+                Some("en"),
+                "ja",
+                ProviderId::GoogleUnofficial,
+                None,
+            )
+            .unwrap();
+        assert_eq!(first.intermediate_text, "[ja] hello");
+        assert_eq!(first.back_translated_text, "[en] [ja] hello");
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        service
+            .back_translate(
+                "hello",
+                Some("en"),
+                "ja",
+                ProviderId::GoogleUnofficial,
+                None,
+            )
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn segment_cache_only_sends_changed_sentences() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let temp = TempDir::new().unwrap();
+        let service = offline_service(&temp)
+            .with_provider(
+                ProviderId::GoogleUnofficial,
+                CountingProvider {
+                    calls: Arc::clone(&calls),
+                },
+            )
+            .with_segment_cache(true);
+
+        let first = "One fish. Two fish. Red fish.\nBlue fish.";
+        let translated = service
+            .translate_text(first, "en", "ja", ProviderId::GoogleUnofficial, None)
+            .unwrap();
+        assert_eq!(
+            translated,
+            "[ja] One fish. [ja] Two fish. [ja] Red fish.\n[ja] Blue fish."
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+
+        let second = "One fish. Two fish. Green fish.\nBlue fish.";
+        let translated = service
+            .translate_text(second, "en", "ja", ProviderId::GoogleUnofficial, None)
+            .unwrap();
+        assert_eq!(
+            translated,
+            "[ja] One fish. [ja] Two fish. [ja] Green fish.\n[ja] Blue fish."
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 5);
+    }
+
+    #[test]
+    fn composed_and_decomposed_text_share_a_cache_entry() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let temp = TempDir::new().unwrap();
+        let service = offline_service(&temp).with_provider(
+            ProviderId::GoogleUnofficial,
+            CountingProvider {
+                calls: Arc::clone(&calls),
+            },
+        );
+
+        for text in ["caf\u{e9} cr\u{e8}me", "cafe\u{301} cre\u{300}me"] {
+            let translated = service
+                .translate_text(text, "fr", "ja", ProviderId::GoogleUnofficial, None)
+                .unwrap();
+            assert_eq!(translated, "[ja] caf\u{e9} cr\u{e8}me");
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn bypassing_the_cache_always_calls_the_provider_and_refreshes_the_entry() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let temp = TempDir::new().unwrap();
+        let service = offline_service(&temp).with_provider(
+            ProviderId::GoogleUnofficial,
+            CountingProvider {
+                calls: Arc::clone(&calls),
+            },
+        );
+        service
+            .memory
+            .store("hello", "stale", "en", "ja", "google_unofficial")
+            .unwrap();
+
+        let cached = service
+            .translate_text("hello", "en", "ja", ProviderId::GoogleUnofficial, None)
+            .unwrap();
+        assert_eq!(cached, "stale");
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        let fresh_service = service.clone().with_bypass_cache(true);
+        for expected_calls in [1, 2] {
+            let fresh = fresh_service
+                .translate_text("hello", "en", "ja", ProviderId::GoogleUnofficial, None)
+                .unwrap();
+            assert_eq!(fresh, "[ja] hello");
+            assert_eq!(calls.load(Ordering::SeqCst), expected_calls);
+        }
+
+        let refreshed = service
+            .translate_text("hello", "en", "ja", ProviderId::GoogleUnofficial, None)
+            .unwrap();
+        assert_eq!(refreshed, "[ja] hello");
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn min_request_interval_spaces_consecutive_requests() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let temp = TempDir::new().unwrap();
+        let service = offline_service(&temp)
+            .with_provider(
+                ProviderId::GoogleUnofficial,
+                CountingProvider {
+                    calls: Arc::clone(&calls),
+                },
+            )
+            .with_min_request_interval(Duration::from_millis(200));
+
+        let started = Instant::now();
+        for text in ["first", "second"] {
+            service
+                .translate_text(text, "en", "ja", ProviderId::GoogleUnofficial, None)
+                .unwrap();
+        }
+        assert!(started.elapsed() >= Duration::from_millis(190));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        let cancel = AtomicBool::new(false);
+        let throttled = thread::scope(|scope| {
+            let waiting = scope.spawn(|| {
+                service.translate_text(
+                    "third",
+                    "en",
+                    "ja",
+                    ProviderId::GoogleUnofficial,
+                    Some(&cancel),
+                )
+            });
+            thread::sleep(Duration::from_millis(20));
+            cancel.store(true, Ordering::Relaxed);
+            waiting.join().unwrap()
+        });
+        assert!(matches!(throttled, Err(TranslationError::Cancelled)));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn request_timeout_is_applied_to_rebuilt_clients() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!(
+            "http://{}/translate_a/single",
+            listener.local_addr().unwrap()
+        );
+        // Accepts connections but never answers.
+        thread::spawn(move || {
+            let _streams: Vec<_> = listener.incoming().collect();
+        });
+        let calls = Arc::new(AtomicUsize::new(0));
+        let temp = TempDir::new().unwrap();
+        let service = service_with_endpoint(&temp, endpoint)
+            .with_provider(
+                ProviderId::LibreTranslate,
+                CountingProvider {
+                    calls: Arc::clone(&calls),
+                },
+            )
+            .with_retry_policy(1, 50)
+            .with_request_timeout(Duration::from_millis(500))
+            .unwrap();
+        assert_eq!(service.request_timeout(), Duration::from_millis(500));
+
+        let started = Instant::now();
+        let error = service
+            .translate_text("hello", "en", "ja", ProviderId::GoogleUnofficial, None)
+            .unwrap_err();
+        assert!(matches!(error, TranslationError::Network(_)), "{error:?}");
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        service
+            .translate_text("hello", "en", "ja", ProviderId::LibreTranslate, None)
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn chunks_long_text_on_sentence_boundaries() {
+        let sentence = "The quick brown fox jumps over the lazy dog. ";
+        let text = sentence.repeat(10_000 / sentence.len() + 1);
+        assert!(text.len() > 10_000);
+
+        let chunks = chunk_text(&text, DEFAULT_MAX_CHUNK_CHARS);
+        assert!(chunks.len() > 1);
+        assert!(
+            chunks
+                .iter()
+                .all(|chunk| chunk.chars().count() <= DEFAULT_MAX_CHUNK_CHARS)
+        );
+        assert!(chunks.iter().all(|chunk| chunk.ends_with(". ")));
+        assert_eq!(chunks.concat(), text);
+
+        let unbroken = "あ".repeat(25);
+        let pieces = chunk_text(&unbroken, 10);
+        assert_eq!(pieces.len(), 3);
+        assert_eq!(pieces.concat(), unbroken);
+    }
+
+    #[test]
+    fn long_input_is_translated_chunk_by_chunk_in_order() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let temp = TempDir::new().unwrap();
+        let service = offline_service(&temp)
+            .with_max_chunk_chars(30)
+            .with_provider(
+                ProviderId::GoogleUnofficial,
+                CountingProvider {
+                    calls: Arc::clone(&calls),
+                },
+            );
+
+        let text = "First sentence here. Second sentence here.\n\nThird one.";
+        let translated = service
+            .translate_text(text, "en", "ja", ProviderId::GoogleUnofficial, None)
+            .unwrap();
+
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert_eq!(
+            translated,
+            "[ja] First sentence here. [ja] Second sentence here.\n\n[ja] Third one."
+        );
+        assert_eq!(service.estimated_round_trip_requests(text), 6);
+        assert_eq!(service.estimated_round_trip_requests("Short."), 2);
+        assert_eq!(service.estimated_round_trip_requests(" \n"), 0);
+    }
+
+    #[test]
+    fn glossary_terms_bypass_the_provider() {
+        let temp = TempDir::new().unwrap();
+        let service = offline_service(&temp)
+            .with_glossary(Glossary::new(vec![crate::glossary::GlossaryEntry {
+                term: "VibeTranslate".to_owned(),
+                translation: None,
+                case_sensitive: true,
+            }]))
+            .with_provider(
+                ProviderId::GoogleUnofficial,
+                CountingProvider {
+                    calls: Arc::new(AtomicUsize::new(0)),
+                },
+            );
+
+        let translated = service
+            .translate_text(
+                "VibeTranslate is fun",
+                "en",
+                "ja",
+                ProviderId::GoogleUnofficial,
+                None,
+            )
+            .unwrap();
+        assert_eq!(translated, "[ja] VibeTranslate is fun");
+    }
+
+    /// Answers after 60 ms on the way to Japanese and 20 ms on the way back.
+    #[derive(Debug)]
+    struct PacedProvider;
+
+    impl TranslationProvider for PacedProvider {
+        fn translate(
+            &self,
+            text: &str,
+            _source_language: &str,
+            target_language: &str,
+            _cancel_flag: Option<&AtomicBool>,
+        ) -> std::result::Result<String, TranslationError> {
+            let delay = if target_language == "ja" { 60 } else { 20 };
+            thread::sleep(Duration::from_millis(delay));
+            Ok(format!("{target_language}({text})"))
+        }
+    }
+
+    #[test]
+    fn back_translation_times_each_hop() {
+        let temp = TempDir::new().unwrap();
+        let service =
+            offline_service(&temp).with_provider(ProviderId::GoogleUnofficial, PacedProvider);
+
+        let result = service
+            .back_translate(
+                "Timed hello",
+                Some("en"),
+                "ja",
+                ProviderId::GoogleUnofficial,
+                None,
+            )
+            .unwrap();
+
+        assert!(result.forward_ms >= 60, "{result:?}");
+        assert!(result.backward_ms >= 20, "{result:?}");
+        let hops = result.forward_ms + result.backward_ms;
+        assert!(hops <= result.duration_ms && result.duration_ms - hops < 20);
+        assert!(result.hop_breakdown().unwrap().starts_with("forward 0."));
+    }
+
+    #[derive(Debug)]
+    struct TaggingProvider {
+        failing_target: Option<&'static str>,
+    }
+
+    impl TranslationProvider for TaggingProvider {
+        fn translate(
+            &self,
+            text: &str,
+            _source_language: &str,
+            target_language: &str,
+            _cancel_flag: Option<&AtomicBool>,
+        ) -> std::result::Result<String, TranslationError> {
+            if self.failing_target == Some(target_language) {
+                return Err(TranslationError::InvalidResponse("boom".to_owned()));
+            }
+            Ok(format!("{target_language}({text})"))
+        }
+    }
+
+    #[test]
+    fn structured_back_translation_keeps_markdown_markup() {
+        let temp = TempDir::new().unwrap();
+        let service = offline_service(&temp).with_provider(
+            ProviderId::GoogleUnofficial,
+            TaggingProvider {
+                failing_target: None,
+            },
+        );
+        let document = StructuredDocument::parse_markdown("## Hi\n\nSay **hello** now");
+
+        let result = service
+            .back_translate_structured(
+                &document,
+                Some("en"),
+                "ja",
+                ProviderId::GoogleUnofficial,
+                None,
+            )
+            .unwrap();
+
+        assert_eq!(result.original_text, "## Hi\n\nSay **hello** now");
+        assert_eq!(
+            result.intermediate_text,
+            "## ja(Hi)\n\nja(Say) **ja(hello)** ja(now)"
+        );
+        assert_eq!(
+            result.back_translated_text,
+            "## en(ja(Hi))\n\nen(ja(Say)) **en(ja(hello))** en(ja(now))"
+        );
+    }
+
+    #[test]
+    fn chain_translates_through_every_pivot_and_back() {
+        let temp = TempDir::new().unwrap();
+        let service = offline_service(&temp).with_provider(
+            ProviderId::GoogleUnofficial,
+            TaggingProvider {
+                failing_target: None,
+            },
+        );
+
+        let (result, hops) = service
+            .back_translate_chain(
+                "hi",
+                Some("en"),
+                &["ja", "de"],
+                ProviderId::GoogleUnofficial,
+                None,
+            )
+            .unwrap();
+
+        let languages: Vec<&str> = hops.iter().map(|(language, _)| language.as_str()).collect();
+        assert_eq!(languages, ["ja", "de", "en"]);
+        assert_eq!(result.intermediate_language, "de");
+        assert_eq!(result.intermediate_text, "de(ja(hi))");
+        assert_eq!(result.back_translated_text, "en(de(ja(hi)))");
+    }
+
+    #[test]
+    fn chain_reports_which_hop_failed() {
+        let temp = TempDir::new().unwrap();
+        let service = offline_service(&temp).with_provider(
+            ProviderId::GoogleUnofficial,
+            TaggingProvider {
+                failing_target: Some("de"),
+            },
+        );
+
+        let error = service
+            .back_translate_chain(
+                "hi",
+                Some("en"),
+                &["ja", "de"],
+                ProviderId::GoogleUnofficial,
+                None,
+            )
+            .unwrap_err();
+
+        assert!(matches!(
+            &error,
+            TranslationError::ChainHopFailed { hop: 2, from, to, .. } if from == "ja" && to == "de"
+        ));
+        assert_eq!(error.partial_intermediate(), Some("ja(hi)"));
+
+        let invalid = service.back_translate_chain(
+            "hi",
+            Some("en"),
+            &["ja", "not a code"],
+            ProviderId::GoogleUnofficial,
+            None,
+        );
+        assert!(matches!(invalid, Err(TranslationError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn retry_policy_updates_are_shared_across_clones() {
+        let temp = TempDir::new().unwrap();
+        let service = offline_service(&temp);
+        let worker = service.clone();
+
+        service.set_retry_policy(7, 1200);
+
+        assert_eq!(worker.retry_policy(), RetryPolicy::new(7, 1200));
+        assert!(worker.retry_delay(1) >= Duration::from_millis(1200));
+        assert_eq!(RetryPolicy::new(0, 10), RetryPolicy::new(1, 50));
+    }
+
+    #[derive(Debug)]
+    struct RecordingProvider {
+        targets: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl TranslationProvider for RecordingProvider {
+        fn translate(
+            &self,
+            text: &str,
+            _source_language: &str,
+            target_language: &str,
+            _cancel_flag: Option<&AtomicBool>,
+        ) -> std::result::Result<String, TranslationError> {
+            self.targets
+                .lock()
+                .unwrap()
+                .push(target_language.to_owned());
+            Ok(format!("{target_language}:{text}"))
+        }
+    }
+
+    #[test]
+    fn detailed_detection_reports_confidence() {
+        let temp = TempDir::new().unwrap();
+        let service = offline_service(&temp);
+
+        let english = service.detect_language_detailed(
+            "The committee will publish its findings after reviewing every submission.",
+        );
+        assert_eq!(english.code, "en");
+        assert!(english.reliable);
+        assert!(english.confidence > 0.8, "{english:?}");
+
+        let ambiguous = service.detect_language_detailed("pan data");
+        assert!(!ambiguous.reliable);
+        assert!(ambiguous.confidence < 0.5, "{ambiguous:?}");
+
+        let empty = service.detect_language_detailed("   ");
+        assert_eq!((empty.code.as_str(), empty.confidence), ("en", 0.0));
+    }
+
+    #[test]
+    fn back_translate_accepts_regional_intermediate_codes() {
+        let targets = Arc::new(Mutex::new(Vec::new()));
+        let temp = TempDir::new().unwrap();
+        let service = offline_service(&temp).with_provider(
+            ProviderId::GoogleUnofficial,
+            RecordingProvider {
+                targets: Arc::clone(&targets),
+            },
+        );
+
+        service
+            .back_translate(
+                "hello",
+                Some("en"),
+                "pt-br",
+                ProviderId::GoogleUnofficial,
+                None,
+            )
+            .unwrap();
+        assert_eq!(*targets.lock().unwrap(), ["pt-BR", "en"]);
+
+        let error = service
+            .back_translate(
+                "hello",
+                Some("en"),
+                "en_au",
+                ProviderId::GoogleUnofficial,
+                None,
+            )
+            .unwrap_err();
+        assert!(matches!(error, TranslationError::InvalidInput(_)));
+    }
 }
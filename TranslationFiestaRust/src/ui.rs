@@ -1,5 +1,5 @@
-use std::fs;
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::{Duration, Instant};
@@ -11,29 +11,83 @@ use eframe::egui::{Color32, RichText, Stroke, Vec2};
 use tracing::{error, info, warn};
 
 use crate::app_paths::AppPaths;
-use crate::batch::{BatchOptions, BatchProcessor, BatchProgress};
+use crate::batch::{BatchOptions, BatchProcessor, BatchProgress, run_completion_hook};
+use crate::config::EffectiveConfig;
+use crate::diff::{DiffOp, DiffSummary, diff_words};
+use crate::epub::load_epub;
 use crate::export::{BatchExportContext, ExportService};
-use crate::file_service::{SupportedFileType, load_text};
-use crate::memory::TranslationMemory;
+use crate::file_service::{SupportedFileType, load_text_with_options};
+use crate::fonts::load_cjk_font_data;
+use crate::history::{history_label, load_history, record_history, save_history};
+use crate::language::{
+    common_languages, is_supported_language_code, language_name, normalize_language_code,
+};
+use crate::memory::{CacheNormalization, EvictionPolicy, TranslationMemory};
 use crate::models::{
-    BackTranslationResult, BatchItemResult, ExportFormat, MemoryEntry, MemoryStats, ProviderId,
+    BackTranslationResult, BatchItemResult, EpubChapter, ExportFormat, GroupedMemoryStats,
+    MemoryEntry, MemoryStats, ProviderId,
+};
+use crate::settings::{
+    AppSettings, DEFAULT_LIBRETRANSLATE_URL, MAX_BATCH_CONCURRENCY, Theme, clamp_window_position,
+    save_settings,
 };
-use crate::settings::{AppSettings, save_settings};
-use crate::translation::{TranslationError, TranslationService};
+use crate::structured::StructuredDocument;
+use crate::text_stats::text_stats;
+use crate::translation::{TranslationError, TranslationService, request_timeout_from_env};
+
+const CLEAR_INPUT_SHORTCUT: egui::KeyboardShortcut = egui::KeyboardShortcut::new(
+    egui::Modifiers::COMMAND.plus(egui::Modifiers::SHIFT),
+    egui::Key::Backspace,
+);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum AppTab {
     Translate,
     Batch,
     Memory,
+    History,
     Export,
     Settings,
 }
 
+/// Word diff of the input against the back-translation, kept until either
+/// text changes so the LCS is not recomputed every frame.
+struct RoundTripDiff {
+    original: String,
+    back_translated: String,
+    original_job: egui::text::LayoutJob,
+    back_translated_job: egui::text::LayoutJob,
+    /// The back-translation with both added and dropped words marked.
+    unified_job: egui::text::LayoutJob,
+    summary: DiffSummary,
+}
+
+impl RoundTripDiff {
+    fn new(original: &str, back_translated: &str) -> Self {
+        let ops = diff_words(original, back_translated);
+        let (original_job, back_translated_job) = diff_column_layout_jobs(&ops);
+        Self {
+            original: original.to_owned(),
+            back_translated: back_translated.to_owned(),
+            original_job,
+            back_translated_job,
+            unified_job: diff_layout_job(&ops),
+            summary: DiffSummary::from_ops(&ops),
+        }
+    }
+
+    fn is_for(&self, original: &str, back_translated: &str) -> bool {
+        self.original == original && self.back_translated == back_translated
+    }
+}
+
 #[derive(Debug)]
 enum UiEvent {
     TranslationCompleted(BackTranslationResult),
-    TranslationFailed(String),
+    TranslationFailed {
+        message: String,
+        intermediate: Option<String>,
+    },
     BatchProgress(BatchProgress),
     BatchCompleted(Vec<BatchItemResult>),
 }
@@ -53,6 +107,20 @@ pub struct TranslationFiestaApp {
     intermediate_text: String,
     back_text: String,
     last_result: Option<BackTranslationResult>,
+    show_round_trip_diff: bool,
+    round_trip_diff: Option<RoundTripDiff>,
+    /// Same cache for the last result's diff on the Export tab.
+    export_diff: Option<RoundTripDiff>,
+    /// The Effective Configuration text and the settings it was resolved
+    /// from; dropped while the panel is collapsed.
+    effective_config: Option<(AppSettings, String)>,
+    /// Treat the input as Markdown and translate only its text.
+    preserve_markup: bool,
+    /// Fetch a fresh translation instead of reusing cached ones.
+    bypass_cache: bool,
+    /// Chapters of the last imported EPUB and which of them fill the input.
+    epub_chapters: Vec<EpubChapter>,
+    epub_selected: Vec<bool>,
 
     is_translating: bool,
     translate_cancel: Arc<AtomicBool>,
@@ -61,11 +129,27 @@ pub struct TranslationFiestaApp {
     batch_results: Vec<BatchItemResult>,
     batch_progress: Option<BatchProgress>,
     is_batch_running: bool,
+    /// The running (or last) batch only validated its files.
+    batch_dry_run: bool,
     batch_cancel: Arc<AtomicBool>,
+    batch_pause: Arc<AtomicBool>,
+    /// Case-insensitive path substring the batch result list is narrowed to.
+    batch_filter: String,
+    batch_failures_only: bool,
 
     memory_stats: MemoryStats,
+    memory_stats_by_provider: BTreeMap<String, GroupedMemoryStats>,
+    memory_stats_by_pair: BTreeMap<String, GroupedMemoryStats>,
     memory_query: String,
     memory_results: Vec<MemoryEntry>,
+    /// Cache key and draft translation of the memory entry being edited.
+    memory_edit: Option<(String, String)>,
+
+    custom_source_language: String,
+    custom_intermediate_language: String,
+
+    /// Completed translations, newest first; mirrored to `AppPaths::history_file`.
+    history: Vec<BackTranslationResult>,
 
     export_format: ExportFormat,
     include_metadata: bool,
@@ -77,6 +161,10 @@ pub struct TranslationFiestaApp {
     rx: Receiver<UiEvent>,
 
     last_save_attempt: Instant,
+    /// Set once the restored window position has been checked against the monitor.
+    window_position_checked: bool,
+    /// Palette currently installed in the egui context; `None` until the first frame.
+    applied_theme: Option<egui::Theme>,
 }
 
 impl TranslationFiestaApp {
@@ -91,6 +179,9 @@ impl TranslationFiestaApp {
         let (tx, rx) = crossbeam_channel::unbounded();
         let clipboard = Clipboard::new().ok();
         let initial_stats = memory.stats().unwrap_or_default();
+        let memory_stats_by_provider = memory.stats_by_provider().unwrap_or_default();
+        let memory_stats_by_pair = memory.stats_by_language_pair().unwrap_or_default();
+        let history = load_history(&paths.history_file);
 
         Self {
             paths,
@@ -107,21 +198,41 @@ impl TranslationFiestaApp {
             intermediate_text: String::new(),
             back_text: String::new(),
             last_result: None,
+            show_round_trip_diff: false,
+            preserve_markup: false,
+            bypass_cache: false,
+            epub_chapters: Vec::new(),
+            epub_selected: Vec::new(),
+            round_trip_diff: None,
+            export_diff: None,
+            effective_config: None,
             is_translating: false,
             translate_cancel: Arc::new(AtomicBool::new(false)),
             batch_files: Vec::new(),
             batch_results: Vec::new(),
             batch_progress: None,
             is_batch_running: false,
+            batch_dry_run: false,
             batch_cancel: Arc::new(AtomicBool::new(false)),
+            batch_pause: Arc::new(AtomicBool::new(false)),
+            batch_filter: String::new(),
+            batch_failures_only: false,
             memory_stats: initial_stats,
+            memory_stats_by_provider,
+            memory_stats_by_pair,
             memory_query: String::new(),
             memory_results: Vec::new(),
+            memory_edit: None,
+            custom_source_language: String::new(),
+            custom_intermediate_language: String::new(),
+            history,
             export_preview: String::new(),
             clipboard,
             tx,
             rx,
             last_save_attempt: Instant::now(),
+            window_position_checked: false,
+            applied_theme: None,
         }
     }
 
@@ -143,29 +254,44 @@ impl TranslationFiestaApp {
         let source_language = self.settings.source_language.clone();
         let intermediate_language = self.settings.intermediate_language.clone();
         let provider = self.settings.provider();
-        let translator = self.translator.clone();
+        let translator = self.translator.clone().with_bypass_cache(self.bypass_cache);
         let cancel = Arc::clone(&self.translate_cancel);
         let tx = self.tx.clone();
+        let preserve_markup = self.preserve_markup;
 
         std::thread::spawn(move || {
-            let outcome = translator.back_translate(
-                &text,
-                Some(source_language.as_str()),
-                &intermediate_language,
-                provider,
-                Some(cancel.as_ref()),
-            );
+            let outcome = if preserve_markup {
+                translator.back_translate_structured(
+                    &StructuredDocument::parse_markdown(&text),
+                    Some(source_language.as_str()),
+                    &intermediate_language,
+                    provider,
+                    Some(cancel.as_ref()),
+                )
+            } else {
+                translator.back_translate(
+                    &text,
+                    Some(source_language.as_str()),
+                    &intermediate_language,
+                    provider,
+                    Some(cancel.as_ref()),
+                )
+            };
 
             match outcome {
                 Ok(result) => {
                     let _ = tx.send(UiEvent::TranslationCompleted(result));
                 }
                 Err(error) => {
+                    let intermediate = error.partial_intermediate().map(ToOwned::to_owned);
                     let message = match error {
                         TranslationError::Cancelled => "Translation cancelled".to_owned(),
                         other => other.to_string(),
                     };
-                    let _ = tx.send(UiEvent::TranslationFailed(message));
+                    let _ = tx.send(UiEvent::TranslationFailed {
+                        message,
+                        intermediate,
+                    });
                 }
             }
         });
@@ -179,7 +305,19 @@ impl TranslationFiestaApp {
         self.status_message = "Cancelling translation...".to_owned();
     }
 
-    fn start_batch_processing(&mut self) {
+    fn clear_input(&mut self) {
+        if self.is_translating {
+            self.status_message = "Cannot clear input while translating.".to_owned();
+            return;
+        }
+
+        self.input_text.clear();
+        self.status_message = "Input cleared".to_owned();
+    }
+
+    /// `dry_run` only loads the files and validates the request, reporting
+    /// problems without sending anything to the provider.
+    fn start_batch_processing(&mut self, dry_run: bool) {
         if self.is_batch_running {
             return;
         }
@@ -188,32 +326,87 @@ impl TranslationFiestaApp {
             return;
         }
 
+        // A lone EPUB is split into its chapters, which run in parallel.
+        let single_epub = match self.batch_files.as_slice() {
+            [path] => path
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("epub"))
+                .then(|| path.clone()),
+            _ => None,
+        };
+
         self.is_batch_running = true;
+        self.batch_dry_run = dry_run;
         self.batch_results.clear();
         self.batch_progress = Some(BatchProgress {
             done: 0,
             total: self.batch_files.len(),
             current_file: String::new(),
+            finished: None,
+            elapsed_ms: 0,
+            estimated_remaining_ms: None,
         });
-        self.status_message = "Batch processing started...".to_owned();
+        self.status_message = if dry_run {
+            "Validating batch files...".to_owned()
+        } else if single_epub.is_some() {
+            "Translating EPUB chapters...".to_owned()
+        } else {
+            "Batch processing started...".to_owned()
+        };
 
         let files = self.batch_files.clone();
         let options = BatchOptions {
             source_language: Some(self.settings.source_language.clone()),
             intermediate_language: self.settings.intermediate_language.clone(),
             provider_id: self.settings.provider(),
+            load_options: self.settings.text_load_options(),
+            concurrency: self.settings.batch_concurrency,
+            dry_run,
+            ..BatchOptions::default()
         };
 
         self.batch_cancel.store(false, Ordering::Relaxed);
+        self.batch_pause.store(false, Ordering::Relaxed);
 
         let processor = self.batch_processor.clone();
         let cancel = Arc::clone(&self.batch_cancel);
+        let pause = Arc::clone(&self.batch_pause);
         let tx = self.tx.clone();
+        let on_complete = if dry_run {
+            String::new()
+        } else {
+            self.settings.on_complete_command.trim().to_owned()
+        };
 
         std::thread::spawn(move || {
-            let results = processor.process_files(&files, &options, cancel.as_ref(), |progress| {
+            let on_progress = |progress| {
                 let _ = tx.send(UiEvent::BatchProgress(progress));
-            });
+            };
+            let results = match &single_epub {
+                Some(path) => processor.process_epub_with_pause(
+                    path,
+                    &options,
+                    cancel.as_ref(),
+                    pause.as_ref(),
+                    on_progress,
+                ),
+                None => processor.process_files_with_pause(
+                    &files,
+                    &options,
+                    cancel.as_ref(),
+                    pause.as_ref(),
+                    on_progress,
+                ),
+            };
+            if !on_complete.is_empty() && !cancel.load(Ordering::Relaxed) {
+                match run_completion_hook(&on_complete, &results, None) {
+                    Ok(status) if !status.success() => {
+                        warn!("batch completion hook exited with {status}");
+                    }
+                    Ok(_) => {}
+                    Err(error) => warn!("batch completion hook failed: {error:#}"),
+                }
+            }
             let _ = tx.send(UiEvent::BatchCompleted(results));
         });
     }
@@ -227,6 +420,32 @@ impl TranslationFiestaApp {
         self.status_message = "Cancelling batch...".to_owned();
     }
 
+    fn is_batch_paused(&self) -> bool {
+        self.batch_pause.load(Ordering::Relaxed)
+    }
+
+    fn toggle_batch_pause(&mut self) {
+        if !self.is_batch_running {
+            return;
+        }
+
+        let paused = !self.is_batch_paused();
+        self.batch_pause.store(paused, Ordering::Relaxed);
+        self.status_message = if paused {
+            self.paused_status()
+        } else {
+            "Resuming batch...".to_owned()
+        };
+    }
+
+    fn paused_status(&self) -> String {
+        let (done, total) = self
+            .batch_progress
+            .as_ref()
+            .map_or((0, 0), |progress| (progress.done, progress.total));
+        format!("Batch paused at {done}/{total}; files in progress will finish")
+    }
+
     fn poll_events(&mut self) {
         while let Ok(event) = self.rx.try_recv() {
             match event {
@@ -234,18 +453,48 @@ impl TranslationFiestaApp {
                     self.intermediate_text = result.intermediate_text.clone();
                     self.back_text = result.back_translated_text.clone();
                     self.last_result = Some(result.clone());
-                    self.status_message =
-                        format!("Done ({:.2}s)", result.duration_ms as f64 / 1000.0);
+                    record_history(
+                        &mut self.history,
+                        result.clone(),
+                        self.settings.history_max_entries,
+                    );
+                    self.persist_history();
+                    let summary = format!(
+                        "Done ({:.2}s{}, {:.0}% match)",
+                        result.duration_ms as f64 / 1000.0,
+                        result
+                            .hop_breakdown()
+                            .map(|hops| format!(": {hops}"))
+                            .unwrap_or_default(),
+                        result.fidelity_score * 100.0
+                    );
+                    self.status_message = match &result.suspicious_reason {
+                        Some(reason) => format!("{summary} - warning: {reason}"),
+                        None => summary,
+                    };
                     self.is_translating = false;
                     self.refresh_memory_stats();
                 }
-                UiEvent::TranslationFailed(message) => {
-                    self.status_message = message;
+                UiEvent::TranslationFailed {
+                    message,
+                    intermediate,
+                } => {
+                    self.status_message = match intermediate {
+                        Some(intermediate) => {
+                            self.intermediate_text = intermediate;
+                            self.back_text.clear();
+                            self.last_result = None;
+                            format!("{message} (intermediate translation shown)")
+                        }
+                        None => message,
+                    };
                     self.is_translating = false;
                 }
                 UiEvent::BatchProgress(progress) => {
                     self.batch_progress = Some(progress.clone());
-                    if !progress.current_file.is_empty() {
+                    if self.is_batch_paused() {
+                        self.status_message = self.paused_status();
+                    } else if !progress.current_file.is_empty() {
                         self.status_message = format!(
                             "Batch {}/{}: {}",
                             progress.done, progress.total, progress.current_file
@@ -262,8 +511,11 @@ impl TranslationFiestaApp {
                         p.done = p.total;
                         p
                     });
-                    self.status_message =
-                        format!("Batch complete: {successful} succeeded, {failed} failed");
+                    self.status_message = if self.batch_dry_run {
+                        format!("Validation complete: {successful} ready, {failed} with problems")
+                    } else {
+                        format!("Batch complete: {successful} succeeded, {failed} failed")
+                    };
                     self.refresh_memory_stats();
                 }
             }
@@ -279,6 +531,18 @@ impl TranslationFiestaApp {
                 warn!("failed to refresh memory stats: {error}");
             }
         }
+        match (
+            self.memory.stats_by_provider(),
+            self.memory.stats_by_language_pair(),
+        ) {
+            (Ok(by_provider), Ok(by_pair)) => {
+                self.memory_stats_by_provider = by_provider;
+                self.memory_stats_by_pair = by_pair;
+            }
+            (Err(error), _) | (_, Err(error)) => {
+                warn!("failed to refresh grouped memory stats: {error}");
+            }
+        }
     }
 
     fn run_memory_search(&mut self) {
@@ -299,6 +563,73 @@ impl TranslationFiestaApp {
         }
     }
 
+    fn persist_history(&mut self) {
+        if let Err(error) = save_history(&self.paths.history_file, &self.history) {
+            warn!("failed to save history: {error:#}");
+            self.status_message = format!("Failed to save history: {error}");
+        }
+    }
+
+    fn clear_history(&mut self) {
+        self.history.clear();
+        self.persist_history();
+        self.status_message = "History cleared".to_owned();
+    }
+
+    fn load_history_entry(&mut self, index: usize) {
+        if self.is_translating {
+            self.status_message = "Cannot load history while translating.".to_owned();
+            return;
+        }
+        let Some(entry) = self.history.get(index).cloned() else {
+            return;
+        };
+
+        self.input_text = entry.original_text.clone();
+        self.intermediate_text = entry.intermediate_text.clone();
+        self.back_text = entry.back_translated_text.clone();
+        self.status_message = format!("Loaded translation from {}", history_label(&entry));
+        self.last_result = Some(entry);
+        self.active_tab = AppTab::Translate;
+    }
+
+    fn delete_memory_entry(&mut self, key: &str) {
+        match self.memory.delete(key) {
+            Ok(_) => {
+                self.memory_results.retain(|entry| entry.cache_key != key);
+                self.refresh_memory_stats();
+                self.status_message = "Memory entry deleted".to_owned();
+            }
+            Err(error) => {
+                self.status_message = format!("Failed to delete memory entry: {error}");
+            }
+        }
+    }
+
+    fn save_memory_edit(&mut self) {
+        let Some((key, draft)) = self.memory_edit.take() else {
+            return;
+        };
+        match self.memory.update_translation(&key, &draft) {
+            Ok(true) => {
+                if let Some(entry) = self
+                    .memory_results
+                    .iter_mut()
+                    .find(|entry| entry.cache_key == key)
+                {
+                    entry.translated_text = draft;
+                }
+                self.status_message = "Memory entry updated".to_owned();
+            }
+            Ok(false) => {
+                self.status_message = "Memory entry no longer exists".to_owned();
+            }
+            Err(error) => {
+                self.status_message = format!("Failed to update memory entry: {error}");
+            }
+        }
+    }
+
     fn clear_memory(&mut self) {
         match self.memory.clear() {
             Ok(_) => {
@@ -317,19 +648,178 @@ impl TranslationFiestaApp {
         dialog = dialog.add_filter("Supported", SupportedFileType::supported_extensions());
 
         if let Some(path) = dialog.pick_file() {
-            match load_text(&path) {
-                Ok(content) => {
-                    self.input_text = content;
-                    self.settings.last_file_path = path.display().to_string();
-                    self.status_message = format!("Loaded {}", path.display());
+            self.load_file_into_input(&path);
+        }
+    }
+
+    fn load_file_into_input(&mut self, path: &Path) {
+        match load_text_with_options(path, self.settings.text_load_options()) {
+            Ok(content) => {
+                self.input_text = content;
+                self.settings.last_file_path = path.display().to_string();
+                self.status_message = format!("Loaded {}", path.display());
+                let book = (SupportedFileType::detect(path) == Some(SupportedFileType::Epub))
+                    .then(|| load_epub(path, self.settings.normalize_whitespace).ok())
+                    .flatten();
+                if let Some(language) = book
+                    .as_ref()
+                    .and_then(|book| book.language.as_deref())
+                    .and_then(normalize_language_code)
+                {
+                    self.status_message
+                        .push_str(&format!(" (source set to {language})"));
+                    self.settings.source_language = language;
                 }
-                Err(error) => {
-                    self.status_message = format!("Import failed: {error}");
+                self.epub_chapters = book.map(|book| book.chapters).unwrap_or_default();
+                self.epub_selected = vec![true; self.epub_chapters.len()];
+            }
+            Err(error) => {
+                self.status_message = format!("Import failed: {error}");
+            }
+        }
+    }
+
+    /// Replaces the input with the chapters ticked in the picker.
+    fn use_selected_chapters(&mut self) {
+        let chapters: Vec<&str> = self
+            .epub_chapters
+            .iter()
+            .zip(&self.epub_selected)
+            .filter(|(_, selected)| **selected)
+            .map(|(chapter, _)| chapter.content.as_str())
+            .collect();
+        self.status_message = format!(
+            "Using {} of {} chapters",
+            chapters.len(),
+            self.epub_chapters.len()
+        );
+        self.input_text = chapters.join("\n\n");
+    }
+
+    fn epub_chapter_picker(&mut self, ui: &mut egui::Ui) {
+        if self.epub_chapters.is_empty() {
+            return;
+        }
+        let selected = self.epub_selected.iter().filter(|&&on| on).count();
+        egui::CollapsingHeader::new(format!(
+            "Chapters ({selected} of {} selected)",
+            self.epub_chapters.len()
+        ))
+        .id_salt("epub_chapters")
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                if ui.button("All").clicked() {
+                    self.epub_selected.fill(true);
+                }
+                if ui.button("None").clicked() {
+                    self.epub_selected.fill(false);
                 }
+                if ui
+                    .add_enabled(selected > 0, egui::Button::new("Use selected"))
+                    .clicked()
+                {
+                    self.use_selected_chapters();
+                }
+            });
+            egui::ScrollArea::vertical()
+                .id_salt("epub_chapter_list")
+                .max_height(160.0)
+                .show(ui, |ui| {
+                    for (chapter, on) in self.epub_chapters.iter().zip(&mut self.epub_selected) {
+                        ui.checkbox(on, format!("{}. {}", chapter.order + 1, chapter.title));
+                    }
+                });
+        });
+    }
+
+    fn handle_dropped_files(&mut self, ctx: &egui::Context) {
+        let dropped = ctx.input(|input| input.raw.dropped_files.clone());
+        if dropped.is_empty() {
+            return;
+        }
+
+        let (supported, unsupported): (Vec<PathBuf>, Vec<PathBuf>) = dropped
+            .into_iter()
+            .filter_map(|file| file.path)
+            .partition(|path| SupportedFileType::detect(path).is_some());
+        let skipped = if unsupported.is_empty() {
+            String::new()
+        } else {
+            format!(
+                " (skipped unsupported: {})",
+                unsupported
+                    .iter()
+                    .map(|path| path.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        };
+
+        match self.active_tab {
+            AppTab::Translate if self.is_translating => {
+                self.status_message = "Cannot import files while translating.".to_owned();
+            }
+            AppTab::Translate => match supported.first() {
+                Some(path) => {
+                    self.load_file_into_input(path);
+                    if supported.len() > 1 {
+                        self.status_message
+                            .push_str(" (only the first dropped file is used here)");
+                    }
+                    self.status_message.push_str(&skipped);
+                }
+                None => self.status_message = format!("No supported files dropped{skipped}"),
+            },
+            AppTab::Batch if self.is_batch_running => {
+                self.status_message =
+                    "Cannot change batch files while a batch is running.".to_owned();
+            }
+            AppTab::Batch => {
+                let before = self.batch_files.len();
+                for path in supported {
+                    if !self.batch_files.contains(&path) {
+                        self.batch_files.push(path);
+                    }
+                }
+                self.status_message = format!(
+                    "Added {} dropped files ({} selected){skipped}",
+                    self.batch_files.len() - before,
+                    self.batch_files.len()
+                );
+            }
+            _ => {
+                self.status_message =
+                    "Drop files on the Translate or Batch tab to import them.".to_owned();
             }
         }
     }
 
+    fn draw_drop_overlay(&self, ctx: &egui::Context) {
+        let hovering = ctx.input(|input| !input.raw.hovered_files.is_empty());
+        if !hovering {
+            return;
+        }
+
+        let message = match self.active_tab {
+            AppTab::Translate => "Drop a file to load it as input",
+            AppTab::Batch => "Drop files to add them to the batch",
+            _ => "Switch to Translate or Batch to drop files",
+        };
+        let painter = ctx.layer_painter(egui::LayerId::new(
+            egui::Order::Foreground,
+            egui::Id::new("file_drop_overlay"),
+        ));
+        let screen = ctx.screen_rect();
+        painter.rect_filled(screen, 0.0, Color32::from_black_alpha(170));
+        painter.text(
+            screen.center(),
+            egui::Align2::CENTER_CENTER,
+            message,
+            egui::FontId::proportional(22.0),
+            Color32::WHITE,
+        );
+    }
+
     fn save_current_result(&mut self) {
         let Some(result) = &self.last_result else {
             self.status_message = "Translate text first.".to_owned();
@@ -398,10 +888,41 @@ impl TranslationFiestaApp {
             return;
         }
 
+        self.copy_to_clipboard(self.back_text.clone(), "Back translation");
+    }
+
+    fn copy_intermediate_translation(&mut self) {
+        if self.intermediate_text.trim().is_empty() {
+            self.status_message = "No intermediate text to copy.".to_owned();
+            return;
+        }
+
+        self.copy_to_clipboard(self.intermediate_text.clone(), "Intermediate translation");
+    }
+
+    /// Original, intermediate, and back-translation in the plain-text export layout.
+    fn copy_full_result(&mut self) {
+        let Some(result) = &self.last_result else {
+            self.status_message = "No translation result to copy.".to_owned();
+            return;
+        };
+
+        match self
+            .exporter
+            .preview_single(result, ExportFormat::Txt, false)
+        {
+            Ok(text) => self.copy_to_clipboard(text, "Full result"),
+            Err(error) => {
+                self.status_message = format!("Failed to format result: {error}");
+            }
+        }
+    }
+
+    fn copy_to_clipboard(&mut self, text: String, what: &str) {
         match self.clipboard.as_mut() {
-            Some(clipboard) => match clipboard.set_text(self.back_text.clone()) {
+            Some(clipboard) => match clipboard.set_text(text) {
                 Ok(_) => {
-                    self.status_message = "Back translation copied to clipboard".to_owned();
+                    self.status_message = format!("{what} copied to clipboard");
                 }
                 Err(error) => {
                     self.status_message = format!("Clipboard copy failed: {error}");
@@ -461,30 +982,25 @@ impl TranslationFiestaApp {
         }
     }
 
-    fn apply_theme(&self, ctx: &egui::Context) {
-        if ctx.style().visuals.panel_fill == Color32::from_rgb(14, 14, 16) {
+    fn apply_theme(&mut self, ctx: &egui::Context) {
+        let resolved = match self.settings.ui_theme() {
+            Theme::Dark => egui::Theme::Dark,
+            Theme::Light => egui::Theme::Light,
+            Theme::System => ctx.system_theme().unwrap_or(egui::Theme::Dark),
+        };
+        if self.applied_theme == Some(resolved) {
             return;
         }
-
-        apply_cjk_font_fallback(ctx);
+        if self.applied_theme.is_none() {
+            apply_cjk_font_fallback(ctx);
+        }
+        self.applied_theme = Some(resolved);
 
         let mut style = (*ctx.style()).clone();
-        style.visuals = egui::Visuals::dark();
-        style.visuals.override_text_color = Some(Color32::from_rgb(228, 228, 231));
-        style.visuals.window_fill = Color32::from_rgb(20, 20, 23);
-        style.visuals.panel_fill = Color32::from_rgb(14, 14, 16);
-        style.visuals.extreme_bg_color = Color32::from_rgb(10, 10, 12);
-        style.visuals.widgets.noninteractive.bg_fill = Color32::from_rgb(28, 28, 32);
-        style.visuals.widgets.inactive.bg_fill = Color32::from_rgb(32, 32, 36);
-        style.visuals.widgets.hovered.bg_fill = Color32::from_rgb(40, 40, 46);
-        style.visuals.widgets.active.bg_fill = Color32::from_rgb(50, 55, 65);
-        style.visuals.widgets.inactive.fg_stroke =
-            Stroke::new(1.0, Color32::from_rgb(200, 200, 210));
-        style.visuals.widgets.hovered.fg_stroke =
-            Stroke::new(1.0, Color32::from_rgb(235, 235, 240));
-        style.visuals.selection.bg_fill = Color32::from_rgb(59, 130, 246);
-        style.visuals.hyperlink_color = Color32::from_rgb(96, 165, 250);
-        style.visuals.window_stroke = Stroke::new(1.0, Color32::from_rgb(42, 42, 46));
+        style.visuals = match resolved {
+            egui::Theme::Dark => dark_visuals(),
+            egui::Theme::Light => light_visuals(),
+        };
 
         style.spacing.item_spacing = Vec2::new(8.0, 8.0);
         style.spacing.button_padding = Vec2::new(16.0, 10.0);
@@ -525,6 +1041,7 @@ impl TranslationFiestaApp {
             tab_button(ui, &mut self.active_tab, AppTab::Translate, "Translate");
             tab_button(ui, &mut self.active_tab, AppTab::Batch, "Batch");
             tab_button(ui, &mut self.active_tab, AppTab::Memory, "Memory");
+            tab_button(ui, &mut self.active_tab, AppTab::History, "History");
             tab_button(ui, &mut self.active_tab, AppTab::Export, "Export");
             tab_button(ui, &mut self.active_tab, AppTab::Settings, "Settings");
             ui.separator();
@@ -549,10 +1066,31 @@ impl TranslationFiestaApp {
                         .lock_focus(true)
                         .hint_text("Type or paste source text..."),
                 );
+                let requests = self
+                    .translator
+                    .estimated_round_trip_requests(&self.input_text);
+                ui.label(
+                    RichText::new(format!(
+                        "{} | ~{requests} requests",
+                        text_stats(&self.input_text)
+                    ))
+                    .small()
+                    .weak(),
+                );
                 ui.horizontal(|ui| {
                     if ui.button("Import").clicked() {
                         self.import_file_into_input();
                     }
+                    if ui
+                        .add_enabled(
+                            !self.is_translating,
+                            egui::Button::new("Clear Input")
+                                .shortcut_text(ui.ctx().format_shortcut(&CLEAR_INPUT_SHORTCUT)),
+                        )
+                        .clicked()
+                    {
+                        self.clear_input();
+                    }
                     if ui
                         .add_enabled(!self.is_translating, egui::Button::new("Backtranslate"))
                         .clicked()
@@ -565,14 +1103,21 @@ impl TranslationFiestaApp {
                     {
                         self.cancel_translation();
                     }
+                    ui.checkbox(&mut self.preserve_markup, "Keep Markdown markup")
+                        .on_hover_text(
+                            "Translate only the text, leaving headings, emphasis, links, and code as written",
+                        );
+                    ui.checkbox(&mut self.bypass_cache, "Skip cache")
+                        .on_hover_text("Ask the provider again and replace the cached translation");
                 });
+                self.epub_chapter_picker(ui);
             });
 
             let right = &mut columns[1];
             right.group(|ui| {
                 ui.heading(format!(
                     "Intermediate ({})",
-                    self.settings.intermediate_language.to_ascii_uppercase()
+                    language_heading(&self.settings.intermediate_language)
                 ));
                 if self.intermediate_text.is_empty() && !self.is_translating {
                     ui.label(
@@ -586,11 +1131,16 @@ impl TranslationFiestaApp {
                             .desired_rows(10)
                             .interactive(false),
                     );
+                    ui.label(
+                        RichText::new(text_stats(&self.intermediate_text).to_string())
+                            .small()
+                            .weak(),
+                    );
                 }
                 ui.add_space(8.0);
                 ui.heading(format!(
                     "Back Translation ({})",
-                    self.settings.source_language.to_ascii_uppercase()
+                    language_heading(&self.settings.source_language)
                 ));
                 if self.back_text.is_empty() && !self.is_translating {
                     ui.label(
@@ -604,12 +1154,27 @@ impl TranslationFiestaApp {
                             .desired_rows(10)
                             .interactive(false),
                     );
+                    ui.label(
+                        RichText::new(text_stats(&self.back_text).to_string())
+                            .small()
+                            .weak(),
+                    );
                 }
 
                 ui.horizontal(|ui| {
                     if ui.button("Copy").clicked() {
                         self.copy_back_translation();
                     }
+                    if ui.button("Copy Intermediate").clicked() {
+                        self.copy_intermediate_translation();
+                    }
+                    if ui
+                        .add_enabled(self.last_result.is_some(), egui::Button::new("Copy All"))
+                        .on_hover_text("Original, intermediate, and back translation as plain text")
+                        .clicked()
+                    {
+                        self.copy_full_result();
+                    }
                     if ui.button("Save").clicked() {
                         self.save_current_result();
                     }
@@ -619,6 +1184,7 @@ impl TranslationFiestaApp {
                         self.last_result = None;
                         self.status_message = "Cleared results".to_owned();
                     }
+                    ui.checkbox(&mut self.show_round_trip_diff, "Compare with original");
                 });
 
                 if self.is_translating {
@@ -632,6 +1198,52 @@ impl TranslationFiestaApp {
                 }
             });
         });
+
+        if self.show_round_trip_diff && !self.back_text.is_empty() {
+            ui.add_space(8.0);
+            self.ui_round_trip_diff(ui);
+        }
+    }
+
+    fn ui_round_trip_diff(&mut self, ui: &mut egui::Ui) {
+        if !self
+            .round_trip_diff
+            .as_ref()
+            .is_some_and(|diff| diff.is_for(&self.input_text, &self.back_text))
+        {
+            self.round_trip_diff = Some(RoundTripDiff::new(&self.input_text, &self.back_text));
+        }
+        let Some(diff) = &self.round_trip_diff else {
+            return;
+        };
+
+        ui.group(|ui| {
+            ui.horizontal(|ui| {
+                ui.heading("Round-Trip Changes");
+                ui.label(
+                    RichText::new(format!(
+                        "{} unchanged, {} added, {} dropped ({:.0}% kept)",
+                        diff.summary.unchanged,
+                        diff.summary.inserted,
+                        diff.summary.deleted,
+                        diff.summary.similarity() * 100.0
+                    ))
+                    .small()
+                    .weak(),
+                );
+            });
+            egui::ScrollArea::vertical()
+                .id_salt("round_trip_diff")
+                .max_height(220.0)
+                .show(ui, |ui| {
+                    ui.columns(2, |columns| {
+                        columns[0].label(RichText::new("Original").strong());
+                        columns[0].label(diff.original_job.clone());
+                        columns[1].label(RichText::new("Back Translation").strong());
+                        columns[1].label(diff.back_translated_job.clone());
+                    });
+                });
+        });
     }
 
     fn ui_batch_tab(&mut self, ui: &mut egui::Ui) {
@@ -646,7 +1258,26 @@ impl TranslationFiestaApp {
                 .add_enabled(!self.is_batch_running, egui::Button::new("Run Batch"))
                 .clicked()
             {
-                self.start_batch_processing();
+                self.start_batch_processing(false);
+            }
+            if ui
+                .add_enabled(!self.is_batch_running, egui::Button::new("Validate"))
+                .on_hover_text("Load every file and check the languages without translating")
+                .clicked()
+            {
+                self.start_batch_processing(true);
+            }
+            let pause_label = if self.is_batch_paused() {
+                "Resume"
+            } else {
+                "Pause"
+            };
+            if ui
+                .add_enabled(self.is_batch_running, egui::Button::new(pause_label))
+                .on_hover_text("Stop starting new files; files already in progress finish")
+                .clicked()
+            {
+                self.toggle_batch_pause();
             }
             if ui
                 .add_enabled(self.is_batch_running, egui::Button::new("Cancel Batch"))
@@ -663,6 +1294,16 @@ impl TranslationFiestaApp {
             {
                 self.save_batch_results();
             }
+            ui.separator();
+            ui.label("Workers");
+            ui.add_enabled(
+                !self.is_batch_running,
+                egui::DragValue::new(&mut self.settings.batch_concurrency)
+                    .range(1..=MAX_BATCH_CONCURRENCY),
+            )
+            .on_hover_text(
+                "Files translated in parallel. More workers raise the chance of rate limits or captcha blocks.",
+            );
         });
 
         if let Some(progress) = &self.batch_progress {
@@ -673,7 +1314,12 @@ impl TranslationFiestaApp {
             };
             ui.add(
                 egui::ProgressBar::new(fraction)
-                    .text(format!("{}/{}", progress.done, progress.total))
+                    .text(format!(
+                        "{}/{} · {}",
+                        progress.done,
+                        progress.total,
+                        progress.timing_label()
+                    ))
                     .show_percentage(),
             );
         }
@@ -697,7 +1343,39 @@ impl TranslationFiestaApp {
             });
 
         ui.separator();
-        ui.label(RichText::new(format!("Batch results: {}", self.batch_results.len())).strong());
+        let filter = self.batch_filter.trim().to_lowercase();
+        let visible: Vec<&BatchItemResult> = self
+            .batch_results
+            .iter()
+            .filter(|item| !self.batch_failures_only || !item.success)
+            .filter(|item| filter.is_empty() || item.file_path.to_lowercase().contains(&filter))
+            .collect();
+        let heading = if visible.len() == self.batch_results.len() {
+            format!("Batch results: {}", self.batch_results.len())
+        } else {
+            format!(
+                "Batch results: {} of {}",
+                visible.len(),
+                self.batch_results.len()
+            )
+        };
+        ui.label(RichText::new(heading).strong());
+
+        let mut copy_failed = false;
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.batch_filter)
+                    .hint_text("Filter by path")
+                    .desired_width(220.0),
+            );
+            ui.checkbox(&mut self.batch_failures_only, "Failures only");
+            copy_failed = ui
+                .add_enabled(
+                    self.batch_results.iter().any(|item| !item.success),
+                    egui::Button::new("Copy failed paths"),
+                )
+                .clicked();
+        });
 
         egui::ScrollArea::vertical().show(ui, |ui| {
             if self.batch_results.is_empty() && !self.is_batch_running {
@@ -706,8 +1384,14 @@ impl TranslationFiestaApp {
                         .color(Color32::from_rgb(113, 113, 122))
                         .italics(),
                 );
+            } else if visible.is_empty() && !self.batch_results.is_empty() {
+                ui.label(
+                    RichText::new("No results match the filter")
+                        .color(Color32::from_rgb(113, 113, 122))
+                        .italics(),
+                );
             }
-            for item in &self.batch_results {
+            for item in visible {
                 ui.group(|ui| {
                     ui.horizontal(|ui| {
                         let status_label = if item.success {
@@ -722,6 +1406,12 @@ impl TranslationFiestaApp {
                     if let Some(error) = &item.error {
                         ui.label(RichText::new(error).color(Color32::from_rgb(239, 68, 68)));
                     }
+                    if let Some(reason) = &item.suspicious_reason {
+                        ui.label(
+                            RichText::new(format!("Warning: {reason}"))
+                                .color(Color32::from_rgb(234, 179, 8)),
+                        );
+                    }
                     if !item.back_translated_text.is_empty() {
                         ui.label("Back translation preview:");
                         let mut preview = truncate_for_preview(&item.back_translated_text, 240);
@@ -735,6 +1425,16 @@ impl TranslationFiestaApp {
                 ui.add_space(8.0);
             }
         });
+
+        if copy_failed {
+            let paths: Vec<&str> = self
+                .batch_results
+                .iter()
+                .filter(|item| !item.success)
+                .map(|item| item.file_path.as_str())
+                .collect();
+            self.copy_to_clipboard(paths.join("\n"), "Failed paths");
+        }
     }
 
     fn ui_memory_tab(&mut self, ui: &mut egui::Ui) {
@@ -762,6 +1462,25 @@ impl TranslationFiestaApp {
             ));
         });
 
+        egui::CollapsingHeader::new("Breakdown by provider and language pair")
+            .id_salt("memory_breakdown")
+            .show(ui, |ui| {
+                ui.columns(2, |columns| {
+                    grouped_stats_grid(
+                        &mut columns[0],
+                        "memory_stats_by_provider",
+                        "Provider",
+                        &self.memory_stats_by_provider,
+                    );
+                    grouped_stats_grid(
+                        &mut columns[1],
+                        "memory_stats_by_pair",
+                        "Language pair",
+                        &self.memory_stats_by_pair,
+                    );
+                });
+            });
+
         ui.separator();
         ui.horizontal(|ui| {
             ui.label("Search:");
@@ -778,6 +1497,8 @@ impl TranslationFiestaApp {
         });
 
         ui.add_space(8.0);
+        let mut delete_key = None;
+        let mut save_edit = false;
         egui::ScrollArea::vertical().show(ui, |ui| {
             for entry in &self.memory_results {
                 ui.group(|ui| {
@@ -791,24 +1512,108 @@ impl TranslationFiestaApp {
                         );
                         ui.label(format!("uses: {}", entry.access_count));
                         ui.label(entry.last_accessed.to_rfc3339());
+                        if ui.button("Edit").clicked() {
+                            self.memory_edit =
+                                Some((entry.cache_key.clone(), entry.translated_text.clone()));
+                        }
+                        if ui.button("🗑").on_hover_text("Delete entry").clicked() {
+                            delete_key = Some(entry.cache_key.clone());
+                        }
                     });
                     ui.label(RichText::new(&entry.source_text).monospace());
+                    match &mut self.memory_edit {
+                        Some((key, draft)) if *key == entry.cache_key => {
+                            ui.add(egui::TextEdit::multiline(draft).desired_rows(3));
+                            ui.horizontal(|ui| {
+                                save_edit = ui.button("Save").clicked();
+                                if ui.button("Cancel").clicked() {
+                                    self.memory_edit = None;
+                                }
+                            });
+                        }
+                        _ => {
+                            ui.label(
+                                RichText::new(&entry.translated_text)
+                                    .color(Color32::from_rgb(145, 208, 255)),
+                            );
+                        }
+                    }
+                });
+                ui.add_space(6.0);
+            }
+
+            if self.memory_results.is_empty() {
+                ui.label(
+                    RichText::new("Search for translations in memory")
+                        .color(Color32::from_rgb(113, 113, 122))
+                        .italics(),
+                );
+            }
+        });
+
+        if save_edit {
+            self.save_memory_edit();
+        }
+        if let Some(key) = delete_key {
+            self.delete_memory_entry(&key);
+        }
+    }
+
+    fn ui_history_tab(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal_wrapped(|ui| {
+            if ui.button("Clear History").clicked() {
+                self.clear_history();
+            }
+            ui.separator();
+            ui.label(format!(
+                "Entries: {} / {}",
+                self.history.len(),
+                self.settings.history_max_entries
+            ));
+        });
+
+        ui.add_space(8.0);
+        let mut load_index = None;
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for (index, entry) in self.history.iter().enumerate() {
+                ui.group(|ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new(history_label(entry)).strong());
+                        ui.label(format!(
+                            "{} | {:.0}% match",
+                            entry.provider_id,
+                            entry.fidelity_score * 100.0
+                        ));
+                        if let Some(hops) = entry.hop_breakdown() {
+                            ui.label(RichText::new(hops).weak());
+                        }
+                        if ui.button("Load").clicked() {
+                            load_index = Some(index);
+                        }
+                    });
                     ui.label(
-                        RichText::new(&entry.translated_text)
+                        RichText::new(truncate_for_preview(&entry.original_text, 160)).monospace(),
+                    );
+                    ui.label(
+                        RichText::new(truncate_for_preview(&entry.back_translated_text, 160))
                             .color(Color32::from_rgb(145, 208, 255)),
                     );
                 });
                 ui.add_space(6.0);
             }
 
-            if self.memory_results.is_empty() {
+            if self.history.is_empty() {
                 ui.label(
-                    RichText::new("Search for translations in memory")
+                    RichText::new("Completed translations appear here")
                         .color(Color32::from_rgb(113, 113, 122))
                         .italics(),
                 );
             }
         });
+
+        if let Some(index) = load_index {
+            self.load_history_entry(index);
+        }
     }
 
     fn ui_export_tab(&mut self, ui: &mut egui::Ui) {
@@ -824,6 +1629,45 @@ impl TranslationFiestaApp {
 
             ui.checkbox(&mut self.include_metadata, "Include metadata");
 
+            let mut highlight_diff = self.exporter.highlight_diff();
+            if ui
+                .checkbox(&mut highlight_diff, "Highlight changes")
+                .on_hover_text("HTML exports mark words the back translation added or dropped")
+                .changed()
+            {
+                self.exporter = self.exporter.with_highlight_diff(highlight_diff);
+                self.rebuild_export_preview();
+            }
+
+            if self.export_format == ExportFormat::Csv {
+                let mut options = self.exporter.options();
+                let delimiter_label = |delimiter: char| match delimiter {
+                    '\t' => "Tab".to_owned(),
+                    other => other.to_string(),
+                };
+                egui::ComboBox::from_id_salt("csv_delimiter")
+                    .selected_text(format!(
+                        "Delimiter {}",
+                        delimiter_label(options.csv_delimiter)
+                    ))
+                    .show_ui(ui, |ui| {
+                        for delimiter in [',', ';', '\t', '|'] {
+                            ui.selectable_value(
+                                &mut options.csv_delimiter,
+                                delimiter,
+                                delimiter_label(delimiter),
+                            );
+                        }
+                    });
+                ui.checkbox(&mut options.csv_always_quote, "Quote all fields");
+                if options != self.exporter.options() {
+                    self.settings.csv_delimiter = options.csv_delimiter;
+                    self.settings.csv_always_quote = options.csv_always_quote;
+                    self.exporter = self.exporter.with_options(options);
+                    self.rebuild_export_preview();
+                }
+            }
+
             if ui.button("Generate Preview").clicked() {
                 self.rebuild_export_preview();
             }
@@ -850,6 +1694,25 @@ impl TranslationFiestaApp {
         });
 
         ui.separator();
+        if let Some(result) = self
+            .last_result
+            .as_ref()
+            .filter(|_| self.exporter.highlight_diff())
+        {
+            if !self.export_diff.as_ref().is_some_and(|diff| {
+                diff.is_for(&result.original_text, &result.back_translated_text)
+            }) {
+                self.export_diff = Some(RoundTripDiff::new(
+                    &result.original_text,
+                    &result.back_translated_text,
+                ));
+            }
+            if let Some(diff) = &self.export_diff {
+                ui.label("Changes");
+                ui.label(diff.unified_job.clone());
+                ui.separator();
+            }
+        }
         ui.label("Preview");
         if self.export_preview.is_empty() {
             self.export_preview = "Generate a preview to inspect export output.".to_owned();
@@ -864,15 +1727,34 @@ impl TranslationFiestaApp {
     fn ui_settings_tab(&mut self, ui: &mut egui::Ui) {
         ui.group(|ui| {
             ui.heading("Language & Provider");
-            ui.horizontal(|ui| {
-                ui.label("Source Language");
-                ui.text_edit_singleline(&mut self.settings.source_language);
-                ui.label("Intermediate Language");
-                ui.text_edit_singleline(&mut self.settings.intermediate_language);
-            });
+            let source_rejected = ui
+                .horizontal(|ui| {
+                    ui.label("Source Language");
+                    language_picker(
+                        ui,
+                        "source_language_picker",
+                        &mut self.settings.source_language,
+                        &mut self.custom_source_language,
+                    )
+                })
+                .inner;
+            let intermediate_rejected = ui
+                .horizontal(|ui| {
+                    ui.label("Intermediate Language");
+                    language_picker(
+                        ui,
+                        "intermediate_language_picker",
+                        &mut self.settings.intermediate_language,
+                        &mut self.custom_intermediate_language,
+                    )
+                })
+                .inner;
+            if let Some(message) = source_rejected.or(intermediate_rejected) {
+                self.status_message = message;
+            }
             ui.label(
                 egui::RichText::new(
-                    "Accepts BCP-47 language codes, e.g. en, fr, zh-CN, pt-BR, zh-Hans",
+                    "Custom codes accept BCP-47, e.g. en, fr, zh-CN, pt-BR, zh-Hans",
                 )
                 .small()
                 .weak(),
@@ -883,13 +1765,58 @@ impl TranslationFiestaApp {
                 egui::ComboBox::from_id_salt("provider_picker")
                     .selected_text(self.settings.provider().display_name())
                     .show_ui(ui, |ui| {
-                        ui.selectable_value(
-                            &mut self.settings.provider_id,
-                            ProviderId::GoogleUnofficial.as_str().to_owned(),
-                            ProviderId::GoogleUnofficial.display_name(),
-                        );
+                        for provider in ProviderId::all() {
+                            ui.selectable_value(
+                                &mut self.settings.provider_id,
+                                provider.as_str().to_owned(),
+                                provider.display_name(),
+                            );
+                        }
                     });
             });
+
+            if self.settings.provider() == ProviderId::DeepL {
+                ui.horizontal(|ui| {
+                    ui.label("DeepL API key");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.settings.deepl_api_key)
+                            .password(true)
+                            .hint_text("falls back to TF_DEEPL_API_KEY"),
+                    );
+                });
+                ui.label(
+                    RichText::new("The key is stored in settings.json (applies on restart)")
+                        .small()
+                        .weak(),
+                );
+            }
+
+            if self.settings.provider() == ProviderId::GoogleOfficial {
+                ui.horizontal(|ui| {
+                    ui.label("Google Cloud API key");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.settings.google_api_key)
+                            .password(true)
+                            .hint_text("falls back to TF_GOOGLE_API_KEY"),
+                    );
+                });
+                ui.label(
+                    RichText::new("The key is stored in settings.json (applies on restart)")
+                        .small()
+                        .weak(),
+                );
+            }
+
+            if self.settings.provider() == ProviderId::LibreTranslate {
+                ui.horizontal(|ui| {
+                    ui.label("LibreTranslate URL");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.settings.libretranslate_url)
+                            .hint_text(DEFAULT_LIBRETRANSLATE_URL),
+                    );
+                });
+                ui.label(RichText::new("Applies on restart").small().weak());
+            }
         });
 
         ui.add_space(10.0);
@@ -908,12 +1835,27 @@ impl TranslationFiestaApp {
                 "Memory DB: {}",
                 self.paths.memory_db_file.display()
             ));
+            ui.label(format!("History: {}", self.paths.history_file.display()));
         });
 
         ui.add_space(10.0);
 
         ui.group(|ui| {
             ui.heading("Behavior");
+            ui.horizontal(|ui| {
+                ui.label("Theme");
+                egui::ComboBox::from_id_salt("theme_picker")
+                    .selected_text(self.settings.ui_theme().display_name())
+                    .show_ui(ui, |ui| {
+                        for theme in Theme::all() {
+                            ui.selectable_value(
+                                &mut self.settings.theme,
+                                theme.as_str().to_owned(),
+                                theme.display_name(),
+                            );
+                        }
+                    });
+            });
             ui.horizontal(|ui| {
                 ui.label("Translation memory max entries");
                 ui.add(
@@ -922,8 +1864,290 @@ impl TranslationFiestaApp {
                         .range(100..=50_000),
                 );
             });
+            ui.horizontal(|ui| {
+                ui.label("History entries kept");
+                let response = ui.add(
+                    egui::DragValue::new(&mut self.settings.history_max_entries)
+                        .speed(5.0)
+                        .range(1..=10_000),
+                );
+                if response.changed() && self.history.len() > self.settings.history_max_entries {
+                    self.history.truncate(self.settings.history_max_entries);
+                    self.persist_history();
+                }
+            });
+            ui.checkbox(
+                &mut self.settings.track_memory_metrics,
+                "Track memory hit/miss metrics (applies on restart)",
+            );
+            ui.checkbox(
+                &mut self.settings.segment_cache,
+                "Cache each sentence separately (applies on restart)",
+            );
+            ui.horizontal(|ui| {
+                ui.label("Eviction policy");
+                egui::ComboBox::from_id_salt("eviction_policy_picker")
+                    .selected_text(self.settings.eviction().display_name())
+                    .show_ui(ui, |ui| {
+                        for policy in EvictionPolicy::all() {
+                            ui.selectable_value(
+                                &mut self.settings.eviction_policy,
+                                policy.as_str().to_owned(),
+                                policy.display_name(),
+                            );
+                        }
+                    });
+                ui.label("Expire after (days, 0 = never)");
+                ui.add(egui::DragValue::new(&mut self.settings.entry_ttl_days).range(0..=3650));
+                ui.label(RichText::new("Applies on restart").small().weak());
+            });
+            ui.horizontal(|ui| {
+                ui.label("Cache namespace");
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.settings.cache_namespace)
+                        .hint_text("e.g. v2")
+                        .desired_width(120.0),
+                );
+                ui.label(
+                    RichText::new("Change to start a fresh cache (applies on restart)")
+                        .small()
+                        .weak(),
+                );
+            });
+            ui.horizontal(|ui| {
+                ui.label("Cache matching");
+                egui::ComboBox::from_id_salt("cache_normalization_picker")
+                    .selected_text(self.settings.cache_normalization().display_name())
+                    .show_ui(ui, |ui| {
+                        for mode in CacheNormalization::all() {
+                            ui.selectable_value(
+                                &mut self.settings.cache_normalize,
+                                mode.as_str().to_owned(),
+                                mode.display_name(),
+                            );
+                        }
+                    });
+                ui.label(RichText::new("Applies on restart").small().weak());
+            });
+            ui.horizontal(|ui| {
+                ui.label("Glossary file");
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.settings.glossary_path)
+                        .hint_text("terms.json or terms.csv")
+                        .desired_width(220.0),
+                );
+                ui.label(RichText::new("Applies on restart").small().weak());
+            });
+            ui.horizontal(|ui| {
+                ui.label("Attempts per request");
+                let attempts = ui.add(
+                    egui::DragValue::new(&mut self.settings.retry_max_attempts).range(1..=10),
+                );
+                ui.label("Base retry delay (ms)");
+                let delay = ui.add(
+                    egui::DragValue::new(&mut self.settings.retry_base_delay_ms)
+                        .range(50..=5000)
+                        .speed(10),
+                );
+                if attempts.changed() || delay.changed() {
+                    self.translator.set_retry_policy(
+                        self.settings.retry_max_attempts,
+                        self.settings.retry_base_delay_ms,
+                    );
+                }
+                ui.label("Retry budget (ms)");
+                ui.add(
+                    egui::DragValue::new(&mut self.settings.max_total_retry_ms)
+                        .range(0..=600_000)
+                        .speed(100),
+                );
+            });
+            ui.horizontal(|ui| {
+                ui.label("Retries when blocked (captcha)");
+                ui.add(
+                    egui::DragValue::new(&mut self.settings.blocked_retry_attempts).range(0..=10),
+                );
+                ui.label("Cooldown (minutes)");
+                ui.add(
+                    egui::DragValue::new(&mut self.settings.blocked_cooldown_minutes).range(1..=60),
+                );
+            });
+            ui.horizontal(|ui| {
+                ui.label("Request timeout (s)");
+                let timeout = ui.add(
+                    egui::DragValue::new(&mut self.settings.request_timeout_seconds).range(5..=120),
+                );
+                if timeout.changed() {
+                    self.apply_request_timeout();
+                }
+                if request_timeout_from_env().is_some() {
+                    ui.label(
+                        RichText::new("TF_UNOFFICIAL_TIMEOUT_SECONDS overrides this")
+                            .small()
+                            .weak(),
+                    );
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Minimum interval between requests (ms)");
+                ui.add(
+                    egui::DragValue::new(&mut self.settings.min_request_interval_ms)
+                        .range(0..=60_000)
+                        .speed(10),
+                );
+            });
+            ui.label(
+                RichText::new(
+                    "0 retries fails fast on captcha pages; retry budget and request interval 0 disable them (applies on restart)",
+                )
+                    .small()
+                    .weak(),
+            );
+            ui.checkbox(
+                &mut self.settings.normalize_whitespace,
+                "Normalize whitespace when importing files",
+            );
+            ui.horizontal(|ui| {
+                let mut limit_mb = self.settings.max_input_file_bytes / (1024 * 1024);
+                ui.label("Max input file size (MB)");
+                if ui
+                    .add(egui::DragValue::new(&mut limit_mb).range(1..=4096))
+                    .changed()
+                {
+                    self.settings.max_input_file_bytes = limit_mb * 1024 * 1024;
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Flag echo at similarity");
+                ui.add(
+                    egui::DragValue::new(&mut self.settings.suspicious_echo_similarity)
+                        .speed(0.01)
+                        .range(0.5..=1.0),
+                );
+                ui.label("Flag untranslated back hop at");
+                ui.add(
+                    egui::DragValue::new(&mut self.settings.suspicious_back_similarity)
+                        .speed(0.01)
+                        .range(0.5..=1.0),
+                );
+            });
+            ui.horizontal(|ui| {
+                ui.label("Run after batch");
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.settings.on_complete_command)
+                        .hint_text("shell command, e.g. ./publish.sh"),
+                );
+            });
+            ui.label(
+                RichText::new(
+                    "Runs through the system shell with TF_TOTAL, TF_SUCCESS, TF_FAILED, TF_OUTPUT set",
+                )
+                .small()
+                .weak(),
+            );
             ui.label("Changes are saved automatically every few seconds and on app close.");
         });
+
+        ui.add_space(10.0);
+
+        let mut copy_request = None;
+        ui.group(|ui| {
+            ui.heading("Effective Configuration");
+            let header =
+                egui::CollapsingHeader::new("Resolved settings, paths, and TF_* overrides")
+                    .id_salt("effective_config")
+                    .show(ui, |ui| {
+                        if !self
+                            .effective_config
+                            .as_ref()
+                            .is_some_and(|(settings, _)| *settings == self.settings)
+                        {
+                            let rendered =
+                                EffectiveConfig::resolve(&self.settings, &self.paths).render();
+                            self.effective_config = Some((self.settings.clone(), rendered));
+                        }
+                        if let Some((_, rendered)) = &self.effective_config {
+                            ui.add(
+                                egui::TextEdit::multiline(&mut rendered.as_str())
+                                    .font(egui::TextStyle::Monospace)
+                                    .desired_width(f32::INFINITY)
+                                    .interactive(false),
+                            );
+                            if ui.button("Copy to Clipboard").clicked() {
+                                copy_request = Some(rendered.clone());
+                            }
+                        }
+                    });
+            if header.body_returned.is_none() {
+                self.effective_config = None;
+            }
+        });
+
+        if let Some(text) = copy_request {
+            self.copy_effective_config(text);
+        }
+    }
+
+    fn copy_effective_config(&mut self, text: String) {
+        self.copy_to_clipboard(text, "Configuration");
+    }
+
+    /// Swaps in a translator whose HTTP client uses the configured timeout;
+    /// requests already running finish with the old one.
+    fn apply_request_timeout(&mut self) {
+        match self
+            .translator
+            .clone()
+            .with_request_timeout(self.settings.request_timeout())
+        {
+            Ok(translator) => {
+                self.batch_processor = BatchProcessor::new(translator.clone());
+                self.translator = translator;
+            }
+            Err(error) => {
+                self.status_message = format!("Request timeout not applied: {error:#}");
+            }
+        }
+    }
+
+    /// Records the window geometry for the next launch, first pulling a
+    /// restored window back on screen if its saved monitor is gone. egui only
+    /// reports the size of the window's monitor, not its origin, so that
+    /// monitor is treated as spanning from (0, 0).
+    fn track_window_geometry(&mut self, ctx: &egui::Context) {
+        let viewport = ctx.input(|input| input.viewport().clone());
+        let (Some(outer), Some(inner)) = (viewport.outer_rect, viewport.inner_rect) else {
+            return;
+        };
+        if viewport.maximized == Some(true) || viewport.fullscreen == Some(true) {
+            return;
+        }
+
+        if !self.window_position_checked {
+            self.window_position_checked = true;
+            if let (Some(_), Some(monitor)) =
+                (self.settings.window_position(), viewport.monitor_size)
+            {
+                let current = (outer.min.x, outer.min.y);
+                let clamped = clamp_window_position(
+                    current,
+                    (outer.width(), outer.height()),
+                    (monitor.x, monitor.y),
+                );
+                if clamped != current {
+                    info!("moving off-screen window from {current:?} to {clamped:?}");
+                    ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(egui::pos2(
+                        clamped.0, clamped.1,
+                    )));
+                    return;
+                }
+            }
+        }
+
+        self.settings.window_pos_x = Some(outer.min.x);
+        self.settings.window_pos_y = Some(outer.min.y);
+        self.settings.window_width = inner.width();
+        self.settings.window_height = inner.height();
     }
 
     fn maybe_autosave_settings(&mut self) {
@@ -943,6 +2167,13 @@ impl eframe::App for TranslationFiestaApp {
         self.apply_theme(ctx);
         self.poll_events();
 
+        if self.active_tab == AppTab::Translate
+            && ctx.input_mut(|input| input.consume_shortcut(&CLEAR_INPUT_SHORTCUT))
+        {
+            self.clear_input();
+        }
+
+        self.handle_dropped_files(ctx);
         self.draw_top_bar(ctx);
 
         egui::CentralPanel::default().show(ctx, |ui| {
@@ -954,15 +2185,19 @@ impl eframe::App for TranslationFiestaApp {
                 AppTab::Translate => self.ui_translate_tab(ui),
                 AppTab::Batch => self.ui_batch_tab(ui),
                 AppTab::Memory => self.ui_memory_tab(ui),
+                AppTab::History => self.ui_history_tab(ui),
                 AppTab::Export => self.ui_export_tab(ui),
                 AppTab::Settings => self.ui_settings_tab(ui),
             }
         });
 
+        self.draw_drop_overlay(ctx);
+
         if self.is_translating || self.is_batch_running {
             ctx.request_repaint_after(Duration::from_millis(33));
         }
 
+        self.track_window_geometry(ctx);
         self.maybe_autosave_settings();
     }
 }
@@ -990,6 +2225,167 @@ fn tab_button(ui: &mut egui::Ui, active_tab: &mut AppTab, value: AppTab, label:
     }
 }
 
+/// The back-translation with added words tinted green and dropped words red
+/// and struck through, matching the HTML export's highlighting.
+fn diff_layout_job(ops: &[DiffOp<'_>]) -> egui::text::LayoutJob {
+    let mut job = egui::text::LayoutJob::default();
+    for op in ops {
+        let (word, format) = match *op {
+            DiffOp::Equal(word) => (word, egui::TextFormat::default()),
+            DiffOp::Insert(word) => (word, inserted_word_format()),
+            DiffOp::Delete(word) => (word, deleted_word_format()),
+        };
+        append_word(&mut job, word, format);
+    }
+    job
+}
+
+/// Side-by-side form of `diff_layout_job`: the original marks the words the
+/// round trip dropped, the back-translation the words it added.
+fn diff_column_layout_jobs(ops: &[DiffOp<'_>]) -> (egui::text::LayoutJob, egui::text::LayoutJob) {
+    let mut original = egui::text::LayoutJob::default();
+    let mut back_translated = egui::text::LayoutJob::default();
+    for op in ops {
+        match *op {
+            DiffOp::Equal(word) => {
+                append_word(&mut original, word, egui::TextFormat::default());
+                append_word(&mut back_translated, word, egui::TextFormat::default());
+            }
+            DiffOp::Delete(word) => append_word(&mut original, word, deleted_word_format()),
+            DiffOp::Insert(word) => append_word(&mut back_translated, word, inserted_word_format()),
+        }
+    }
+    (original, back_translated)
+}
+
+fn append_word(job: &mut egui::text::LayoutJob, word: &str, format: egui::TextFormat) {
+    if !job.text.is_empty() {
+        job.append(" ", 0.0, egui::TextFormat::default());
+    }
+    job.append(word, 0.0, format);
+}
+
+fn inserted_word_format() -> egui::TextFormat {
+    egui::TextFormat {
+        background: Color32::from_rgba_unmultiplied(62, 180, 110, 70),
+        ..Default::default()
+    }
+}
+
+fn deleted_word_format() -> egui::TextFormat {
+    egui::TextFormat {
+        background: Color32::from_rgba_unmultiplied(224, 82, 82, 70),
+        strikethrough: Stroke::new(1.0, Color32::from_rgb(224, 82, 82)),
+        ..Default::default()
+    }
+}
+
+fn dark_visuals() -> egui::Visuals {
+    let mut visuals = egui::Visuals::dark();
+    visuals.override_text_color = Some(Color32::from_rgb(228, 228, 231));
+    visuals.window_fill = Color32::from_rgb(20, 20, 23);
+    visuals.panel_fill = Color32::from_rgb(14, 14, 16);
+    visuals.extreme_bg_color = Color32::from_rgb(10, 10, 12);
+    visuals.widgets.noninteractive.bg_fill = Color32::from_rgb(28, 28, 32);
+    visuals.widgets.inactive.bg_fill = Color32::from_rgb(32, 32, 36);
+    visuals.widgets.hovered.bg_fill = Color32::from_rgb(40, 40, 46);
+    visuals.widgets.active.bg_fill = Color32::from_rgb(50, 55, 65);
+    visuals.widgets.inactive.fg_stroke = Stroke::new(1.0, Color32::from_rgb(200, 200, 210));
+    visuals.widgets.hovered.fg_stroke = Stroke::new(1.0, Color32::from_rgb(235, 235, 240));
+    visuals.selection.bg_fill = Color32::from_rgb(59, 130, 246);
+    visuals.hyperlink_color = Color32::from_rgb(96, 165, 250);
+    visuals.window_stroke = Stroke::new(1.0, Color32::from_rgb(42, 42, 46));
+    visuals
+}
+
+fn light_visuals() -> egui::Visuals {
+    let mut visuals = egui::Visuals::light();
+    visuals.override_text_color = Some(Color32::from_rgb(24, 24, 27));
+    visuals.window_fill = Color32::from_rgb(250, 250, 251);
+    visuals.panel_fill = Color32::from_rgb(244, 244, 246);
+    visuals.extreme_bg_color = Color32::from_rgb(255, 255, 255);
+    visuals.widgets.noninteractive.bg_fill = Color32::from_rgb(236, 236, 240);
+    visuals.widgets.inactive.bg_fill = Color32::from_rgb(228, 228, 233);
+    visuals.widgets.hovered.bg_fill = Color32::from_rgb(216, 216, 224);
+    visuals.widgets.active.bg_fill = Color32::from_rgb(200, 210, 228);
+    visuals.widgets.inactive.fg_stroke = Stroke::new(1.0, Color32::from_rgb(52, 52, 60));
+    visuals.widgets.hovered.fg_stroke = Stroke::new(1.0, Color32::from_rgb(24, 24, 27));
+    visuals.selection.bg_fill = Color32::from_rgb(147, 187, 250);
+    visuals.hyperlink_color = Color32::from_rgb(37, 99, 235);
+    visuals.window_stroke = Stroke::new(1.0, Color32::from_rgb(212, 212, 218));
+    visuals
+}
+
+/// Combo box of `common_languages` plus a custom-code field for anything else.
+/// Returns a status message when a custom code fails validation.
+fn language_picker(
+    ui: &mut egui::Ui,
+    id_salt: &str,
+    code: &mut String,
+    custom: &mut String,
+) -> Option<String> {
+    let selected = language_name(code)
+        .map(|name| format!("{name} ({code})"))
+        .unwrap_or_else(|| format!("Custom ({code})"));
+    egui::ComboBox::from_id_salt(id_salt)
+        .selected_text(selected)
+        .show_ui(ui, |ui| {
+            for (known, name) in common_languages() {
+                ui.selectable_value(code, (*known).to_owned(), format!("{name} ({known})"));
+            }
+        });
+
+    let response = ui.add(
+        egui::TextEdit::singleline(custom)
+            .desired_width(90.0)
+            .hint_text("custom"),
+    );
+    let submitted = response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter));
+    if !(ui.button("Use").clicked() || submitted) || custom.trim().is_empty() {
+        return None;
+    }
+
+    if !is_supported_language_code(custom) {
+        return Some(format!("'{}' is not a valid language code", custom.trim()));
+    }
+    *code = normalize_language_code(custom).unwrap_or_default();
+    custom.clear();
+    None
+}
+
+/// `Japanese, JA` for known languages, otherwise just the uppercased code.
+fn language_heading(code: &str) -> String {
+    match language_name(code) {
+        Some(name) => format!("{name}, {}", code.to_ascii_uppercase()),
+        None => code.to_ascii_uppercase(),
+    }
+}
+
+fn grouped_stats_grid(
+    ui: &mut egui::Ui,
+    id_salt: &str,
+    heading: &str,
+    groups: &BTreeMap<String, GroupedMemoryStats>,
+) {
+    egui::Grid::new(id_salt)
+        .striped(true)
+        .num_columns(4)
+        .show(ui, |ui| {
+            ui.label(RichText::new(heading).strong());
+            ui.label(RichText::new("Entries").strong());
+            ui.label(RichText::new("Accesses").strong());
+            ui.label(RichText::new("Reused").strong());
+            ui.end_row();
+            for (key, group) in groups {
+                ui.label(key);
+                ui.label(group.entries.to_string());
+                ui.label(group.total_accesses.to_string());
+                ui.label(group.reuses().to_string());
+                ui.end_row();
+            }
+        });
+}
+
 fn truncate_for_preview(value: &str, limit: usize) -> String {
     let count = value.chars().count();
     if count <= limit {
@@ -1052,40 +2448,3 @@ fn apply_cjk_font_fallback(ctx: &egui::Context) {
     ctx.set_fonts(fonts);
     info!("loaded Japanese fallback font from {}", font_path.display());
 }
-
-fn load_cjk_font_data() -> Option<(String, Vec<u8>, PathBuf)> {
-    for candidate in cjk_font_candidates() {
-        let path = PathBuf::from(candidate);
-        let bytes = match fs::read(&path) {
-            Ok(bytes) => bytes,
-            Err(_) => continue,
-        };
-
-        let stem = path
-            .file_stem()
-            .and_then(|name| name.to_str())
-            .unwrap_or("cjk-fallback");
-        let font_name = format!("cjk-{stem}");
-        return Some((font_name, bytes, path));
-    }
-    None
-}
-
-fn cjk_font_candidates() -> &'static [&'static str] {
-    &[
-        "/System/Library/Fonts/Supplemental/Arial Unicode.ttf",
-        "/System/Library/Fonts/Supplemental/Hiragino Sans GB.ttc",
-        "/System/Library/Fonts/Supplemental/Songti.ttc",
-        "/Library/Fonts/Arial Unicode.ttf",
-        r"C:\Windows\Fonts\YuGothM.ttc",
-        r"C:\Windows\Fonts\YuGothR.ttc",
-        r"C:\Windows\Fonts\Meiryo.ttc",
-        r"C:\Windows\Fonts\msgothic.ttc",
-        "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
-        "/usr/share/fonts/opentype/noto/NotoSerifCJK-Regular.ttc",
-        "/usr/share/fonts/truetype/noto/NotoSansCJK-Regular.ttc",
-        "/usr/share/fonts/truetype/noto/NotoSansJP-Regular.otf",
-        "/usr/share/fonts/truetype/noto/NotoSansJP-Regular.ttf",
-        "/usr/local/share/fonts/NotoSansCJK-Regular.ttc",
-    ]
-}
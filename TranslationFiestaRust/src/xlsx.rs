@@ -0,0 +1,268 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use zip::CompressionMethod;
+use zip::write::SimpleFileOptions;
+
+use crate::html::escape_xml;
+
+/// Excel refuses cells longer than this many characters.
+const MAX_CELL_CHARS: usize = 32_767;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum XlsxCell {
+    Text(String),
+    Number(f64),
+    Bool(bool),
+}
+
+impl From<&str> for XlsxCell {
+    fn from(value: &str) -> Self {
+        Self::Text(value.to_owned())
+    }
+}
+
+impl From<String> for XlsxCell {
+    fn from(value: String) -> Self {
+        Self::Text(value)
+    }
+}
+
+/// A worksheet with a bold header row followed by data rows.
+#[derive(Debug, Clone)]
+pub struct XlsxSheet {
+    pub name: String,
+    pub header: Vec<String>,
+    pub rows: Vec<Vec<XlsxCell>>,
+    /// Keep the header visible while scrolling and filterable for review.
+    pub frozen_header: bool,
+}
+
+impl XlsxSheet {
+    pub fn new(name: impl Into<String>, header: &[&str]) -> Self {
+        Self {
+            name: name.into(),
+            header: header.iter().map(|column| (*column).to_owned()).collect(),
+            rows: Vec::new(),
+            frozen_header: false,
+        }
+    }
+
+    /// Freezes the header row and puts an autofilter on it.
+    pub fn with_frozen_header(mut self) -> Self {
+        self.frozen_header = true;
+        self
+    }
+
+    pub fn push_row(&mut self, row: Vec<XlsxCell>) {
+        self.rows.push(row);
+    }
+}
+
+/// Writes a minimal SpreadsheetML workbook, one worksheet part per sheet.
+pub fn write_workbook(path: &Path, sheets: &[XlsxSheet]) -> Result<()> {
+    let file =
+        File::create(path).with_context(|| format!("failed to create {}", path.display()))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    zip.start_file("[Content_Types].xml", options)?;
+    zip.write_all(content_types_xml(sheets.len()).as_bytes())?;
+
+    zip.start_file("_rels/.rels", options)?;
+    zip.write_all(ROOT_RELATIONSHIPS_XML.as_bytes())?;
+
+    zip.start_file("xl/workbook.xml", options)?;
+    zip.write_all(workbook_xml(sheets).as_bytes())?;
+
+    zip.start_file("xl/_rels/workbook.xml.rels", options)?;
+    zip.write_all(workbook_relationships_xml(sheets.len()).as_bytes())?;
+
+    zip.start_file("xl/styles.xml", options)?;
+    zip.write_all(STYLES_XML.as_bytes())?;
+
+    for (index, sheet) in sheets.iter().enumerate() {
+        zip.start_file(format!("xl/worksheets/sheet{}.xml", index + 1), options)?;
+        zip.write_all(worksheet_xml(sheet).as_bytes())?;
+    }
+
+    zip.finish()
+        .with_context(|| format!("failed to finish workbook {}", path.display()))?;
+    Ok(())
+}
+
+const ROOT_RELATIONSHIPS_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+  <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/>
+</Relationships>"#;
+
+/// Style 0 is the default; style 1 is the bold header.
+const STYLES_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<styleSheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">
+  <fonts count="2"><font><sz val="11"/><name val="Calibri"/></font><font><b/><sz val="11"/><name val="Calibri"/></font></fonts>
+  <fills count="2"><fill><patternFill patternType="none"/></fill><fill><patternFill patternType="gray125"/></fill></fills>
+  <borders count="1"><border><left/><right/><top/><bottom/><diagonal/></border></borders>
+  <cellStyleXfs count="1"><xf numFmtId="0" fontId="0" fillId="0" borderId="0"/></cellStyleXfs>
+  <cellXfs count="2"><xf numFmtId="0" fontId="0" fillId="0" borderId="0" xfId="0"/><xf numFmtId="0" fontId="1" fillId="0" borderId="0" xfId="0" applyFont="1"/></cellXfs>
+</styleSheet>"#;
+
+fn content_types_xml(sheet_count: usize) -> String {
+    let mut overrides = String::new();
+    for index in 1..=sheet_count {
+        overrides.push_str(&format!(
+            "<Override PartName=\"/xl/worksheets/sheet{index}.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml\"/>"
+        ));
+    }
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?><Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\"><Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/><Default Extension=\"xml\" ContentType=\"application/xml\"/><Override PartName=\"/xl/workbook.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml\"/><Override PartName=\"/xl/styles.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml\"/>{overrides}</Types>"
+    )
+}
+
+fn workbook_xml(sheets: &[XlsxSheet]) -> String {
+    let mut entries = String::new();
+    for (index, sheet) in sheets.iter().enumerate() {
+        entries.push_str(&format!(
+            "<sheet name=\"{}\" sheetId=\"{}\" r:id=\"rId{}\"/>",
+            escape_xml(&sheet_name(&sheet.name)),
+            index + 1,
+            index + 1
+        ));
+    }
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?><workbook xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\" xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\"><sheets>{entries}</sheets></workbook>"
+    )
+}
+
+fn workbook_relationships_xml(sheet_count: usize) -> String {
+    let mut relationships = String::new();
+    for index in 1..=sheet_count {
+        relationships.push_str(&format!(
+            "<Relationship Id=\"rId{index}\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet\" Target=\"worksheets/sheet{index}.xml\"/>"
+        ));
+    }
+    relationships.push_str(&format!(
+        "<Relationship Id=\"rId{}\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles\" Target=\"styles.xml\"/>",
+        sheet_count + 1
+    ));
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?><Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">{relationships}</Relationships>"
+    )
+}
+
+fn worksheet_xml(sheet: &XlsxSheet) -> String {
+    let mut rows = String::new();
+
+    rows.push_str("<row r=\"1\">");
+    for (column, title) in sheet.header.iter().enumerate() {
+        rows.push_str(&text_cell(&cell_ref(column, 1), title, true));
+    }
+    rows.push_str("</row>");
+
+    for (offset, row) in sheet.rows.iter().enumerate() {
+        let row_number = offset + 2;
+        rows.push_str(&format!("<row r=\"{row_number}\">"));
+        for (column, cell) in row.iter().enumerate() {
+            let reference = cell_ref(column, row_number);
+            match cell {
+                XlsxCell::Text(value) => rows.push_str(&text_cell(&reference, value, false)),
+                XlsxCell::Number(value) if value.is_finite() => {
+                    rows.push_str(&format!("<c r=\"{reference}\"><v>{value}</v></c>"));
+                }
+                XlsxCell::Number(_) => {}
+                XlsxCell::Bool(value) => rows.push_str(&format!(
+                    "<c r=\"{reference}\" t=\"b\"><v>{}</v></c>",
+                    u8::from(*value)
+                )),
+            }
+        }
+        rows.push_str("</row>");
+    }
+
+    let (views, filter) = if sheet.frozen_header {
+        let last_cell = cell_ref(sheet.header.len().max(1) - 1, sheet.rows.len() + 1);
+        (
+            "<sheetViews><sheetView workbookViewId=\"0\"><pane ySplit=\"1\" topLeftCell=\"A2\" activePane=\"bottomLeft\" state=\"frozen\"/></sheetView></sheetViews>".to_owned(),
+            format!("<autoFilter ref=\"A1:{last_cell}\"/>"),
+        )
+    } else {
+        (String::new(), String::new())
+    };
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?><worksheet xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\">{views}<sheetData>{rows}</sheetData>{filter}</worksheet>"
+    )
+}
+
+fn text_cell(reference: &str, value: &str, bold: bool) -> String {
+    let style = if bold { " s=\"1\"" } else { "" };
+    format!(
+        "<c r=\"{reference}\" t=\"inlineStr\"{style}><is><t xml:space=\"preserve\">{}</t></is></c>",
+        escape_xml(&cell_text(value))
+    )
+}
+
+/// Drops control characters XML 1.0 cannot carry and clamps to Excel's cell limit.
+fn cell_text(value: &str) -> String {
+    value
+        .chars()
+        .filter(|ch| !ch.is_control() || matches!(ch, '\t' | '\n' | '\r'))
+        .take(MAX_CELL_CHARS)
+        .collect()
+}
+
+/// Sheet names are limited to 31 characters and may not contain `[]:*?/\`.
+fn sheet_name(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .filter(|ch| !matches!(ch, '[' | ']' | ':' | '*' | '?' | '/' | '\\'))
+        .take(31)
+        .collect();
+    if cleaned.is_empty() {
+        "Sheet".to_owned()
+    } else {
+        cleaned
+    }
+}
+
+fn cell_ref(column: usize, row: usize) -> String {
+    let mut letters = Vec::new();
+    let mut remaining = column + 1;
+    while remaining > 0 {
+        let rem = (remaining - 1) % 26;
+        letters.push(char::from(b'A' + rem as u8));
+        remaining = (remaining - 1) / 26;
+    }
+    letters.iter().rev().collect::<String>() + &row.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cell_refs_roll_over_after_z() {
+        assert_eq!(cell_ref(0, 1), "A1");
+        assert_eq!(cell_ref(25, 3), "Z3");
+        assert_eq!(cell_ref(26, 2), "AA2");
+    }
+
+    #[test]
+    fn frozen_sheets_freeze_and_filter_the_header_row() {
+        let plain = worksheet_xml(&XlsxSheet::new("Summary", &["metric", "value"]));
+        assert!(!plain.contains("<pane") && !plain.contains("<autoFilter"));
+
+        let mut sheet =
+            XlsxSheet::new("Results", &["file_path", "success", "error"]).with_frozen_header();
+        sheet.push_row(vec!["a.txt".into(), XlsxCell::Bool(true), "".into()]);
+        sheet.push_row(vec!["b.txt".into(), XlsxCell::Bool(false), "boom".into()]);
+
+        let xml = worksheet_xml(&sheet);
+        assert!(xml.contains("<pane ySplit=\"1\" topLeftCell=\"A2\""));
+        assert!(xml.contains("<autoFilter ref=\"A1:C3\"/>"));
+        assert!(xml.contains("<c r=\"A1\" t=\"inlineStr\" s=\"1\">"));
+    }
+}